@@ -4,7 +4,26 @@ use std::path::PathBuf;
 
 use schemars::{schema::RootSchema, schema_for};
 
-use mask::msg::{OwnerResponse, HandleMsg, InitMsg, QueryMsg};
+use mask::msg::{
+    AdminProposalResponse, AdminProposalsResponse, AdminVoteResponse, AdminsResponse,
+    AggregateResponse, AllowedTargetsResponse, ArchiveMsg, AttestationResponse, AuctionResponse,
+    AuthorizationsResponse, BatchingResponse, BlackoutsResponse, CalendarResponse,
+    ChainProfileResponse, CoOwnersResponse, CollectedFeesResponse, ConfigDiffResponse,
+    ConfigResponse, ContractVersionResponse, CooldownsResponse, CosignerResponse,
+    CostEstimateResponse, DataEntriesResponse, DataResponse, DenomMetadataResponse,
+    DepositsResponse, DescribeCoinsResponse, EchoMsg, ExecuteManyResult, ExecutionResponse,
+    ExecutionsResponse, ExportChunkResponse, FeaturesResponse, GlobalStatsResponse, GrantResponse,
+    GrantsResponse, GuardiansResponse, HandleMsg, HistoryResponse, InitMsg, IsDeniedResponse,
+    MacrosResponse, MetricsResponse, MigrateRecordsResult, OperatorWindowResponse,
+    OperatorsResponse, OwnerResponse, PendingConfigResponse, PendingOwnerResponse,
+    PermissionsResponse, ProposalResponse, ProposalsResponse, QueryMsg, QueuedExecutionResponse,
+    ReceiptMsg, RecoveryResponse, RecoverySessionResponse, ReflectResult, ResolveResponse,
+    RevenueResponse, RulesResponse, RulesetMsg, ScheduledBatchesResponse, SearchResponse,
+    SelfTestResult, SessionResponse, SimulationResponse, SpendLimitResponse, StagedBatchResponse,
+    StagedBatchesResponse, StakeholdersResponse, StatsResponse, StatusesResponse,
+    StorageUsageResponse, SuccessorResponse, SwapResponse, TagSpendResponse,
+    TaggedExecutionsResponse, TagsResponse, ValidationResponse, VerifyStateResponse,
+};
 use mask::state::State;
 
 fn main() {
@@ -21,11 +40,242 @@ fn main() {
     let schema = schema_for!(QueryMsg);
     export_schema(&schema, &pwd, "query_msg.json");
 
+    let schema = schema_for!(ReceiptMsg);
+    export_schema(&schema, &pwd, "receipt_msg.json");
+
+    let schema = schema_for!(ArchiveMsg);
+    export_schema(&schema, &pwd, "archive_msg.json");
+
+    let schema = schema_for!(RulesetMsg);
+    export_schema(&schema, &pwd, "ruleset_msg.json");
+
+    let schema = schema_for!(EchoMsg);
+    export_schema(&schema, &pwd, "echo_msg.json");
+
     let schema = schema_for!(State);
     export_schema(&schema, &pwd, "state.json");
 
     let schema = schema_for!(OwnerResponse);
     export_schema(&schema, &pwd, "owner_response.json");
+
+    let schema = schema_for!(ConfigResponse);
+    export_schema(&schema, &pwd, "config_response.json");
+
+    let schema = schema_for!(ContractVersionResponse);
+    export_schema(&schema, &pwd, "contract_version_response.json");
+
+    let schema = schema_for!(PendingOwnerResponse);
+    export_schema(&schema, &pwd, "pending_owner_response.json");
+
+    let schema = schema_for!(ChainProfileResponse);
+    export_schema(&schema, &pwd, "chain_profile_response.json");
+
+    let schema = schema_for!(DenomMetadataResponse);
+    export_schema(&schema, &pwd, "denom_metadata_response.json");
+
+    let schema = schema_for!(DepositsResponse);
+    export_schema(&schema, &pwd, "deposits_response.json");
+
+    let schema = schema_for!(DescribeCoinsResponse);
+    export_schema(&schema, &pwd, "describe_coins_response.json");
+
+    let schema = schema_for!(ExecutionResponse);
+    export_schema(&schema, &pwd, "execution_response.json");
+
+    let schema = schema_for!(ExecuteManyResult);
+    export_schema(&schema, &pwd, "execute_many_result.json");
+
+    let schema = schema_for!(ReflectResult);
+    export_schema(&schema, &pwd, "reflect_result.json");
+
+    let schema = schema_for!(MigrateRecordsResult);
+    export_schema(&schema, &pwd, "migrate_records_result.json");
+
+    let schema = schema_for!(SelfTestResult);
+    export_schema(&schema, &pwd, "self_test_result.json");
+
+    let schema = schema_for!(ExecutionsResponse);
+    export_schema(&schema, &pwd, "executions_response.json");
+
+    let schema = schema_for!(HistoryResponse);
+    export_schema(&schema, &pwd, "history_response.json");
+
+    let schema = schema_for!(CostEstimateResponse);
+    export_schema(&schema, &pwd, "cost_estimate_response.json");
+
+    let schema = schema_for!(AggregateResponse);
+    export_schema(&schema, &pwd, "aggregate_response.json");
+
+    let schema = schema_for!(BatchingResponse);
+    export_schema(&schema, &pwd, "batching_response.json");
+
+    let schema = schema_for!(CoOwnersResponse);
+    export_schema(&schema, &pwd, "co_owners_response.json");
+
+    let schema = schema_for!(AdminProposalResponse);
+    export_schema(&schema, &pwd, "admin_proposal_response.json");
+
+    let schema = schema_for!(AdminProposalsResponse);
+    export_schema(&schema, &pwd, "admin_proposals_response.json");
+
+    let schema = schema_for!(AdminVoteResponse);
+    export_schema(&schema, &pwd, "admin_vote_response.json");
+
+    let schema = schema_for!(AdminsResponse);
+    export_schema(&schema, &pwd, "admins_response.json");
+
+    let schema = schema_for!(CooldownsResponse);
+    export_schema(&schema, &pwd, "cooldowns_response.json");
+
+    let schema = schema_for!(OperatorsResponse);
+    export_schema(&schema, &pwd, "operators_response.json");
+
+    let schema = schema_for!(OperatorWindowResponse);
+    export_schema(&schema, &pwd, "operator_window_response.json");
+
+    let schema = schema_for!(CalendarResponse);
+    export_schema(&schema, &pwd, "calendar_response.json");
+
+    let schema = schema_for!(BlackoutsResponse);
+    export_schema(&schema, &pwd, "blackouts_response.json");
+
+    let schema = schema_for!(AllowedTargetsResponse);
+    export_schema(&schema, &pwd, "allowed_targets_response.json");
+
+    let schema = schema_for!(StagedBatchResponse);
+    export_schema(&schema, &pwd, "staged_batch_response.json");
+
+    let schema = schema_for!(StagedBatchesResponse);
+    export_schema(&schema, &pwd, "staged_batches_response.json");
+
+    let schema = schema_for!(ScheduledBatchesResponse);
+    export_schema(&schema, &pwd, "scheduled_batches_response.json");
+
+    let schema = schema_for!(StakeholdersResponse);
+    export_schema(&schema, &pwd, "stakeholders_response.json");
+
+    let schema = schema_for!(SwapResponse);
+    export_schema(&schema, &pwd, "swap_response.json");
+
+    let schema = schema_for!(PermissionsResponse);
+    export_schema(&schema, &pwd, "permissions_response.json");
+
+    let schema = schema_for!(ProposalResponse);
+    export_schema(&schema, &pwd, "proposal_response.json");
+
+    let schema = schema_for!(ProposalsResponse);
+    export_schema(&schema, &pwd, "proposals_response.json");
+
+    let schema = schema_for!(AuctionResponse);
+    export_schema(&schema, &pwd, "auction_response.json");
+
+    let schema = schema_for!(TagsResponse);
+    export_schema(&schema, &pwd, "tags_response.json");
+
+    let schema = schema_for!(TaggedExecutionsResponse);
+    export_schema(&schema, &pwd, "tagged_executions_response.json");
+
+    let schema = schema_for!(TagSpendResponse);
+    export_schema(&schema, &pwd, "tag_spend_response.json");
+
+    let schema = schema_for!(RecoveryResponse);
+    export_schema(&schema, &pwd, "recovery_response.json");
+
+    let schema = schema_for!(RecoverySessionResponse);
+    export_schema(&schema, &pwd, "recovery_session_response.json");
+
+    let schema = schema_for!(CollectedFeesResponse);
+    export_schema(&schema, &pwd, "collected_fees_response.json");
+
+    let schema = schema_for!(IsDeniedResponse);
+    export_schema(&schema, &pwd, "is_denied_response.json");
+
+    let schema = schema_for!(StatsResponse);
+    export_schema(&schema, &pwd, "stats_response.json");
+
+    let schema = schema_for!(GlobalStatsResponse);
+    export_schema(&schema, &pwd, "global_stats_response.json");
+
+    let schema = schema_for!(MacrosResponse);
+    export_schema(&schema, &pwd, "macros_response.json");
+
+    let schema = schema_for!(ResolveResponse);
+    export_schema(&schema, &pwd, "resolve_response.json");
+
+    let schema = schema_for!(SearchResponse);
+    export_schema(&schema, &pwd, "search_response.json");
+
+    let schema = schema_for!(StatusesResponse);
+    export_schema(&schema, &pwd, "statuses_response.json");
+
+    let schema = schema_for!(MetricsResponse);
+    export_schema(&schema, &pwd, "metrics_response.json");
+
+    let schema = schema_for!(StorageUsageResponse);
+    export_schema(&schema, &pwd, "storage_usage_response.json");
+
+    let schema = schema_for!(RulesResponse);
+    export_schema(&schema, &pwd, "rules_response.json");
+
+    let schema = schema_for!(DataResponse);
+    export_schema(&schema, &pwd, "data_response.json");
+
+    let schema = schema_for!(DataEntriesResponse);
+    export_schema(&schema, &pwd, "data_entries_response.json");
+
+    let schema = schema_for!(SessionResponse);
+    export_schema(&schema, &pwd, "session_response.json");
+
+    let schema = schema_for!(CosignerResponse);
+    export_schema(&schema, &pwd, "cosigner_response.json");
+
+    let schema = schema_for!(AttestationResponse);
+    export_schema(&schema, &pwd, "attestation_response.json");
+
+    let schema = schema_for!(QueuedExecutionResponse);
+    export_schema(&schema, &pwd, "queued_execution_response.json");
+
+    let schema = schema_for!(AuthorizationsResponse);
+    export_schema(&schema, &pwd, "authorizations_response.json");
+
+    let schema = schema_for!(GuardiansResponse);
+    export_schema(&schema, &pwd, "guardians_response.json");
+
+    let schema = schema_for!(RevenueResponse);
+    export_schema(&schema, &pwd, "revenue_response.json");
+
+    let schema = schema_for!(SuccessorResponse);
+    export_schema(&schema, &pwd, "successor_response.json");
+
+    let schema = schema_for!(SimulationResponse);
+    export_schema(&schema, &pwd, "simulation_response.json");
+
+    let schema = schema_for!(SpendLimitResponse);
+    export_schema(&schema, &pwd, "spend_limit_response.json");
+
+    let schema = schema_for!(PendingConfigResponse);
+    export_schema(&schema, &pwd, "pending_config_response.json");
+
+    let schema = schema_for!(ConfigDiffResponse);
+    export_schema(&schema, &pwd, "config_diff_response.json");
+
+    let schema = schema_for!(ExportChunkResponse);
+    export_schema(&schema, &pwd, "export_chunk_response.json");
+
+    let schema = schema_for!(FeaturesResponse);
+    export_schema(&schema, &pwd, "features_response.json");
+
+    let schema = schema_for!(GrantResponse);
+    export_schema(&schema, &pwd, "grant_response.json");
+
+    let schema = schema_for!(GrantsResponse);
+    export_schema(&schema, &pwd, "grants_response.json");
+
+    let schema = schema_for!(ValidationResponse);
+    export_schema(&schema, &pwd, "validation_response.json");
+
+    let schema = schema_for!(VerifyStateResponse);
+    export_schema(&schema, &pwd, "verify_state_response.json");
 }
 
 // panics if any error writing out the schema
@@ -0,0 +1,40 @@
+//! Writes every fixture of tests/fixtures as its own file, for client SDKs to test against:
+//!
+//! cargo vectors [dir]
+//!
+//! Files are named <dir>/<group>/<index>_<name>.json, the index keeps the fixture order.
+//! Their content is exactly the JSON the contract reads and writes, without a trailing newline.
+
+#[path = "../tests/fixtures/mod.rs"]
+mod fixtures;
+
+use std::env::{args, current_dir};
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use fixtures::Fixtures;
+
+fn main() {
+    let dir = match args().nth(1) {
+        Some(dir) => PathBuf::from(dir),
+        None => current_dir().unwrap().join("vectors"),
+    };
+
+    export_group(&dir, "messages", fixtures::messages());
+    export_group(&dir, "handle_msgs", fixtures::handle_msgs());
+    export_group(&dir, "query_msgs", fixtures::query_msgs());
+    export_group(&dir, "responses", fixtures::responses());
+    export_group(&dir, "edge_cases", fixtures::edge_cases());
+}
+
+// panics if any error writing out the vectors
+// overwrites any existing vectors of the group
+fn export_group(dir: &Path, group: &str, fixtures: Fixtures) {
+    let dir = dir.join(group);
+    create_dir_all(&dir).unwrap();
+    for (i, (name, json)) in fixtures.iter().enumerate() {
+        let path = dir.join(format!("{:03}_{}.json", i, name));
+        write(&path, json).unwrap();
+        println!("{}", path.to_str().unwrap());
+    }
+}
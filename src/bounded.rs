@@ -0,0 +1,116 @@
+//! Size bounds of the address sets authorization checks go through.
+//!
+//! Every owner, operator or guardian check scans its set, so none may grow without limit.
+//! The bound is a value carried by the collection rather than a const generic, which this
+//! toolchain does not have yet.
+
+use std::ops::Deref;
+
+use cosmwasm::errors::Result;
+
+use crate::errors::ContractError;
+use crate::state::{MAX_CO_OWNERS, MAX_GUARDIANS, MAX_OPERATORS};
+
+/// Bound names a set and how many items it may hold
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bound {
+    pub set: &'static str,
+    pub max: usize,
+}
+
+pub const CO_OWNERS: Bound = Bound {
+    set: "co_owners",
+    max: MAX_CO_OWNERS,
+};
+pub const OPERATORS: Bound = Bound {
+    set: "operators",
+    max: MAX_OPERATORS,
+};
+pub const GUARDIANS: Bound = Bound {
+    set: "guardians",
+    max: MAX_GUARDIANS,
+};
+
+impl Bound {
+    /// Fails with LimitExceeded if a set of this size would be over the bound
+    pub fn check(self, len: usize) -> Result<()> {
+        if len > self.max {
+            return ContractError::LimitExceeded {
+                set: self.set.to_string(),
+                max: self.max as u32,
+            }
+            .fail();
+        }
+        Ok(())
+    }
+}
+
+/// BoundedVec is a list which refuses to grow over its bound
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundedVec<T> {
+    items: Vec<T>,
+    bound: Bound,
+}
+
+impl<T> BoundedVec<T> {
+    pub fn new(bound: Bound) -> Self {
+        BoundedVec {
+            items: vec![],
+            bound,
+        }
+    }
+
+    pub fn push(&mut self, item: T) -> Result<()> {
+        self.bound.check(self.items.len() + 1)?;
+        self.items.push(item);
+        Ok(())
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Deref for BoundedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THREE: Bound = Bound {
+        set: "things",
+        max: 3,
+    };
+
+    #[test]
+    fn fills_up_to_the_bound() {
+        let mut list = BoundedVec::new(THREE);
+        for i in 0..3 {
+            list.push(i).unwrap();
+        }
+        assert!(list.contains(&2));
+
+        let err = list.push(3).unwrap_err();
+        let expected = ContractError::LimitExceeded {
+            set: "things".to_string(),
+            max: 3,
+        };
+        assert!(err.to_string().contains(&expected.to_string()));
+        assert_eq!(vec![0, 1, 2], list.into_vec());
+    }
+
+    #[test]
+    fn checks_sizes_at_the_boundary() {
+        assert!(THREE.check(0).is_ok());
+        assert!(THREE.check(3).is_ok());
+        assert!(THREE.check(4).is_err());
+        assert!(GUARDIANS.check(MAX_GUARDIANS).is_ok());
+        assert!(GUARDIANS.check(MAX_GUARDIANS + 1).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Builders for contracts calling a deployed mask.
+//!
+//! They encode the HandleMsg of this crate, so callers cannot drift from msg.rs:
+//!
+//! `MaskMsg::reflect(msgs).with_memo(memo).into_cosmos(mask_addr)?`
+//!
+//! Queries cannot be built, as contracts have no querier to send them with.
+
+use snafu::ResultExt;
+
+use cosmwasm::encoding::Binary;
+use cosmwasm::errors::{Result, SerializeErr};
+use cosmwasm::serde::to_vec;
+use cosmwasm::types::{Coin, CosmosMsg, HumanAddr};
+
+use crate::msg::HandleMsg;
+use crate::state::TravelMemo;
+
+/// MaskMsg is a call to the mask contract with any HandleMsg
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaskMsg {
+    pub msg: HandleMsg,
+    /// Funds sent along, eg. to pay the reflect fee
+    pub send: Option<Vec<Coin>>,
+}
+
+impl MaskMsg {
+    pub fn new(msg: HandleMsg) -> Self {
+        MaskMsg { msg, send: None }
+    }
+
+    /// Starts a ReflectMsg without tags or memo
+    pub fn reflect(msgs: Vec<CosmosMsg>) -> ReflectBuilder {
+        ReflectBuilder {
+            msgs,
+            tags: vec![],
+            memo: None,
+            send: None,
+        }
+    }
+
+    pub fn with_funds(mut self, send: Vec<Coin>) -> Self {
+        self.send = Some(send);
+        self
+    }
+
+    /// The message executing the call on the mask at contract_addr
+    pub fn into_cosmos(self, contract_addr: HumanAddr) -> Result<CosmosMsg> {
+        let msg = to_vec(&self.msg).context(SerializeErr { kind: "HandleMsg" })?;
+        Ok(CosmosMsg::Contract {
+            contract_addr,
+            msg: Binary(msg),
+            send: self.send,
+        })
+    }
+}
+
+impl From<HandleMsg> for MaskMsg {
+    fn from(msg: HandleMsg) -> Self {
+        MaskMsg::new(msg)
+    }
+}
+
+/// ReflectBuilder adds the optional parts of a ReflectMsg
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReflectBuilder {
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+    send: Option<Vec<Coin>>,
+}
+
+impl ReflectBuilder {
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_memo(mut self, memo: TravelMemo) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    pub fn with_funds(mut self, send: Vec<Coin>) -> Self {
+        self.send = Some(send);
+        self
+    }
+
+    pub fn build(self) -> MaskMsg {
+        MaskMsg {
+            msg: HandleMsg::ReflectMsg {
+                msgs: self.msgs,
+                tags: self.tags,
+                memo: self.memo,
+            },
+            send: self.send,
+        }
+    }
+
+    pub fn into_cosmos(self, contract_addr: HumanAddr) -> Result<CosmosMsg> {
+        self.build().into_cosmos(contract_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm::serde::from_slice;
+    use cosmwasm::types::coin;
+
+    fn decode(msg: CosmosMsg) -> (HumanAddr, HandleMsg, Option<Vec<Coin>>) {
+        match msg {
+            CosmosMsg::Contract {
+                contract_addr,
+                msg,
+                send,
+            } => (contract_addr, from_slice(msg.as_slice()).unwrap(), send),
+            _ => panic!("Must call a contract"),
+        }
+    }
+
+    #[test]
+    fn reflect_round_trips() {
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos1mask"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("100", "uatom"),
+        };
+        let memo = TravelMemo {
+            originator: "client-42".to_string(),
+            beneficiary: "acct-7".to_string(),
+        };
+        let msg = MaskMsg::reflect(vec![send.clone()])
+            .with_tags(vec!["ops".to_string()])
+            .with_memo(memo.clone())
+            .with_funds(coin("5", "uatom"))
+            .into_cosmos(HumanAddr::from("cosmos1mask"))
+            .unwrap();
+
+        let (contract_addr, msg, funds) = decode(msg);
+        assert_eq!(HumanAddr::from("cosmos1mask"), contract_addr);
+        let expected = HandleMsg::ReflectMsg {
+            msgs: vec![send],
+            tags: vec!["ops".to_string()],
+            memo: Some(memo),
+        };
+        assert_eq!(expected, msg);
+        assert_eq!(Some(coin("5", "uatom")), funds);
+    }
+
+    #[test]
+    fn any_handle_msg_round_trips() {
+        let pause = HandleMsg::Pause {};
+        let msg = MaskMsg::from(pause.clone())
+            .into_cosmos(HumanAddr::from("cosmos1mask"))
+            .unwrap();
+        assert_eq!((HumanAddr::from("cosmos1mask"), pause, None), decode(msg));
+    }
+}
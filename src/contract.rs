@@ -1,25 +1,163 @@
+use sha2::{Digest, Sha256};
 use snafu::ResultExt;
 
-use cosmwasm::errors::{Result, SerializeErr, unauthorized};
+use cosmwasm::encoding::Binary;
+use cosmwasm::errors::{contract_err, dyn_contract_err, unauthorized, Error, Result, SerializeErr};
 use cosmwasm::serde::to_vec;
-use cosmwasm::traits::{Api, Extern, Storage};
-use cosmwasm::types::{CosmosMsg, Env, HumanAddr, log, Response};
+use cosmwasm::traits::{Api, Extern, ReadonlyStorage, Storage};
+use cosmwasm::types::{
+    log, CanonicalAddr, Coin, CosmosMsg, Env, HumanAddr, LogAttribute, Response,
+};
 
-use crate::msg::{HandleMsg, InitMsg, QueryMsg, OwnerResponse};
-use crate::state::{config, config_read, State};
+use crate::bounded::{BoundedVec, CO_OWNERS, GUARDIANS, OPERATORS};
+use crate::errors::ContractError;
+use crate::math::{self, mul_ratio, safe_sum, SafeMath};
+use crate::middleware::{self, Batch, MiddlewareConfig, Verdict, MAX_MIDDLEWARES};
+use crate::msg::{
+    deprecation_logs, AdminProposalResponse, AdminProposalsResponse, AdminVoteResponse,
+    AdminsResponse, AggregateQuery, AggregateResponse, AggregateResult, AllowedTargetsResponse,
+    ArchiveMsg, AttestationResponse, AuctionResponse, AuthorizationResponse,
+    AuthorizationsResponse, BatchChunk, BatchingResponse, BlackoutsResponse, BudgetChange,
+    CalendarResponse, ChainProfileResponse, CoOwnersResponse, CoinDescription,
+    CollectedFeesResponse, ConfigDiffResponse, ConfigOverride, ConfigResponse,
+    ContractVersionResponse, CooldownChange, CooldownInfo, CooldownsResponse, CosignerResponse,
+    CostEstimateResponse, DataEntriesResponse, DataEntry, DataResponse, DenomMetadataResponse,
+    DepositsResponse, DescribeCoinsResponse, EchoMsg, ExecuteManyResult, ExecutionResponse,
+    ExecutionsResponse, ExportChunkResponse, ExportEntry, ExportNamespace, FeaturesResponse,
+    GlobalStatsResponse, GrantResponse, GrantsResponse, GuardiansResponse, HandleMsg,
+    HistoryEntryResponse, HistoryResponse, InitMsg, IsDeniedResponse, KindStatuses, Lookup,
+    MacroArg, MacroResponse, MacroRunsResponse, MacrosResponse, Metric, MetricKind,
+    MetricsResponse, MigrateNamespace, MigrateRecordsResult, NamespaceUsage, OnEmpty, OperatorSpec,
+    OperatorWindowResponse, OperatorsResponse, Order, OwnerResponse, PayrollEntry,
+    PendingConfigResponse, PendingOwnerResponse, PermissionsResponse, ProposalResponse,
+    ProposalsResponse, QueryMsg, QueuedExecutionResponse, ReceiptMsg, RecoveryResponse,
+    RecoverySessionResponse, ReflectResult, ResolveResponse, RevenueResponse, RulesResponse,
+    RulesetMsg, ScheduledBatchResponse, ScheduledBatchesResponse, SearchGroup, SearchHit,
+    SearchResponse, SelfTestResult, SessionResponse, SimulationResponse, SpendLimitResponse,
+    StagedBatchResponse, StagedBatchesResponse, StakeholderMsg, StakeholdersResponse,
+    StatsResponse, StatusCode, StatusesResponse, StorageUsageResponse, SubsystemCheck,
+    SuccessorResponse, SwapResponse, TagSpendResponse, TaggedExecutionsResponse, TagsResponse,
+    ValidationResponse, VerifySection, VerifyStateResponse, Violation, ViolationKind, WeightMsg,
+    DEPRECATIONS,
+};
+use crate::pagination::{
+    clamp_limit, decode_start_after, encode_cursor, paginate, paginate_bounded, start_key,
+};
+use crate::pointer;
+use crate::reference::{found, ref_log, EntityKind, Reference};
+use crate::staging;
+use crate::state::{
+    adjust_metric, admin_proposals, admin_proposals_read, attestation, attestation_read, auctions,
+    auctions_read, calendars, calendars_read, coins_of, collected_fees, collected_fees_read,
+    config, config_read, config_version, config_version_read, contract_version,
+    contract_version_read, count_kinds, data, data_keys, data_keys_read, data_read, denoms,
+    denoms_read, denylist, denylist_read, executions, executions_read, gas_stats, gas_stats_read,
+    global_stats, global_stats_read, grants, grants_read, guardians, guardians_read, history,
+    history_read, last_admin_proposal_id, last_execution_id, last_grant_id, last_history_seq,
+    last_one_time_auth_id, last_proposal_id, last_scheduled_batch_id, last_staged_batch_id,
+    last_used, last_used_read, lifetime_deposits, lifetime_deposits_read, macro_names,
+    macro_names_read, macro_runs, macro_runs_key, macro_runs_read, macros, macros_read,
+    metrics_read, middleware_metric, migration_cursors, migration_cursors_read, msg_hash,
+    next_admin_proposal_id, next_auction_id, next_execution_id, next_grant_id, next_heartbeat_seq,
+    next_history_seq, next_one_time_auth_id, next_proposal_id, next_queued_execution_id,
+    next_scheduled_batch_id, next_staged_batch_id, next_swap_id, one_time_auth_ids,
+    one_time_auth_ids_read, one_time_auths, one_time_auths_read, operator_calendars,
+    operator_calendars_read, operator_windows, operator_windows_read, parse_amount, pending_owner,
+    pending_owner_read, permissions, permissions_read, proposals, proposals_read,
+    queued_executions, queued_executions_read, recovery_session, recovery_session_read,
+    reset_metrics, revenue, revenue_period, revenue_read, revenue_settled, revenue_settled_read,
+    rule_metric, scheduled_batches, scheduled_batches_read, sent_amount, sessions, sessions_read,
+    spend_tracker, spend_tracker_read, staged_batches, staged_batches_read, stakeholders,
+    stakeholders_read, stats, stats_read, sum_coins, swaps, swaps_read, tag_counts,
+    tag_counts_read, tag_index, tag_index_key, tag_index_read, tag_spend, tag_spend_read,
+    validate_calendar_name, validate_macro_name, validate_plain_text, validate_tag_name,
+    AdminProposal, Amount, Attestation, Auction, Budget, Calendar, ContractVersion, Cooldown,
+    Cosign, DenomMetadata, Duration, Execution, ExecutionWindow, Expiration, GasHeuristic,
+    GasStats, Grant, Guardians, HistoryEntry, KindCount, LastUsed, Macro, MacroParam, MacroQuota,
+    MacroRuns, Milestone, MsgKind, OneTimeAuth, OwnerWeight, PendingConfig, PendingOwner,
+    PendingReceipt, Permission, Proposal, ProposalStatus, QueuedExecution, RecoverySession, Rule,
+    RuleAction, ScheduledBatch, Session, SpendLimit, SpendTracker, StagedBatch, Stakeholder,
+    Standing, State, Status, Swap, TagBudget, TagSpend, TimeRange, TravelMemo, Undo, WasmIntent,
+    EXECUTIONS_TOTAL, EXECUTION_PREFIX, MAX_BATCH_MEMO_LEN, MAX_DATA_KEYS, MAX_DATA_KEY_LEN,
+    MAX_DESCRIPTION_LEN, MAX_MACROS, MAX_MACRO_PARAMS, MAX_RULES, MAX_RULESETS,
+    MIDDLEWARE_EVALUATIONS, MIDDLEWARE_SHADOW_BLOCKS, QUEUE_DEPTH, REVENUE_PERIOD_SECS,
+};
+use crate::usage;
 
 pub fn init<S: Storage, A: Api>(
     deps: &mut Extern<S, A>,
     env: Env,
-    _msg: InitMsg,
+    msg: InitMsg,
 ) -> Result<Response> {
+    usage::tracked(deps, |deps| init_state(deps, env, msg))
+}
+
+fn init_state<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msg: InitMsg,
+) -> Result<Response> {
+    msg.chain.validate()?;
+    // make sure the profile matches the chain we are actually running on
+    let contract = deps.api.human_address(&env.contract.address)?;
+    msg.chain.validate_address(&contract)?;
+
     let state = State {
         owner: env.message.signer,
+        co_owners: vec![],
+        admin_threshold: None,
+        weights: vec![],
+        cooldowns: vec![],
+        operators: vec![],
+        operator_manifest_nonce: 0,
+        tags: vec![],
+        budgets: vec![],
+        recovery: None,
+        paused: false,
+        evacuated: false,
+        decommissioned: false,
+        renounced: false,
+        successor: None,
+        pending: None,
+        chain: msg.chain,
+        max_msg_bytes: None,
+        allowed_targets: None,
+        deposit_hook: None,
+        depositors: None,
+        travel_rule: None,
+        spend_limits: None,
+        suspended_operators: None,
+        four_eyes: None,
+        reflect_fee: None,
+        rulesets: None,
+        rules: None,
+        cosign: None,
+        middlewares: None,
+        attestation_max_age: None,
+        archive: None,
+        gas_heuristics: None,
     };
 
     config(&mut deps.storage).save(&state)?;
+    contract_version(&mut deps.storage).save(&ContractVersion::current())?;
+
+    Ok(Response {
+        log: heartbeat(deps, env.block.height)?,
+        ..Response::default()
+    })
+}
 
-    Ok(Response::default())
+/// The height and a sequence number which goes up by one with every successful call,
+/// so indexers can tell if they missed or reordered events of this contract
+fn heartbeat<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    height: i64,
+) -> Result<Vec<LogAttribute>> {
+    let seq = next_heartbeat_seq(&mut deps.storage)?;
+    Ok(vec![
+        log("height", &height.to_string()),
+        log("seq", &seq.to_string()),
+    ])
 }
 
 pub fn handle<S: Storage, A: Api>(
@@ -28,146 +166,14426 @@ pub fn handle<S: Storage, A: Api>(
     msg: HandleMsg,
 ) -> Result<Response> {
     match msg {
-        HandleMsg::ReflectMsg { msg} => try_reflect(deps, env, msg),
-        HandleMsg::ChangeOwner { owner } => try_change_owner(deps, env, owner),
+        #[cfg(feature = "dry-run")]
+        HandleMsg::DryRun { msg } => dry_run(deps, env, *msg),
+        msg => usage::tracked(deps, |deps| handle_versioned(deps, env, msg)),
     }
 }
 
-pub fn try_reflect<S: Storage, A: Api>(
+fn handle_versioned<S: Storage, A: Api>(
     deps: &mut Extern<S, A>,
     env: Env,
-    msg: CosmosMsg,
+    msg: HandleMsg,
 ) -> Result<Response> {
-    let state = config(&mut deps.storage).load()?;
-    if env.message.signer != state.owner {
-        return unauthorized();
+    let before = config_read(&deps.storage).load()?;
+    let funds = Funds::of(&deps.api, &env)?;
+    let mut res = handle_msg(deps, env, msg)?;
+    funds.check_outflows(&res.messages)?;
+    let after = config_read(&deps.storage).load()?;
+    if after != before {
+        let version = config_version_read(&deps.storage).may_load()?.unwrap_or(0) + 1;
+        config_version(&mut deps.storage).save(&version)?;
+        // ahead of the heartbeat, which stays last
+        let at = res.log.len().saturating_sub(2);
+        res.log
+            .insert(at, log("config_version", &version.to_string()));
+        let changed = changed_fields(&before, &after).join(",");
+        res.log.insert(at + 1, log("config_changed", &changed));
     }
-    let res = Response {
-        messages: vec![msg],
-        log: vec![log("action", "reflect")],
-        data: None,
-    };
     Ok(res)
 }
 
-pub fn try_change_owner<S: Storage, A: Api>(
+/// What a call may send out at most: the balance of the contract,
+/// which already includes the funds sent along
+struct Funds {
+    contract: HumanAddr,
+    available: Option<Vec<Coin>>,
+}
+
+impl Funds {
+    fn of<A: Api>(api: &A, env: &Env) -> Result<Funds> {
+        Ok(Funds {
+            contract: api.human_address(&env.contract.address)?,
+            available: env.contract.balance.clone(),
+        })
+    }
+
+    /// The last line of defense against a handle which sends more than the contract holds
+    fn check_outflows(&self, msgs: &[CosmosMsg]) -> Result<()> {
+        // without a balance there is nothing to check against
+        let available = match &self.available {
+            Some(available) => available,
+            None => return Ok(()),
+        };
+        let outflows = msgs.iter().flat_map(|msg| match msg {
+            CosmosMsg::Send {
+                from_address,
+                amount,
+                ..
+            } if *from_address == self.contract => &amount[..],
+            CosmosMsg::Contract {
+                send: Some(send), ..
+            } => &send[..],
+            _ => &[],
+        });
+        for outflow in sum_coins(outflows)? {
+            let held = available
+                .iter()
+                .find(|c| c.denom == outflow.denom)
+                .map_or(Ok(0), |c| parse_amount(&c.amount))?;
+            if parse_amount(&outflow.amount)? > held {
+                return ContractError::OutflowExceedsFunds {
+                    denom: outflow.denom,
+                    outflow: outflow.amount,
+                    available: held.to_string(),
+                }
+                .fail();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs msg on staged storage, so nothing it writes is kept, and logs the messages it would
+/// send instead of sending them
+#[cfg(feature = "dry-run")]
+fn dry_run<S: Storage, A: Api>(
     deps: &mut Extern<S, A>,
     env: Env,
-    owner: HumanAddr,
+    msg: HandleMsg,
 ) -> Result<Response> {
-    let api = deps.api;
-    config(&mut deps.storage).update(&|mut state| {
-        if env.message.signer != state.owner {
-            return unauthorized();
+    let (mut res, writes) = staging::staged(deps, |deps| {
+        usage::tracked(deps, |deps| handle_versioned(deps, env, msg))
+    })?;
+    res.log.insert(0, log("dry_run", "true"));
+    for (i, msg) in res.messages.drain(..).enumerate() {
+        res.log.push(log(
+            &format!("dry_run_msg_{}", i),
+            &middleware::describe(&msg),
+        ));
+    }
+    res.log.push(log("dry_run_writes", &writes.to_string()));
+    Ok(res)
+}
+
+fn handle_msg<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msg: HandleMsg,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if state.decommissioned {
+        return match state.successor {
+            Some(successor) => ContractError::MovedPermanently {
+                successor: deps.api.human_address(&successor)?,
+            }
+            .fail(),
+            None => ContractError::Decommissioned {}.fail(),
+        };
+    }
+    // a due config change takes effect before anything runs under the old one
+    let activated = activate_due_config(deps, &env)?;
+    let height = env.block.height;
+    let action = msg.action();
+
+    if state.paused && !msg.runs_while_paused() {
+        return ContractError::ContractPaused {
+            action: action.to_string(),
         }
-        state.owner = api.canonical_address(&owner)?;
-        Ok(state)
+        .fail();
+    }
+    if !state.accepts_funds_from(&env) {
+        return ContractError::DepositorNotAllowed {
+            sender: deps.api.human_address(&env.message.signer)?,
+        }
+        .fail();
+    }
+    track_deposits(deps, &env)?;
+    enforce_cooldown(deps, &env, action)?;
+    let mut res = match msg {
+        HandleMsg::ActivatePending {} => {
+            if !activated {
+                return contract_err("No config change is due");
+            }
+            Ok(Response {
+                log: vec![log("action", "activate_pending")],
+                ..Response::default()
+            })
+        }
+        // its probes go through handle_msg, so it cannot run inside one of them
+        HandleMsg::SelfTest { echo } => try_self_test(deps, env, echo),
+        msg => execute_msg(deps, env, msg),
+    }
+    .or_else(|err| match err {
+        // no one can be authorized anymore, so say why
+        Error::Unauthorized { .. } if state.renounced => {
+            ContractError::OwnershipRenounced {}.fail()
+        }
+        err => Err(err),
     })?;
-    Ok(Response{
-        log: vec![
-            log("action", "change_owner"),
-            log("owner", owner.as_str()),
-        ],
-        ..Response::default()
-    })
+    if activated {
+        res.log.push(log("config_activated", &height.to_string()));
+    }
+    res.log.extend(deprecation_logs(DEPRECATIONS, action));
+    res.log.extend(heartbeat(deps, height)?);
+    Ok(res)
 }
 
-pub fn query<S: Storage, A: Api>(deps: &Extern<S, A>, msg: QueryMsg) -> Result<Vec<u8>> {
+/// Runs the handler of the message, without any of the checks handle_msg does around it
+fn execute_msg<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msg: HandleMsg,
+) -> Result<Response> {
     match msg {
-        QueryMsg::GetOwner {} => query_owner(deps),
+        HandleMsg::ReflectMsg { msgs, tags, memo } => try_reflect(deps, env, msgs, tags, memo),
+        HandleMsg::ChangeOwner { owner } => try_change_owner(deps, env, owner),
+        HandleMsg::RegisterDenom {
+            denom,
+            symbol,
+            exponent,
+        } => try_register_denom(deps, env, denom, symbol, exponent),
+        HandleMsg::ReportGas {
+            execution_id,
+            gas_used,
+        } => try_report_gas(deps, env, execution_id, gas_used),
+        HandleMsg::SetCoOwners { co_owners } => try_set_co_owners(deps, env, co_owners),
+        HandleMsg::SetAdminWeights { weights, threshold } => {
+            try_set_admin_weights(deps, env, weights, threshold)
+        }
+        HandleMsg::ProposeAdmin { msg } => try_propose_admin(deps, env, *msg),
+        HandleMsg::ApproveAdmin { id } => try_approve_admin(deps, env, id),
+        HandleMsg::SetCooldown { action, period } => try_set_cooldown(deps, env, action, period),
+        HandleMsg::SetOperators { operators } => try_set_operators(deps, env, operators),
+        HandleMsg::ApplyOperatorManifest {
+            operators,
+            manifest_nonce,
+        } => try_apply_operator_manifest(deps, env, operators, manifest_nonce),
+        HandleMsg::StageBatch {
+            msgs,
+            memo,
+            receipt_contract,
+            tags,
+        } => try_stage_batch(deps, env, msgs, memo, receipt_contract, tags),
+        HandleMsg::ExecuteStaged { id } => try_execute_staged(deps, env, id),
+        HandleMsg::SetTags { tags } => try_set_tags(deps, env, tags),
+        HandleMsg::SetTagBudget { tag, budget } => try_set_tag_budget(deps, env, tag, budget),
+        HandleMsg::SetRecovery { recovery } => try_set_recovery(deps, env, recovery),
+        HandleMsg::Evacuate { to } => try_evacuate(deps, env, to),
+        HandleMsg::Resume {} | HandleMsg::Unpause {} => try_unpause(deps, env),
+        HandleMsg::Pause {} => try_pause(deps, env),
+        HandleMsg::ScheduleConfig {
+            change,
+            activate_at,
+        } => try_schedule_config(deps, env, change, activate_at),
+        HandleMsg::CancelPending {} => try_cancel_pending(deps, env),
+        // the call wrapping it already activated any due change
+        HandleMsg::ActivatePending {} => contract_err("No config change is due"),
+        HandleMsg::Decommission { successor } => try_decommission(deps, env, successor),
+        HandleMsg::RepairState { token } => try_repair_state(deps, env, token),
+        HandleMsg::SetMsgByteLimit { limit } => try_set_msg_byte_limit(deps, env, limit),
+        HandleMsg::ExecuteMany {
+            targets,
+            msg,
+            send,
+            tags,
+        } => try_execute_many(deps, env, targets, msg, send, tags),
+        HandleMsg::RunPayroll { entries, tag } => try_run_payroll(deps, env, entries, tag),
+        HandleMsg::CreateGrant {
+            grantee,
+            approver,
+            milestones,
+        } => try_create_grant(deps, env, grantee, approver, milestones),
+        HandleMsg::ApproveMilestone { id } => try_approve_milestone(deps, env, id),
+        HandleMsg::ProposeNewOwner { owner, expires } => {
+            try_propose_new_owner(deps, env, owner, expires)
+        }
+        HandleMsg::AcceptOwnership {} => try_accept_ownership(deps, env),
+        HandleMsg::Clawback { id, reason } => try_clawback(deps, env, id, reason),
+        HandleMsg::AddAdmin { addr } => try_add_admin(deps, env, addr),
+        HandleMsg::RemoveAdmin { addr } => try_remove_admin(deps, env, addr),
+        HandleMsg::AddAllowedTarget { contract } => try_add_allowed_target(deps, env, contract),
+        HandleMsg::RemoveAllowedTarget { contract } => {
+            try_remove_allowed_target(deps, env, contract)
+        }
+        HandleMsg::CreateSwap {
+            counterparty,
+            give,
+            want,
+            expires,
+        } => try_create_swap(deps, env, counterparty, give, want, expires),
+        HandleMsg::FillSwap { id } => try_fill_swap(deps, env, id),
+        HandleMsg::GrantPermission {
+            grantee,
+            msg_types,
+            expires_at_height,
+        } => try_grant_permission(deps, env, grantee, msg_types, expires_at_height),
+        HandleMsg::RevokePermission { grantee } => try_revoke_permission(deps, env, grantee),
+        HandleMsg::CreateAuction {
+            sell,
+            start_price,
+            end_price,
+            duration,
+        } => try_create_auction(deps, env, sell, start_price, end_price, duration),
+        HandleMsg::BuyAuction { id } => try_buy_auction(deps, env, id),
+        HandleMsg::CancelAuction { id } => try_cancel_auction(deps, env, id),
+        HandleMsg::Schedule {
+            msgs,
+            execute_after,
+            calendar,
+        } => try_schedule(deps, env, msgs, execute_after, calendar),
+        HandleMsg::ExecuteScheduled { id } => try_execute_scheduled(deps, env, id),
+        HandleMsg::CancelScheduled { id } => try_cancel_scheduled(deps, env, id),
+        HandleMsg::Deposit { tag } => try_deposit(deps, env, tag),
+        HandleMsg::SetStakeholders { stakeholders } => {
+            try_set_stakeholders(deps, env, stakeholders)
+        }
+        HandleMsg::DistributeRevenue { tag, period } => {
+            try_distribute_revenue(deps, env, tag, period)
+        }
+        HandleMsg::SetDepositHook { contract } => try_set_deposit_hook(deps, env, contract),
+        HandleMsg::SetDepositors { depositors } => try_set_depositors(deps, env, depositors),
+        HandleMsg::SetTravelRule { thresholds } => try_set_travel_rule(deps, env, thresholds),
+        HandleMsg::Propose {
+            msgs,
+            description,
+            intents,
+        } => try_propose(deps, env, msgs, description, intents),
+        HandleMsg::Approve { id } => try_approve(deps, env, id),
+        HandleMsg::Reject { id } => try_reject(deps, env, id),
+        HandleMsg::SetSpendLimit {
+            denom,
+            amount,
+            period_secs,
+        } => try_set_spend_limit(deps, env, denom, amount, period_secs),
+        HandleMsg::RemoveSpendLimit { denom } => try_remove_spend_limit(deps, env, denom),
+        HandleMsg::SuspendOperator { operator } => try_suspend_operator(deps, env, operator),
+        HandleMsg::ReinstateOperator { operator } => try_reinstate_operator(deps, env, operator),
+        HandleMsg::SetOperatorWindow { operator, window } => {
+            try_set_operator_window(deps, env, operator, window)
+        }
+        HandleMsg::SetCalendar {
+            name,
+            allowed,
+            blackouts,
+        } => try_set_calendar(deps, env, name, allowed, blackouts),
+        HandleMsg::SetOperatorCalendar { operator, calendar } => {
+            try_set_operator_calendar(deps, env, operator, calendar)
+        }
+        HandleMsg::Withdraw { to, amount } => try_withdraw(deps, env, to, amount),
+        HandleMsg::SetGuardians {
+            guardians,
+            threshold,
+            window,
+        } => try_set_guardians(deps, env, guardians, threshold, window),
+        HandleMsg::InitiateRecovery { new_owner } => try_initiate_recovery(deps, env, new_owner),
+        HandleMsg::ApproveRecovery {} => try_approve_recovery(deps, env),
+        HandleMsg::CancelRecovery {} => try_cancel_recovery(deps, env),
+        HandleMsg::Denylist { add, remove } => try_denylist(deps, env, add, remove),
+        HandleMsg::SetFourEyes { kinds } => try_set_four_eyes(deps, env, kinds),
+        HandleMsg::SetFee { fee } => try_set_fee(deps, env, fee),
+        HandleMsg::WithdrawFees { to } => try_withdraw_fees(deps, env, to),
+        HandleMsg::SetRulesets { contracts } => try_set_rulesets(deps, env, contracts),
+        HandleMsg::RenounceOwnership { confirm } => try_renounce_ownership(deps, env, confirm),
+        HandleMsg::SetRules { rules } => try_set_rules(deps, env, rules),
+        HandleMsg::SetData { key, value } => try_set_data(deps, env, key, value),
+        HandleMsg::RemoveData { key } => try_remove_data(deps, env, key),
+        HandleMsg::CreateSession {
+            key_addr,
+            max_msgs,
+            expires,
+        } => try_create_session(deps, env, key_addr, max_msgs, expires),
+        HandleMsg::SetCosigner {
+            cosigner,
+            thresholds,
+        } => try_set_cosigner(deps, env, cosigner, thresholds),
+        HandleMsg::ConfirmExecution { id } => try_confirm_execution(deps, env, id),
+        HandleMsg::CancelExecution { id } => try_cancel_execution(deps, env, id),
+        HandleMsg::AuthorizeOnce {
+            grantee,
+            msg_hash,
+            expires,
+        } => try_authorize_once(deps, env, grantee, msg_hash, expires),
+        HandleMsg::ExecuteAuthorized { msg } => try_execute_authorized(deps, env, msg),
+        HandleMsg::MigrateRecords { namespace, limit } => {
+            try_migrate_records(deps, env, namespace, limit)
+        }
+        HandleMsg::SetMiddlewares { middlewares } => try_set_middlewares(deps, env, middlewares),
+        HandleMsg::Attest {
+            checksum,
+            signer,
+            signature,
+        } => try_attest(deps, env, checksum, signer, signature),
+        HandleMsg::RequireAttestation { max_age } => try_require_attestation(deps, env, max_age),
+        HandleMsg::SetArchive { contract } => try_set_archive(deps, env, contract),
+        HandleMsg::SetGasHeuristics { heuristics } => try_set_gas_heuristics(deps, env, heuristics),
+        HandleMsg::ExecuteWithLookup {
+            lookup,
+            template,
+            tags,
+            memo,
+        } => try_execute_with_lookup(deps, env, lookup, template, tags, memo),
+        HandleMsg::SaveMacro {
+            name,
+            msgs,
+            params,
+            runners,
+            quota,
+        } => try_save_macro(deps, env, name, msgs, params, runners, quota),
+        HandleMsg::RemoveMacro { name } => try_remove_macro(deps, env, name),
+        HandleMsg::RunMacro { name, args } => try_run_macro(deps, env, name, args),
+        HandleMsg::IfConfigVersion {
+            config_version,
+            msg,
+        } => try_if_config_version(deps, env, config_version, *msg),
+        // handle runs it before anything, as nothing may be written for real
+        #[cfg(feature = "dry-run")]
+        HandleMsg::DryRun { .. } => contract_err("A dry run cannot be nested in another message"),
+        HandleMsg::SelfTest { .. } => {
+            contract_err("A self test cannot be nested in another message")
+        }
     }
 }
 
-fn query_owner<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+/// Runs a message wrapped in another one, such as an approved admin proposal or a self test probe.
+/// The call went through handle_msg already, only what depends on the action runs again:
+/// the pause, as a proposal may be approved after the contract was paused, the cooldown
+/// and the deprecation logs.
+fn handle_inner<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msg: HandleMsg,
+) -> Result<Response> {
     let state = config_read(&deps.storage).load()?;
+    let action = msg.action();
+    if state.paused && !msg.runs_while_paused() {
+        return ContractError::ContractPaused {
+            action: action.to_string(),
+        }
+        .fail();
+    }
+    enforce_cooldown(deps, &env, action)?;
+    let mut res = execute_msg(deps, env, msg)?;
+    res.log.extend(deprecation_logs(DEPRECATIONS, action));
+    Ok(res)
+}
 
-    let resp = OwnerResponse {
-        owner: deps.api.human_address(&state.owner)?,
+/// Adds the funds sent along with the call to the lifetime deposits
+fn track_deposits<S: Storage, A: Api>(deps: &mut Extern<S, A>, env: &Env) -> Result<()> {
+    let sent = match &env.message.sent_funds {
+        Some(sent) if !sent.is_empty() => sent,
+        _ => return Ok(()),
     };
-    to_vec(&resp).context(SerializeErr {
-        kind: "OwnerResponse",
-    })
+    let totals = lifetime_deposits_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let totals = sum_coins(totals.iter().chain(sent.iter()))?;
+    lifetime_deposits(&mut deps.storage).save(&totals)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm::errors::Error;
-    use cosmwasm::mock::{dependencies, mock_env};
-    use cosmwasm::serde::from_slice;
-    use cosmwasm::types::coin;
+/// Applies the pending config change if it is due, returning whether it did
+fn activate_due_config<S: Storage, A: Api>(deps: &mut Extern<S, A>, env: &Env) -> Result<bool> {
+    let mut state = config(&mut deps.storage).load()?;
+    let pending = match state.pending.take() {
+        Some(pending) if pending.activate_at.is_expired(env) => pending,
+        _ => return Ok(false),
+    };
+    apply_override(&mut state, pending.change);
+    config(&mut deps.storage).save(&state)?;
+    Ok(true)
+}
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = dependencies(20);
+fn apply_override(state: &mut State, change: ConfigOverride) {
+    if let Some(tags) = change.tags {
+        state.tags = tags;
+    }
+    if let Some(budgets) = change.budgets {
+        state.budgets = budgets;
+    }
+    if let Some(cooldowns) = change.cooldowns {
+        state.cooldowns = cooldowns;
+    }
+    let known = state.tags.clone();
+    state.budgets.retain(|b| known.contains(&b.tag));
+}
 
-        let msg = InitMsg { count: 17 };
-        let env = mock_env(&deps.api, "creator", &coin("1000", "earth"), &[]);
+/// Rejects the action if it was used too recently, otherwise records this use
+fn enforce_cooldown<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    action: &str,
+) -> Result<()> {
+    let state = config(&mut deps.storage).load()?;
+    let cooldown = match state.cooldowns.iter().find(|c| c.action == action) {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+    if let Some(last) = last_used(&mut deps.storage).may_load(action.as_bytes())? {
+        let earliest = cooldown.period.after(last.height, last.time);
+        if !earliest.is_expired(env) {
+            return ContractError::CooldownActive {
+                action: action.to_string(),
+                earliest,
+            }
+            .fail();
+        }
+    }
+    let now = LastUsed {
+        height: env.block.height as u64,
+        time: env.block.time as u64,
+    };
+    last_used(&mut deps.storage).save(action.as_bytes(), &now)
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let res = init(&mut deps, env, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+pub fn try_reflect<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if msgs.is_empty() {
+        return contract_err("Must reflect at least one message");
+    }
+    // permissions and limits must see the messages as they will go out
+    let (msgs, middleware_logs) = run_middlewares(deps, &env, msgs, &tags)?;
+    let mut fee = None;
+    let delegated = !state.is_owner(&env.message.signer);
+    if delegated {
+        let signer = env.message.signer.as_slice();
+        let permitted = match permissions_read(&deps.storage).may_load(signer)? {
+            Some(p) => p.allows(&env, &msgs),
+            None => false,
+        };
+        if !permitted {
+            use_session(deps, &env, msgs.len())?;
+        }
+        fee = state.reflect_fee.clone();
+    }
+    state.validate_tags(&tags)?;
+    if let Some(memo) = &memo {
+        memo.validate()?;
+    }
+    if let Some(fee) = &fee {
+        collect_fee(deps, &env, fee)?;
+    }
+    let msg_count = msgs.len() as u32;
+    let (mut res, dispatched) = dispatch_chained(deps, &env, msgs, tags, memo)?;
+    if let Dispatched::Queued(id) = dispatched {
+        if delegated {
+            amend_queued(deps, id, |q| q.standing = Some(Standing::Delegate))?;
+        }
+    }
+    let execution_id = dispatched.execution_id();
+    res.log.insert(0, log("action", "reflect"));
+    for coin in fee.iter().flatten() {
+        res.log
+            .push(log("fee", &format!("{}{}", coin.amount, coin.denom)));
+    }
+    res.log.extend(middleware_logs);
+    let result = ReflectResult {
+        execution_id,
+        msg_count,
+    };
+    res.data = Some(Binary(to_vec(&result).context(SerializeErr {
+        kind: "ReflectResult",
+    })?));
+    Ok(res)
+}
 
-        // it worked, let's query the state
-        let res = query(&deps, QueryMsg::GetCount {}).unwrap();
-        let value: CountResponse = from_slice(&res).unwrap();
-        assert_eq!(17, value.count);
+/// Whether the messages together send more than a co-sign threshold.
+/// Summing over the batch keeps a large payment from passing as several small ones.
+fn needs_cosign(state: &State, msgs: &[CosmosMsg]) -> Result<bool> {
+    let thresholds = match &state.cosign {
+        Some(cosign) => &cosign.thresholds,
+        None => return Ok(false),
+    };
+    for threshold in thresholds.iter() {
+        if sent_amount(msgs, &threshold.denom)? > parse_amount(&threshold.amount)? {
+            return Ok(true);
+        }
     }
+    Ok(false)
+}
 
-    #[test]
-    fn increment() {
-        let mut deps = dependencies(20);
+/// Holds the batch back for the co-signer, nothing is sent yet
+fn queue_for_cosign<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+) -> Result<(Response, Dispatched)> {
+    // an owner must still be one on confirmation, try_reflect marks the batches of delegates
+    let state = config_read(&deps.storage).load()?;
+    let standing = if state.is_owner(&env.message.signer) {
+        Standing::Owner
+    } else {
+        Standing::Handler
+    };
+    let id = next_queued_execution_id(&mut deps.storage)?;
+    let queued = QueuedExecution {
+        id,
+        sender: env.message.signer.clone(),
+        msgs,
+        tags,
+        memo,
+        queued_height: env.block.height as u64,
+        closed: false,
+        undo: None,
+        standing: Some(standing),
+        receipt: None,
+    };
+    queued_executions(&mut deps.storage).save(&id.to_be_bytes(), &queued)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, true)?;
 
-        let msg = InitMsg { count: 17 };
-        let env = mock_env(
-            &deps.api,
-            "creator",
-            &coin("2", "token"),
-            &coin("2", "token"),
-        );
-        let _res = init(&mut deps, env, msg).unwrap();
+    let res = Response {
+        log: vec![
+            log("queued_id", &id.to_string()),
+            ref_log(EntityKind::QueuedExecution, id),
+        ],
+        ..Response::default()
+    };
+    Ok((res, Dispatched::Queued(id)))
+}
 
-        // beneficiary can release it
-        let env = mock_env(&deps.api, "anyone", &coin("2", "token"), &[]);
-        let msg = HandleMsg::Increment {};
-        let _res = handle(&mut deps, env, msg).unwrap();
+/// Lets the handler which dispatched a batch record more about it once it is queued,
+/// such as what to take back if it is cancelled
+fn amend_queued<S: Storage, A: Api, F: FnOnce(&mut QueuedExecution)>(
+    deps: &mut Extern<S, A>,
+    id: u64,
+    amend: F,
+) -> Result<()> {
+    let mut queued = load_open_queued(deps, id)?;
+    amend(&mut queued);
+    queued_executions(&mut deps.storage).save(&id.to_be_bytes(), &queued)
+}
 
-        // should increase counter by 1
-        let res = query(&deps, QueryMsg::GetCount {}).unwrap();
-        let value: CountResponse = from_slice(&res).unwrap();
-        assert_eq!(18, value.count);
+/// Takes msg_count messages from the session of the signer, failing if it has none left
+fn use_session<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    msg_count: usize,
+) -> Result<()> {
+    let signer = env.message.signer.as_slice();
+    let mut session = match sessions_read(&deps.storage).may_load(signer)? {
+        Some(session) => session,
+        None => return unauthorized(),
+    };
+    if session.expires.is_expired(env) {
+        return ContractError::Expired {
+            what: "Session".to_string(),
+            at: session.expires,
+        }
+        .fail();
+    }
+    let msg_count = msg_count as u64;
+    if msg_count > session.remaining_msgs {
+        return dyn_contract_err(format!(
+            "Session has {} messages left, {} given",
+            session.remaining_msgs, msg_count
+        ));
     }
+    session.remaining_msgs -= msg_count;
+    sessions(&mut deps.storage).save(signer, &session)
+}
 
-    #[test]
-    fn reset() {
-        let mut deps = dependencies(20);
+/// Fails unless the funds sent along cover the fee, which is then added to the collected fees
+fn collect_fee<S: Storage, A: Api>(deps: &mut Extern<S, A>, env: &Env, fee: &[Coin]) -> Result<()> {
+    let sent = sum_coins(env.message.sent_funds.iter().flatten())?;
+    for coin in fee.iter() {
+        let paid = match sent.iter().find(|c| c.denom == coin.denom) {
+            Some(c) => parse_amount(&c.amount)?,
+            None => 0,
+        };
+        if paid < parse_amount(&coin.amount)? {
+            return ContractError::FeeRequired { fee: coin.clone() }.fail();
+        }
+    }
+    let collected = collected_fees_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let collected = sum_coins(collected.iter().chain(fee.iter()))?;
+    collected_fees(&mut deps.storage).save(&collected)
+}
 
-        let msg = InitMsg { count: 17 };
-        let env = mock_env(
-            &deps.api,
-            "creator",
-            &coin("2", "token"),
-            &coin("2", "token"),
-        );
-        let _res = init(&mut deps, env, msg).unwrap();
+pub fn try_set_fee<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    fee: Vec<Amount>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let fee: Vec<Coin> = coins_of(&fee, &state.chain)
+        .into_iter()
+        .filter(|c| c.amount != "0")
+        .collect();
+    if sum_coins(&fee)?.len() != fee.len() {
+        return contract_err("Fee lists the same denom twice");
+    }
+    state.reflect_fee = if fee.is_empty() { None } else { Some(fee) };
+    config(&mut deps.storage).save(&state)?;
 
-        // beneficiary can release it
-        let unauth_params = mock_env(&deps.api, "anyone", &coin("2", "token"), &[]);
-        let msg = HandleMsg::Reset { count: 5 };
-        let res = handle(&mut deps, unauth_params, msg);
-        match res {
-            Err(Error::Unauthorized { .. }) => {}
-            _ => panic!("Must return unauthorized error"),
+    let mut res = Response {
+        log: vec![log("action", "set_fee")],
+        ..Response::default()
+    };
+    for coin in state.reflect_fee.iter().flatten() {
+        res.log
+            .push(log("fee", &format!("{}{}", coin.amount, coin.denom)));
+    }
+    Ok(res)
+}
+
+pub fn try_withdraw_fees<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    to: HumanAddr,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    state.chain.validate_address(&to)?;
+    let collected = collected_fees_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    if collected.is_empty() {
+        return contract_err("No fees to withdraw");
+    }
+    collected_fees(&mut deps.storage).save(&vec![])?;
+
+    let send = CosmosMsg::Send {
+        from_address: deps.api.human_address(&env.contract.address)?,
+        to_address: to.clone(),
+        amount: collected.clone(),
+    };
+    let (mut res, dispatched) = dispatch(deps, &env, vec![send], vec![], None)?;
+    if let Dispatched::Queued(id) = dispatched {
+        let undo = Undo::CollectedFees { fees: collected };
+        amend_queued(deps, id, |q| q.undo = Some(undo))?;
+    }
+    res.log.insert(0, log("action", "withdraw_fees"));
+    res.log.insert(1, log("to", to.as_str()));
+    Ok(res)
+}
+
+pub fn try_set_rulesets<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    contracts: Vec<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if contracts.len() > MAX_RULESETS {
+        return dyn_contract_err(format!("At most {} rulesets are allowed", MAX_RULESETS));
+    }
+    let contract = deps.api.human_address(&env.contract.address)?;
+    for (i, addr) in contracts.iter().enumerate() {
+        state.chain.validate_address(addr)?;
+        if *addr == contract {
+            return contract_err("The contract cannot be its own ruleset");
+        }
+        if contracts[..i].contains(addr) {
+            return contract_err("Duplicate ruleset");
+        }
+    }
+    let mut res = Response {
+        log: vec![log("action", "set_rulesets")],
+        ..Response::default()
+    };
+    for addr in contracts.iter() {
+        res.log.push(log("contract", addr.as_str()));
+    }
+    state.rulesets = if contracts.is_empty() {
+        None
+    } else {
+        Some(contracts)
+    };
+    config(&mut deps.storage).save(&state)?;
+    Ok(res)
+}
+
+pub fn try_withdraw<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    to: HumanAddr,
+    amount: Option<Vec<Coin>>,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    state.chain.validate_address(&to)?;
+    let amount = match amount {
+        Some(amount) => sum_coins(&amount)?,
+        None => env.contract.balance.clone().unwrap_or_default(),
+    };
+    let amount: Vec<Coin> = amount.into_iter().filter(|c| c.amount != "0").collect();
+    if amount.is_empty() {
+        return contract_err("Nothing to withdraw");
+    }
+
+    let send = CosmosMsg::Send {
+        from_address: deps.api.human_address(&env.contract.address)?,
+        to_address: to.clone(),
+        amount,
+    };
+    let (mut res, _) = dispatch(deps, &env, vec![send], vec![], None)?;
+    res.log.insert(0, log("action", "withdraw"));
+    res.log.insert(1, log("to", to.as_str()));
+    Ok(res)
+}
+
+/// Most targets one ExecuteMany may call
+pub const MAX_TARGETS: usize = 25;
+
+pub fn try_execute_many<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    targets: Vec<HumanAddr>,
+    msg: Binary,
+    send: Option<Vec<Coin>>,
+    tags: Vec<String>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    state.validate_tags(&tags)?;
+    if targets.is_empty() || targets.len() > MAX_TARGETS {
+        return dyn_contract_err(format!("Between 1 and {} targets are allowed", MAX_TARGETS));
+    }
+    for (i, target) in targets.iter().enumerate() {
+        state.chain.validate_address(target)?;
+        if targets[..i].contains(target) {
+            return dyn_contract_err(format!("Duplicate target {}", target.as_str()));
         }
+    }
 
-        // only the original creator can reset the counter
-        let auth_params = mock_env(&deps.api, "creator", &coin("2", "token"), &[]);
-        let msg = HandleMsg::Reset { count: 5 };
-        let _res = handle(&mut deps, auth_params, msg).unwrap();
+    let msgs = targets
+        .iter()
+        .map(|target| CosmosMsg::Contract {
+            contract_addr: target.clone(),
+            msg: msg.clone(),
+            send: send.clone(),
+        })
+        .collect::<Vec<_>>();
+    let (mut res, dispatched) = dispatch(deps, &env, msgs, tags, None)?;
+    res.log.insert(0, log("action", "execute_many"));
+    res.log
+        .insert(1, log("targets", &targets.len().to_string()));
+    let result = ExecuteManyResult {
+        execution_id: dispatched.execution_id(),
+        targets,
+    };
+    res.data = Some(Binary(to_vec(&result).context(SerializeErr {
+        kind: "ExecuteManyResult",
+    })?));
+    Ok(res)
+}
+
+/// Most entries one RunPayroll may pay
+pub const MAX_PAYROLL_ENTRIES: usize = 50;
+
+pub fn try_run_payroll<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    entries: Vec<PayrollEntry>,
+    tag: String,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    let tags = vec![tag];
+    state.validate_tags(&tags)?;
+    if entries.is_empty() || entries.len() > MAX_PAYROLL_ENTRIES {
+        return dyn_contract_err(format!(
+            "Between 1 and {} payroll entries are allowed",
+            MAX_PAYROLL_ENTRIES
+        ));
+    }
+    for entry in entries.iter() {
+        state.chain.validate_address(&entry.recipient)?;
+        if entry.amount.is_empty() {
+            return dyn_contract_err(format!("Nothing to pay {}", entry.recipient.as_str()));
+        }
+    }
 
-        // should now be 5
-        let res = query(&deps, QueryMsg::GetCount {}).unwrap();
-        let value: CountResponse = from_slice(&res).unwrap();
-        assert_eq!(5, value.count);
+    let contract = deps.api.human_address(&env.contract.address)?;
+    let recipients = entries.len();
+    let mut msgs: Vec<CosmosMsg> = entries
+        .into_iter()
+        .map(|entry| CosmosMsg::Send {
+            from_address: contract.clone(),
+            to_address: entry.recipient,
+            amount: entry.amount,
+        })
+        .collect();
+    order_sends(&mut msgs);
+    let totals = payroll_totals(&msgs)?;
+    let (mut res, _) = dispatch(deps, &env, msgs, tags, None)?;
+    res.log.insert(0, log("action", "run_payroll"));
+    res.log
+        .insert(1, log("recipients", &recipients.to_string()));
+    for (i, total) in totals.iter().enumerate() {
+        res.log.insert(2 + i, log("total", total));
+    }
+    Ok(res)
+}
+
+/// Sums up the payroll per denom, ordered by denom, as "<amount><denom>"
+fn payroll_totals(msgs: &[CosmosMsg]) -> Result<Vec<String>> {
+    let mut denoms: Vec<&str> = vec![];
+    for msg in msgs.iter() {
+        if let CosmosMsg::Send { amount, .. } = msg {
+            for coin in amount.iter() {
+                if !denoms.contains(&coin.denom.as_str()) {
+                    denoms.push(&coin.denom);
+                }
+            }
+        }
+    }
+    denoms.sort();
+    denoms
+        .into_iter()
+        .map(|denom| Ok(format!("{}{}", sent_amount(msgs, denom)?, denom)))
+        .collect()
+}
+
+/// Puts the sends of a composite handle (payroll, distribution, evacuation) in their
+/// documented order: by recipient, and the coins of each send by denom.
+/// The sort is stable, so sends to the same recipient keep the order they were built in.
+fn order_sends(msgs: &mut [CosmosMsg]) {
+    for msg in msgs.iter_mut() {
+        if let CosmosMsg::Send { amount, .. } = msg {
+            amount.sort_by(|a, b| a.denom.cmp(&b.denom));
+        }
+    }
+    msgs.sort_by(|a, b| send_recipient(a).cmp(send_recipient(b)));
+}
+
+/// Fails with NotOwner for the signer of env
+fn not_owner<A: Api, T>(api: &A, env: &Env) -> Result<T> {
+    ContractError::NotOwner {
+        sender: api.human_address(&env.message.signer)?,
+    }
+    .fail()
+}
+
+fn send_recipient(msg: &CosmosMsg) -> &str {
+    match msg {
+        CosmosMsg::Send { to_address, .. } => to_address.as_str(),
+        _ => "",
+    }
+}
+
+/// What dispatch did with a batch
+enum Dispatched {
+    /// Sent out as the execution with this id
+    Executed(u64),
+    /// Held back for the co-signer as the queued execution with this id
+    Queued(u64),
+}
+
+impl Dispatched {
+    fn execution_id(&self) -> Option<u64> {
+        match self {
+            Dispatched::Executed(id) => Some(*id),
+            Dispatched::Queued(_) => None,
+        }
+    }
+}
+
+/// Runs the batch through the configured middlewares, returning the messages they let
+/// through and what they logged
+fn run_middlewares<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+    tags: &[String],
+) -> Result<(Vec<CosmosMsg>, Vec<LogAttribute>)> {
+    let state = config_read(&deps.storage).load()?;
+    let mut batch = Batch {
+        env,
+        execution_id: last_execution_id(&deps.storage)? + 1,
+        msgs,
+        tags,
+    };
+    let chain = middleware::chain(state.middlewares.as_ref().map_or(&[], |m| &m[..]));
+    let outcome = middleware::run(&chain, &mut batch)?;
+    if batch.msgs.is_empty() {
+        return contract_err("The middlewares left no message to reflect");
+    }
+    // rejections fail the call and roll back counting them, only the others stay counted
+    for (i, verdict) in outcome.verdicts.iter().enumerate() {
+        adjust_metric(
+            &mut deps.storage,
+            &middleware_metric(i, MIDDLEWARE_EVALUATIONS),
+            true,
+        )?;
+        if let Verdict::Shadowed { .. } = verdict {
+            let name = middleware_metric(i, MIDDLEWARE_SHADOW_BLOCKS);
+            adjust_metric(&mut deps.storage, &name, true)?;
+        }
+    }
+    Ok((batch.msgs, outcome.logs))
+}
+
+/// Emits the messages on behalf of the (already authorized) signer and records the execution.
+/// The batch runs through the middlewares first, and messages of four-eyes kinds are rejected,
+/// they must go through an approved proposal.
+/// Batches over a co-sign threshold are queued for the co-signer instead.
+fn dispatch<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+) -> Result<(Response, Dispatched)> {
+    let (msgs, logs) = run_middlewares(deps, env, msgs, &tags)?;
+    let (mut res, dispatched) = dispatch_chained(deps, env, msgs, tags, memo)?;
+    res.log.extend(logs);
+    Ok((res, dispatched))
+}
+
+/// Like dispatch, for a batch which already ran through the middlewares
+fn dispatch_chained<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+) -> Result<(Response, Dispatched)> {
+    let state = config_read(&deps.storage).load()?;
+    // checked before queueing, to fail now rather than on confirmation
+    check_four_eyes(&state, &msgs)?;
+    if needs_cosign(&state, &msgs)? {
+        return queue_for_cosign(deps, env, msgs, tags, memo);
+    }
+    let res = execute_batch(deps, env, msgs, tags, memo, None)?;
+    let id = last_execution_id(&deps.storage)?;
+    Ok((res, Dispatched::Executed(id)))
+}
+
+/// Like dispatch, for coins the contract owes whoever just paid it for them, such as the
+/// counterparty filling a swap. Those are never queued, as the co-signer could hold back
+/// the coins while the payment for them stays in the contract.
+fn dispatch_owed<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    payment: CosmosMsg,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    let msgs = vec![payment];
+    check_four_eyes(&state, &msgs)?;
+    dispatch_reviewed(deps, env, msgs, vec![], None, None)
+}
+
+/// Like dispatch, for messages a second person already approved.
+/// The intents they were checked against go into the audit log.
+/// Queued batches run through the middlewares again, as the chain may have changed since.
+fn dispatch_reviewed<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+    intents: Option<Vec<WasmIntent>>,
+) -> Result<Response> {
+    let (msgs, logs) = run_middlewares(deps, env, msgs, &tags)?;
+    let mut res = execute_batch(deps, env, msgs, tags, memo, intents)?;
+    res.log.extend(logs);
+    Ok(res)
+}
+
+/// Checks the batch against the policy, records the execution and emits the messages
+fn execute_batch<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+    intents: Option<Vec<WasmIntent>>,
+) -> Result<Response> {
+    // the contract acts as signer for approved admin proposals, so no one may make it call itself
+    let contract = deps.api.human_address(&env.contract.address)?;
+    for msg in msgs.iter() {
+        if let CosmosMsg::Contract { contract_addr, .. } = msg {
+            if *contract_addr == contract {
+                return contract_err("Cannot reflect messages to the contract itself");
+            }
+        }
+    }
+
+    let state = config_read(&deps.storage).load()?;
+    check_attestation(deps, env, &state)?;
+    check_targets(&state, &msgs)?;
+    for rule in check_rules(&state, &msgs)? {
+        adjust_metric(&mut deps.storage, &rule_metric(rule), true)?;
+    }
+    check_denylist(deps, &msgs)?;
+    check_travel_rule(&state, &msgs, &memo)?;
+    let msg_bytes = check_msg_bytes(&state, &msgs)?;
+    track_spend_limits(deps, env, &state, &msgs)?;
+    let mut checks = vec![];
+    for contract_addr in state.rulesets.iter().flatten() {
+        checks.push(ruleset_check(
+            deps,
+            env,
+            contract_addr,
+            &msgs,
+            &tags,
+            &memo,
+        )?);
+    }
+
+    let id = next_execution_id(&mut deps.storage)?;
+    let archived = match &state.archive {
+        Some(contract_addr) => Some(archive_msg(env, contract_addr, id, &msgs)?),
+        None => None,
+    };
+    let execution = Execution {
+        id,
+        sender: env.message.signer.clone(),
+        height: env.block.height,
+        msgs: count_kinds(&msgs),
+        gas_used: None,
+        tags,
+        memo,
+        payload_hash: archived.as_ref().map(|(hash, _)| hash.clone()),
+    };
+    executions(&mut deps.storage).save(&id.to_be_bytes(), &execution)?;
+    adjust_metric(&mut deps.storage, EXECUTIONS_TOTAL, true)?;
+    let msg_kinds = execution.msgs.clone();
+    record_history(deps, env, "reflect", msg_kinds, None, intents)?;
+    let sender = deps.api.human_address(&env.message.signer)?;
+    let mut logs = vec![
+        log("execution_id", &id.to_string()),
+        ref_log(EntityKind::Execution, id),
+        log("sender", sender.as_str()),
+        log("msg_count", &msgs.len().to_string()),
+        log("msg_bytes", &msg_bytes.to_string()),
+        log("config_checksum", &state.checksum()?),
+    ];
+    for tag in execution.tags.iter() {
+        let n = tag_counts_read(&deps.storage)
+            .may_load(tag.as_bytes())?
+            .unwrap_or(0);
+        tag_index(&mut deps.storage).save(&tag_index_key(tag, n), &id)?;
+        tag_counts(&mut deps.storage).save(tag.as_bytes(), &(n + 1))?;
+        if let Some(b) = state.budgets.iter().find(|b| b.tag == *tag) {
+            logs.extend(track_spend(deps, env, tag, &b.budget, &msgs)?);
+        }
+    }
+
+    // the checks go first, so the rules contracts see the balance before anything is moved
+    let archived = archived.map(|(_, msg)| msg);
+    let res = Response {
+        messages: checks.into_iter().chain(msgs).chain(archived).collect(),
+        log: logs,
+        data: None,
+    };
+    Ok(res)
+}
+
+/// Builds the message sending the batch to the archive, along with the hash of its payload
+fn archive_msg(
+    env: &Env,
+    contract_addr: &HumanAddr,
+    execution_id: u64,
+    msgs: &[CosmosMsg],
+) -> Result<(Binary, CosmosMsg)> {
+    let payload = to_vec(&msgs.to_vec()).context(SerializeErr { kind: "CosmosMsg" })?;
+    let hash = Binary(Sha256::digest(&payload).to_vec());
+    let archive = ArchiveMsg::Archive {
+        execution_id,
+        height: env.block.height,
+        payload: Binary(payload),
+    };
+    let msg = CosmosMsg::Contract {
+        contract_addr: contract_addr.clone(),
+        msg: Binary(to_vec(&archive).context(SerializeErr { kind: "ArchiveMsg" })?),
+        send: None,
+    };
+    Ok((hash, msg))
+}
+
+/// Builds the message asking the rules contract to check a batch
+fn ruleset_check<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    env: &Env,
+    contract_addr: &HumanAddr,
+    msgs: &[CosmosMsg],
+    tags: &[String],
+    memo: &Option<TravelMemo>,
+) -> Result<CosmosMsg> {
+    let check = RulesetMsg::CheckReflect {
+        sender: deps.api.human_address(&env.message.signer)?,
+        msgs: msgs.to_vec(),
+        tags: tags.to_vec(),
+        memo: memo.clone(),
+        height: env.block.height,
+        time: env.block.time,
+    };
+    Ok(CosmosMsg::Contract {
+        contract_addr: contract_addr.clone(),
+        msg: Binary(to_vec(&check).context(SerializeErr { kind: "RulesetMsg" })?),
+        send: None,
+    })
+}
+
+/// Adds what the messages send to the spend of the tag in this period.
+/// Reports crossing 80% and 100% of the budget, and fails when going over an enforced one.
+fn track_spend<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    tag: &str,
+    budget: &Budget,
+    msgs: &[CosmosMsg],
+) -> Result<Vec<LogAttribute>> {
+    let mut spend = match tag_spend_read(&deps.storage).may_load(tag.as_bytes())? {
+        Some(spend) if !spend.resets_at(budget.period).is_expired(env) => spend,
+        _ => TagSpend::starting(env),
+    };
+    let (before, after) = check_budget(tag, budget, &spend.spent, msgs)?;
+    spend.spent = after.to_string();
+    tag_spend(&mut deps.storage).save(tag.as_bytes(), &spend)?;
+
+    let limit = parse_amount(&budget.limit.amount)?;
+    let mut logs = vec![];
+    let warn_at = limit - limit / 5;
+    if before < warn_at && after >= warn_at {
+        logs.push(log("budget_warning", tag));
+    }
+    if before < limit && after >= limit {
+        logs.push(log("budget_reached", tag));
+    }
+    Ok(logs)
+}
+
+/// Appends an entry to the audit log
+fn record_history<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    action: &str,
+    msgs: Vec<KindCount>,
+    owner: Option<CanonicalAddr>,
+    intents: Option<Vec<WasmIntent>>,
+) -> Result<()> {
+    let seq = next_history_seq(&mut deps.storage)?;
+    let entry = HistoryEntry {
+        seq,
+        sender: env.message.signer.clone(),
+        height: env.block.height as u64,
+        action: action.to_string(),
+        msgs,
+        owner,
+        intents,
+    };
+    track_stats(&mut deps.storage, &entry)?;
+    history(&mut deps.storage).save(&seq.to_be_bytes(), &entry)
+}
+
+/// Counts a history entry into the stats of its sender and the global ones.
+/// Failed calls leave no trace in storage, so only successes are counted.
+fn track_stats<S: Storage>(storage: &mut S, entry: &HistoryEntry) -> Result<()> {
+    let key = entry.sender.as_slice();
+    let mut sender = stats_read(storage).may_load(key)?.unwrap_or_default();
+    let mut global = global_stats_read(storage).may_load()?.unwrap_or_default();
+    sender.last_height = entry.height;
+    if entry.action == "reflect" {
+        // statistics saturate rather than fail the execution they count
+        let msgs = entry.msgs.iter().map(|k| u64::from(k.count));
+        sender.reflections = sender.reflections.saturating_add(1);
+        global.msgs_reflected = msgs.fold(global.msgs_reflected, u64::saturating_add);
+    }
+    if entry.owner.is_some() {
+        global.owner_changes = global.owner_changes.saturating_add(1);
+    }
+    stats(storage).save(key, &sender)?;
+    global_stats(storage).save(&global)
+}
+
+/// Adds what the messages send to the spend of each limited denom in this period,
+/// failing if any goes over its limit
+fn track_spend_limits<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    state: &State,
+    msgs: &[CosmosMsg],
+) -> Result<()> {
+    for limit in state.spend_limits.iter().flatten() {
+        let denom = limit.limit.denom.as_bytes();
+        let since = limit.period_start(env.block.time as u64);
+        let spent = match spend_tracker_read(&deps.storage).may_load(denom)? {
+            Some(tracker) if tracker.since == since => tracker.spent,
+            _ => "0".to_string(),
+        };
+        let after = check_spend_limit(limit, &spent, msgs)?;
+        if after != parse_amount(&spent)? {
+            let tracker = SpendTracker {
+                since,
+                spent: after.to_string(),
+            };
+            spend_tracker(&mut deps.storage).save(denom, &tracker)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the spend after the messages, failing if it goes over the limit
+fn check_spend_limit(limit: &SpendLimit, spent: &str, msgs: &[CosmosMsg]) -> Result<u128> {
+    let before = parse_amount(spent)?;
+    let after = before.saturating_add(sent_amount(msgs, &limit.limit.denom)?);
+    if after > parse_amount(&limit.limit.amount)? {
+        return ContractError::SpendLimitExceeded {
+            limit: limit.limit.clone(),
+            period_secs: limit.period_secs,
+            spent: spent.to_string(),
+        }
+        .fail();
+    }
+    Ok(after)
+}
+
+/// Returns the serialized size of the messages, failing if it is over the limit
+fn check_msg_bytes(state: &State, msgs: &[CosmosMsg]) -> Result<u64> {
+    let mut size: u64 = 0;
+    for msg in msgs.iter() {
+        let len = to_vec(msg)
+            .context(SerializeErr { kind: "CosmosMsg" })?
+            .len() as u64;
+        size = size.saturating_add(len);
+    }
+    match state.max_msg_bytes {
+        Some(limit) if size > limit => ContractError::MessagesTooLarge { size, limit }.fail(),
+        _ => Ok(size),
+    }
+}
+
+/// Fails if a message calls a contract which is not on the allowlist
+fn check_targets(state: &State, msgs: &[CosmosMsg]) -> Result<()> {
+    if let Some(targets) = &state.allowed_targets {
+        for msg in msgs.iter() {
+            if let CosmosMsg::Contract { contract_addr, .. } = msg {
+                if !targets.contains(contract_addr) {
+                    return ContractError::TargetNotAllowed {
+                        contract: contract_addr.clone(),
+                    }
+                    .fail();
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fails on the first message whose first matching rule denies it.
+/// Returns the rules which allowed a message, once for each message.
+fn check_rules(state: &State, msgs: &[CosmosMsg]) -> Result<Vec<usize>> {
+    let rules = match &state.rules {
+        Some(rules) => rules,
+        None => return Ok(vec![]),
+    };
+    let mut allowed_by = vec![];
+    for (i, msg) in msgs.iter().enumerate() {
+        for (j, rule) in rules.iter().enumerate() {
+            if !rule.matches(msg, &state.chain)? {
+                continue;
+            }
+            if rule.action == RuleAction::Deny {
+                return ContractError::RuleDenied {
+                    msg: i as u32,
+                    rule: j as u32,
+                }
+                .fail();
+            }
+            allowed_by.push(j);
+            break;
+        }
+    }
+    Ok(allowed_by)
+}
+
+/// Fails if a message is of a kind which needs a second approver
+fn check_four_eyes(state: &State, msgs: &[CosmosMsg]) -> Result<()> {
+    let kinds = match &state.four_eyes {
+        Some(kinds) => kinds,
+        None => return Ok(()),
+    };
+    match msgs.iter().map(MsgKind::of).find(|k| kinds.contains(k)) {
+        Some(kind) => ContractError::SecondApproverRequired { kind }.fail(),
+        None => Ok(()),
+    }
+}
+
+/// Fails if a message sends funds to an address on the deny-list.
+/// Each recipient is looked up once, however many messages pay it.
+fn check_denylist<S: Storage, A: Api>(deps: &Extern<S, A>, msgs: &[CosmosMsg]) -> Result<()> {
+    let mut screened: Vec<&HumanAddr> = vec![];
+    for msg in msgs.iter() {
+        if let CosmosMsg::Send { to_address, .. } = msg {
+            if screened.contains(&to_address) {
+                continue;
+            }
+            screened.push(to_address);
+            let addr = deps.api.canonical_address(to_address)?;
+            if denylist_read(&deps.storage).may_load(addr.as_slice())? == Some(true) {
+                return ContractError::RecipientDenied {
+                    recipient: to_address.clone(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fails if a message sends more than a travel rule threshold without a memo
+fn check_travel_rule(state: &State, msgs: &[CosmosMsg], memo: &Option<TravelMemo>) -> Result<()> {
+    let thresholds = match (&state.travel_rule, memo) {
+        (Some(thresholds), None) => thresholds,
+        _ => return Ok(()),
+    };
+    for msg in msgs.iter() {
+        for threshold in thresholds.iter() {
+            let sent = sent_amount(std::slice::from_ref(msg), &threshold.denom)?;
+            if sent > parse_amount(&threshold.amount)? {
+                return ContractError::MemoRequired {
+                    threshold: threshold.clone(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the spend before and after the messages, failing if they go over an enforced budget
+fn check_budget(
+    tag: &str,
+    budget: &Budget,
+    spent: &str,
+    msgs: &[CosmosMsg],
+) -> Result<(u128, u128)> {
+    let limit = parse_amount(&budget.limit.amount)?;
+    let before = parse_amount(spent)?;
+    let after = before.saturating_add(sent_amount(msgs, &budget.limit.denom)?);
+    if budget.enforce && after > limit {
+        return ContractError::BudgetExceeded {
+            tag: tag.to_string(),
+            limit: budget.limit.clone(),
+            spent: spent.to_string(),
+        }
+        .fail();
+    }
+    Ok((before, after))
+}
+
+pub fn try_set_operators<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    operators: Vec<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let mut canonical = BoundedVec::new(OPERATORS);
+    for addr in operators.iter() {
+        state.chain.validate_address(addr)?;
+        let addr = deps.api.canonical_address(addr)?;
+        if !canonical.contains(&addr) {
+            canonical.push(addr)?;
+        }
+    }
+    state.operators = canonical.into_vec();
+    state.prune_suspended();
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_operators"),
+            log("operators", &state.operators.len().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_apply_operator_manifest<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    operators: Vec<OperatorSpec>,
+    manifest_nonce: u64,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    // an older (or the same) manifest must never roll back a later review
+    if manifest_nonce <= state.operator_manifest_nonce {
+        return contract_err("Manifest nonce must be greater than the last applied one");
+    }
+    let mut canonical = BoundedVec::new(OPERATORS);
+    for spec in operators.iter() {
+        state.chain.validate_address(&spec.addr)?;
+        let addr = deps.api.canonical_address(&spec.addr)?;
+        if canonical.contains(&addr) {
+            return contract_err("Duplicate operator in manifest");
+        }
+        canonical.push(addr)?;
+    }
+    state.operators = canonical.into_vec();
+    state.operator_manifest_nonce = manifest_nonce;
+    state.prune_suspended();
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "apply_operator_manifest"),
+            log("manifest_nonce", &manifest_nonce.to_string()),
+            log("operators", &state.operators.len().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+/// Fails if the signer is an operator acting outside its execution window or calendar
+fn check_operator_window<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    env: &Env,
+    state: &State,
+) -> Result<()> {
+    let signer = &env.message.signer;
+    if state.is_owner(signer) {
+        return Ok(());
+    }
+    if let Some(Some(window)) = operator_windows_read(&deps.storage).may_load(signer.as_slice())? {
+        if !window.is_open(env) {
+            return ContractError::OutsideWindow {
+                operator: deps.api.human_address(signer)?,
+            }
+            .fail();
+        }
+    }
+    match operator_calendars_read(&deps.storage).may_load(signer.as_slice())? {
+        Some(Some(calendar)) => check_calendar(deps, env, &calendar),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if the calendar is closed at the block time
+fn check_calendar<S: Storage, A: Api>(deps: &Extern<S, A>, env: &Env, name: &str) -> Result<()> {
+    let calendar = calendars_read(&deps.storage).load(name.as_bytes())?;
+    if !calendar.is_open(env.block.time as u64) {
+        return ContractError::CalendarClosed {
+            calendar: name.to_string(),
+        }
+        .fail();
+    }
+    Ok(())
+}
+
+pub fn try_set_calendar<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    name: String,
+    allowed: Vec<TimeRange>,
+    blackouts: Vec<TimeRange>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    validate_calendar_name(&name)?;
+    let calendar = Calendar { allowed, blackouts };
+    calendar.validate()?;
+    calendars(&mut deps.storage).save(name.as_bytes(), &calendar)?;
+
+    Ok(Response {
+        log: vec![log("action", "set_calendar"), log("calendar", &name)],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_operator_calendar<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    operator: HumanAddr,
+    calendar: Option<String>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let addr = deps.api.canonical_address(&operator)?;
+    if !state.operators.contains(&addr) {
+        return dyn_contract_err(format!("{} is not an operator", operator.as_str()));
+    }
+    if let Some(name) = &calendar {
+        require_calendar(deps, name)?;
+    }
+    operator_calendars(&mut deps.storage).save(addr.as_slice(), &calendar)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_operator_calendar"),
+            log("operator", operator.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+fn require_calendar<S: Storage, A: Api>(deps: &Extern<S, A>, name: &str) -> Result<()> {
+    match calendars_read(&deps.storage).may_load(name.as_bytes())? {
+        Some(_) => Ok(()),
+        None => dyn_contract_err(format!("Unknown calendar {}", name)),
+    }
+}
+
+pub fn try_set_operator_window<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    operator: HumanAddr,
+    window: Option<ExecutionWindow>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let addr = deps.api.canonical_address(&operator)?;
+    if !state.operators.contains(&addr) {
+        return dyn_contract_err(format!("{} is not an operator", operator.as_str()));
+    }
+    if let Some(window) = &window {
+        window.validate()?;
+    }
+    operator_windows(&mut deps.storage).save(addr.as_slice(), &window)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_operator_window"),
+            log("operator", operator.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_suspend_operator<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    operator: HumanAddr,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let addr = deps.api.canonical_address(&operator)?;
+    if !state.operators.contains(&addr) {
+        return dyn_contract_err(format!("{} is not an operator", operator.as_str()));
+    }
+    if !state.is_active_operator(&addr) {
+        return dyn_contract_err(format!("{} is already suspended", operator.as_str()));
+    }
+    state
+        .suspended_operators
+        .get_or_insert_with(Vec::new)
+        .push(addr);
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "suspend_operator"),
+            log("operator", operator.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_reinstate_operator<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    operator: HumanAddr,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let addr = deps.api.canonical_address(&operator)?;
+    if !state
+        .suspended_operators
+        .iter()
+        .flatten()
+        .any(|a| *a == addr)
+    {
+        return dyn_contract_err(format!("{} is not suspended", operator.as_str()));
+    }
+    if let Some(suspended) = state.suspended_operators.as_mut() {
+        suspended.retain(|a| *a != addr);
+    }
+    state.prune_suspended();
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "reinstate_operator"),
+            log("operator", operator.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_tags<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    tags: Vec<String>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    for tag in tags.iter() {
+        validate_tag_name(tag)?;
+    }
+    state.tags = vec![];
+    for tag in tags.into_iter() {
+        if !state.tags.contains(&tag) {
+            state.tags.push(tag);
+        }
+    }
+    let known = state.tags.clone();
+    state.budgets.retain(|b| known.contains(&b.tag));
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_tags"),
+            log("tags", &state.tags.len().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_tag_budget<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    tag: String,
+    budget: Option<Budget>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.validate_tags(&[tag.clone()])?;
+    state.budgets.retain(|b| b.tag != tag);
+    if let Some(budget) = budget {
+        budget.validate()?;
+        state.budgets.push(TagBudget {
+            tag: tag.clone(),
+            budget,
+        });
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![log("action", "set_tag_budget"), log("tag", &tag)],
+        ..Response::default()
+    })
+}
+
+pub fn try_stage_batch<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msgs: Vec<CosmosMsg>,
+    memo: String,
+    receipt_contract: Option<HumanAddr>,
+    tags: Vec<String>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_active_operator(&env.message.signer) {
+        return unauthorized();
+    }
+    check_operator_window(deps, &env, &state)?;
+    if msgs.is_empty() {
+        return contract_err("Cannot stage an empty batch");
+    }
+    if memo.len() > MAX_BATCH_MEMO_LEN {
+        return dyn_contract_err(format!(
+            "Memo must be at most {} characters",
+            MAX_BATCH_MEMO_LEN
+        ));
+    }
+    validate_plain_text("Memo", &memo)?;
+    state.validate_tags(&tags)?;
+    if let Some(addr) = &receipt_contract {
+        state.chain.validate_address(addr)?;
+        if *addr == deps.api.human_address(&env.contract.address)? {
+            return contract_err("Cannot send receipts to the contract itself");
+        }
+    }
+
+    let id = next_staged_batch_id(&mut deps.storage)?;
+    let batch = StagedBatch {
+        id,
+        operator: env.message.signer,
+        msgs,
+        memo,
+        receipt_contract,
+        tags,
+        staged_height: env.block.height,
+        executed: false,
+    };
+    staged_batches(&mut deps.storage).save(&id.to_be_bytes(), &batch)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, true)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "stage_batch"),
+            log("batch_id", &id.to_string()),
+            ref_log(EntityKind::StagedBatch, id),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_execute_staged<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    let mut batch = found(
+        staged_batches(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::StagedBatch,
+        id,
+    )?;
+    if batch.executed {
+        return contract_err("Batch was already executed");
+    }
+    batch.executed = true;
+    staged_batches(&mut deps.storage).save(&id.to_be_bytes(), &batch)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, false)?;
+
+    let (mut res, dispatched) = dispatch(deps, &env, batch.msgs, batch.tags, None)?;
+    res.log.insert(0, log("action", "execute_staged"));
+    res.log.insert(1, log("batch_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::StagedBatch, id));
+    if let Some(contract_addr) = batch.receipt_contract {
+        let receipt = PendingReceipt {
+            contract_addr,
+            batch_id: id,
+        };
+        match dispatched {
+            Dispatched::Executed(execution_id) => {
+                res.messages.push(receipt_msg(&env, receipt, execution_id)?);
+            }
+            // a batch held back for the co-signer reports once it is confirmed
+            Dispatched::Queued(queued_id) => {
+                amend_queued(deps, queued_id, |q| q.receipt = Some(receipt))?;
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// Builds the message reporting the execution of a staged batch to its receipt contract
+fn receipt_msg(env: &Env, receipt: PendingReceipt, execution_id: u64) -> Result<CosmosMsg> {
+    let msg = ReceiptMsg::ExecutionReceipt {
+        batch_id: receipt.batch_id,
+        execution_id,
+        height: env.block.height,
+    };
+    Ok(CosmosMsg::Contract {
+        contract_addr: receipt.contract_addr,
+        msg: Binary(to_vec(&msg).context(SerializeErr { kind: "ReceiptMsg" })?),
+        send: None,
+    })
+}
+
+pub fn try_schedule<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msgs: Vec<CosmosMsg>,
+    execute_after: Expiration,
+    calendar: Option<String>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    if msgs.is_empty() {
+        return contract_err("Cannot schedule an empty batch");
+    }
+    if execute_after.is_expired(&env) {
+        return contract_err("Must schedule for the future");
+    }
+    if let Some(name) = &calendar {
+        require_calendar(deps, name)?;
+    }
+
+    let id = next_scheduled_batch_id(&mut deps.storage)?;
+    let batch = ScheduledBatch {
+        id,
+        owner: env.message.signer,
+        msgs,
+        execute_after,
+        closed: false,
+        calendar,
+    };
+    scheduled_batches(&mut deps.storage).save(&id.to_be_bytes(), &batch)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, true)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "schedule"),
+            log("batch_id", &id.to_string()),
+            ref_log(EntityKind::ScheduledBatch, id),
+            log("execute_after", &execute_after.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_execute_scheduled<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let mut batch = found(
+        scheduled_batches(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::ScheduledBatch,
+        id,
+    )?;
+    if batch.closed {
+        return contract_err("Scheduled batch was already executed or cancelled");
+    }
+    if !batch.execute_after.is_expired(&env) {
+        return dyn_contract_err(format!(
+            "Batch cannot execute before {}",
+            batch.execute_after
+        ));
+    }
+    if let Some(name) = &batch.calendar {
+        check_calendar(deps, &env, name)?;
+    }
+    batch.closed = true;
+    scheduled_batches(&mut deps.storage).save(&id.to_be_bytes(), &batch)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, false)?;
+
+    // anyone may run a due batch, it goes out as sent by the owner who scheduled it
+    let mut owner_env = env;
+    owner_env.message.signer = batch.owner;
+    let (mut res, _) = dispatch(deps, &owner_env, batch.msgs, vec![], None)?;
+    res.log.insert(0, log("action", "execute_scheduled"));
+    res.log.insert(1, log("batch_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::ScheduledBatch, id));
+    Ok(res)
+}
+
+pub fn try_cancel_scheduled<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    let mut batch = found(
+        scheduled_batches(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::ScheduledBatch,
+        id,
+    )?;
+    if batch.closed {
+        return contract_err("Scheduled batch was already executed or cancelled");
+    }
+    batch.closed = true;
+    scheduled_batches(&mut deps.storage).save(&id.to_be_bytes(), &batch)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, false)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "cancel_scheduled"),
+            log("batch_id", &id.to_string()),
+            ref_log(EntityKind::ScheduledBatch, id),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_propose<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msgs: Vec<CosmosMsg>,
+    description: String,
+    intents: Option<Vec<WasmIntent>>,
+) -> Result<Response> {
+    if msgs.is_empty() {
+        return contract_err("Cannot propose an empty batch");
+    }
+    if let Some(intents) = &intents {
+        for intent in intents.iter() {
+            intent.validate()?;
+        }
+        check_intents(&msgs, intents)?;
+    }
+    if description.trim().is_empty() || description.len() > MAX_DESCRIPTION_LEN {
+        return dyn_contract_err(format!(
+            "Description must be 1 to {} characters",
+            MAX_DESCRIPTION_LEN
+        ));
+    }
+    validate_plain_text("Description", &description)?;
+
+    let id = next_proposal_id(&mut deps.storage)?;
+    let proposal = Proposal {
+        id,
+        proposer: env.message.signer,
+        msgs,
+        description,
+        status: ProposalStatus::Pending,
+        intents,
+    };
+    proposals(&mut deps.storage).save(&id.to_be_bytes(), &proposal)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, true)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "propose"),
+            log("proposal_id", &id.to_string()),
+            ref_log(EntityKind::Proposal, id),
+        ],
+        ..Response::default()
+    })
+}
+
+/// Fails unless each contract call matches the intent declared for it, in order
+fn check_intents(msgs: &[CosmosMsg], intents: &[WasmIntent]) -> Result<()> {
+    let calls: Vec<&CosmosMsg> = msgs
+        .iter()
+        .filter(|m| MsgKind::of(m) == MsgKind::Contract)
+        .collect();
+    if calls.len() != intents.len() {
+        return dyn_contract_err(format!(
+            "Declared {} intents for {} contract calls",
+            intents.len(),
+            calls.len()
+        ));
+    }
+    for (msg, intent) in calls.into_iter().zip(intents.iter()) {
+        if let Some(detail) = intent.mismatch(msg)? {
+            return ContractError::IntentMismatch {
+                contract: intent.contract_addr.clone(),
+                detail,
+            }
+            .fail();
+        }
+    }
+    Ok(())
+}
+
+/// Loads a proposal an owner may still decide on
+fn load_pending_proposal<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    env: &Env,
+    id: u64,
+) -> Result<Proposal> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, env);
+    }
+    let proposal = found(
+        proposals_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Proposal,
+        id,
+    )?;
+    if proposal.status != ProposalStatus::Pending {
+        return contract_err("Proposal was already approved or rejected");
+    }
+    Ok(proposal)
+}
+
+pub fn try_approve<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let mut proposal = load_pending_proposal(deps, &env, id)?;
+    let state = config_read(&deps.storage).load()?;
+    if proposal.proposer == env.message.signer && check_four_eyes(&state, &proposal.msgs).is_err() {
+        return contract_err("Proposal needs an approver other than its proposer");
+    }
+    proposal.status = ProposalStatus::Approved;
+    proposals(&mut deps.storage).save(&id.to_be_bytes(), &proposal)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, false)?;
+
+    if let Some(intents) = &proposal.intents {
+        check_intents(&proposal.msgs, intents)?;
+    }
+    let intents = proposal.intents;
+    let mut res = dispatch_reviewed(deps, &env, proposal.msgs, vec![], None, intents)?;
+    res.log.insert(0, log("action", "approve"));
+    res.log.insert(1, log("proposal_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::Proposal, id));
+    Ok(res)
+}
+
+pub fn try_reject<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let mut proposal = load_pending_proposal(deps, &env, id)?;
+    proposal.status = ProposalStatus::Rejected;
+    proposals(&mut deps.storage).save(&id.to_be_bytes(), &proposal)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, false)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "reject"),
+            log("proposal_id", &id.to_string()),
+            ref_log(EntityKind::Proposal, id),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_deposit<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    tag: String,
+) -> Result<Response> {
+    validate_tag_name(&tag)?;
+    let sent = sum_coins(env.message.sent_funds.iter().flatten())?;
+    if sent.is_empty() {
+        return contract_err("Must deposit some funds");
+    }
+
+    let period = revenue_period(env.block.time as u64);
+    let key = tag_index_key(&tag, period);
+    let mut bucket = revenue(&mut deps.storage);
+    let before = bucket.may_load(&key)?.unwrap_or_default();
+    bucket.save(&key, &sum_coins(before.iter().chain(sent.iter()))?)?;
+
+    let mut res = Response {
+        log: vec![
+            log("action", "deposit"),
+            log("tag", &tag),
+            log("period", &period.to_string()),
+        ],
+        ..Response::default()
+    };
+    for coin in sent.iter() {
+        res.log
+            .push(log("amount", &format!("{}{}", coin.amount, coin.denom)));
+    }
+    if let Some(contract_addr) = config_read(&deps.storage).load()?.deposit_hook {
+        let receipt = ReceiptMsg::DepositReceipt {
+            sender: deps.api.human_address(&env.message.signer)?,
+            amount: sent,
+            tag,
+        };
+        res.messages.push(CosmosMsg::Contract {
+            contract_addr,
+            msg: Binary(to_vec(&receipt).context(SerializeErr { kind: "ReceiptMsg" })?),
+            send: None,
+        });
+    }
+    Ok(res)
+}
+
+pub fn try_set_deposit_hook<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    contract: Option<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if let Some(addr) = &contract {
+        state.chain.validate_address(addr)?;
+        if *addr == deps.api.human_address(&env.contract.address)? {
+            return contract_err("Cannot send receipts to the contract itself");
+        }
+    }
+    state.deposit_hook = contract;
+    config(&mut deps.storage).save(&state)?;
+
+    let hook = state
+        .deposit_hook
+        .as_ref()
+        .map(|h| h.as_str())
+        .unwrap_or("");
+    Ok(Response {
+        log: vec![log("action", "set_deposit_hook"), log("contract", hook)],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_travel_rule<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    thresholds: Option<Vec<Amount>>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let thresholds = thresholds.map(|t| coins_of(&t, &state.chain));
+    if let Some(thresholds) = &thresholds {
+        if thresholds.is_empty() {
+            return contract_err("Travel rule needs at least one threshold");
+        }
+        // one threshold per denom
+        if sum_coins(thresholds)?.len() != thresholds.len() {
+            return contract_err("Travel rule has two thresholds for the same denom");
+        }
+    }
+    state.travel_rule = thresholds;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Response {
+        log: vec![log("action", "set_travel_rule")],
+        ..Response::default()
+    };
+    for threshold in state.travel_rule.iter().flatten() {
+        let value = format!("{}{}", threshold.amount, threshold.denom);
+        res.log.push(log("threshold", &value));
+    }
+    Ok(res)
+}
+
+pub fn try_set_rules<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    rules: Vec<Rule>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if rules.len() > MAX_RULES {
+        return dyn_contract_err(format!("At most {} rules are allowed", MAX_RULES));
+    }
+    for rule in rules.iter() {
+        rule.validate()?;
+        if let Some(target) = &rule.target {
+            state.chain.validate_address(target)?;
+        }
+    }
+    let count = rules.len();
+    state.rules = if rules.is_empty() { None } else { Some(rules) };
+    config(&mut deps.storage).save(&state)?;
+    reset_metrics(&mut deps.storage, (0..count).map(rule_metric))?;
+
+    Ok(Response {
+        log: vec![log("action", "set_rules"), log("rules", &count.to_string())],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_middlewares<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    middlewares: Vec<MiddlewareConfig>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if middlewares.len() > MAX_MIDDLEWARES {
+        return dyn_contract_err(format!(
+            "At most {} middlewares are allowed",
+            MAX_MIDDLEWARES
+        ));
+    }
+    for middleware in middlewares.iter() {
+        middleware.validate(&state.chain)?;
+    }
+    let count = middlewares.len();
+    state.middlewares = if middlewares.is_empty() {
+        None
+    } else {
+        Some(middlewares)
+    };
+    config(&mut deps.storage).save(&state)?;
+    let counters = [MIDDLEWARE_EVALUATIONS, MIDDLEWARE_SHADOW_BLOCKS];
+    let names = (0..count).flat_map(|i| counters.iter().map(move |c| middleware_metric(i, c)));
+    reset_metrics(&mut deps.storage, names)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_middlewares"),
+            log("middlewares", &count.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_attest<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    checksum: String,
+    signer: String,
+    signature: Binary,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    if checksum != state.checksum()? {
+        return contract_err("Can only attest the active configuration");
+    }
+    if signer.is_empty() || signature.0.is_empty() {
+        return contract_err("The attestation needs a signer and a signature");
+    }
+    let signed = Attestation {
+        checksum,
+        signer,
+        signature,
+        height: env.block.height as u64,
+        time: env.block.time as u64,
+    };
+    attestation(&mut deps.storage).save(&signed)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "attest"),
+            log("signer", &signed.signer),
+            log("config_checksum", &signed.checksum),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_require_attestation<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    max_age: Option<Duration>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.attestation_max_age = max_age;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "require_attestation"),
+            log("required", &max_age.is_some().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_archive<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    contract: Option<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let mut res = Response {
+        log: vec![log("action", "set_archive")],
+        ..Response::default()
+    };
+    if let Some(addr) = &contract {
+        state.chain.validate_address(addr)?;
+        if *addr == deps.api.human_address(&env.contract.address)? {
+            return contract_err("The contract cannot be its own archive");
+        }
+        res.log.push(log("contract", addr.as_str()));
+    }
+    state.archive = contract;
+    config(&mut deps.storage).save(&state)?;
+    Ok(res)
+}
+
+pub fn try_set_gas_heuristics<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    heuristics: Vec<GasHeuristic>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    for (i, heuristic) in heuristics.iter().enumerate() {
+        if heuristic.gas_per_msg == 0 {
+            return contract_err("Gas per message must be above zero");
+        }
+        if heuristics[..i].iter().any(|h| h.kind == heuristic.kind) {
+            return dyn_contract_err(format!(
+                "Duplicate heuristic for {}",
+                heuristic.kind.as_str()
+            ));
+        }
+    }
+    let count = heuristics.len();
+    state.gas_heuristics = if heuristics.is_empty() {
+        None
+    } else {
+        Some(heuristics)
+    };
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_gas_heuristics"),
+            log("heuristics", &count.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+/// What ExecuteWithLookup replaces in its template
+pub const LOOKUP_PLACEHOLDER: &str = "$lookup";
+
+pub fn try_execute_with_lookup<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    lookup: Lookup,
+    template: CosmosMsg,
+    tags: Vec<String>,
+    memo: Option<TravelMemo>,
+) -> Result<Response> {
+    // queries are public, try_reflect decides who may reflect the result
+    let data = query(deps, lookup.query)?;
+    // nothing at the pointer, eg. no coin of the denom yet, is as empty as null
+    let looked_up = pointer::find(&data, &lookup.pointer)?.unwrap_or(b"null");
+    let mut value = match &lookup.expr {
+        Some(_) if is_empty_lookup(looked_up) => looked_up.to_vec(),
+        Some(expr) => {
+            let x = parse_amount(&lookup_amount(looked_up)?)?;
+            format!("\"{}\"", math::eval(expr, x)?).into_bytes()
+        }
+        None => looked_up.to_vec(),
+    };
+    if is_empty_lookup(&value) {
+        match lookup.on_empty.unwrap_or(OnEmpty::Abort {}) {
+            OnEmpty::Abort {} => {
+                return ContractError::EmptyLookup {
+                    placeholder: LOOKUP_PLACEHOLDER.to_string(),
+                    value: String::from_utf8_lossy(&value).into_owned(),
+                }
+                .fail();
+            }
+            OnEmpty::Skip {} => {
+                return Ok(Response {
+                    log: vec![
+                        log("action", "execute_with_lookup"),
+                        log("looked_up", &String::from_utf8_lossy(looked_up)),
+                        log("skipped", "empty"),
+                    ],
+                    ..Response::default()
+                });
+            }
+            OnEmpty::Default { amount } => {
+                parse_amount(&amount)?;
+                value = format!("\"{}\"", amount).into_bytes();
+            }
+        }
+    }
+    let msg = fill_template(template, &value)?;
+    let mut res = try_reflect(deps, env, vec![msg], tags, memo)?;
+    res.log[0] = log("action", "execute_with_lookup");
+    res.log
+        .insert(1, log("looked_up", &String::from_utf8_lossy(looked_up)));
+    if value != looked_up {
+        res.log
+            .insert(2, log("evaluated", &String::from_utf8_lossy(&value)));
+    }
+    Ok(res)
+}
+
+/// Whether a looked up value is null, zero or empty, and so nothing worth sending
+fn is_empty_lookup(value: &[u8]) -> bool {
+    let empty: &[&[u8]] = &[b"null", b"0", b"\"0\"", b"\"\"", b"[]", b"{}"];
+    empty.contains(&value)
+}
+
+/// The digits of a looked up amount, serialized as a JSON string or, for counts, a number
+fn lookup_amount(value: &[u8]) -> Result<String> {
+    let quoted = value.len() >= 2 && value[0] == b'"' && value[value.len() - 1] == b'"';
+    let digits = if quoted {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+    match std::str::from_utf8(digits) {
+        Ok(amount) if !amount.is_empty() && amount.bytes().all(|b| b.is_ascii_digit()) => {
+            Ok(amount.to_string())
+        }
+        _ => dyn_contract_err(format!("Not an amount: {}", String::from_utf8_lossy(value))),
+    }
+}
+
+/// The template with value in place of every LOOKUP_PLACEHOLDER, which it must have
+pub fn fill_template(template: CosmosMsg, value: &[u8]) -> Result<CosmosMsg> {
+    if let CosmosMsg::Opaque { .. } = template {
+        return contract_err("Opaque messages cannot take a lookup");
+    }
+    match fill_placeholder(template, LOOKUP_PLACEHOLDER, value)? {
+        (msg, true) => Ok(msg),
+        (_, false) => dyn_contract_err(format!("The template has no {}", LOOKUP_PLACEHOLDER)),
+    }
+}
+
+/// The message with value in place of placeholder in its coin amounts, and of the JSON string
+/// placeholder in the msg of a contract call. Also whether there was anything to replace.
+fn fill_placeholder(msg: CosmosMsg, placeholder: &str, value: &[u8]) -> Result<(CosmosMsg, bool)> {
+    let mut filled = false;
+    let mut fill_coins = |coins: &mut Vec<Coin>| -> Result<()> {
+        for coin in coins.iter_mut().filter(|c| c.amount == placeholder) {
+            coin.amount = lookup_amount(value)?;
+            filled = true;
+        }
+        Ok(())
+    };
+    let msg = match msg {
+        CosmosMsg::Send {
+            from_address,
+            to_address,
+            mut amount,
+        } => {
+            fill_coins(&mut amount)?;
+            CosmosMsg::Send {
+                from_address,
+                to_address,
+                amount,
+            }
+        }
+        CosmosMsg::Contract {
+            contract_addr,
+            msg,
+            mut send,
+        } => {
+            if let Some(send) = send.as_mut() {
+                fill_coins(send)?;
+            }
+            let placeholder = format!("\"{}\"", placeholder).into_bytes();
+            let mut out = Vec::with_capacity(msg.0.len());
+            let mut rest = msg.as_slice();
+            while !rest.is_empty() {
+                if rest.starts_with(&placeholder) {
+                    out.extend_from_slice(value);
+                    rest = &rest[placeholder.len()..];
+                    filled = true;
+                } else {
+                    out.push(rest[0]);
+                    rest = &rest[1..];
+                }
+            }
+            CosmosMsg::Contract {
+                contract_addr,
+                msg: Binary(out),
+                send,
+            }
+        }
+        msg @ CosmosMsg::Opaque { .. } => msg,
+    };
+    Ok((msg, filled))
+}
+
+pub fn try_if_config_version<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    expected: u64,
+    msg: HandleMsg,
+) -> Result<Response> {
+    let current = config_version_read(&deps.storage).may_load()?.unwrap_or(0);
+    if current != expected {
+        return ContractError::ConfigChanged { expected, current }.fail();
+    }
+    handle_inner(deps, env, msg)
+}
+
+/// The data key a SelfTest writes and removes again
+pub const SELF_TEST_KEY: &str = "self_test";
+
+/// Runs each probe on staged storage, so nothing is kept. Like a dry run, handle runs it
+/// before anything else, and its probes go through every check a real call does.
+pub fn try_self_test<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    echo: Option<HumanAddr>,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    if let Some(echo) = &echo {
+        state.chain.validate_address(echo)?;
+    }
+    let ping = to_vec(&EchoMsg::Ping {
+        height: env.block.height,
+    })
+    .context(SerializeErr { kind: "EchoMsg" })?;
+    // the contract may not call itself, so without an echo contract it sends itself nothing
+    let contract = deps.api.human_address(&env.contract.address)?;
+    let probe = match &echo {
+        Some(echo) => CosmosMsg::Contract {
+            contract_addr: echo.clone(),
+            msg: Binary(ping.clone()),
+            send: None,
+        },
+        None => CosmosMsg::Send {
+            from_address: contract.clone(),
+            to_address: contract,
+            amount: vec![],
+        },
+    };
+    // a batch cannot be scheduled for a point which has passed, so it is triggered a block later
+    let mut later = env.clone();
+    later.block.height += 1;
+    let next_batch = last_scheduled_batch_id(&deps.storage)? + 1;
+    let probes = vec![
+        (
+            "reflect",
+            vec![(
+                env.clone(),
+                HandleMsg::ReflectMsg {
+                    msgs: vec![probe.clone()],
+                    tags: vec![],
+                    memo: None,
+                },
+            )],
+        ),
+        (
+            "schedule",
+            vec![
+                (
+                    env.clone(),
+                    HandleMsg::Schedule {
+                        msgs: vec![probe.clone()],
+                        execute_after: Expiration::AtHeight(later.block.height as u64),
+                        calendar: None,
+                    },
+                ),
+                (later, HandleMsg::ExecuteScheduled { id: next_batch }),
+            ],
+        ),
+        (
+            "data",
+            vec![
+                (
+                    env.clone(),
+                    HandleMsg::SetData {
+                        key: SELF_TEST_KEY.to_string(),
+                        value: Binary(ping),
+                    },
+                ),
+                (
+                    env.clone(),
+                    HandleMsg::RemoveData {
+                        key: SELF_TEST_KEY.to_string(),
+                    },
+                ),
+            ],
+        ),
+    ];
+
+    let mut res = Response {
+        log: vec![log("action", "self_test")],
+        ..Response::default()
+    };
+    let mut runs = vec![];
+    for (subsystem, calls) in probes {
+        let ran = staging::staged(deps, |deps| {
+            for (env, msg) in calls {
+                handle_inner(deps, env, msg)?;
+            }
+            Ok(())
+        });
+        runs.push((subsystem, ran));
+    }
+    // no handle writes the history by itself, so an entry is appended and read back directly.
+    // It cannot be pruned as storage cannot remove keys, it goes with the staged writes instead.
+    let ran = staging::staged(deps, |deps| {
+        record_history(deps, &env, "self_test", vec![], None, None)?;
+        let seq = last_history_seq(&deps.storage)?;
+        match history_read(&deps.storage).may_load(&seq.to_be_bytes())? {
+            Some(entry) if entry.action == "self_test" => Ok(()),
+            _ => contract_err("History entry was not recorded"),
+        }
+    });
+    runs.push(("history", ran));
+
+    // the probe goes out only if reflecting it passed the checks of every other message
+    let reflected = runs[0].1.is_ok();
+    let mut checks = vec![];
+    for (subsystem, ran) in runs {
+        res.log
+            .push(log(subsystem, if ran.is_ok() { "pass" } else { "fail" }));
+        checks.push(SubsystemCheck {
+            subsystem: subsystem.to_string(),
+            passed: ran.is_ok(),
+            error: ran.err().map(|err| err.to_string()),
+        });
+    }
+    let passed = checks.iter().filter(|c| c.passed).count();
+    res.log
+        .push(log("passed", &format!("{}/{}", passed, checks.len())));
+    if echo.is_some() && reflected {
+        for contract_addr in state.rulesets.iter().flatten() {
+            let probes = [probe.clone()];
+            res.messages.push(ruleset_check(
+                deps,
+                &env,
+                contract_addr,
+                &probes,
+                &[],
+                &None,
+            )?);
+        }
+        res.messages.push(probe);
+    }
+    res.data = Some(Binary(to_vec(&SelfTestResult { checks }).context(
+        SerializeErr {
+            kind: "SelfTestResult",
+        },
+    )?));
+    Ok(res)
+}
+
+/// The placeholder of a macro param
+fn macro_placeholder(param: &str) -> String {
+    format!("${}", param)
+}
+
+pub fn try_save_macro<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    name: String,
+    msgs: Vec<CosmosMsg>,
+    params: Vec<MacroParam>,
+    runners: Vec<HumanAddr>,
+    quota: Option<MacroQuota>,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    validate_macro_name("Macro", &name)?;
+    if msgs.is_empty() {
+        return contract_err("A macro needs at least one message");
+    }
+    if quota.map_or(false, |q| q.runs == 0) {
+        return contract_err("A quota must allow at least one run");
+    }
+    if params.len() > MAX_MACRO_PARAMS {
+        return ContractError::LimitExceeded {
+            set: "macro params".to_string(),
+            max: MAX_MACRO_PARAMS as u32,
+        }
+        .fail();
+    }
+    for (i, param) in params.iter().enumerate() {
+        validate_macro_name("Param", &param.name)?;
+        if params[..i].iter().any(|p| p.name == param.name) {
+            return dyn_contract_err(format!("Duplicate param {}", param.name));
+        }
+        if let Some(max) = &param.max {
+            parse_amount(max)?;
+        }
+        let placeholder = macro_placeholder(&param.name);
+        let mut used = false;
+        for msg in msgs.iter() {
+            used |= fill_placeholder(msg.clone(), &placeholder, b"\"0\"")?.1;
+        }
+        if !used {
+            return dyn_contract_err(format!("Param {} is not used", param.name));
+        }
+    }
+    let mut canonical_runners: Vec<CanonicalAddr> = Vec::with_capacity(runners.len());
+    for runner in runners.iter() {
+        state.chain.validate_address(runner)?;
+        let canonical = deps.api.canonical_address(runner)?;
+        if !canonical_runners.contains(&canonical) {
+            canonical_runners.push(canonical);
+        }
+    }
+
+    let mut names = macro_names_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    if let Err(i) = names.binary_search(&name) {
+        if names.len() >= MAX_MACROS {
+            return ContractError::LimitExceeded {
+                set: "macros".to_string(),
+                max: MAX_MACROS as u32,
+            }
+            .fail();
+        }
+        names.insert(i, name.clone());
+        macro_names(&mut deps.storage).save(&names)?;
+    }
+    let saved = Macro {
+        msgs,
+        params,
+        runners: canonical_runners,
+        quota,
+    };
+    macros(&mut deps.storage).save(name.as_bytes(), &saved)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "save_macro"),
+            log("name", &name),
+            log("params", &saved.params.len().to_string()),
+            log("runners", &saved.runners.len().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_remove_macro<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    name: String,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let mut names = macro_names_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    match names.binary_search(&name) {
+        Ok(i) => names.remove(i),
+        Err(_) => return dyn_contract_err(format!("No macro named {}", name)),
+    };
+    macro_names(&mut deps.storage).save(&names)?;
+    // storage cannot delete, so at least free the messages
+    let emptied = Macro {
+        msgs: vec![],
+        params: vec![],
+        runners: vec![],
+        quota: None,
+    };
+    macros(&mut deps.storage).save(name.as_bytes(), &emptied)?;
+
+    Ok(Response {
+        log: vec![log("action", "remove_macro"), log("name", &name)],
+        ..Response::default()
+    })
+}
+
+pub fn try_run_macro<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    name: String,
+    args: Vec<MacroArg>,
+) -> Result<Response> {
+    let names = macro_names_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    if names.binary_search(&name).is_err() {
+        return dyn_contract_err(format!("No macro named {}", name));
+    }
+    let saved = macros_read(&deps.storage).load(name.as_bytes())?;
+    let state = config_read(&deps.storage).load()?;
+    let is_owner = state.is_owner(&env.message.signer);
+    if !is_owner && !saved.runners.contains(&env.message.signer) {
+        return unauthorized();
+    }
+    if let (Some(quota), false) = (saved.quota, is_owner) {
+        let key = macro_runs_key(&env.message.signer, &name);
+        let mut runs = match macro_runs_read(&deps.storage).may_load(&key)? {
+            Some(runs) if !runs.resets_at(quota.period).is_expired(&env) => runs,
+            _ => MacroRuns::starting(&env),
+        };
+        if runs.runs >= quota.runs {
+            return ContractError::QuotaExhausted {
+                name,
+                resets_at: runs.resets_at(quota.period),
+            }
+            .fail();
+        }
+        runs.runs += 1;
+        macro_runs(&mut deps.storage).save(&key, &runs)?;
+    }
+    for (i, arg) in args.iter().enumerate() {
+        if args[..i].iter().any(|a| a.name == arg.name) {
+            return dyn_contract_err(format!("Duplicate arg {}", arg.name));
+        }
+        if !saved.params.iter().any(|p| p.name == arg.name) {
+            return dyn_contract_err(format!("Macro {} has no param {}", name, arg.name));
+        }
+    }
+    let mut msgs = saved.msgs;
+    let mut log_args = Vec::with_capacity(saved.params.len());
+    for param in saved.params.iter() {
+        let arg = match args.iter().find(|a| a.name == param.name) {
+            Some(arg) => arg,
+            None => return dyn_contract_err(format!("Missing arg {}", param.name)),
+        };
+        let amount = parse_amount(&arg.amount)?;
+        if let Some(max) = &param.max {
+            if amount > parse_amount(max)? {
+                return dyn_contract_err(format!("{} may be at most {}", param.name, max));
+            }
+        }
+        let placeholder = macro_placeholder(&param.name);
+        let value = format!("\"{}\"", amount).into_bytes();
+        let mut filled = Vec::with_capacity(msgs.len());
+        for msg in msgs {
+            filled.push(fill_placeholder(msg, &placeholder, &value)?.0);
+        }
+        msgs = filled;
+        log_args.push(format!("{}={}", param.name, amount));
+    }
+
+    let (mut res, _) = dispatch(deps, &env, msgs, vec![], None)?;
+    res.log.insert(0, log("action", "run_macro"));
+    res.log.insert(1, log("macro", &name));
+    res.log.insert(2, log("args", &log_args.join(",")));
+    Ok(res)
+}
+
+/// Fails if the configuration needs an attestation and has no fresh one
+fn check_attestation<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    env: &Env,
+    state: &State,
+) -> Result<()> {
+    let max_age = match state.attestation_max_age {
+        Some(max_age) => max_age,
+        None => return Ok(()),
+    };
+    match attestation_read(&deps.storage).may_load()? {
+        Some(a) if a.is_fresh(state, max_age, env)? => Ok(()),
+        Some(_) => contract_err("The attestation is outdated, the configuration needs a new one"),
+        None => contract_err("The configuration needs an attestation"),
+    }
+}
+
+pub fn try_set_data<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    key: String,
+    value: Binary,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    if key.is_empty() || key.len() > MAX_DATA_KEY_LEN {
+        return dyn_contract_err(format!(
+            "Data keys must have 1 to {} bytes",
+            MAX_DATA_KEY_LEN
+        ));
+    }
+    let mut keys = data_keys_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    if let Err(i) = keys.binary_search(&key) {
+        if keys.len() >= MAX_DATA_KEYS {
+            return dyn_contract_err(format!("At most {} data keys are allowed", MAX_DATA_KEYS));
+        }
+        keys.insert(i, key.clone());
+        data_keys(&mut deps.storage).save(&keys)?;
+    }
+    data(&mut deps.storage).set(key.as_bytes(), value.as_slice());
+
+    Ok(Response {
+        log: vec![log("action", "set_data"), log("key", &key)],
+        ..Response::default()
+    })
+}
+
+pub fn try_remove_data<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    key: String,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    let mut keys = data_keys_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    match keys.binary_search(&key) {
+        Ok(i) => keys.remove(i),
+        Err(_) => return dyn_contract_err(format!("No data stored under {}", key)),
+    };
+    data_keys(&mut deps.storage).save(&keys)?;
+    // storage cannot delete, so at least free the value
+    data(&mut deps.storage).set(key.as_bytes(), &[]);
+
+    Ok(Response {
+        log: vec![log("action", "remove_data"), log("key", &key)],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_four_eyes<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    kinds: Option<Vec<MsgKind>>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if let Some(kinds) = &kinds {
+        if kinds.is_empty() {
+            return contract_err("Four eyes needs at least one message kind");
+        }
+        if kinds
+            .iter()
+            .enumerate()
+            .any(|(i, k)| kinds[..i].contains(k))
+        {
+            return contract_err("Four eyes lists a message kind twice");
+        }
+    }
+    state.four_eyes = kinds;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Response {
+        log: vec![log("action", "set_four_eyes")],
+        ..Response::default()
+    };
+    for kind in state.four_eyes.iter().flatten() {
+        res.log.push(log("kind", kind.as_str()));
+    }
+    Ok(res)
+}
+
+/// Most denoms which may have a spend limit
+pub const MAX_SPEND_LIMITS: usize = 20;
+
+pub fn try_set_spend_limit<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    denom: String,
+    amount: String,
+    period_secs: u64,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if denom.is_empty() || parse_amount(&amount)? == 0 {
+        return contract_err("Spend limit must be a positive amount of a denom");
+    }
+    if period_secs == 0 {
+        return contract_err("Spend limit period must be at least one second");
+    }
+
+    let mut limits: Vec<SpendLimit> = state
+        .spend_limits
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|l| l.limit.denom != denom)
+        .collect();
+    if limits.len() >= MAX_SPEND_LIMITS {
+        return dyn_contract_err(format!(
+            "At most {} denoms may have a spend limit",
+            MAX_SPEND_LIMITS
+        ));
+    }
+    limits.push(SpendLimit {
+        limit: Coin {
+            denom: denom.clone(),
+            amount: amount.clone(),
+        },
+        period_secs,
+    });
+    state.spend_limits = Some(limits);
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_spend_limit"),
+            log("limit", &format!("{}{}", amount, denom)),
+            log("period_secs", &period_secs.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_remove_spend_limit<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    denom: String,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let limits: Vec<SpendLimit> = state
+        .spend_limits
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|l| l.limit.denom != denom)
+        .collect();
+    state.spend_limits = if limits.is_empty() {
+        None
+    } else {
+        Some(limits)
+    };
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![log("action", "remove_spend_limit"), log("denom", &denom)],
+        ..Response::default()
+    })
+}
+
+/// Most depositors which may be allowed
+pub const MAX_DEPOSITORS: usize = 50;
+
+pub fn try_set_depositors<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    depositors: Option<Vec<HumanAddr>>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.depositors = match &depositors {
+        Some(list) => {
+            if list.len() > MAX_DEPOSITORS {
+                return dyn_contract_err(format!(
+                    "At most {} depositors can be allowed",
+                    MAX_DEPOSITORS
+                ));
+            }
+            let mut canonical = Vec::with_capacity(list.len());
+            for addr in list.iter() {
+                state.chain.validate_address(addr)?;
+                let addr = deps.api.canonical_address(addr)?;
+                if !canonical.contains(&addr) {
+                    canonical.push(addr);
+                }
+            }
+            Some(canonical)
+        }
+        None => None,
+    };
+    config(&mut deps.storage).save(&state)?;
+
+    let count = match &state.depositors {
+        Some(list) => list.len().to_string(),
+        None => "any".to_string(),
+    };
+    Ok(Response {
+        log: vec![log("action", "set_depositors"), log("depositors", &count)],
+        ..Response::default()
+    })
+}
+
+/// Most stakeholders revenue may be distributed to
+pub const MAX_STAKEHOLDERS: usize = 50;
+
+pub fn try_set_stakeholders<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    holders: Vec<StakeholderMsg>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if holders.len() > MAX_STAKEHOLDERS {
+        return dyn_contract_err(format!(
+            "At most {} stakeholders are allowed",
+            MAX_STAKEHOLDERS
+        ));
+    }
+    let mut canonical: Vec<Stakeholder> = Vec::with_capacity(holders.len());
+    for holder in holders.iter() {
+        state.chain.validate_address(&holder.addr)?;
+        if holder.shares == 0 {
+            return dyn_contract_err(format!(
+                "Stakeholder {} has no shares",
+                holder.addr.as_str()
+            ));
+        }
+        let addr = deps.api.canonical_address(&holder.addr)?;
+        if canonical.iter().any(|c| c.addr == addr) {
+            return dyn_contract_err(format!(
+                "Stakeholder {} is listed twice",
+                holder.addr.as_str()
+            ));
+        }
+        canonical.push(Stakeholder {
+            addr,
+            shares: holder.shares,
+        });
+    }
+    stakeholders(&mut deps.storage).save(&canonical)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_stakeholders"),
+            log("stakeholders", &canonical.len().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_distribute_revenue<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    tag: String,
+    period: u64,
+) -> Result<Response> {
+    validate_tag_name(&tag)?;
+    if period >= revenue_period(env.block.time as u64) {
+        return contract_err("Can only distribute periods which have ended");
+    }
+    let key = tag_index_key(&tag, period);
+    if revenue_settled_read(&deps.storage)
+        .may_load(&key)?
+        .unwrap_or(false)
+    {
+        return dyn_contract_err(format!(
+            "Revenue of {} in period {} was already distributed",
+            tag, period
+        ));
+    }
+    let holders = stakeholders_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    if holders.is_empty() {
+        return contract_err("No stakeholders to distribute to");
+    }
+    let totals = revenue_read(&deps.storage)
+        .may_load(&key)?
+        .unwrap_or_default();
+    revenue_settled(&mut deps.storage).save(&key, &true)?;
+
+    // shares are rounded down, the remainder stays in the contract
+    let total_shares = safe_sum(holders.iter().map(|h| u128::from(h.shares)))?;
+    let contract = deps.api.human_address(&env.contract.address)?;
+    let mut msgs = vec![];
+    for holder in holders.iter() {
+        let mut amount = vec![];
+        for coin in totals.iter() {
+            let total = parse_amount(&coin.amount)?;
+            let share = mul_ratio(total, u128::from(holder.shares), total_shares)?;
+            if share > 0 {
+                amount.push(Coin {
+                    denom: coin.denom.clone(),
+                    amount: share.to_string(),
+                });
+            }
+        }
+        if !amount.is_empty() {
+            msgs.push(CosmosMsg::Send {
+                from_address: contract.clone(),
+                to_address: deps.api.human_address(&holder.addr)?,
+                amount,
+            });
+        }
+    }
+    order_sends(&mut msgs);
+    let mut res = if msgs.is_empty() {
+        Response::default()
+    } else {
+        let (res, dispatched) = dispatch(deps, &env, msgs, vec![], None)?;
+        if let Dispatched::Queued(id) = dispatched {
+            let tag = tag.clone();
+            let undo = Undo::RevenueSettled { tag, period };
+            amend_queued(deps, id, |q| q.undo = Some(undo))?;
+        }
+        res
+    };
+    res.log.insert(0, log("action", "distribute_revenue"));
+    res.log.insert(1, log("tag", &tag));
+    res.log.insert(2, log("period", &period.to_string()));
+    Ok(res)
+}
+
+/// Most milestones one grant may have
+pub const MAX_MILESTONES: usize = 20;
+
+pub fn try_create_grant<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    grantee: HumanAddr,
+    approver: HumanAddr,
+    milestones: Vec<Milestone>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.chain.validate_address(&grantee)?;
+    state.chain.validate_address(&approver)?;
+    if milestones.is_empty() || milestones.len() > MAX_MILESTONES {
+        return dyn_contract_err(format!(
+            "Between 1 and {} milestones are allowed",
+            MAX_MILESTONES
+        ));
+    }
+    for milestone in milestones.iter() {
+        if milestone.amount.is_empty() {
+            return dyn_contract_err(format!("Milestone {} pays nothing", milestone.description));
+        }
+    }
+
+    let id = next_grant_id(&mut deps.storage)?;
+    let grant = Grant {
+        id,
+        grantee,
+        approver: deps.api.canonical_address(&approver)?,
+        milestones,
+        released: 0,
+        created_height: env.block.height,
+        clawed_back: false,
+        queued: None,
+    };
+    // fails early on amounts that do not parse or overflow
+    grant.remaining()?;
+    grants(&mut deps.storage).save(&id.to_be_bytes(), &grant)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "create_grant"),
+            log("grant_id", &id.to_string()),
+            ref_log(EntityKind::Grant, id),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_approve_milestone<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let mut grant = found(
+        grants(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Grant,
+        id,
+    )?;
+    if env.message.signer != grant.approver {
+        return unauthorized();
+    }
+    if grant.clawed_back {
+        return contract_err("Grant was clawed back");
+    }
+    if grant.is_complete() {
+        return contract_err("All milestones of the grant were released");
+    }
+    // cancelling a queued payment releases its milestone again, so none may follow it yet
+    if let Some(queued_id) = grant.queued {
+        if load_open_queued(deps, queued_id).is_ok() {
+            return contract_err("The last milestone still waits for the co-signer");
+        }
+    }
+    let milestone = grant.milestones[grant.released as usize].clone();
+    grant.released += 1;
+
+    let payment = CosmosMsg::Send {
+        from_address: deps.api.human_address(&env.contract.address)?,
+        to_address: grant.grantee.clone(),
+        amount: milestone.amount,
+    };
+    let (mut res, dispatched) = dispatch(deps, &env, vec![payment], vec![], None)?;
+    grant.queued = match dispatched {
+        Dispatched::Queued(queued_id) => {
+            let undo = Undo::Milestone { grant_id: id };
+            amend_queued(deps, queued_id, |q| q.undo = Some(undo))?;
+            Some(queued_id)
+        }
+        Dispatched::Executed(_) => None,
+    };
+    grants(&mut deps.storage).save(&id.to_be_bytes(), &grant)?;
+    res.log.insert(0, log("action", "approve_milestone"));
+    res.log.insert(1, log("grant_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::Grant, id));
+    res.log
+        .insert(3, log("milestone", &grant.released.to_string()));
+    Ok(res)
+}
+
+pub fn try_create_swap<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    counterparty: HumanAddr,
+    give: Vec<Coin>,
+    want: Vec<Coin>,
+    expires: Expiration,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.chain.validate_address(&counterparty)?;
+    if give.is_empty() || want.is_empty() {
+        return contract_err("A swap must give and want something");
+    }
+    if expires.is_expired(&env) {
+        return contract_err("Expiration must be in the future");
+    }
+    // fails early on amounts that do not parse or overflow
+    sum_coins(give.iter().chain(want.iter()))?;
+
+    let id = next_swap_id(&mut deps.storage)?;
+    let swap = Swap {
+        id,
+        counterparty: deps.api.canonical_address(&counterparty)?,
+        give,
+        want,
+        expires,
+        filled: false,
+    };
+    swaps(&mut deps.storage).save(&id.to_be_bytes(), &swap)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "create_swap"),
+            log("swap_id", &id.to_string()),
+            ref_log(EntityKind::Swap, id),
+            log("expires", &expires.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_fill_swap<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let mut swap = found(
+        swaps(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Swap,
+        id,
+    )?;
+    if env.message.signer != swap.counterparty {
+        return unauthorized();
+    }
+    if swap.filled {
+        return contract_err("Swap was already filled");
+    }
+    if swap.expires.is_expired(&env) {
+        return ContractError::Expired {
+            what: "Swap".to_string(),
+            at: swap.expires,
+        }
+        .fail();
+    }
+    let sent = env.message.sent_funds.clone().unwrap_or_default();
+    if !same_coins(&sent, &swap.want)? {
+        return contract_err("Must send exactly the wanted coins");
+    }
+    swap.filled = true;
+    swaps(&mut deps.storage).save(&id.to_be_bytes(), &swap)?;
+
+    let payment = CosmosMsg::Send {
+        from_address: deps.api.human_address(&env.contract.address)?,
+        to_address: deps.api.human_address(&swap.counterparty)?,
+        amount: swap.give,
+    };
+    let mut res = dispatch_owed(deps, &env, payment)?;
+    res.log.insert(0, log("action", "fill_swap"));
+    res.log.insert(1, log("swap_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::Swap, id));
+    Ok(res)
+}
+
+/// Whether both add up to the same amount of every denom
+fn same_coins(a: &[Coin], b: &[Coin]) -> Result<bool> {
+    let mut a = sum_coins(a)?;
+    let mut b = sum_coins(b)?;
+    a.sort_by(|x, y| x.denom.cmp(&y.denom));
+    b.sort_by(|x, y| x.denom.cmp(&y.denom));
+    Ok(a == b)
+}
+
+pub fn try_grant_permission<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    grantee: HumanAddr,
+    msg_types: Vec<MsgKind>,
+    expires_at_height: u64,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.chain.validate_address(&grantee)?;
+    if msg_types.is_empty() {
+        return contract_err("A permission must allow some message kind");
+    }
+    if expires_at_height <= env.block.height as u64 {
+        return contract_err("Expiration must be in the future");
+    }
+    let mut kinds: Vec<MsgKind> = vec![];
+    for kind in msg_types {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    let canonical = deps.api.canonical_address(&grantee)?;
+    let permission = Permission {
+        msg_types: kinds,
+        expires_at_height,
+    };
+    permissions(&mut deps.storage).save(canonical.as_slice(), &permission)?;
+
+    let kinds: Vec<&str> = permission.msg_types.iter().map(|k| k.as_str()).collect();
+    Ok(Response {
+        log: vec![
+            log("action", "grant_permission"),
+            log("grantee", grantee.as_str()),
+            log("msg_types", &kinds.join(",")),
+            log("expires_at_height", &expires_at_height.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_create_session<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    key_addr: HumanAddr,
+    max_msgs: u64,
+    expires: Expiration,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.chain.validate_address(&key_addr)?;
+    if max_msgs == 0 {
+        return contract_err("A session must allow some messages");
+    }
+    if expires.is_expired(&env) {
+        return contract_err("Expiration must be in the future");
+    }
+    let canonical = deps.api.canonical_address(&key_addr)?;
+    let session = Session {
+        remaining_msgs: max_msgs,
+        expires,
+    };
+    sessions(&mut deps.storage).save(canonical.as_slice(), &session)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "create_session"),
+            log("key_addr", key_addr.as_str()),
+            log("max_msgs", &max_msgs.to_string()),
+            log("expires", &expires.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_cosigner<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    cosigner: Option<HumanAddr>,
+    thresholds: Vec<Amount>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let thresholds = coins_of(&thresholds, &state.chain);
+    state.cosign = match &cosigner {
+        Some(addr) => {
+            state.chain.validate_address(addr)?;
+            let canonical = deps.api.canonical_address(addr)?;
+            if state.is_owner(&canonical) {
+                return contract_err("The co-signer must not be an owner");
+            }
+            if thresholds.is_empty() {
+                return contract_err("Co-signing needs at least one threshold");
+            }
+            // one threshold per denom
+            if sum_coins(&thresholds)?.len() != thresholds.len() {
+                return contract_err("Co-signing has two thresholds for the same denom");
+            }
+            Some(Cosign {
+                cosigner: canonical,
+                thresholds,
+            })
+        }
+        None => None,
+    };
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Response {
+        log: vec![log("action", "set_cosigner")],
+        ..Response::default()
+    };
+    if let (Some(addr), Some(cosign)) = (&cosigner, &state.cosign) {
+        res.log.push(log("cosigner", addr.as_str()));
+        for threshold in cosign.thresholds.iter() {
+            let value = format!("{}{}", threshold.amount, threshold.denom);
+            res.log.push(log("threshold", &value));
+        }
+    }
+    Ok(res)
+}
+
+/// Loads a queued batch which is neither confirmed nor cancelled
+fn load_open_queued<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<QueuedExecution> {
+    let queued = found(
+        queued_executions_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::QueuedExecution,
+        id,
+    )?;
+    if queued.closed {
+        return contract_err("Execution was already confirmed or cancelled");
+    }
+    Ok(queued)
+}
+
+pub fn try_confirm_execution<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    match &state.cosign {
+        Some(cosign) if cosign.cosigner == env.message.signer => {}
+        _ => return unauthorized(),
+    }
+    let mut queued = load_open_queued(deps, id)?;
+    queued.closed = true;
+    queued_executions(&mut deps.storage).save(&id.to_be_bytes(), &queued)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, false)?;
+
+    // the batch goes out and is recorded as sent by whoever queued it,
+    // without being queued again as the co-signer just confirmed it
+    check_four_eyes(&state, &queued.msgs)?;
+    let mut sender_env = env;
+    sender_env.message.signer = queued.sender.clone();
+    check_standing(deps, &sender_env, &state, &queued)?;
+    let mut res = dispatch_reviewed(
+        deps,
+        &sender_env,
+        queued.msgs,
+        queued.tags,
+        queued.memo,
+        None,
+    )?;
+    if let Some(receipt) = queued.receipt {
+        let execution_id = last_execution_id(&deps.storage)?;
+        res.messages
+            .push(receipt_msg(&sender_env, receipt, execution_id)?);
+    }
+    res.log.insert(0, log("action", "confirm_execution"));
+    res.log.insert(1, log("queued_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::QueuedExecution, id));
+    Ok(res)
+}
+
+/// Fails unless the sender of a queued batch may still send it, as its permission or
+/// session may have lapsed, or it may no longer be an owner, while the batch waited
+fn check_standing<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    env: &Env,
+    state: &State,
+    queued: &QueuedExecution,
+) -> Result<()> {
+    let signer = &env.message.signer;
+    match queued.standing {
+        Some(Standing::Owner) if !state.is_owner(signer) => not_owner(&deps.api, env),
+        Some(Standing::Delegate) if !state.is_owner(signer) => {
+            let permitted = match permissions_read(&deps.storage).may_load(signer.as_slice())? {
+                Some(p) => p.allows(env, &queued.msgs),
+                None => false,
+            };
+            if permitted {
+                return Ok(());
+            }
+            // the session already paid for the messages when they were queued
+            match sessions_read(&deps.storage).may_load(signer.as_slice())? {
+                Some(session) if session.expires.is_expired(env) => ContractError::Expired {
+                    what: "Session".to_string(),
+                    at: session.expires,
+                }
+                .fail(),
+                Some(_) => Ok(()),
+                None => unauthorized(),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn try_authorize_once<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    grantee: HumanAddr,
+    msg_hash: Binary,
+    expires: Expiration,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    state.chain.validate_address(&grantee)?;
+    if msg_hash.as_slice().len() != 32 {
+        return contract_err("msg_hash must be a SHA-256 hash of 32 bytes");
+    }
+    if expires.is_expired(&env) {
+        return contract_err("Expiration must be in the future");
+    }
+    if open_authorization(deps, &msg_hash)?.is_some() {
+        return contract_err("The message is already authorized");
+    }
+
+    let id = next_one_time_auth_id(&mut deps.storage)?;
+    let auth = OneTimeAuth {
+        id,
+        grantee: deps.api.canonical_address(&grantee)?,
+        msg_hash: msg_hash.clone(),
+        expires,
+        used: false,
+    };
+    one_time_auths(&mut deps.storage).save(&id.to_be_bytes(), &auth)?;
+    one_time_auth_ids(&mut deps.storage).save(msg_hash.as_slice(), &id)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "authorize_once"),
+            log("authorization_id", &id.to_string()),
+            ref_log(EntityKind::Authorization, id),
+            log("grantee", grantee.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+/// The authorization given last for the hash, if it was not used yet
+fn open_authorization<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    msg_hash: &Binary,
+) -> Result<Option<OneTimeAuth>> {
+    let id = match one_time_auth_ids_read(&deps.storage).may_load(msg_hash.as_slice())? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let auth = found(
+        one_time_auths_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Authorization,
+        id,
+    )?;
+    Ok(if auth.used { None } else { Some(auth) })
+}
+
+pub fn try_execute_authorized<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msg: CosmosMsg,
+) -> Result<Response> {
+    let mut auth = match open_authorization(deps, &msg_hash(&msg)?)? {
+        Some(auth) if auth.grantee == env.message.signer => auth,
+        _ => return unauthorized(),
+    };
+    if auth.expires.is_expired(&env) {
+        return ContractError::Expired {
+            what: "Authorization".to_string(),
+            at: auth.expires,
+        }
+        .fail();
+    }
+    // used up before dispatching, so the message cannot be replayed
+    auth.used = true;
+    one_time_auths(&mut deps.storage).save(&auth.id.to_be_bytes(), &auth)?;
+
+    let (mut res, _) = dispatch(deps, &env, vec![msg], vec![], None)?;
+    res.log.insert(0, log("action", "execute_authorized"));
+    res.log
+        .insert(1, log("authorization_id", &auth.id.to_string()));
+    res.log
+        .insert(2, ref_log(EntityKind::Authorization, auth.id));
+    Ok(res)
+}
+
+/// How many records MigrateRecords upgrades without a limit
+const DEFAULT_MIGRATE_LIMIT: u32 = 50;
+const MAX_MIGRATE_LIMIT: u32 = 200;
+
+pub fn try_migrate_records<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    namespace: MigrateNamespace,
+    limit: Option<u32>,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    let limit = match limit.unwrap_or(DEFAULT_MIGRATE_LIMIT) {
+        0 => 1,
+        limit => limit.min(MAX_MIGRATE_LIMIT),
+    };
+    let (prefix, last) = match namespace {
+        MigrateNamespace::Executions => (EXECUTION_PREFIX, last_execution_id(&deps.storage)?),
+    };
+    let from = migration_cursors_read(&deps.storage)
+        .may_load(prefix)?
+        .unwrap_or(0);
+    let to = last.min(from.saturating_add(u64::from(limit)));
+
+    let mut upgraded = 0u32;
+    let mut unreadable = 0u32;
+    for id in from + 1..=to {
+        let res = match namespace {
+            MigrateNamespace::Executions => {
+                executions(&mut deps.storage).upgrade(&id.to_be_bytes())
+            }
+        };
+        match res {
+            Ok(true) => upgraded += 1,
+            Ok(false) => {}
+            Err(_) => unreadable += 1,
+        }
+    }
+    migration_cursors(&mut deps.storage).save(prefix, &to)?;
+
+    let result = MigrateRecordsResult {
+        upgraded,
+        unreadable,
+        migrated_up_to: to,
+        last,
+        done: to == last,
+    };
+    let data = to_vec(&result).context(SerializeErr {
+        kind: "MigrateRecordsResult",
+    })?;
+    Ok(Response {
+        log: vec![
+            log("action", "migrate_records"),
+            log("upgraded", &upgraded.to_string()),
+            log("migrated_up_to", &to.to_string()),
+        ],
+        data: Some(Binary(data)),
+        ..Response::default()
+    })
+}
+
+pub fn try_cancel_execution<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    let mut queued = load_open_queued(deps, id)?;
+    let is_cosigner = state
+        .cosign
+        .as_ref()
+        .map_or(false, |c| c.cosigner == env.message.signer);
+    if !is_cosigner && !state.is_owner(&env.message.signer) {
+        return unauthorized();
+    }
+    queued.closed = true;
+    queued_executions(&mut deps.storage).save(&id.to_be_bytes(), &queued)?;
+    adjust_metric(&mut deps.storage, QUEUE_DEPTH, false)?;
+    if let Some(undo) = queued.undo {
+        apply_undo(deps, undo)?;
+    }
+
+    Ok(Response {
+        log: vec![
+            log("action", "cancel_execution"),
+            log("queued_id", &id.to_string()),
+            ref_log(EntityKind::QueuedExecution, id),
+        ],
+        ..Response::default()
+    })
+}
+
+/// Takes back what a handler changed before its batch was queued
+fn apply_undo<S: Storage, A: Api>(deps: &mut Extern<S, A>, undo: Undo) -> Result<()> {
+    match undo {
+        Undo::CollectedFees { fees } => {
+            let collected = collected_fees_read(&deps.storage)
+                .may_load()?
+                .unwrap_or_default();
+            let collected = sum_coins(collected.iter().chain(fees.iter()))?;
+            collected_fees(&mut deps.storage).save(&collected)
+        }
+        Undo::RevenueSettled { tag, period } => {
+            revenue_settled(&mut deps.storage).save(&tag_index_key(&tag, period), &false)
+        }
+        Undo::Milestone { grant_id } => {
+            let mut grant = found(
+                grants(&mut deps.storage).may_load(&grant_id.to_be_bytes())?,
+                EntityKind::Grant,
+                grant_id,
+            )?;
+            grant.released -= 1;
+            grant.queued = None;
+            grants(&mut deps.storage).save(&grant_id.to_be_bytes(), &grant)
+        }
+    }
+}
+
+pub fn try_revoke_permission<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    grantee: HumanAddr,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let canonical = deps.api.canonical_address(&grantee)?;
+    match permissions_read(&deps.storage).may_load(canonical.as_slice())? {
+        Some(ref p) if !p.msg_types.is_empty() => {}
+        _ => return dyn_contract_err(format!("{} has no permission", grantee.as_str())),
+    }
+    let revoked = Permission {
+        msg_types: vec![],
+        expires_at_height: 0,
+    };
+    permissions(&mut deps.storage).save(canonical.as_slice(), &revoked)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "revoke_permission"),
+            log("grantee", grantee.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_create_auction<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    sell: Vec<Coin>,
+    start_price: Coin,
+    end_price: Coin,
+    duration: Duration,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if sell.is_empty() {
+        return contract_err("Must sell something");
+    }
+    sum_coins(&sell)?;
+    if start_price.denom != end_price.denom {
+        return contract_err("Start and end price must be in the same denom");
+    }
+    if parse_amount(&start_price.amount)? < parse_amount(&end_price.amount)? {
+        return contract_err("Start price must not be below the end price");
+    }
+    match duration {
+        Duration::Height(0) | Duration::Time(0) => {
+            return contract_err("Duration must not be zero")
+        }
+        _ => {}
+    }
+
+    let id = next_auction_id(&mut deps.storage)?;
+    let auction = Auction {
+        id,
+        sell,
+        start_price,
+        end_price,
+        duration,
+        start_height: env.block.height as u64,
+        start_time: env.block.time as u64,
+        buyer: None,
+        paid: None,
+        cancelled: false,
+    };
+    auctions(&mut deps.storage).save(&id.to_be_bytes(), &auction)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "create_auction"),
+            log("auction_id", &id.to_string()),
+            ref_log(EntityKind::Auction, id),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_buy_auction<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let mut auction = found(
+        auctions(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Auction,
+        id,
+    )?;
+    if !auction.is_open() {
+        return contract_err("Auction is closed");
+    }
+    let price = auction.price_at(&env)?;
+    let denom = auction.start_price.denom.clone();
+    let sent = sum_coins(env.message.sent_funds.iter().flatten())?;
+    if sent.iter().any(|c| c.denom != denom) {
+        return dyn_contract_err(format!("Must pay in {}", denom));
+    }
+    let paid = match sent.first() {
+        Some(coin) => parse_amount(&coin.amount)?,
+        None => 0,
+    };
+    if paid < price {
+        return dyn_contract_err(format!("Must pay at least {}{}", price, denom));
+    }
+    auction.buyer = Some(env.message.signer.clone());
+    auction.paid = Some(Coin {
+        denom: denom.clone(),
+        amount: price.to_string(),
+    });
+    auctions(&mut deps.storage).save(&id.to_be_bytes(), &auction)?;
+
+    // the excess goes back along with the sold coins
+    let mut amount = auction.sell;
+    if paid > price {
+        amount.push(Coin {
+            denom: denom.clone(),
+            amount: paid.safe_sub(price)?.to_string(),
+        });
+    }
+    let payment = CosmosMsg::Send {
+        from_address: deps.api.human_address(&env.contract.address)?,
+        to_address: deps.api.human_address(&env.message.signer)?,
+        amount: sum_coins(&amount)?,
+    };
+    let mut res = dispatch_owed(deps, &env, payment)?;
+    res.log.insert(0, log("action", "buy_auction"));
+    res.log.insert(1, log("auction_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::Auction, id));
+    res.log
+        .insert(3, log("price", &format!("{}{}", price, denom)));
+    Ok(res)
+}
+
+pub fn try_cancel_auction<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let mut auction = found(
+        auctions(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Auction,
+        id,
+    )?;
+    if !auction.is_open() {
+        return contract_err("Auction is closed");
+    }
+    auction.cancelled = true;
+    auctions(&mut deps.storage).save(&id.to_be_bytes(), &auction)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "cancel_auction"),
+            log("auction_id", &id.to_string()),
+            ref_log(EntityKind::Auction, id),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_change_owner<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    owner: HumanAddr,
+) -> Result<Response> {
+    let api = deps.api;
+    let state = config(&mut deps.storage).update(&|mut state| {
+        if !state.can_admin(&env) {
+            return unauthorized();
+        }
+        state.chain.validate_address(&owner)?;
+        state.owner = api.canonical_address(&owner)?;
+        Ok(state)
+    })?;
+    record_history(deps, &env, "change_owner", vec![], Some(state.owner), None)?;
+    Ok(Response{
+        log: vec![
+            log("action", "change_owner"),
+            log("owner", owner.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_clawback<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+    reason: String,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if reason.is_empty() {
+        return contract_err("A clawback needs a reason");
+    }
+    let mut grant = found(
+        grants(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Grant,
+        id,
+    )?;
+    if grant.is_complete() {
+        return contract_err("Grant has nothing left to claw back");
+    }
+    let returned = grant.remaining()?;
+    grant.clawed_back = true;
+    grants(&mut deps.storage).save(&id.to_be_bytes(), &grant)?;
+
+    let mut logs = vec![
+        log("action", "clawback"),
+        log("grant_id", &id.to_string()),
+        ref_log(EntityKind::Grant, id),
+        log("reason", &reason),
+    ];
+    for coin in returned.iter() {
+        logs.push(log("returned", &format!("{}{}", coin.amount, coin.denom)));
+    }
+    Ok(Response {
+        log: logs,
+        ..Response::default()
+    })
+}
+
+pub fn try_propose_new_owner<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    owner: HumanAddr,
+    expires: Expiration,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.chain.validate_address(&owner)?;
+    if expires.is_expired(&env) {
+        return contract_err("Expiration must be in the future");
+    }
+    let pending = PendingOwner {
+        owner: deps.api.canonical_address(&owner)?,
+        expires,
+    };
+    pending_owner(&mut deps.storage).save(&Some(pending))?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "propose_new_owner"),
+            log("owner", owner.as_str()),
+            log("expires", &expires.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_accept_ownership<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+) -> Result<Response> {
+    let pending = match pending_owner_read(&deps.storage).may_load()? {
+        Some(Some(pending)) => pending,
+        _ => return contract_err("No ownership transfer is pending"),
+    };
+    if env.message.signer != pending.owner {
+        return unauthorized();
+    }
+    if pending.expires.is_expired(&env) {
+        return ContractError::Expired {
+            what: "Ownership transfer".to_string(),
+            at: pending.expires,
+        }
+        .fail();
+    }
+    config(&mut deps.storage).update(&|mut state| {
+        state.owner = pending.owner.clone();
+        Ok(state)
+    })?;
+    pending_owner(&mut deps.storage).save(&None)?;
+    let owner = Some(pending.owner.clone());
+    record_history(deps, &env, "accept_ownership", vec![], owner, None)?;
+
+    let owner = deps.api.human_address(&pending.owner)?;
+    Ok(Response {
+        log: vec![
+            log("action", "accept_ownership"),
+            log("owner", owner.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_guardians<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    addrs: Vec<HumanAddr>,
+    threshold: u64,
+    window: Duration,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let set = if addrs.is_empty() {
+        None
+    } else {
+        GUARDIANS.check(addrs.len())?;
+        if threshold == 0 || threshold > addrs.len() as u64 {
+            return contract_err("Threshold must be between 1 and the number of guardians");
+        }
+        if window == Duration::Height(0) || window == Duration::Time(0) {
+            return contract_err("Recovery window must be positive");
+        }
+        let mut canonical: Vec<CanonicalAddr> = vec![];
+        for addr in addrs.iter() {
+            state.chain.validate_address(addr)?;
+            let addr = deps.api.canonical_address(addr)?;
+            if state.is_owner(&addr) {
+                return contract_err("An owner cannot be a guardian");
+            }
+            if canonical.contains(&addr) {
+                return contract_err("Duplicate guardian");
+            }
+            canonical.push(addr);
+        }
+        Some(Guardians {
+            addrs: canonical,
+            threshold,
+            window,
+        })
+    };
+    guardians(&mut deps.storage).save(&set)?;
+    recovery_session(&mut deps.storage).save(&None)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_guardians"),
+            log("guardians", &addrs.len().to_string()),
+            log(
+                "threshold",
+                &set.map(|g| g.threshold).unwrap_or(0).to_string(),
+            ),
+        ],
+        ..Response::default()
+    })
+}
+
+/// Loads the guardians, failing unless the signer is one of them
+fn load_guardians<S: Storage, A: Api>(deps: &Extern<S, A>, env: &Env) -> Result<Guardians> {
+    match guardians_read(&deps.storage).may_load()? {
+        Some(Some(guardians)) if guardians.is_guardian(&env.message.signer) => Ok(guardians),
+        _ => unauthorized(),
+    }
+}
+
+pub fn try_initiate_recovery<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    new_owner: HumanAddr,
+) -> Result<Response> {
+    let guardians = load_guardians(deps, &env)?;
+    if let Some(Some(session)) = recovery_session_read(&deps.storage).may_load()? {
+        if !session.expires.is_expired(&env) {
+            return contract_err("A recovery is already in progress");
+        }
+    }
+    let state = config_read(&deps.storage).load()?;
+    state.chain.validate_address(&new_owner)?;
+    let session = RecoverySession {
+        new_owner: deps.api.canonical_address(&new_owner)?,
+        approvals: vec![env.message.signer.clone()],
+        expires: guardians
+            .window
+            .after(env.block.height as u64, env.block.time as u64),
+    };
+
+    let mut logs = vec![
+        log("action", "initiate_recovery"),
+        log("new_owner", new_owner.as_str()),
+        log("expires", &session.expires.to_string()),
+    ];
+    logs.extend(advance_recovery(deps, &env, &guardians, session)?);
+    Ok(Response {
+        log: logs,
+        ..Response::default()
+    })
+}
+
+pub fn try_approve_recovery<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+) -> Result<Response> {
+    let guardians = load_guardians(deps, &env)?;
+    let mut session = match recovery_session_read(&deps.storage).may_load()? {
+        Some(Some(session)) => session,
+        _ => return contract_err("No recovery is in progress"),
+    };
+    if session.expires.is_expired(&env) {
+        return ContractError::Expired {
+            what: "Recovery".to_string(),
+            at: session.expires,
+        }
+        .fail();
+    }
+    if session.approvals.contains(&env.message.signer) {
+        return contract_err("Guardian already approved this recovery");
+    }
+    session.approvals.push(env.message.signer.clone());
+
+    let mut logs = vec![
+        log("action", "approve_recovery"),
+        log("approvals", &session.approvals.len().to_string()),
+    ];
+    logs.extend(advance_recovery(deps, &env, &guardians, session)?);
+    Ok(Response {
+        log: logs,
+        ..Response::default()
+    })
+}
+
+/// Replaces the owner if the session has enough approvals, otherwise saves it
+fn advance_recovery<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: &Env,
+    guardians: &Guardians,
+    session: RecoverySession,
+) -> Result<Vec<LogAttribute>> {
+    if (session.approvals.len() as u64) < guardians.threshold {
+        recovery_session(&mut deps.storage).save(&Some(session))?;
+        return Ok(vec![]);
+    }
+    config(&mut deps.storage).update(&|mut state| {
+        state.owner = session.new_owner.clone();
+        Ok(state)
+    })?;
+    // a transfer the lost key proposed must not override the recovery
+    pending_owner(&mut deps.storage).save(&None)?;
+    recovery_session(&mut deps.storage).save(&None)?;
+    let owner = Some(session.new_owner.clone());
+    record_history(deps, env, "recover_ownership", vec![], owner, None)?;
+
+    let owner = deps.api.human_address(&session.new_owner)?;
+    Ok(vec![log("recovered", owner.as_str())])
+}
+
+pub fn try_cancel_recovery<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    match recovery_session_read(&deps.storage).may_load()? {
+        Some(Some(_)) => recovery_session(&mut deps.storage).save(&None)?,
+        _ => return contract_err("No recovery is in progress"),
+    }
+
+    Ok(Response {
+        log: vec![log("action", "cancel_recovery")],
+        ..Response::default()
+    })
+}
+
+pub fn try_denylist<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    add: Vec<HumanAddr>,
+    remove: Vec<HumanAddr>,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if add.iter().any(|a| remove.contains(a)) {
+        return contract_err("Cannot add and remove the same address");
+    }
+    for addr in add.iter() {
+        state.chain.validate_address(addr)?;
+        let addr = deps.api.canonical_address(addr)?;
+        denylist(&mut deps.storage).save(addr.as_slice(), &true)?;
+    }
+    for addr in remove.iter() {
+        let addr = deps.api.canonical_address(addr)?;
+        denylist(&mut deps.storage).save(addr.as_slice(), &false)?;
+    }
+
+    Ok(Response {
+        log: vec![
+            log("action", "denylist"),
+            log("added", &add.len().to_string()),
+            log("removed", &remove.len().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+/// Most contracts the allowlist may hold
+pub const MAX_ALLOWED_TARGETS: usize = 50;
+
+pub fn try_add_allowed_target<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    contract: HumanAddr,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.chain.validate_address(&contract)?;
+    let targets = state.allowed_targets.get_or_insert_with(Vec::new);
+    if targets.contains(&contract) {
+        return dyn_contract_err(format!("{} is already allowed", contract.as_str()));
+    }
+    if targets.len() == MAX_ALLOWED_TARGETS {
+        return dyn_contract_err(format!(
+            "At most {} targets can be allowed",
+            MAX_ALLOWED_TARGETS
+        ));
+    }
+    targets.push(contract.clone());
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "add_allowed_target"),
+            log("contract", contract.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_remove_allowed_target<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    contract: HumanAddr,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    match &mut state.allowed_targets {
+        Some(targets) if targets.contains(&contract) => targets.retain(|t| *t != contract),
+        _ => return dyn_contract_err(format!("{} is not allowed", contract.as_str())),
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "remove_allowed_target"),
+            log("contract", contract.as_str()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_recovery<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    recovery: Option<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.recovery = match &recovery {
+        Some(addr) => {
+            state.chain.validate_address(addr)?;
+            Some(deps.api.canonical_address(addr)?)
+        }
+        None => None,
+    };
+    config(&mut deps.storage).save(&state)?;
+
+    let recovery = recovery.as_ref().map(|r| r.as_str()).unwrap_or("");
+    Ok(Response {
+        log: vec![log("action", "set_recovery"), log("recovery", recovery)],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_msg_byte_limit<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    limit: Option<u64>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if limit == Some(0) {
+        return contract_err("Byte limit must be positive");
+    }
+    state.max_msg_bytes = limit;
+    config(&mut deps.storage).save(&state)?;
+
+    let limit = limit.map(|l| l.to_string()).unwrap_or_default();
+    Ok(Response {
+        log: vec![log("action", "set_msg_byte_limit"), log("limit", &limit)],
+        ..Response::default()
+    })
+}
+
+pub fn try_evacuate<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    to: Option<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    let to = match to {
+        Some(to) => {
+            if !state.can_admin(&env) {
+                return unauthorized();
+            }
+            state.chain.validate_address(&to)?;
+            to
+        }
+        None => {
+            if !state.is_owner(&env.message.signer) && !state.can_admin(&env) {
+                return unauthorized();
+            }
+            match &state.recovery {
+                Some(recovery) => deps.api.human_address(recovery)?,
+                None => return contract_err("No recovery address configured"),
+            }
+        }
+    };
+    state.paused = true;
+    state.evacuated = true;
+    config(&mut deps.storage).save(&state)?;
+
+    let balance = env.contract.balance.unwrap_or_default();
+    let mut messages = vec![];
+    if !balance.is_empty() {
+        messages.push(CosmosMsg::Send {
+            from_address: deps.api.human_address(&env.contract.address)?,
+            to_address: to.clone(),
+            amount: balance,
+        });
+    }
+    order_sends(&mut messages);
+    Ok(Response {
+        messages,
+        log: vec![log("action", "evacuate"), log("to", to.as_str())],
+        data: None,
+    })
+}
+
+pub fn try_pause<S: Storage, A: Api>(deps: &mut Extern<S, A>, env: Env) -> Result<Response> {
+    config(&mut deps.storage).update(&|mut state| {
+        if !state.is_owner(&env.message.signer) && !state.can_admin(&env) {
+            return unauthorized();
+        }
+        state.paused = true;
+        Ok(state)
+    })?;
+    Ok(Response {
+        log: vec![log("action", "pause")],
+        ..Response::default()
+    })
+}
+
+pub fn try_unpause<S: Storage, A: Api>(deps: &mut Extern<S, A>, env: Env) -> Result<Response> {
+    config(&mut deps.storage).update(&|mut state| {
+        if !state.can_admin(&env) {
+            return unauthorized();
+        }
+        state.paused = false;
+        state.evacuated = false;
+        Ok(state)
+    })?;
+    Ok(Response {
+        log: vec![log("action", "unpause")],
+        ..Response::default()
+    })
+}
+
+pub fn try_schedule_config<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    change: ConfigOverride,
+    activate_at: Expiration,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if state.pending.is_some() {
+        return contract_err("A config change is already pending");
+    }
+    if activate_at.is_expired(&env) {
+        return contract_err("Activation must be in the future");
+    }
+    if let Some(tags) = &change.tags {
+        for tag in tags.iter() {
+            validate_tag_name(tag)?;
+        }
+    }
+    if let Some(budgets) = &change.budgets {
+        let tags = change.tags.as_ref().unwrap_or(&state.tags);
+        for b in budgets.iter() {
+            if !tags.contains(&b.tag) {
+                return dyn_contract_err(format!("Unknown tag {}", b.tag));
+            }
+            b.budget.validate()?;
+        }
+    }
+    if let Some(cooldowns) = &change.cooldowns {
+        for c in cooldowns.iter() {
+            if !HandleMsg::ACTIONS.contains(&c.action.as_str()) {
+                return dyn_contract_err(format!("Unknown action {}", c.action));
+            }
+        }
+    }
+    state.pending = Some(PendingConfig {
+        change,
+        activate_at,
+    });
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "schedule_config"),
+            log("activate_at", &activate_at.to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_cancel_pending<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if state.pending.take().is_none() {
+        return contract_err("No config change is pending");
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![log("action", "cancel_pending")],
+        ..Response::default()
+    })
+}
+
+pub fn try_decommission<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    successor: Option<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if !state.evacuated {
+        return contract_err("Must evacuate before decommissioning");
+    }
+    if let Some(addr) = &successor {
+        state.chain.validate_address(addr)?;
+        if *addr == deps.api.human_address(&env.contract.address)? {
+            return contract_err("Contract cannot be its own successor");
+        }
+        state.successor = Some(deps.api.canonical_address(addr)?);
+    }
+    // records stay in storage (there is no removal), but nothing can act on them anymore
+    state.operators = vec![];
+    state.operator_manifest_nonce = 0;
+    state.suspended_operators = None;
+    state.cooldowns = vec![];
+    state.tags = vec![];
+    state.budgets = vec![];
+    state.pending = None;
+    state.decommissioned = true;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![log("action", "decommission")],
+        ..Response::default()
+    })
+}
+
+pub fn try_renounce_ownership<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    confirm: String,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if confirm != deps.api.human_address(&env.contract.address)?.as_str() {
+        return contract_err("To renounce, confirm with the contract address");
+    }
+    // no one may bring an owner back
+    pending_owner(&mut deps.storage).save(&None)?;
+    guardians(&mut deps.storage).save(&None)?;
+    recovery_session(&mut deps.storage).save(&None)?;
+    state.renounced = true;
+    config(&mut deps.storage).save(&state)?;
+    record_history(deps, &env, "renounce_ownership", vec![], None, None)?;
+
+    Ok(Response {
+        log: vec![log("action", "renounce_ownership")],
+        ..Response::default()
+    })
+}
+
+/// Most tag index entries a repair moves or counts at once
+const MAX_REPAIR_SCAN: u64 = 100;
+
+pub fn try_repair_state<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    token: String,
+) -> Result<Response> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let key = match token.splitn(2, '@').nth(1).and_then(parse_state_key) {
+        Some(key) => key,
+        None => return dyn_contract_err(format!("Invalid repair token: {}", token)),
+    };
+    // only repair what verifying the record still reports, so a stale token does nothing
+    let violation = violations_at(deps, &key)?
+        .into_iter()
+        .find(|v| v.token == token);
+    let violation = match violation {
+        Some(violation) => violation,
+        None => return dyn_contract_err(format!("No violation {} found", token)),
+    };
+
+    let repaired = match (violation.kind, key) {
+        (ViolationKind::IdMismatch, StateKey::Execution(id)) => {
+            let mut execution = found(
+                executions_read(&deps.storage).may_load(&id.to_be_bytes())?,
+                EntityKind::Execution,
+                id,
+            )?;
+            execution.id = id;
+            executions(&mut deps.storage).save(&id.to_be_bytes(), &execution)?;
+            format!("set id to {}", id)
+        }
+        (ViolationKind::IdMismatch, StateKey::StagedBatch(id)) => {
+            let mut batch = found(
+                staged_batches_read(&deps.storage).may_load(&id.to_be_bytes())?,
+                EntityKind::StagedBatch,
+                id,
+            )?;
+            batch.id = id;
+            staged_batches(&mut deps.storage).save(&id.to_be_bytes(), &batch)?;
+            format!("set id to {}", id)
+        }
+        (ViolationKind::IdMismatch, StateKey::AdminProposal(id)) => {
+            let mut proposal = found(
+                admin_proposals_read(&deps.storage).may_load(&id.to_be_bytes())?,
+                EntityKind::AdminProposal,
+                id,
+            )?;
+            proposal.id = id;
+            admin_proposals(&mut deps.storage).save(&id.to_be_bytes(), &proposal)?;
+            format!("set id to {}", id)
+        }
+        (ViolationKind::DanglingReference, StateKey::TagIndex(tag, n))
+        | (ViolationKind::MissingRecord, StateKey::TagIndex(tag, n))
+        | (ViolationKind::CorruptRecord, StateKey::TagIndex(tag, n)) => {
+            drop_tag_index_entry(deps, &tag, n)?
+        }
+        (ViolationKind::CounterMismatch, StateKey::TagCount(tag)) => recount_tag(deps, &tag)?,
+        _ => return dyn_contract_err(format!("Violation {} cannot be repaired", token)),
+    };
+
+    Ok(Response {
+        log: vec![
+            log("action", "repair_state"),
+            log("key", &violation.key),
+            log("repaired", &repaired),
+        ],
+        ..Response::default()
+    })
+}
+
+/// StateKey is a record VerifyState may report a violation for
+enum StateKey {
+    Execution(u64),
+    StagedBatch(u64),
+    AdminProposal(u64),
+    TagIndex(String, u64),
+    TagCount(String),
+}
+
+fn parse_state_key(key: &str) -> Option<StateKey> {
+    let number = |n: &str| n.parse::<u64>().ok();
+    let parts: Vec<&str> = key.split('/').collect();
+    let key = match parts.as_slice() {
+        ["executions", id] => StateKey::Execution(number(id)?),
+        ["staged_batches", id] => StateKey::StagedBatch(number(id)?),
+        ["admin_proposals", id] => StateKey::AdminProposal(number(id)?),
+        ["tag_index", tag, n] => StateKey::TagIndex(tag.to_string(), number(n)?),
+        ["tag_counts", tag] => StateKey::TagCount(tag.to_string()),
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Verifies just the record under key
+fn violations_at<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    key: &StateKey,
+) -> Result<Vec<Violation>> {
+    let (section, from) = match key {
+        StateKey::Execution(id) => (VerifySection::Executions, Some(*id)),
+        StateKey::StagedBatch(id) => (VerifySection::StagedBatches, Some(*id)),
+        StateKey::AdminProposal(id) => (VerifySection::AdminProposals, Some(*id)),
+        StateKey::TagIndex(tag, n) => (VerifySection::TagIndex { tag: tag.clone() }, Some(*n)),
+        // the counter is checked along with the end of the index
+        StateKey::TagCount(tag) => (VerifySection::TagIndex { tag: tag.clone() }, None),
+    };
+    let (_, until) = verify_range(deps, &section)?;
+    let from = from.unwrap_or(until);
+    let end = until.min(from.saturating_add(1));
+    Ok(verify_section(deps, &section, from, end, until))
+}
+
+/// Removes the n-th entry from the tag index by moving the ones after it down.
+/// Storage cannot be deleted from, so the freed last entry is overwritten with 0.
+fn drop_tag_index_entry<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    tag: &str,
+    n: u64,
+) -> Result<String> {
+    let count = tag_counts_read(&deps.storage)
+        .may_load(tag.as_bytes())?
+        .unwrap_or(0);
+    if count - n > MAX_REPAIR_SCAN {
+        return dyn_contract_err(format!("Too many entries after {} to move", n));
+    }
+    let mut index = tag_index(&mut deps.storage);
+    for m in n + 1..count {
+        let id = read_verified(index.may_load(&tag_index_key(tag, m))).unwrap_or(0);
+        index.save(&tag_index_key(tag, m - 1), &id)?;
+    }
+    index.save(&tag_index_key(tag, count - 1), &0)?;
+    tag_counts(&mut deps.storage).save(tag.as_bytes(), &(count - 1))?;
+    Ok(format!("dropped entry {}, count is {}", n, count - 1))
+}
+
+/// Raises the tag counter over the entries found beyond it
+fn recount_tag<S: Storage, A: Api>(deps: &mut Extern<S, A>, tag: &str) -> Result<String> {
+    let count = tag_counts_read(&deps.storage)
+        .may_load(tag.as_bytes())?
+        .unwrap_or(0);
+    let index = tag_index_read(&deps.storage);
+    let mut recount = count;
+    while recount < count + MAX_REPAIR_SCAN {
+        match read_verified(index.may_load(&tag_index_key(tag, recount))) {
+            Some(id) if id != 0 => recount += 1,
+            _ => break,
+        }
+    }
+    tag_counts(&mut deps.storage).save(tag.as_bytes(), &recount)?;
+    Ok(format!("count is {}", recount))
+}
+
+pub fn try_register_denom<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    denom: String,
+    symbol: String,
+    exponent: u32,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if denom.is_empty() || symbol.is_empty() {
+        return contract_err("denom and symbol must not be empty");
+    }
+    let metadata = DenomMetadata { symbol, exponent };
+    denoms(&mut deps.storage).save(denom.as_bytes(), &metadata)?;
+    Ok(Response {
+        log: vec![log("action", "register_denom"), log("denom", &denom)],
+        ..Response::default()
+    })
+}
+
+pub fn try_report_gas<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    execution_id: u64,
+    gas_used: u64,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let key = execution_id.to_be_bytes();
+    let mut execution = executions(&mut deps.storage).load(&key)?;
+    if execution.gas_used.is_some() {
+        return contract_err("Gas already reported for this execution");
+    }
+    execution.gas_used = Some(gas_used);
+    executions(&mut deps.storage).save(&key, &execution)?;
+
+    // apportion the gas evenly over all messages in the execution
+    let total: u64 = execution.msgs.iter().map(|k| u64::from(k.count)).sum();
+    for kc in execution.msgs.iter() {
+        let share = (u128::from(gas_used) * u128::from(kc.count) / u128::from(total)) as u64;
+        let mut stats = gas_stats(&mut deps.storage);
+        let mut current = stats
+            .may_load(kc.kind.as_str().as_bytes())?
+            .unwrap_or_default();
+        current.msgs = current.msgs.saturating_add(u64::from(kc.count));
+        current.gas = current.gas.saturating_add(share);
+        stats.save(kc.kind.as_str().as_bytes(), &current)?;
+    }
+
+    Ok(Response {
+        log: vec![
+            log("action", "report_gas"),
+            log("execution_id", &execution_id.to_string()),
+            ref_log(EntityKind::Execution, execution_id),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_co_owners<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    co_owners: Vec<HumanAddr>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let mut canonical = BoundedVec::new(CO_OWNERS);
+    for addr in co_owners.iter() {
+        state.chain.validate_address(addr)?;
+        let addr = deps.api.canonical_address(addr)?;
+        if addr == state.owner || canonical.contains(&addr) {
+            return contract_err("Co-owners must be unique and differ from the owner");
+        }
+        canonical.push(addr)?;
+    }
+    state.co_owners = canonical.into_vec();
+    state.validate_threshold()?;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![
+            log("action", "set_co_owners"),
+            log("co_owners", &co_owners.len().to_string()),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_add_admin<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    addr: HumanAddr,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    state.chain.validate_address(&addr)?;
+    let canonical = deps.api.canonical_address(&addr)?;
+    if state.is_owner(&canonical) {
+        return dyn_contract_err(format!("{} is already an admin", addr.as_str()));
+    }
+    CO_OWNERS.check(state.co_owners.len() + 1)?;
+    state.co_owners.push(canonical);
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![log("action", "add_admin"), log("admin", addr.as_str())],
+        ..Response::default()
+    })
+}
+
+pub fn try_remove_admin<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    addr: HumanAddr,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let canonical = deps.api.canonical_address(&addr)?;
+    if !state.is_owner(&canonical) {
+        return dyn_contract_err(format!("{} is not an admin", addr.as_str()));
+    }
+    if !state.is_joint() {
+        return contract_err("Cannot remove the last admin");
+    }
+    let owner_removed = state.owner == canonical;
+    if owner_removed {
+        state.owner = state.co_owners.remove(0);
+    } else {
+        state.co_owners.retain(|o| *o != canonical);
+    }
+    state.weights.retain(|w| w.addr != canonical);
+    state.validate_threshold()?;
+    config(&mut deps.storage).save(&state)?;
+    if owner_removed {
+        record_history(deps, &env, "remove_admin", vec![], Some(state.owner), None)?;
+    }
+
+    Ok(Response {
+        log: vec![log("action", "remove_admin"), log("admin", addr.as_str())],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_admin_weights<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    weights: Vec<WeightMsg>,
+    threshold: Option<u64>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    let mut canonical: Vec<OwnerWeight> = Vec::with_capacity(weights.len());
+    for w in weights.iter() {
+        let addr = deps.api.canonical_address(&w.addr)?;
+        if !state.is_owner(&addr) {
+            return dyn_contract_err(format!("{} is not an owner", w.addr.as_str()));
+        }
+        if canonical.iter().any(|c| c.addr == addr) {
+            return contract_err("Duplicate weight entry");
+        }
+        canonical.push(OwnerWeight {
+            addr,
+            weight: w.weight,
+        });
+    }
+    state.weights = canonical;
+    state.admin_threshold = threshold;
+    state.validate_threshold()?;
+    config(&mut deps.storage).save(&state)?;
+
+    let threshold = match threshold {
+        Some(t) => t.to_string(),
+        None => "all".to_string(),
+    };
+    Ok(Response {
+        log: vec![
+            log("action", "set_admin_weights"),
+            log("threshold", &threshold),
+        ],
+        ..Response::default()
+    })
+}
+
+pub fn try_set_cooldown<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    action: String,
+    period: Option<Duration>,
+) -> Result<Response> {
+    let mut state = config(&mut deps.storage).load()?;
+    if !state.can_admin(&env) {
+        return unauthorized();
+    }
+    if !HandleMsg::ACTIONS.contains(&action.as_str()) {
+        return dyn_contract_err(format!("Unknown action {}", action));
+    }
+    state.cooldowns.retain(|c| c.action != action);
+    if let Some(period) = period {
+        state.cooldowns.push(Cooldown {
+            action: action.clone(),
+            period,
+        });
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(Response {
+        log: vec![log("action", "set_cooldown"), log("cooldown", &action)],
+        ..Response::default()
+    })
+}
+
+pub fn try_propose_admin<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    msg: HandleMsg,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_joint() {
+        return contract_err("Admin proposals are only used by joint accounts");
+    }
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    match msg {
+        HandleMsg::ReflectMsg { .. }
+        | HandleMsg::ProposeAdmin { .. }
+        | HandleMsg::ApproveAdmin { .. } => {
+            return contract_err("Only admin actions can be proposed");
+        }
+        _ => {}
+    }
+
+    let id = next_admin_proposal_id(&mut deps.storage)?;
+    let proposal = AdminProposal {
+        id,
+        msg,
+        approvals: vec![env.message.signer.clone()],
+        executed: false,
+    };
+    admin_proposals(&mut deps.storage).save(&id.to_be_bytes(), &proposal)?;
+
+    let mut res = execute_if_approved(deps, env, proposal)?;
+    res.log.insert(0, log("action", "propose_admin"));
+    res.log.insert(1, log("proposal_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::AdminProposal, id));
+    Ok(res)
+}
+
+pub fn try_approve_admin<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    id: u64,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_owner(&env.message.signer) {
+        return not_owner(&deps.api, &env);
+    }
+    let mut proposal = found(
+        admin_proposals(&mut deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::AdminProposal,
+        id,
+    )?;
+    if proposal.executed {
+        return contract_err("Proposal was already executed");
+    }
+    if proposal.approvals.contains(&env.message.signer) {
+        return contract_err("Already approved");
+    }
+    proposal.approvals.push(env.message.signer.clone());
+    admin_proposals(&mut deps.storage).save(&id.to_be_bytes(), &proposal)?;
+
+    let mut res = execute_if_approved(deps, env, proposal)?;
+    res.log.insert(0, log("action", "approve_admin"));
+    res.log.insert(1, log("proposal_id", &id.to_string()));
+    res.log.insert(2, ref_log(EntityKind::AdminProposal, id));
+    Ok(res)
+}
+
+/// Runs the proposed admin action once every current owner approved it.
+/// The action executes with the contract itself as signer, which is what `State::can_admin`
+/// accepts for joint accounts.
+fn execute_if_approved<S: Storage, A: Api>(
+    deps: &mut Extern<S, A>,
+    env: Env,
+    mut proposal: AdminProposal,
+) -> Result<Response> {
+    let state = config(&mut deps.storage).load()?;
+    if !state.is_approved(&proposal.approvals) {
+        return Ok(Response::default());
+    }
+
+    proposal.executed = true;
+    admin_proposals(&mut deps.storage).save(&proposal.id.to_be_bytes(), &proposal)?;
+
+    let mut inner_env = env;
+    inner_env.message.signer = inner_env.contract.address.clone();
+    let mut res = handle_inner(deps, inner_env, proposal.msg)?;
+    res.log.insert(0, log("executed", "true"));
+    Ok(res)
+}
+
+pub fn query<S: Storage, A: Api>(deps: &Extern<S, A>, msg: QueryMsg) -> Result<Vec<u8>> {
+    match msg {
+        QueryMsg::GetOwner {} => query_owner(deps),
+        QueryMsg::GetConfig {} => query_config(deps),
+        QueryMsg::GetChainProfile {} => query_chain_profile(deps),
+        QueryMsg::DenomMetadata { denom } => query_denom_metadata(deps, denom),
+        QueryMsg::DescribeCoins { coins } => query_describe_coins(deps, coins),
+        QueryMsg::Execution { id } => query_execution(deps, id),
+        QueryMsg::ListExecutions {
+            start_after,
+            limit,
+            order,
+            min_height,
+            max_height,
+            sender,
+            tag,
+            kind,
+        } => {
+            let filter = ExecutionFilter {
+                min_height,
+                max_height,
+                sender,
+                tag,
+                kind,
+            };
+            query_list_executions(deps, start_after, limit, order, filter)
+        }
+        QueryMsg::EstimateCost { kind } => query_estimate_cost(deps, kind),
+        QueryMsg::GetCoOwners {} => query_co_owners(deps),
+        QueryMsg::AdminProposal { id } => query_admin_proposal(deps, id),
+        QueryMsg::ListAdminProposals { start_after, limit } => {
+            query_list_admin_proposals(deps, start_after, limit)
+        }
+        QueryMsg::AdminVote { id, owner } => query_admin_vote(deps, id, owner),
+        QueryMsg::Cooldowns {} => query_cooldowns(deps),
+        QueryMsg::Operators {} => query_operators(deps),
+        QueryMsg::StagedBatch { id } => query_staged_batch(deps, id),
+        QueryMsg::ListStagedBatches { start_after, limit } => {
+            query_list_staged_batches(deps, start_after, limit)
+        }
+        QueryMsg::Tags {} => query_tags(deps),
+        QueryMsg::ListTaggedExecutions {
+            tag,
+            start_after,
+            limit,
+        } => query_list_tagged_executions(deps, tag, start_after, limit),
+        QueryMsg::TagSpend { tag } => query_tag_spend(deps, tag),
+        QueryMsg::Recovery {} => query_recovery(deps),
+        QueryMsg::Successor {} => query_successor(deps),
+        QueryMsg::PendingConfig {} => query_pending_config(deps),
+        QueryMsg::ConfigDiff {} => query_config_diff(deps),
+        QueryMsg::SimulateWithConfig {
+            config_override,
+            sender,
+            msgs,
+            tags,
+        } => query_simulate_with_config(deps, config_override, sender, msgs, tags),
+        QueryMsg::ExportChunk {
+            namespace,
+            start_after,
+            max_bytes,
+        } => query_export_chunk(deps, namespace, start_after, max_bytes),
+        QueryMsg::VerifyState {
+            section,
+            start_after,
+            limit,
+        } => query_verify_state(deps, section, start_after, limit),
+        QueryMsg::Features {} => query_features(),
+        QueryMsg::Grant { id } => query_grant(deps, id),
+        QueryMsg::ListGrants {
+            grantee,
+            start_after,
+            limit,
+        } => query_list_grants(deps, grantee, start_after, limit),
+        QueryMsg::GetPendingOwner {} => query_pending_owner(deps),
+        QueryMsg::ListAdmins { start_after, limit } => query_list_admins(deps, start_after, limit),
+        QueryMsg::AllowedTargets {} => query_allowed_targets(deps),
+        QueryMsg::Swap { id } => query_swap(deps, id),
+        QueryMsg::Permissions { grantee } => query_permissions(deps, grantee),
+        QueryMsg::Auction { id } => query_auction(deps, id),
+        QueryMsg::ListScheduled { start_after, limit } => {
+            query_list_scheduled(deps, start_after, limit)
+        }
+        QueryMsg::Revenue { tag, period } => query_revenue(deps, tag, period),
+        QueryMsg::Stakeholders {} => query_stakeholders(deps),
+        QueryMsg::Proposal { id } => query_proposal(deps, id),
+        QueryMsg::ListProposals { start_after, limit } => {
+            query_list_proposals(deps, start_after, limit)
+        }
+        QueryMsg::SpendLimit { denom } => query_spend_limit(deps, denom),
+        QueryMsg::ContractVersion {} => query_contract_version(deps),
+        QueryMsg::OperatorWindow { operator } => query_operator_window(deps, operator),
+        QueryMsg::Calendar { name } => query_calendar(deps, name),
+        QueryMsg::UpcomingBlackouts {
+            calendar,
+            after,
+            limit,
+        } => query_upcoming_blackouts(deps, calendar, after, limit),
+        QueryMsg::Deposits {} => query_deposits(deps),
+        QueryMsg::History { start_after, limit } => query_history(deps, start_after, limit),
+        QueryMsg::Guardians {} => query_guardians(deps),
+        QueryMsg::RecoverySession {} => query_recovery_session(deps),
+        QueryMsg::IsDenied { addr } => query_is_denied(deps, addr),
+        QueryMsg::CollectedFees {} => query_collected_fees(deps),
+        QueryMsg::ValidateMsg { sender, msg } => query_validate_msg(deps, sender, msg),
+        QueryMsg::Stats { addr } => query_stats(deps, addr),
+        QueryMsg::GlobalStats {} => query_global_stats(deps),
+        QueryMsg::Metrics {} => query_metrics(deps),
+        QueryMsg::Rules {} => query_rules(deps),
+        QueryMsg::GetData { key } => query_get_data(deps, key),
+        QueryMsg::ListData { start_after, limit } => query_list_data(deps, start_after, limit),
+        QueryMsg::Session { key_addr } => query_session(deps, key_addr),
+        QueryMsg::Cosigner {} => query_cosigner(deps),
+        QueryMsg::QueuedExecution { id } => query_queued_execution(deps, id),
+        QueryMsg::ListAuthorizations { start_after, limit } => {
+            query_list_authorizations(deps, start_after, limit)
+        }
+        QueryMsg::Attestation {} => query_attestation(deps),
+        QueryMsg::StorageUsage {} => query_storage_usage(deps),
+        QueryMsg::SuggestBatching { msgs, gas_budget } => {
+            query_suggest_batching(deps, msgs, gas_budget)
+        }
+        QueryMsg::Aggregate { queries } => query_aggregate(deps, queries),
+        QueryMsg::Macros {} => query_macros(deps),
+        QueryMsg::Resolve { reference } => query_resolve(deps, reference),
+        QueryMsg::Statuses {} => query_statuses(),
+        QueryMsg::Search { term, limit } => query_search(deps, term, limit),
+    }
+}
+
+/// Joint accounts (multisig) and scheduled config changes are always built in.
+/// ibc and cw20 need a newer cosmwasm, so no build has them yet.
+fn query_features() -> Result<Vec<u8>> {
+    let mut features = vec!["multisig".to_string(), "scheduler".to_string()];
+    if cfg!(feature = "backtraces") {
+        features.push("backtraces".to_string());
+    }
+
+    let resp = FeaturesResponse { features };
+    to_vec(&resp).context(SerializeErr {
+        kind: "FeaturesResponse",
+    })
+}
+
+fn query_config<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let mut co_owners = Vec::with_capacity(state.co_owners.len());
+    for addr in state.co_owners.iter() {
+        co_owners.push(deps.api.human_address(addr)?);
+    }
+    let depositors = match &state.depositors {
+        Some(list) => {
+            let mut humans = Vec::with_capacity(list.len());
+            for addr in list.iter() {
+                humans.push(deps.api.human_address(addr)?);
+            }
+            Some(humans)
+        }
+        None => None,
+    };
+    let checksum = state.checksum()?;
+    let resp = ConfigResponse {
+        owner: deps.api.human_address(&state.owner)?,
+        co_owners,
+        paused: state.paused,
+        decommissioned: state.decommissioned,
+        deposit_hook: state.deposit_hook,
+        depositors,
+        travel_rule: state.travel_rule,
+        four_eyes: state.four_eyes,
+        reflect_fee: state.reflect_fee,
+        rulesets: state.rulesets,
+        middlewares: state.middlewares,
+        archive: state.archive,
+        checksum,
+        config_version: config_version_read(&deps.storage).may_load()?.unwrap_or(0),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ConfigResponse",
+    })
+}
+
+fn query_owner<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let resp = OwnerResponse {
+        owner: deps.api.human_address(&state.owner)?,
+        renounced: state.renounced,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "OwnerResponse",
+    })
+}
+
+fn query_pending_owner<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let resp = match pending_owner_read(&deps.storage).may_load()? {
+        Some(Some(pending)) => PendingOwnerResponse {
+            owner: Some(deps.api.human_address(&pending.owner)?),
+            expires: Some(pending.expires),
+        },
+        _ => PendingOwnerResponse {
+            owner: None,
+            expires: None,
+        },
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "PendingOwnerResponse",
+    })
+}
+
+fn query_allowed_targets<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let resp = AllowedTargetsResponse {
+        targets: state.allowed_targets,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AllowedTargetsResponse",
+    })
+}
+
+fn query_swap<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let swap = found(
+        swaps_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Swap,
+        id,
+    )?;
+
+    let status = if swap.filled {
+        Status::Filled
+    } else {
+        Status::Open
+    };
+    let resp = SwapResponse {
+        id: swap.id,
+        counterparty: deps.api.human_address(&swap.counterparty)?,
+        give: swap.give,
+        want: swap.want,
+        expires: swap.expires,
+        filled: swap.filled,
+        status,
+        status_code: status.code(),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "SwapResponse",
+    })
+}
+
+fn query_permissions<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    grantee: HumanAddr,
+) -> Result<Vec<u8>> {
+    let canonical = deps.api.canonical_address(&grantee)?;
+    let macros = macro_runs_of(deps, &canonical)?;
+    let resp = match permissions_read(&deps.storage).may_load(canonical.as_slice())? {
+        Some(p) if !p.msg_types.is_empty() => PermissionsResponse {
+            grantee,
+            msg_types: p.msg_types,
+            expires_at_height: Some(p.expires_at_height),
+            macros,
+        },
+        _ => PermissionsResponse {
+            grantee,
+            msg_types: vec![],
+            expires_at_height: None,
+            macros,
+        },
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "PermissionsResponse",
+    })
+}
+
+/// The macros runner may run, with what is left of their quotas
+fn macro_runs_of<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    runner: &CanonicalAddr,
+) -> Result<Vec<MacroRunsResponse>> {
+    let names = macro_names_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let mut runnable = vec![];
+    for name in names {
+        let found = macros_read(&deps.storage).load(name.as_bytes())?;
+        if !found.runners.contains(runner) {
+            continue;
+        }
+        let (remaining, resets_at) = match found.quota {
+            Some(quota) => {
+                let key = macro_runs_key(runner, &name);
+                match macro_runs_read(&deps.storage).may_load(&key)? {
+                    Some(runs) => (
+                        Some(quota.runs.saturating_sub(runs.runs)),
+                        Some(runs.resets_at(quota.period)),
+                    ),
+                    None => (Some(quota.runs), None),
+                }
+            }
+            None => (None, None),
+        };
+        runnable.push(MacroRunsResponse {
+            name,
+            quota: found.quota,
+            remaining,
+            resets_at,
+        });
+    }
+    Ok(runnable)
+}
+
+fn query_auction<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let auction = found(
+        auctions_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Auction,
+        id,
+    )?;
+    let buyer = match auction.buyer {
+        Some(buyer) => Some(deps.api.human_address(&buyer)?),
+        None => None,
+    };
+
+    let status = if auction.cancelled {
+        Status::Cancelled
+    } else if auction.paid.is_some() {
+        Status::Sold
+    } else {
+        Status::Open
+    };
+    let resp = AuctionResponse {
+        id: auction.id,
+        sell: auction.sell,
+        start_price: auction.start_price,
+        end_price: auction.end_price,
+        duration: auction.duration,
+        start_height: auction.start_height,
+        start_time: auction.start_time,
+        buyer,
+        paid: auction.paid,
+        cancelled: auction.cancelled,
+        status,
+        status_code: status.code(),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AuctionResponse",
+    })
+}
+
+fn query_recovery<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let recovery = match state.recovery {
+        Some(addr) => Some(deps.api.human_address(&addr)?),
+        None => None,
+    };
+    let resp = RecoveryResponse {
+        recovery,
+        paused: state.paused,
+        evacuated: state.evacuated,
+        decommissioned: state.decommissioned,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "RecoveryResponse",
+    })
+}
+
+fn query_successor<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let successor = match state.successor {
+        Some(addr) => Some(deps.api.human_address(&addr)?),
+        None => None,
+    };
+    let resp = SuccessorResponse { successor };
+    to_vec(&resp).context(SerializeErr {
+        kind: "SuccessorResponse",
+    })
+}
+
+fn query_pending_config<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let resp = PendingConfigResponse {
+        pending: state.pending,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "PendingConfigResponse",
+    })
+}
+
+fn query_config_diff<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let active = config_read(&deps.storage).load()?;
+    let mut resp = ConfigDiffResponse {
+        activate_at: None,
+        tags_added: vec![],
+        tags_removed: vec![],
+        budgets: vec![],
+        cooldowns: vec![],
+    };
+    if let Some(pending) = active.pending.clone() {
+        let mut next = active.clone();
+        apply_override(&mut next, pending.change);
+        resp = diff_config(&active, &next);
+        resp.activate_at = Some(pending.activate_at);
+    }
+    to_vec(&resp).context(SerializeErr {
+        kind: "ConfigDiffResponse",
+    })
+}
+
+/// Compares the policy fields of both configs
+fn diff_config(old: &State, new: &State) -> ConfigDiffResponse {
+    let tags_added = new
+        .tags
+        .iter()
+        .filter(|t| !old.tags.contains(t))
+        .cloned()
+        .collect();
+    let tags_removed = old
+        .tags
+        .iter()
+        .filter(|t| !new.tags.contains(t))
+        .cloned()
+        .collect();
+
+    let budget_of = |state: &State, tag: &str| {
+        state
+            .budgets
+            .iter()
+            .find(|b| b.tag == tag)
+            .map(|b| b.budget.clone())
+    };
+    let mut budgets: Vec<BudgetChange> = vec![];
+    for b in old.budgets.iter().chain(new.budgets.iter()) {
+        if budgets.iter().any(|c| c.tag == b.tag) {
+            continue;
+        }
+        let change = BudgetChange {
+            tag: b.tag.clone(),
+            old: budget_of(old, &b.tag),
+            new: budget_of(new, &b.tag),
+        };
+        if change.old != change.new {
+            budgets.push(change);
+        }
+    }
+
+    let period_of = |state: &State, action: &str| {
+        state
+            .cooldowns
+            .iter()
+            .find(|c| c.action == action)
+            .map(|c| c.period)
+    };
+    let mut cooldowns: Vec<CooldownChange> = vec![];
+    for c in old.cooldowns.iter().chain(new.cooldowns.iter()) {
+        if cooldowns.iter().any(|change| change.action == c.action) {
+            continue;
+        }
+        let change = CooldownChange {
+            action: c.action.clone(),
+            old: period_of(old, &c.action),
+            new: period_of(new, &c.action),
+        };
+        if change.old != change.new {
+            cooldowns.push(change);
+        }
+    }
+
+    ConfigDiffResponse {
+        activate_at: None,
+        tags_added,
+        tags_removed,
+        budgets,
+        cooldowns,
+    }
+}
+
+/// The names of the top-level fields which differ between both configs
+fn changed_fields(old: &State, new: &State) -> Vec<&'static str> {
+    let mut changed = vec![];
+    macro_rules! compare {
+        ($($field:ident),*) => {
+            $(if old.$field != new.$field {
+                changed.push(stringify!($field));
+            })*
+        };
+    }
+    compare!(
+        owner,
+        co_owners,
+        admin_threshold,
+        weights,
+        cooldowns,
+        operators,
+        operator_manifest_nonce,
+        tags,
+        budgets,
+        recovery,
+        paused,
+        evacuated,
+        decommissioned,
+        renounced,
+        successor,
+        pending,
+        chain,
+        max_msg_bytes,
+        allowed_targets,
+        deposit_hook,
+        depositors,
+        travel_rule,
+        spend_limits,
+        suspended_operators,
+        four_eyes,
+        reflect_fee,
+        rulesets,
+        rules,
+        cosign,
+        middlewares,
+        attestation_max_age,
+        archive,
+        gas_heuristics
+    );
+    changed
+}
+
+fn query_simulate_with_config<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    config_override: ConfigOverride,
+    sender: HumanAddr,
+    msgs: Vec<CosmosMsg>,
+    tags: Vec<String>,
+) -> Result<Vec<u8>> {
+    let mut state = config_read(&deps.storage).load()?;
+    apply_override(&mut state, config_override);
+
+    let error = match simulate_reflect(deps, &state, &sender, &msgs, &tags) {
+        Ok(()) => None,
+        Err(err) => Some(err.to_string()),
+    };
+    let resp = SimulationResponse { error };
+    to_vec(&resp).context(SerializeErr {
+        kind: "SimulationResponse",
+    })
+}
+
+fn query_validate_msg<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    sender: HumanAddr,
+    msg: CosmosMsg,
+) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let reason = match simulate_reflect(deps, &state, &sender, &[msg], &[]) {
+        Ok(()) => None,
+        Err(err) => Some(err.to_string()),
+    };
+    let resp = ValidationResponse {
+        valid: reason.is_none(),
+        reason,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ValidationResponse",
+    })
+}
+
+/// Runs the checks a reflect call would go through, without storing anything.
+/// Queries know neither the block nor the contract address, so cooldowns, self calls
+/// and the expiry of permissions are not checked, and budget periods are assumed to
+/// still be running. Neither are fees, as queries send no funds.
+fn simulate_reflect<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    state: &State,
+    sender: &HumanAddr,
+    msgs: &[CosmosMsg],
+    tags: &[String],
+) -> Result<()> {
+    if state.decommissioned {
+        return ContractError::Decommissioned {}.fail();
+    }
+    if state.paused {
+        return ContractError::ContractPaused {
+            action: "reflect".to_string(),
+        }
+        .fail();
+    }
+    let sender = deps.api.canonical_address(sender)?;
+    if !state.is_owner(&sender) {
+        let permitted = match permissions_read(&deps.storage).may_load(sender.as_slice())? {
+            Some(p) => msgs.iter().all(|m| p.msg_types.contains(&MsgKind::of(m))),
+            None => false,
+        };
+        let in_session = match sessions_read(&deps.storage).may_load(sender.as_slice())? {
+            Some(session) => msgs.len() as u64 <= session.remaining_msgs,
+            None => false,
+        };
+        if !permitted && !in_session {
+            return unauthorized();
+        }
+    }
+    state.validate_tags(tags)?;
+    check_four_eyes(state, msgs)?;
+    check_targets(state, msgs)?;
+    check_rules(state, msgs)?;
+    check_denylist(deps, msgs)?;
+    check_msg_bytes(state, msgs)?;
+    for tag in tags.iter() {
+        if let Some(b) = state.budgets.iter().find(|b| b.tag == *tag) {
+            let spent = match tag_spend_read(&deps.storage).may_load(tag.as_bytes())? {
+                Some(spend) => spend.spent,
+                None => "0".to_string(),
+            };
+            check_budget(tag, &b.budget, &spent, msgs)?;
+        }
+    }
+    for limit in state.spend_limits.iter().flatten() {
+        let denom = limit.limit.denom.as_bytes();
+        let spent = match spend_tracker_read(&deps.storage).may_load(denom)? {
+            Some(tracker) => tracker.spent,
+            None => "0".to_string(),
+        };
+        check_spend_limit(limit, &spent, msgs)?;
+    }
+    Ok(())
+}
+
+fn query_chain_profile<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let resp = ChainProfileResponse { chain: state.chain };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ChainProfileResponse",
+    })
+}
+
+fn query_denom_metadata<S: Storage, A: Api>(deps: &Extern<S, A>, denom: String) -> Result<Vec<u8>> {
+    let metadata = denoms_read(&deps.storage).may_load(denom.as_bytes())?;
+
+    let resp = DenomMetadataResponse { denom, metadata };
+    to_vec(&resp).context(SerializeErr {
+        kind: "DenomMetadataResponse",
+    })
+}
+
+fn query_describe_coins<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    coins: Vec<Coin>,
+) -> Result<Vec<u8>> {
+    let registry = denoms_read(&deps.storage);
+    let mut described = Vec::with_capacity(coins.len());
+    for coin in coins {
+        let (symbol, display_amount) = match registry.may_load(coin.denom.as_bytes())? {
+            Some(meta) => {
+                let display = meta.display_amount(&coin.amount)?;
+                (Some(meta.symbol), Some(display))
+            }
+            None => (None, None),
+        };
+        described.push(CoinDescription {
+            denom: coin.denom,
+            amount: coin.amount,
+            symbol,
+            display_amount,
+        });
+    }
+
+    let resp = DescribeCoinsResponse { coins: described };
+    to_vec(&resp).context(SerializeErr {
+        kind: "DescribeCoinsResponse",
+    })
+}
+
+fn execution_response<A: Api>(api: &A, execution: Execution) -> Result<ExecutionResponse> {
+    Ok(ExecutionResponse {
+        id: execution.id,
+        sender: api.human_address(&execution.sender)?,
+        height: execution.height,
+        msgs: execution.msgs,
+        gas_used: execution.gas_used,
+        tags: execution.tags,
+        memo: execution.memo,
+        payload_hash: execution.payload_hash,
+    })
+}
+
+fn query_execution<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let execution = found(
+        executions_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Execution,
+        id,
+    )?;
+
+    let resp = execution_response(&deps.api, execution)?;
+    to_vec(&resp).context(SerializeErr {
+        kind: "ExecutionResponse",
+    })
+}
+
+/// Most executions ListExecutions looks at in one query
+const MAX_EXECUTION_SCAN: usize = 100;
+
+struct ExecutionFilter {
+    min_height: Option<i64>,
+    max_height: Option<i64>,
+    sender: Option<HumanAddr>,
+    tag: Option<String>,
+    kind: Option<MsgKind>,
+}
+
+fn query_list_executions<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order: Option<Order>,
+    filter: ExecutionFilter,
+) -> Result<Vec<u8>> {
+    let last = last_execution_id(&deps.storage)?;
+    let executions_bucket = executions_read(&deps.storage);
+
+    // heights never decrease with the id, so the range maps to a span of ids
+    let mut low = 1;
+    if let Some(height) = filter.min_height {
+        low = first_execution_at(deps, last, height)?;
+    }
+    let mut high = last;
+    if let Some(height) = filter.max_height {
+        high = first_execution_at(deps, last, height.saturating_add(1))? - 1;
+    }
+    let sender = match &filter.sender {
+        Some(addr) => Some(deps.api.canonical_address(addr)?),
+        None => None,
+    };
+
+    let start_after = decode_start_after(start_after)?;
+    let limit = clamp_limit(limit);
+    let load = |id: u64| {
+        let execution = executions_bucket.load(&id.to_be_bytes())?;
+        if let Some(sender) = &sender {
+            if execution.sender != *sender {
+                return Ok(None);
+            }
+        }
+        if let Some(tag) = &filter.tag {
+            if !execution.tags.contains(tag) {
+                return Ok(None);
+            }
+        }
+        if let Some(kind) = filter.kind {
+            if !execution.msgs.iter().any(|k| k.kind == kind && k.count > 0) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(execution_response(&deps.api, execution)?))
+    };
+    let page = match order.unwrap_or(Order::Asc) {
+        Order::Asc => {
+            let keys = start_key(start_after, low).map(|start| start..=high);
+            paginate_bounded(keys.into_iter().flatten(), limit, MAX_EXECUTION_SCAN, load)?
+        }
+        Order::Desc => {
+            let end = start_after.map_or(high, |id| high.min(id.saturating_sub(1)));
+            paginate_bounded((low..=end).rev(), limit, MAX_EXECUTION_SCAN, load)?
+        }
+    };
+
+    let resp = ExecutionsResponse {
+        executions: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ExecutionsResponse",
+    })
+}
+
+/// Finds the first execution at or above the height, or last + 1 if there is none
+fn first_execution_at<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    last: u64,
+    height: i64,
+) -> Result<u64> {
+    let executions_bucket = executions_read(&deps.storage);
+    let (mut low, mut high) = (1, last + 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if executions_bucket.load(&mid.to_be_bytes())?.height < height {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+fn query_macros<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let names = macro_names_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let saved = macros_read(&deps.storage);
+    let mut list = Vec::with_capacity(names.len());
+    for name in names {
+        let found = saved.load(name.as_bytes())?;
+        let runners = found
+            .runners
+            .iter()
+            .map(|r| deps.api.human_address(r))
+            .collect::<Result<Vec<_>>>()?;
+        list.push(MacroResponse {
+            name,
+            msgs: found.msgs,
+            params: found.params,
+            runners,
+            quota: found.quota,
+        });
+    }
+
+    let resp = MacrosResponse { macros: list };
+    to_vec(&resp).context(SerializeErr {
+        kind: "MacrosResponse",
+    })
+}
+
+fn query_resolve<S: Storage, A: Api>(deps: &Extern<S, A>, reference: String) -> Result<Vec<u8>> {
+    let Reference { kind, id } = Reference::parse(&reference)?;
+    let key = id.to_be_bytes();
+    let storage = &deps.storage;
+    let record = match kind {
+        EntityKind::Execution => {
+            to_vec(&found(executions_read(storage).may_load(&key)?, kind, id)?)
+        }
+        EntityKind::StagedBatch => to_vec(&found(
+            staged_batches_read(storage).may_load(&key)?,
+            kind,
+            id,
+        )?),
+        EntityKind::ScheduledBatch => to_vec(&found(
+            scheduled_batches_read(storage).may_load(&key)?,
+            kind,
+            id,
+        )?),
+        EntityKind::Proposal => to_vec(&found(proposals_read(storage).may_load(&key)?, kind, id)?),
+        EntityKind::AdminProposal => to_vec(&found(
+            admin_proposals_read(storage).may_load(&key)?,
+            kind,
+            id,
+        )?),
+        EntityKind::Grant => to_vec(&found(grants_read(storage).may_load(&key)?, kind, id)?),
+        EntityKind::Swap => to_vec(&found(swaps_read(storage).may_load(&key)?, kind, id)?),
+        EntityKind::QueuedExecution => to_vec(&found(
+            queued_executions_read(storage).may_load(&key)?,
+            kind,
+            id,
+        )?),
+        EntityKind::Authorization => to_vec(&found(
+            one_time_auths_read(storage).may_load(&key)?,
+            kind,
+            id,
+        )?),
+        EntityKind::Auction => to_vec(&found(auctions_read(storage).may_load(&key)?, kind, id)?),
+    }
+    .context(SerializeErr { kind: "record" })?;
+
+    let resp = ResolveResponse {
+        reference,
+        kind,
+        id,
+        record: Binary(record),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ResolveResponse",
+    })
+}
+
+fn query_statuses() -> Result<Vec<u8>> {
+    let kinds = EntityKind::ALL
+        .iter()
+        .filter(|kind| !kind.statuses().is_empty())
+        .map(|&kind| KindStatuses {
+            kind,
+            statuses: kind
+                .statuses()
+                .iter()
+                .map(|&status| StatusCode {
+                    status,
+                    code: status.code(),
+                })
+                .collect(),
+        })
+        .collect();
+    let resp = StatusesResponse { kinds };
+    to_vec(&resp).context(SerializeErr {
+        kind: "StatusesResponse",
+    })
+}
+
+fn query_search<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    term: String,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let limit = clamp_limit(limit);
+    let mut groups: Vec<SearchGroup> = vec![];
+    let mut add = |kind: &str, reference: Option<Reference>, summary: String| {
+        let hit = SearchHit {
+            reference: reference.map(|r| r.to_string()),
+            summary,
+        };
+        match groups.iter_mut().find(|g| g.kind == kind) {
+            Some(group) if group.hits.len() < limit => group.hits.push(hit),
+            Some(_) => {}
+            None => groups.push(SearchGroup {
+                kind: kind.to_string(),
+                hits: vec![hit],
+            }),
+        }
+    };
+
+    if let Ok(reference) = Reference::parse(&term) {
+        if let Some(summary) = entity_summary(deps, reference)? {
+            add(reference.kind.name(), Some(reference), summary);
+        }
+    }
+
+    // a term which is no valid address simply finds no address
+    let state = config_read(&deps.storage).load()?;
+    if let Ok(addr) = deps.api.canonical_address(&HumanAddr::from(term.as_str())) {
+        if state.owner == addr {
+            add("owner", None, "owner".to_string());
+        } else if state.co_owners.contains(&addr) {
+            add("owner", None, "co-owner".to_string());
+        }
+        if state.operators.contains(&addr) {
+            let suspended = !state.is_active_operator(&addr);
+            add(
+                "operator",
+                None,
+                if suspended { "suspended" } else { "active" }.to_string(),
+            );
+        }
+        if let Some(p) = permissions_read(&deps.storage).may_load(addr.as_slice())? {
+            let kinds: Vec<&str> = p.msg_types.iter().map(|k| k.as_str()).collect();
+            let summary = format!("{} until height {}", kinds.join(","), p.expires_at_height);
+            add("permission", None, summary);
+        }
+        if let Some(s) = sessions_read(&deps.storage).may_load(addr.as_slice())? {
+            let summary = format!("{} msgs left until {}", s.remaining_msgs, s.expires);
+            add("session", None, summary);
+        }
+        if let Some(s) = stats_read(&deps.storage).may_load(addr.as_slice())? {
+            let summary = format!(
+                "{} reflections, last at height {}",
+                s.reflections, s.last_height
+            );
+            add("sender", None, summary);
+        }
+        if denylist_read(&deps.storage).may_load(addr.as_slice())? == Some(true) {
+            add("denied", None, "denied".to_string());
+        }
+    }
+
+    let count = tag_counts_read(&deps.storage)
+        .may_load(term.as_bytes())?
+        .unwrap_or(0);
+    if state.tags.contains(&term) || count > 0 {
+        add("tag", None, format!("{} executions", count));
+        // the latest executions first
+        let index = tag_index_read(&deps.storage);
+        for n in (0..count).rev().take(limit) {
+            let id = index.load(&tag_index_key(&term, n))?;
+            let reference = Reference::new(EntityKind::Execution, id);
+            if let Some(summary) = entity_summary(deps, reference)? {
+                add(EntityKind::Execution.name(), Some(reference), summary);
+            }
+        }
+    }
+
+    let resp = SearchResponse { term, groups };
+    to_vec(&resp).context(SerializeErr {
+        kind: "SearchResponse",
+    })
+}
+
+/// A line about the referenced entity, None if it does not exist
+fn entity_summary<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    reference: Reference,
+) -> Result<Option<String>> {
+    let key = reference.id.to_be_bytes();
+    let storage = &deps.storage;
+    let open = |closed: bool| if closed { "closed" } else { "open" };
+    let summary = match reference.kind {
+        EntityKind::Execution => executions_read(storage).may_load(&key)?.map(|e| {
+            let msgs: u64 = e.msgs.iter().map(|k| u64::from(k.count)).sum();
+            format!("{} msgs at height {}", msgs, e.height)
+        }),
+        EntityKind::StagedBatch => staged_batches_read(storage).may_load(&key)?.map(|b| {
+            let status = if b.executed { "executed" } else { "staged" };
+            format!("{} msgs, {}: {}", b.msgs.len(), status, b.memo)
+        }),
+        EntityKind::ScheduledBatch => scheduled_batches_read(storage).may_load(&key)?.map(|b| {
+            let closed = open(b.closed);
+            format!(
+                "{} msgs after {}, {}",
+                b.msgs.len(),
+                b.execute_after,
+                closed
+            )
+        }),
+        EntityKind::Proposal => proposals_read(storage).may_load(&key)?.map(|p| {
+            let status = match p.status {
+                ProposalStatus::Pending => "pending",
+                ProposalStatus::Approved => "approved",
+                ProposalStatus::Rejected => "rejected",
+            };
+            format!("{} msgs, {}: {}", p.msgs.len(), status, p.description)
+        }),
+        EntityKind::AdminProposal => admin_proposals_read(storage).may_load(&key)?.map(|p| {
+            let approvals = p.approvals.len();
+            format!(
+                "{} with {} approvals, {}",
+                p.msg.action(),
+                approvals,
+                open(p.executed)
+            )
+        }),
+        EntityKind::Grant => grants_read(storage).may_load(&key)?.map(|g| {
+            let clawed_back = if g.clawed_back { ", clawed back" } else { "" };
+            format!(
+                "{} of {} milestones released to {}{}",
+                g.released,
+                g.milestones.len(),
+                g.grantee.as_str(),
+                clawed_back
+            )
+        }),
+        EntityKind::Swap => swaps_read(storage).may_load(&key)?.map(|s| {
+            if s.filled {
+                "filled".to_string()
+            } else {
+                format!("open until {}", s.expires)
+            }
+        }),
+        EntityKind::QueuedExecution => queued_executions_read(storage).may_load(&key)?.map(|q| {
+            let closed = open(q.closed);
+            format!(
+                "{} msgs at height {}, {}",
+                q.msgs.len(),
+                q.queued_height,
+                closed
+            )
+        }),
+        EntityKind::Authorization => one_time_auths_read(storage).may_load(&key)?.map(|a| {
+            if a.used {
+                "used".to_string()
+            } else {
+                format!("valid until {}", a.expires)
+            }
+        }),
+        EntityKind::Auction => auctions_read(storage).may_load(&key)?.map(|a| {
+            if a.cancelled {
+                "cancelled".to_string()
+            } else if a.paid.is_some() {
+                "sold".to_string()
+            } else {
+                "open".to_string()
+            }
+        }),
+    };
+    Ok(summary)
+}
+
+fn query_tags<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let resp = TagsResponse { tags: state.tags };
+    to_vec(&resp).context(SerializeErr {
+        kind: "TagsResponse",
+    })
+}
+
+fn query_list_tagged_executions<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    tag: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let count = tag_counts_read(&deps.storage)
+        .may_load(tag.as_bytes())?
+        .unwrap_or(0);
+    let index = tag_index_read(&deps.storage);
+    let executions_bucket = executions_read(&deps.storage);
+
+    // cursors are positions in the tag index
+    let keys = start_key(decode_start_after(start_after)?, 0).map(|start| start..count);
+    let page = paginate(keys.into_iter().flatten(), clamp_limit(limit), |n| {
+        let id = index.load(&tag_index_key(&tag, n))?;
+        let execution = executions_bucket.load(&id.to_be_bytes())?;
+        Ok(Some(execution_response(&deps.api, execution)?))
+    })?;
+
+    let resp = TaggedExecutionsResponse {
+        tag,
+        executions: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "TaggedExecutionsResponse",
+    })
+}
+
+fn query_estimate_cost<S: Storage, A: Api>(deps: &Extern<S, A>, kind: MsgKind) -> Result<Vec<u8>> {
+    let stats: GasStats = gas_stats_read(&deps.storage)
+        .may_load(kind.as_str().as_bytes())?
+        .unwrap_or_default();
+
+    let gas_per_msg = if stats.msgs == 0 {
+        None
+    } else {
+        Some(stats.gas / stats.msgs)
+    };
+    let resp = CostEstimateResponse {
+        kind,
+        samples: stats.msgs,
+        gas_per_msg,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "CostEstimateResponse",
+    })
+}
+
+/// Most queries one Aggregate may run
+pub const MAX_AGGREGATE_QUERIES: usize = 20;
+
+fn query_aggregate<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    queries: Vec<AggregateQuery>,
+) -> Result<Vec<u8>> {
+    if queries.len() > MAX_AGGREGATE_QUERIES {
+        let msg = format!(
+            "At most {} queries can be aggregated",
+            MAX_AGGREGATE_QUERIES
+        );
+        return dyn_contract_err(msg);
+    }
+    let mut results = Vec::with_capacity(queries.len());
+    for entry in queries {
+        let res = match entry.query {
+            QueryMsg::Aggregate { .. } => contract_err("Aggregates cannot be nested"),
+            msg => query(deps, msg),
+        };
+        let res = match (res, entry.pointer) {
+            (Ok(data), Some(pointer)) => pointer::select(&data, &pointer).map(|v| v.to_vec()),
+            (res, _) => res,
+        };
+        results.push(match res {
+            Ok(data) => AggregateResult {
+                data: Some(Binary(data)),
+                error: None,
+            },
+            Err(err) => AggregateResult {
+                data: None,
+                error: Some(err.to_string()),
+            },
+        });
+    }
+
+    let resp = AggregateResponse { results };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AggregateResponse",
+    })
+}
+
+fn query_suggest_batching<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    msgs: Vec<CosmosMsg>,
+    gas_budget: u64,
+) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let heuristics = state.gas_heuristics.unwrap_or_default();
+    let gas_per_msg = |kind: MsgKind| -> Result<u64> {
+        if let Some(h) = heuristics.iter().find(|h| h.kind == kind) {
+            return Ok(h.gas_per_msg);
+        }
+        let stats: GasStats = gas_stats_read(&deps.storage)
+            .may_load(kind.as_str().as_bytes())?
+            .unwrap_or_default();
+        match stats.msgs {
+            0 => dyn_contract_err(format!("No gas estimate for {} messages", kind.as_str())),
+            n => Ok(stats.gas / n),
+        }
+    };
+
+    let mut chunks: Vec<BatchChunk> = vec![];
+    for (i, msg) in msgs.iter().enumerate() {
+        let gas = gas_per_msg(MsgKind::of(msg))?;
+        let i = i as u32;
+        match chunks.last_mut() {
+            Some(chunk) if chunk.gas.saturating_add(gas) <= gas_budget => {
+                chunk.end = i + 1;
+                chunk.gas += gas;
+            }
+            _ => chunks.push(BatchChunk {
+                start: i,
+                end: i + 1,
+                gas,
+                over_budget: gas > gas_budget,
+            }),
+        }
+    }
+
+    let resp = BatchingResponse { chunks };
+    to_vec(&resp).context(SerializeErr {
+        kind: "BatchingResponse",
+    })
+}
+
+fn query_co_owners<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let mut co_owners = Vec::with_capacity(state.co_owners.len());
+    for addr in state.co_owners.iter() {
+        co_owners.push(deps.api.human_address(addr)?);
+    }
+    let mut weights = vec![];
+    for addr in state.owners().iter() {
+        weights.push(WeightMsg {
+            addr: deps.api.human_address(addr)?,
+            weight: state.weight_of(addr),
+        });
+    }
+    let resp = CoOwnersResponse {
+        owner: deps.api.human_address(&state.owner)?,
+        co_owners,
+        admin_threshold: state.admin_threshold,
+        weights,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "CoOwnersResponse",
+    })
+}
+
+fn query_list_admins<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let owners = config_read(&deps.storage).load()?.owners();
+
+    let count = owners.len() as u64;
+    let keys = start_key(decode_start_after(start_after)?, 0).map(|start| start..count);
+    let page = paginate(keys.into_iter().flatten(), clamp_limit(limit), |i| {
+        Ok(Some(deps.api.human_address(&owners[i as usize])?))
+    })?;
+
+    let resp = AdminsResponse {
+        admins: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AdminsResponse",
+    })
+}
+
+fn admin_proposal_response<A: Api>(
+    api: &A,
+    state: &State,
+    proposal: AdminProposal,
+) -> Result<AdminProposalResponse> {
+    let mut approvals = Vec::with_capacity(proposal.approvals.len());
+    for addr in proposal.approvals.iter() {
+        approvals.push(api.human_address(addr)?);
+    }
+    let status = if proposal.executed {
+        Status::Executed
+    } else {
+        Status::Pending
+    };
+    Ok(AdminProposalResponse {
+        id: proposal.id,
+        approved_weight: state.approved_weight(&proposal.approvals),
+        msg: proposal.msg,
+        approvals,
+        executed: proposal.executed,
+        status,
+        status_code: status.code(),
+    })
+}
+
+fn query_admin_proposal<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let proposal = found(
+        admin_proposals_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::AdminProposal,
+        id,
+    )?;
+
+    let resp = admin_proposal_response(&deps.api, &state, proposal)?;
+    to_vec(&resp).context(SerializeErr {
+        kind: "AdminProposalResponse",
+    })
+}
+
+fn query_list_admin_proposals<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let last = last_admin_proposal_id(&deps.storage)?;
+    let proposals_bucket = admin_proposals_read(&deps.storage);
+
+    let keys = start_key(decode_start_after(start_after)?, 1).map(|start| start..=last);
+    let page = paginate(keys.into_iter().flatten(), clamp_limit(limit), |id| {
+        let proposal = proposals_bucket.load(&id.to_be_bytes())?;
+        if proposal.executed {
+            return Ok(None);
+        }
+        Ok(Some(admin_proposal_response(&deps.api, &state, proposal)?))
+    })?;
+
+    let resp = AdminProposalsResponse {
+        proposals: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AdminProposalsResponse",
+    })
+}
+
+fn query_admin_vote<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    id: u64,
+    owner: HumanAddr,
+) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let proposal = found(
+        admin_proposals_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::AdminProposal,
+        id,
+    )?;
+    let addr = deps.api.canonical_address(&owner)?;
+
+    let resp = AdminVoteResponse {
+        id,
+        approved: proposal.approvals.contains(&addr),
+        weight: if state.is_owner(&addr) {
+            state.weight_of(&addr)
+        } else {
+            0
+        },
+        owner,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AdminVoteResponse",
+    })
+}
+
+fn query_cooldowns<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let used = last_used_read(&deps.storage);
+
+    let mut cooldowns = Vec::with_capacity(state.cooldowns.len());
+    for cooldown in state.cooldowns {
+        let next_allowed = used
+            .may_load(cooldown.action.as_bytes())?
+            .map(|last| cooldown.period.after(last.height, last.time));
+        cooldowns.push(CooldownInfo {
+            cooldown,
+            next_allowed,
+        });
+    }
+    let resp = CooldownsResponse { cooldowns };
+    to_vec(&resp).context(SerializeErr {
+        kind: "CooldownsResponse",
+    })
+}
+
+fn query_operators<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+
+    let mut operators = Vec::with_capacity(state.operators.len());
+    for addr in state.operators.iter() {
+        operators.push(deps.api.human_address(addr)?);
+    }
+    let mut suspended = vec![];
+    for addr in state.suspended_operators.iter().flatten() {
+        suspended.push(deps.api.human_address(addr)?);
+    }
+    let resp = OperatorsResponse {
+        operators,
+        manifest_nonce: state.operator_manifest_nonce,
+        suspended,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "OperatorsResponse",
+    })
+}
+
+fn staged_batch_response<A: Api>(api: &A, batch: StagedBatch) -> Result<StagedBatchResponse> {
+    let status = if batch.executed {
+        Status::Executed
+    } else {
+        Status::Staged
+    };
+    Ok(StagedBatchResponse {
+        id: batch.id,
+        operator: api.human_address(&batch.operator)?,
+        msgs: batch.msgs,
+        memo: batch.memo,
+        receipt_contract: batch.receipt_contract,
+        tags: batch.tags,
+        staged_height: batch.staged_height,
+        executed: batch.executed,
+        status,
+        status_code: status.code(),
+    })
+}
+
+fn query_staged_batch<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let batch = found(
+        staged_batches_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::StagedBatch,
+        id,
+    )?;
+
+    let resp = staged_batch_response(&deps.api, batch)?;
+    to_vec(&resp).context(SerializeErr {
+        kind: "StagedBatchResponse",
+    })
+}
+
+fn query_list_staged_batches<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let last = last_staged_batch_id(&deps.storage)?;
+    let batches_bucket = staged_batches_read(&deps.storage);
+
+    let keys = start_key(decode_start_after(start_after)?, 1).map(|start| start..=last);
+    let page = paginate(keys.into_iter().flatten(), clamp_limit(limit), |id| {
+        let batch = batches_bucket.load(&id.to_be_bytes())?;
+        if batch.executed {
+            return Ok(None);
+        }
+        Ok(Some(staged_batch_response(&deps.api, batch)?))
+    })?;
+
+    let resp = StagedBatchesResponse {
+        batches: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "StagedBatchesResponse",
+    })
+}
+
+fn query_revenue<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    tag: String,
+    period: u64,
+) -> Result<Vec<u8>> {
+    validate_tag_name(&tag)?;
+    let key = tag_index_key(&tag, period);
+    let totals = revenue_read(&deps.storage)
+        .may_load(&key)?
+        .unwrap_or_default();
+    let settled = revenue_settled_read(&deps.storage)
+        .may_load(&key)?
+        .unwrap_or(false);
+
+    let resp = RevenueResponse {
+        tag,
+        period,
+        starts_at: period.safe_mul(REVENUE_PERIOD_SECS)?,
+        totals,
+        settled,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "RevenueResponse",
+    })
+}
+
+fn query_stakeholders<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let holders = stakeholders_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+
+    let mut stakeholders = Vec::with_capacity(holders.len());
+    for holder in holders.iter() {
+        stakeholders.push(StakeholderMsg {
+            addr: deps.api.human_address(&holder.addr)?,
+            shares: holder.shares,
+        });
+    }
+    let resp = StakeholdersResponse { stakeholders };
+    to_vec(&resp).context(SerializeErr {
+        kind: "StakeholdersResponse",
+    })
+}
+
+fn query_list_scheduled<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let last = last_scheduled_batch_id(&deps.storage)?;
+    let batches_bucket = scheduled_batches_read(&deps.storage);
+
+    let keys = start_key(decode_start_after(start_after)?, 1).map(|start| start..=last);
+    let page = paginate(keys.into_iter().flatten(), clamp_limit(limit), |id| {
+        let batch = batches_bucket.load(&id.to_be_bytes())?;
+        if batch.closed {
+            return Ok(None);
+        }
+        Ok(Some(ScheduledBatchResponse {
+            id: batch.id,
+            owner: deps.api.human_address(&batch.owner)?,
+            msgs: batch.msgs,
+            execute_after: batch.execute_after,
+            calendar: batch.calendar,
+        }))
+    })?;
+
+    let resp = ScheduledBatchesResponse {
+        batches: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ScheduledBatchesResponse",
+    })
+}
+
+fn proposal_response<A: Api>(api: &A, proposal: Proposal) -> Result<ProposalResponse> {
+    Ok(ProposalResponse {
+        id: proposal.id,
+        proposer: api.human_address(&proposal.proposer)?,
+        msgs: proposal.msgs,
+        description: proposal.description,
+        status: proposal.status,
+        status_code: Status::from(proposal.status).code(),
+        intents: proposal.intents,
+    })
+}
+
+fn query_proposal<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let proposal = found(
+        proposals_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Proposal,
+        id,
+    )?;
+    let resp = proposal_response(&deps.api, proposal)?;
+    to_vec(&resp).context(SerializeErr {
+        kind: "ProposalResponse",
+    })
+}
+
+fn query_list_proposals<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let last = last_proposal_id(&deps.storage)?;
+    let proposals_bucket = proposals_read(&deps.storage);
+
+    let keys = start_key(decode_start_after(start_after)?, 1).map(|start| start..=last);
+    let page = paginate(keys.into_iter().flatten(), clamp_limit(limit), |id| {
+        let proposal = proposals_bucket.load(&id.to_be_bytes())?;
+        if proposal.status != ProposalStatus::Pending {
+            return Ok(None);
+        }
+        Ok(Some(proposal_response(&deps.api, proposal)?))
+    })?;
+
+    let resp = ProposalsResponse {
+        proposals: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ProposalsResponse",
+    })
+}
+
+fn grant_response<A: Api>(api: &A, grant: Grant) -> Result<GrantResponse> {
+    let remaining = grant.remaining()?;
+    let status = if grant.clawed_back {
+        Status::ClawedBack
+    } else if grant.is_complete() {
+        Status::Completed
+    } else {
+        Status::Active
+    };
+    Ok(GrantResponse {
+        id: grant.id,
+        grantee: grant.grantee,
+        approver: api.human_address(&grant.approver)?,
+        milestones: grant.milestones,
+        released: grant.released,
+        remaining,
+        created_height: grant.created_height,
+        clawed_back: grant.clawed_back,
+        status,
+        status_code: status.code(),
+    })
+}
+
+fn query_grant<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let grant = found(
+        grants_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::Grant,
+        id,
+    )?;
+
+    let resp = grant_response(&deps.api, grant)?;
+    to_vec(&resp).context(SerializeErr {
+        kind: "GrantResponse",
+    })
+}
+
+/// Most grants one ListGrants looks at, so filtering by grantee does bounded work
+const MAX_GRANT_SCAN: usize = 100;
+
+fn query_list_grants<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    grantee: Option<HumanAddr>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let last = last_grant_id(&deps.storage)?;
+    let grants_bucket = grants_read(&deps.storage);
+
+    let keys = start_key(decode_start_after(start_after)?, 1).map(|start| start..=last);
+    let keys = keys.into_iter().flatten();
+    let page = paginate_bounded(keys, clamp_limit(limit), MAX_GRANT_SCAN, |id| {
+        let grant = grants_bucket.load(&id.to_be_bytes())?;
+        match &grantee {
+            Some(grantee) if *grantee != grant.grantee => Ok(None),
+            _ => Ok(Some(grant_response(&deps.api, grant)?)),
+        }
+    })?;
+
+    let resp = GrantsResponse {
+        grants: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "GrantsResponse",
+    })
+}
+
+/// Default and most key and value bytes ExportChunk returns at once
+const DEFAULT_EXPORT_BYTES: u32 = 16 * 1024;
+const MAX_EXPORT_BYTES: u32 = 64 * 1024;
+
+fn query_export_chunk<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    namespace: ExportNamespace,
+    start_after: Option<String>,
+    max_bytes: Option<u32>,
+) -> Result<Vec<u8>> {
+    let last = match namespace {
+        ExportNamespace::Executions => last_execution_id(&deps.storage)?,
+        ExportNamespace::StagedBatches => last_staged_batch_id(&deps.storage)?,
+        ExportNamespace::AdminProposals => last_admin_proposal_id(&deps.storage)?,
+    };
+    let max_bytes = max_bytes
+        .unwrap_or(DEFAULT_EXPORT_BYTES)
+        .min(MAX_EXPORT_BYTES) as usize;
+    // records are serialized the same way they are stored, without the layout version
+    let load = |key: &[u8]| -> Result<Option<Vec<u8>>> {
+        let value = match namespace {
+            ExportNamespace::Executions => executions_read(&deps.storage)
+                .may_load(key)?
+                .map(|r| to_vec(&r)),
+            ExportNamespace::StagedBatches => staged_batches_read(&deps.storage)
+                .may_load(key)?
+                .map(|r| to_vec(&r)),
+            ExportNamespace::AdminProposals => admin_proposals_read(&deps.storage)
+                .may_load(key)?
+                .map(|r| to_vec(&r)),
+        };
+        value.transpose().context(SerializeErr {
+            kind: "ExportEntry",
+        })
+    };
+
+    let keys = start_key(decode_start_after(start_after)?, 1).map(|start| start..=last);
+    let mut entries = vec![];
+    let mut size = 0;
+    let mut next = None;
+    for id in keys.into_iter().flatten() {
+        let key = id.to_be_bytes();
+        let value = match load(&key)? {
+            Some(value) => value,
+            None => continue,
+        };
+        // always return one entry, even if it alone is over the budget, so exports progress
+        if !entries.is_empty() && size + key.len() + value.len() > max_bytes {
+            next = Some(encode_cursor(id - 1));
+            break;
+        }
+        size += key.len() + value.len();
+        entries.push(ExportEntry {
+            key: Binary(key.to_vec()),
+            value: Binary(value),
+        });
+    }
+
+    let resp = ExportChunkResponse {
+        entries,
+        is_truncated: next.is_some(),
+        next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ExportChunkResponse",
+    })
+}
+
+/// Most records VerifyState checks in one query
+const MAX_VERIFY_SCAN: u32 = 100;
+
+fn query_verify_state<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    section: VerifySection,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let start_after = decode_start_after(start_after)?;
+    let limit = u64::from(limit.unwrap_or(MAX_VERIFY_SCAN).max(1).min(MAX_VERIFY_SCAN));
+    let (from, until) = verify_range(deps, &section)?;
+    // past the last key there is nothing left to verify
+    let from = start_key(start_after, from).unwrap_or(until);
+    let end = until.min(from.saturating_add(limit));
+
+    let violations = verify_section(deps, &section, from, end, until);
+    let next = if end < until {
+        Some(encode_cursor(end - 1))
+    } else {
+        None
+    };
+    let resp = VerifyStateResponse {
+        violations,
+        is_truncated: next.is_some(),
+        next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "VerifyStateResponse",
+    })
+}
+
+/// Every section checks the keys from..until, ids start at 1 and index positions at 0
+fn verify_range<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    section: &VerifySection,
+) -> Result<(u64, u64)> {
+    let range = match section {
+        VerifySection::Executions => (1, last_execution_id(&deps.storage)? + 1),
+        VerifySection::StagedBatches => (1, last_staged_batch_id(&deps.storage)? + 1),
+        VerifySection::AdminProposals => (1, last_admin_proposal_id(&deps.storage)? + 1),
+        VerifySection::TagIndex { tag } => {
+            let count = read_verified(tag_counts_read(&deps.storage).may_load(tag.as_bytes()));
+            (0, count.unwrap_or(0))
+        }
+    };
+    Ok(range)
+}
+
+/// Checks the keys from..end of the section. If end is until, the checks which need the
+/// whole section run as well.
+fn verify_section<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    section: &VerifySection,
+    from: u64,
+    end: u64,
+    until: u64,
+) -> Vec<Violation> {
+    let mut violations = vec![];
+    match section {
+        VerifySection::Executions => {
+            let bucket = executions_read(&deps.storage);
+            let mut prev_height = match from {
+                1 => None,
+                _ => read_verified(bucket.may_load(&(from - 1).to_be_bytes())).map(|e| e.height),
+            };
+            for id in from..end {
+                let key = format!("executions/{}", id);
+                let record = bucket.may_load(&id.to_be_bytes());
+                let record = verify_readable(&mut violations, &key, record);
+                if let Some(execution) = record {
+                    verify_id(&mut violations, &key, id, execution.id);
+                    if prev_height.map_or(false, |h| h > execution.height) {
+                        let detail = format!("height {} is below the one before", execution.height);
+                        violations.push(Violation::new(ViolationKind::HeightOrder, key, detail));
+                    }
+                    prev_height = Some(execution.height);
+                }
+            }
+        }
+        VerifySection::StagedBatches => {
+            let bucket = staged_batches_read(&deps.storage);
+            for id in from..end {
+                let key = format!("staged_batches/{}", id);
+                let record = bucket.may_load(&id.to_be_bytes());
+                let record = verify_readable(&mut violations, &key, record);
+                if let Some(record) = record {
+                    verify_id(&mut violations, &key, id, record.id);
+                }
+            }
+        }
+        VerifySection::AdminProposals => {
+            let bucket = admin_proposals_read(&deps.storage);
+            for id in from..end {
+                let key = format!("admin_proposals/{}", id);
+                let record = bucket.may_load(&id.to_be_bytes());
+                let record = verify_readable(&mut violations, &key, record);
+                if let Some(record) = record {
+                    verify_id(&mut violations, &key, id, record.id);
+                }
+            }
+        }
+        VerifySection::TagIndex { tag } => {
+            let index = tag_index_read(&deps.storage);
+            let executions_bucket = executions_read(&deps.storage);
+            for n in from..end {
+                let key = format!("tag_index/{}/{}", tag, n);
+                let entry = index.may_load(&tag_index_key(tag, n));
+                let id = match verify_readable(&mut violations, &key, entry) {
+                    Some(id) => id,
+                    None => continue,
+                };
+                // unreadable executions are reported when checking the executions
+                let detail = match executions_bucket.may_load(&id.to_be_bytes()) {
+                    Ok(None) => format!("execution {} does not exist", id),
+                    Ok(Some(ref execution)) if !execution.tags.contains(tag) => {
+                        format!("execution {} does not have the tag", id)
+                    }
+                    _ => continue,
+                };
+                violations.push(Violation::new(
+                    ViolationKind::DanglingReference,
+                    key,
+                    detail,
+                ));
+            }
+            // with the last slice, make sure the counter covers all entries.
+            // Dropped entries beyond the counter are 0, which is no execution id.
+            let beyond = read_verified(index.may_load(&tag_index_key(tag, until)));
+            if end == until && beyond.map_or(false, |id| id != 0) {
+                let key = format!("tag_counts/{}", tag);
+                let detail = format!("index has entries beyond the count of {}", until);
+                violations.push(Violation::new(ViolationKind::CounterMismatch, key, detail));
+            }
+        }
+    }
+    violations
+}
+
+/// A record which cannot be read is treated as missing, it is reported where it is stored
+fn read_verified<T>(record: Result<Option<T>>) -> Option<T> {
+    record.ok().and_then(|r| r)
+}
+
+/// Reports the record under key if it is missing or cannot be read
+fn verify_readable<T>(
+    violations: &mut Vec<Violation>,
+    key: &str,
+    record: Result<Option<T>>,
+) -> Option<T> {
+    let (kind, detail) = match record {
+        Ok(Some(record)) => return Some(record),
+        Ok(None) => (ViolationKind::MissingRecord, "no record".to_string()),
+        Err(err) => (ViolationKind::CorruptRecord, err.to_string()),
+    };
+    violations.push(Violation::new(kind, key.to_string(), detail));
+    None
+}
+
+fn verify_id(violations: &mut Vec<Violation>, key: &str, id: u64, record_id: u64) {
+    if id != record_id {
+        let detail = format!("stored under {} but has id {}", id, record_id);
+        violations.push(Violation::new(
+            ViolationKind::IdMismatch,
+            key.to_string(),
+            detail,
+        ));
+    }
+}
+
+fn query_tag_spend<S: Storage, A: Api>(deps: &Extern<S, A>, tag: String) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let budget = state
+        .budgets
+        .into_iter()
+        .find(|b| b.tag == tag)
+        .map(|b| b.budget);
+    let spend = tag_spend_read(&deps.storage).may_load(tag.as_bytes())?;
+
+    // queries have no block info, so the spend of a period that just ended is still shown
+    let (spent, resets_at) = match (&budget, spend) {
+        (Some(budget), Some(spend)) => {
+            let resets_at = spend.resets_at(budget.period);
+            (spend.spent, Some(resets_at))
+        }
+        _ => ("0".to_string(), None),
+    };
+    let resp = TagSpendResponse {
+        tag,
+        budget,
+        spent,
+        resets_at,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "TagSpendResponse",
+    })
+}
+
+fn query_operator_window<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    operator: HumanAddr,
+) -> Result<Vec<u8>> {
+    let addr = deps.api.canonical_address(&operator)?;
+    let window = operator_windows_read(&deps.storage)
+        .may_load(addr.as_slice())?
+        .unwrap_or(None);
+    let calendar = operator_calendars_read(&deps.storage)
+        .may_load(addr.as_slice())?
+        .unwrap_or(None);
+    let resp = OperatorWindowResponse {
+        operator,
+        window,
+        calendar,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "OperatorWindowResponse",
+    })
+}
+
+fn query_calendar<S: Storage, A: Api>(deps: &Extern<S, A>, name: String) -> Result<Vec<u8>> {
+    let calendar = calendars_read(&deps.storage).load(name.as_bytes())?;
+    let resp = CalendarResponse {
+        name,
+        allowed: calendar.allowed,
+        blackouts: calendar.blackouts,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "CalendarResponse",
+    })
+}
+
+fn query_upcoming_blackouts<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    name: String,
+    after: u64,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let calendar = calendars_read(&deps.storage).load(name.as_bytes())?;
+    let mut blackouts: Vec<TimeRange> = calendar
+        .blackouts
+        .into_iter()
+        .filter(|r| r.end > after)
+        .collect();
+    blackouts.sort_by_key(|r| r.start);
+    blackouts.truncate(clamp_limit(limit));
+
+    let resp = BlackoutsResponse {
+        calendar: name,
+        blackouts,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "BlackoutsResponse",
+    })
+}
+
+fn query_deposits<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let totals = lifetime_deposits_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let resp = DepositsResponse { totals };
+    to_vec(&resp).context(SerializeErr {
+        kind: "DepositsResponse",
+    })
+}
+
+fn query_collected_fees<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let collected = collected_fees_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let resp = CollectedFeesResponse { collected };
+    to_vec(&resp).context(SerializeErr {
+        kind: "CollectedFeesResponse",
+    })
+}
+
+fn query_is_denied<S: Storage, A: Api>(deps: &Extern<S, A>, addr: HumanAddr) -> Result<Vec<u8>> {
+    let canonical = deps.api.canonical_address(&addr)?;
+    let denied = denylist_read(&deps.storage).may_load(canonical.as_slice())? == Some(true);
+    let resp = IsDeniedResponse { addr, denied };
+    to_vec(&resp).context(SerializeErr {
+        kind: "IsDeniedResponse",
+    })
+}
+
+fn query_stats<S: Storage, A: Api>(deps: &Extern<S, A>, addr: HumanAddr) -> Result<Vec<u8>> {
+    let canonical = deps.api.canonical_address(&addr)?;
+    let stats = stats_read(&deps.storage).may_load(canonical.as_slice())?;
+    let resp = StatsResponse {
+        addr,
+        reflections: stats.as_ref().map(|s| s.reflections).unwrap_or(0),
+        last_height: stats.map(|s| s.last_height),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "StatsResponse",
+    })
+}
+
+fn query_global_stats<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let stats = global_stats_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let resp = GlobalStatsResponse {
+        msgs_reflected: stats.msgs_reflected,
+        owner_changes: stats.owner_changes,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "GlobalStatsResponse",
+    })
+}
+
+fn query_metrics<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let stored = |name: &str| -> Result<u64> {
+        Ok(metrics_read(&deps.storage)
+            .may_load(name.as_bytes())?
+            .unwrap_or(0))
+    };
+    let metric = |name: &str, kind: MetricKind, value: u64| Metric {
+        name: name.to_string(),
+        kind,
+        value,
+    };
+    let mut metrics = vec![
+        metric(
+            EXECUTIONS_TOTAL,
+            MetricKind::Counter,
+            stored(EXECUTIONS_TOTAL)?,
+        ),
+        metric(QUEUE_DEPTH, MetricKind::Gauge, stored(QUEUE_DEPTH)?),
+        metric("paused", MetricKind::Gauge, state.paused as u64),
+    ];
+    // blocking ones fail the call, so only evaluations and shadow blocks can be counted
+    for i in 0..state.middlewares.as_ref().map_or(0, |m| m.len()) {
+        for counter in [MIDDLEWARE_EVALUATIONS, MIDDLEWARE_SHADOW_BLOCKS].iter() {
+            let name = middleware_metric(i, counter);
+            metrics.push(metric(&name, MetricKind::Counter, stored(&name)?));
+        }
+    }
+    for j in 0..state.rules.as_ref().map_or(0, |r| r.len()) {
+        let name = rule_metric(j);
+        metrics.push(metric(&name, MetricKind::Counter, stored(&name)?));
+    }
+    let resp = MetricsResponse { metrics };
+    to_vec(&resp).context(SerializeErr {
+        kind: "MetricsResponse",
+    })
+}
+
+fn query_rules<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let resp = RulesResponse {
+        rules: state.rules.unwrap_or_default(),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "RulesResponse",
+    })
+}
+
+fn query_attestation<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let attestation = attestation_read(&deps.storage).may_load()?;
+    let current = match &attestation {
+        Some(a) => a.checksum == state.checksum()?,
+        None => false,
+    };
+
+    let resp = AttestationResponse {
+        attestation,
+        max_age: state.attestation_max_age,
+        current,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AttestationResponse",
+    })
+}
+
+fn query_storage_usage<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let mut namespaces: Vec<NamespaceUsage> = usage::usage(&deps.storage)?
+        .into_iter()
+        .map(|(namespace, usage)| NamespaceUsage {
+            namespace,
+            entries: usage.entries,
+            bytes: usage.bytes,
+        })
+        .collect();
+    // the biggest first, as those are the ones worth pruning
+    namespaces.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+    let resp = StorageUsageResponse { namespaces };
+    to_vec(&resp).context(SerializeErr {
+        kind: "StorageUsageResponse",
+    })
+}
+
+fn query_cosigner<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let resp = match state.cosign {
+        Some(cosign) => CosignerResponse {
+            cosigner: Some(deps.api.human_address(&cosign.cosigner)?),
+            thresholds: cosign.thresholds,
+        },
+        None => CosignerResponse {
+            cosigner: None,
+            thresholds: vec![],
+        },
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "CosignerResponse",
+    })
+}
+
+fn query_queued_execution<S: Storage, A: Api>(deps: &Extern<S, A>, id: u64) -> Result<Vec<u8>> {
+    let queued = found(
+        queued_executions_read(&deps.storage).may_load(&id.to_be_bytes())?,
+        EntityKind::QueuedExecution,
+        id,
+    )?;
+    let status = if queued.closed {
+        Status::Closed
+    } else {
+        Status::Queued
+    };
+    let resp = QueuedExecutionResponse {
+        id: queued.id,
+        sender: deps.api.human_address(&queued.sender)?,
+        msgs: queued.msgs,
+        tags: queued.tags,
+        memo: queued.memo,
+        queued_height: queued.queued_height,
+        closed: queued.closed,
+        status,
+        status_code: status.code(),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "QueuedExecutionResponse",
+    })
+}
+
+/// Most authorizations ListAuthorizations looks at per page, used ones included
+const MAX_AUTHORIZATION_SCAN: usize = 100;
+
+fn query_list_authorizations<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let last = last_one_time_auth_id(&deps.storage)?;
+    let auths = one_time_auths_read(&deps.storage);
+
+    let keys = start_key(decode_start_after(start_after)?, 1).map(|start| start..=last);
+    let page = paginate_bounded(
+        keys.into_iter().flatten(),
+        clamp_limit(limit),
+        MAX_AUTHORIZATION_SCAN,
+        |id| {
+            let auth = auths.load(&id.to_be_bytes())?;
+            if auth.used {
+                return Ok(None);
+            }
+            Ok(Some(AuthorizationResponse {
+                id: auth.id,
+                grantee: deps.api.human_address(&auth.grantee)?,
+                msg_hash: auth.msg_hash,
+                expires: auth.expires,
+            }))
+        },
+    )?;
+
+    let resp = AuthorizationsResponse {
+        authorizations: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "AuthorizationsResponse",
+    })
+}
+
+fn query_session<S: Storage, A: Api>(deps: &Extern<S, A>, key_addr: HumanAddr) -> Result<Vec<u8>> {
+    let canonical = deps.api.canonical_address(&key_addr)?;
+    let session = sessions_read(&deps.storage).may_load(canonical.as_slice())?;
+    let resp = SessionResponse {
+        key_addr,
+        remaining_msgs: session.as_ref().map_or(0, |s| s.remaining_msgs),
+        expires: session.map(|s| s.expires),
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "SessionResponse",
+    })
+}
+
+fn query_get_data<S: Storage, A: Api>(deps: &Extern<S, A>, key: String) -> Result<Vec<u8>> {
+    let keys = data_keys_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let value = match keys.binary_search(&key) {
+        Ok(_) => data_read(&deps.storage).get(key.as_bytes()).map(Binary),
+        Err(_) => None,
+    };
+    let resp = DataResponse { key, value };
+    to_vec(&resp).context(SerializeErr {
+        kind: "DataResponse",
+    })
+}
+
+fn query_list_data<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let keys = data_keys_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    // keys are sorted, so the page starts at the first key after start_after
+    let start = match start_after {
+        Some(after) => match keys.binary_search(&after) {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        },
+        None => 0,
+    };
+    let store = data_read(&deps.storage);
+    let page = paginate(start as u64..keys.len() as u64, clamp_limit(limit), |i| {
+        let key = keys[i as usize].clone();
+        let value = Binary(store.get(key.as_bytes()).unwrap_or_default());
+        Ok(Some(DataEntry { key, value }))
+    })?;
+
+    // the cursor is the last key, so removing keys does not shift the next page
+    let next = if page.is_truncated {
+        page.items.last().map(|e| e.key.clone())
+    } else {
+        None
+    };
+    let resp = DataEntriesResponse {
+        entries: page.items,
+        is_truncated: page.is_truncated,
+        next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "DataEntriesResponse",
+    })
+}
+
+fn query_guardians<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let resp = match guardians_read(&deps.storage).may_load()? {
+        Some(Some(g)) => GuardiansResponse {
+            guardians: g
+                .addrs
+                .iter()
+                .map(|a| deps.api.human_address(a))
+                .collect::<Result<_>>()?,
+            threshold: Some(g.threshold),
+            window: Some(g.window),
+        },
+        _ => GuardiansResponse {
+            guardians: vec![],
+            threshold: None,
+            window: None,
+        },
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "GuardiansResponse",
+    })
+}
+
+fn query_recovery_session<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let resp = match recovery_session_read(&deps.storage).may_load()? {
+        Some(Some(session)) => RecoverySessionResponse {
+            new_owner: Some(deps.api.human_address(&session.new_owner)?),
+            approvals: session
+                .approvals
+                .iter()
+                .map(|a| deps.api.human_address(a))
+                .collect::<Result<_>>()?,
+            expires: Some(session.expires),
+        },
+        _ => RecoverySessionResponse {
+            new_owner: None,
+            approvals: vec![],
+            expires: None,
+        },
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "RecoverySessionResponse",
+    })
+}
+
+fn query_contract_version<S: Storage, A: Api>(deps: &Extern<S, A>) -> Result<Vec<u8>> {
+    let version = contract_version_read(&deps.storage).may_load()?;
+    let resp = ContractVersionResponse { version };
+    to_vec(&resp).context(SerializeErr {
+        kind: "ContractVersionResponse",
+    })
+}
+
+fn query_history<S: Storage, A: Api>(
+    deps: &Extern<S, A>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<u8>> {
+    let last = last_history_seq(&deps.storage)?;
+    let history_bucket = history_read(&deps.storage);
+
+    // newest first, so the next page continues below the cursor
+    let end = match decode_start_after(start_after)? {
+        Some(seq) => last.min(seq.saturating_sub(1)),
+        None => last,
+    };
+    let page = paginate((1..=end).rev(), clamp_limit(limit), |seq| {
+        let entry = history_bucket.load(&seq.to_be_bytes())?;
+        let owner = match &entry.owner {
+            Some(owner) => Some(deps.api.human_address(owner)?),
+            None => None,
+        };
+        Ok(Some(HistoryEntryResponse {
+            seq: entry.seq,
+            sender: deps.api.human_address(&entry.sender)?,
+            height: entry.height,
+            action: entry.action,
+            msgs: entry.msgs,
+            owner,
+            intents: entry.intents,
+        }))
+    })?;
+
+    let resp = HistoryResponse {
+        entries: page.items,
+        is_truncated: page.is_truncated,
+        next: page.next,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "HistoryResponse",
+    })
+}
+
+fn query_spend_limit<S: Storage, A: Api>(deps: &Extern<S, A>, denom: String) -> Result<Vec<u8>> {
+    let state = config_read(&deps.storage).load()?;
+    let limit = state
+        .spend_limits
+        .unwrap_or_default()
+        .into_iter()
+        .find(|l| l.limit.denom == denom);
+    let tracker = spend_tracker_read(&deps.storage).may_load(denom.as_bytes())?;
+
+    // like for tag budgets, the spend of a period that just ended is still shown
+    let (spent, resets_at) = match (&limit, tracker) {
+        (Some(limit), Some(tracker)) => {
+            let resets_at = Expiration::AtTime(tracker.since.saturating_add(limit.period_secs));
+            (tracker.spent, Some(resets_at))
+        }
+        _ => ("0".to_string(), None),
+    };
+    let resp = SpendLimitResponse {
+        denom,
+        limit,
+        spent,
+        resets_at,
+    };
+    to_vec(&resp).context(SerializeErr {
+        kind: "SpendLimitResponse",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm::encoding::Binary;
+    use cosmwasm::errors::Error;
+    use cosmwasm::mock::{dependencies, mock_env};
+    use cosmwasm::serde::from_slice;
+    use cosmwasm::types::{coin, MessageInfo};
+
+    use crate::msg::{Deprecation, WeightMsg};
+    use crate::state::{ChainProfile, Expiration, Weekday};
+    use crate::versioned::Versioned;
+    use cw_storage::{prefixed, prefixed_read};
+
+    fn assert_contract_error<T: std::fmt::Debug>(res: Result<T>, expected: ContractError) {
+        match res {
+            Err(Error::DynContractErr { msg, .. }) => assert_eq!(expected.payload(), msg),
+            other => panic!("Expected {:?}, got {:?}", expected, other),
+        }
+    }
+
+    fn mock_chain() -> ChainProfile {
+        ChainProfile {
+            bech32_prefix: "cosmos".to_string(),
+            native_denom: "uatom".to_string(),
+            block_time_secs: 5,
+            default_denom: None,
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &coin("1000", "earth"), &[]);
+
+        // we can just call .unwrap() to assert this was a success
+        let res = init(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_slice(&res).unwrap();
+        assert_eq!("creator", value.owner.as_str());
+
+        let res = query(&deps, QueryMsg::GetChainProfile {}).unwrap();
+        let value: ChainProfileResponse = from_slice(&res).unwrap();
+        assert_eq!(mock_chain(), value.chain);
+
+        let res = query(&deps, QueryMsg::ContractVersion {}).unwrap();
+        let value: ContractVersionResponse = from_slice(&res).unwrap();
+        let version = value.version.unwrap();
+        assert_eq!("mask", version.contract);
+        assert_eq!(env!("CARGO_PKG_VERSION"), version.version);
+    }
+
+    #[test]
+    fn init_rejects_foreign_chain_profile() {
+        let mut deps = dependencies(20);
+
+        let chain = ChainProfile {
+            bech32_prefix: "terra".to_string(),
+            ..mock_chain()
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = init(&mut deps, env, InitMsg { chain });
+        match res {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject a prefix not matching the contract address"),
+        }
+
+        let chain = ChainProfile {
+            block_time_secs: 0,
+            ..mock_chain()
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = init(&mut deps, env, InitMsg { chain });
+        match res {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject zero block time"),
+        }
+
+        let chain = ChainProfile {
+            default_denom: Some(String::new()),
+            ..mock_chain()
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = init(&mut deps, env, InitMsg { chain });
+        match res {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject an empty default denom"),
+        }
+    }
+
+    #[test]
+    fn settings_default_to_the_chain_denom() {
+        let mut deps = dependencies(20);
+
+        let chain = ChainProfile {
+            default_denom: Some("ustable".to_string()),
+            ..mock_chain()
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, InitMsg { chain }).unwrap();
+
+        let thresholds = vec![
+            Amount {
+                denom: None,
+                amount: "1000".to_string(),
+            },
+            Amount {
+                denom: Some("uatom".to_string()),
+                amount: "50".to_string(),
+            },
+        ];
+        let msg = HandleMsg::SetTravelRule {
+            thresholds: Some(thresholds),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(log("threshold", "1000ustable"), res.log[1]);
+        assert_eq!(log("threshold", "50uatom"), res.log[2]);
+
+        let res = query(&deps, QueryMsg::GetChainProfile {}).unwrap();
+        let value: ChainProfileResponse = from_slice(&res).unwrap();
+        assert_eq!("ustable", value.chain.default_denom());
+    }
+
+    #[test]
+    fn reflect() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &coin("2", "token"), &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let payload = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+
+        // only the owner can reflect
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![payload.clone()],
+            tags: vec![],
+            memo: None,
+        };
+        let res = handle(&mut deps, env, msg);
+        match res {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![payload.clone()],
+            tags: vec![],
+            memo: None,
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(vec![payload.clone()], res.messages);
+        assert_eq!(
+            vec![
+                log("action", "reflect"),
+                log("execution_id", "1"),
+                log("ref", "exec-1"),
+                log("sender", "creator"),
+                log("msg_count", "1"),
+            ],
+            res.log[..5].to_vec()
+        );
+        let result: ReflectResult = from_slice(&res.data.unwrap().0).unwrap();
+        let expected = ReflectResult {
+            execution_id: Some(1),
+            msg_count: 1,
+        };
+        assert_eq!(expected, result);
+
+        // there must be something to reflect
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![],
+            tags: vec![],
+            memo: None,
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject an empty batch"),
+        }
+
+        // a batch is emitted in order, as one execution
+        let call = CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1other"),
+            msg: Binary(b"{}".to_vec()),
+            send: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![payload.clone(), call.clone()],
+            tags: vec![],
+            memo: None,
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(vec![payload, call], res.messages);
+        assert_eq!(log("execution_id", "2"), res.log[1]);
+    }
+
+    #[test]
+    fn change_owner() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &coin("2", "token"), &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        // only the owner can change the owner
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1friend"),
+        };
+        let res = handle(&mut deps, env, msg);
+        match res {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // addresses from other chains are rejected
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("terra1friend"),
+        };
+        let res = handle(&mut deps, env, msg);
+        match res {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject foreign address"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1friend"),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_slice(&res).unwrap();
+        assert_eq!("cosmos1friend", value.owner.as_str());
+    }
+
+    #[test]
+    fn register_and_describe_denoms() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let register = HandleMsg::RegisterDenom {
+            denom: "uatom".to_string(),
+            symbol: "ATOM".to_string(),
+            exponent: 6,
+        };
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        let res = handle(&mut deps, env, register.clone());
+        match res {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, register).unwrap();
+
+        let res = query(
+            &deps,
+            QueryMsg::DenomMetadata {
+                denom: "uatom".to_string(),
+            },
+        )
+        .unwrap();
+        let value: DenomMetadataResponse = from_slice(&res).unwrap();
+        assert_eq!(
+            Some(DenomMetadata {
+                symbol: "ATOM".to_string(),
+                exponent: 6
+            }),
+            value.metadata
+        );
+
+        let mut coins = coin("1500000", "uatom");
+        coins.extend(coin("42", "unknown"));
+        let res = query(&deps, QueryMsg::DescribeCoins { coins }).unwrap();
+        let value: DescribeCoinsResponse = from_slice(&res).unwrap();
+        assert_eq!(2, value.coins.len());
+        assert_eq!(Some("ATOM".to_string()), value.coins[0].symbol);
+        assert_eq!(Some("1.5".to_string()), value.coins[0].display_amount);
+        assert_eq!("42", value.coins[1].amount);
+        assert_eq!(None, value.coins[1].display_amount);
+    }
+
+    #[test]
+    fn report_gas_and_estimate_cost() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        // nothing is known yet
+        let res = query(
+            &deps,
+            QueryMsg::EstimateCost {
+                kind: MsgKind::Send,
+            },
+        )
+        .unwrap();
+        let value: CostEstimateResponse = from_slice(&res).unwrap();
+        assert_eq!(0, value.samples);
+        assert_eq!(None, value.gas_per_msg);
+
+        let payload = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        for _ in 0..2 {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            let msg = HandleMsg::ReflectMsg {
+                msgs: vec![payload.clone()],
+                tags: vec![],
+                memo: None,
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        let res = query(&deps, QueryMsg::Execution { id: 2 }).unwrap();
+        let value: ExecutionResponse = from_slice(&res).unwrap();
+        assert_eq!(2, value.id);
+        assert_eq!("creator", value.sender.as_str());
+        assert_eq!(MsgKind::Send, value.msgs[0].kind);
+        assert_eq!(None, value.gas_used);
+
+        // only the owner reports gas
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        let msg = HandleMsg::ReportGas {
+            execution_id: 1,
+            gas_used: 30_000,
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReportGas {
+            execution_id: 1,
+            gas_used: 30_000,
+        };
+        let _res = handle(&mut deps, env, msg.clone()).unwrap();
+        // reporting twice would skew the statistics
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, msg) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject second report"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReportGas {
+            execution_id: 2,
+            gas_used: 40_000,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let res = query(
+            &deps,
+            QueryMsg::EstimateCost {
+                kind: MsgKind::Send,
+            },
+        )
+        .unwrap();
+        let value: CostEstimateResponse = from_slice(&res).unwrap();
+        assert_eq!(2, value.samples);
+        assert_eq!(Some(35_000), value.gas_per_msg);
+
+        // unknown executions cannot be reported
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReportGas {
+            execution_id: 7,
+            gas_used: 40_000,
+        };
+        assert!(handle(&mut deps, env, msg).is_err());
+    }
+
+    #[test]
+    fn joint_account() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        // the single owner sets up the joint account directly
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCoOwners {
+            co_owners: vec![HumanAddr::from("cosmos1partner")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let res = query(&deps, QueryMsg::GetCoOwners {}).unwrap();
+        let value: CoOwnersResponse = from_slice(&res).unwrap();
+        assert_eq!("creator", value.owner.as_str());
+        assert_eq!(vec![HumanAddr::from("cosmos1partner")], value.co_owners);
+
+        // any owner may reflect
+        let payload = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![payload.clone()],
+            tags: vec![],
+            memo: None,
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(vec![payload], res.messages);
+
+        // but no one may take admin actions alone any more
+        let change = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1friend"),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, change.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // nor sneak them in with a reflected call to ourself
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Contract {
+                contract_addr: HumanAddr::from("cosmos2contract"),
+                msg: Binary(vec![]),
+                send: None,
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject self calls"),
+        }
+
+        // outsiders cannot propose
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        let msg = HandleMsg::ProposeAdmin {
+            msg: Box::new(change.clone()),
+        };
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("anyone"),
+        };
+        assert_contract_error(handle(&mut deps, env, msg), expected);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ProposeAdmin {
+            msg: Box::new(change.clone()),
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(log("proposal_id", "1"), res.log[1]);
+        // not executed yet
+        let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_slice(&res).unwrap();
+        assert_eq!("creator", value.owner.as_str());
+
+        // approving twice does not count
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ApproveAdmin { id: 1 }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject double approval"),
+        }
+
+        // the last approval executes it
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ApproveAdmin { id: 1 }).unwrap();
+        assert!(res.log.contains(&log("action", "change_owner")));
+        let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_slice(&res).unwrap();
+        assert_eq!("cosmos1friend", value.owner.as_str());
+
+        let res = query(&deps, QueryMsg::AdminProposal { id: 1 }).unwrap();
+        let value: AdminProposalResponse = from_slice(&res).unwrap();
+        assert!(value.executed);
+        assert_eq!(change, value.msg);
+        assert_eq!(2, value.approvals.len());
+
+        // cannot run twice
+        let env = mock_env(&deps.api, "cosmos1friend", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ApproveAdmin { id: 1 }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject executed proposal"),
+        }
+    }
+
+    #[test]
+    fn weighted_admin_voting() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCoOwners {
+            co_owners: vec![
+                HumanAddr::from("cosmos1director"),
+                HumanAddr::from("cosmos1officer"),
+            ],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // weights can only be given to owners
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let env = Env {
+            message: MessageInfo {
+                signer: env.contract.address.clone(),
+                sent_funds: None,
+            },
+            ..env
+        };
+        let msg = HandleMsg::SetAdminWeights {
+            weights: vec![WeightMsg {
+                addr: HumanAddr::from("cosmos1stranger"),
+                weight: 5,
+            }],
+            threshold: Some(3),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject non-owner weights"),
+        }
+
+        // director 3, officer 1, creator 1 (default), threshold 3 of 5
+        let set_weights = HandleMsg::SetAdminWeights {
+            weights: vec![WeightMsg {
+                addr: HumanAddr::from("cosmos1director"),
+                weight: 3,
+            }],
+            threshold: Some(3),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ProposeAdmin {
+            msg: Box::new(set_weights),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos1director", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::ApproveAdmin { id: 1 }).unwrap();
+        // still needs officer, as unanimity applies until the weights are set
+        let env = mock_env(&deps.api, "cosmos1officer", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ApproveAdmin { id: 1 }).unwrap();
+        assert!(res.log.contains(&log("action", "set_admin_weights")));
+
+        let res = query(&deps, QueryMsg::GetCoOwners {}).unwrap();
+        let value: CoOwnersResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(3), value.admin_threshold);
+        assert_eq!(3, value.weights[1].weight);
+        assert_eq!(1, value.weights[2].weight);
+
+        // two proposals, one passes with the director alone
+        let env = mock_env(&deps.api, "cosmos1officer", &[], &[]);
+        let msg = HandleMsg::ProposeAdmin {
+            msg: Box::new(HandleMsg::ChangeOwner {
+                owner: HumanAddr::from("cosmos1officer"),
+            }),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos1director", &[], &[]);
+        let msg = HandleMsg::ProposeAdmin {
+            msg: Box::new(HandleMsg::RegisterDenom {
+                denom: "uatom".to_string(),
+                symbol: "ATOM".to_string(),
+                exponent: 6,
+            }),
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert!(res.log.contains(&log("action", "register_denom")));
+
+        let res = query(
+            &deps,
+            QueryMsg::ListAdminProposals {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: AdminProposalsResponse = from_slice(&res).unwrap();
+        assert_eq!(1, value.proposals.len());
+        assert_eq!(2, value.proposals[0].id);
+        assert_eq!(1, value.proposals[0].approved_weight);
+
+        let res = query(
+            &deps,
+            QueryMsg::AdminVote {
+                id: 2,
+                owner: HumanAddr::from("cosmos1officer"),
+            },
+        )
+        .unwrap();
+        let value: AdminVoteResponse = from_slice(&res).unwrap();
+        assert!(value.approved);
+        assert_eq!(1, value.weight);
+        let res = query(
+            &deps,
+            QueryMsg::AdminVote {
+                id: 2,
+                owner: HumanAddr::from("cosmos1director"),
+            },
+        )
+        .unwrap();
+        let value: AdminVoteResponse = from_slice(&res).unwrap();
+        assert!(!value.approved);
+        assert_eq!(3, value.weight);
+    }
+
+    #[test]
+    fn cooldowns() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCooldown {
+            action: "withdraw_all".to_string(),
+            period: Some(Duration::Height(10)),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject unknown actions"),
+        }
+
+        // change owner at most once a day, reflect every 10 blocks
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCooldown {
+            action: "change_owner".to_string(),
+            period: Some(Duration::Time(86_400)),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCooldown {
+            action: "reflect".to_string(),
+            period: Some(Duration::Height(10)),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let payload = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![payload],
+            tags: vec![],
+            memo: None,
+        };
+        let mut env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env.clone(), reflect.clone()).unwrap();
+
+        env.block.height += 9;
+        let res = handle(&mut deps, env.clone(), reflect.clone());
+        assert_contract_error(
+            res,
+            ContractError::CooldownActive {
+                action: "reflect".to_string(),
+                earliest: Expiration::AtHeight(12_355),
+            },
+        );
+        env.block.height += 1;
+        let _res = handle(&mut deps, env.clone(), reflect.clone()).unwrap();
+
+        let change = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1creator"),
+        };
+        let _res = handle(&mut deps, env.clone(), change.clone()).unwrap();
+        let mut env = mock_env(&deps.api, "cosmos1creator", &[], &[]);
+        env.block.time += 3_600;
+        let res = handle(&mut deps, env.clone(), change.clone());
+        assert_contract_error(
+            res,
+            ContractError::CooldownActive {
+                action: "change_owner".to_string(),
+                earliest: Expiration::AtTime(1_571_797_419 + 86_400),
+            },
+        );
+
+        let res = query(&deps, QueryMsg::Cooldowns {}).unwrap();
+        let value: CooldownsResponse = from_slice(&res).unwrap();
+        assert_eq!(2, value.cooldowns.len());
+        assert_eq!("change_owner", value.cooldowns[0].cooldown.action);
+        assert_eq!(
+            Some(Expiration::AtTime(1_571_797_419 + 86_400)),
+            value.cooldowns[0].next_allowed
+        );
+
+        // removing the cooldown lifts the restriction
+        let msg = HandleMsg::SetCooldown {
+            action: "change_owner".to_string(),
+            period: None,
+        };
+        let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        let _res = handle(&mut deps, env, change).unwrap();
+    }
+
+    #[test]
+    fn stage_and_execute_batches() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetOperators {
+            operators: vec![HumanAddr::from("cosmos1ops")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let payload = vec![
+            CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            },
+            CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1other"),
+                amount: coin("2", "token"),
+            },
+        ];
+        let stage = HandleMsg::StageBatch {
+            msgs: payload.clone(),
+            memo: "monthly bills".to_string(),
+            receipt_contract: None,
+            tags: vec![],
+        };
+
+        // only operators stage
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        match handle(&mut deps, env, stage.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let quoted = HandleMsg::StageBatch {
+            msgs: payload.clone(),
+            memo: "\"monthly\" bills".to_string(),
+            receipt_contract: None,
+            tags: vec![],
+        };
+        match handle(&mut deps, env, quoted) {
+            Err(Error::DynContractErr { msg, .. }) => assert_eq!(
+                "Memo cannot contain quotes, backslashes or control characters",
+                msg
+            ),
+            _ => panic!("Must reject a quote in the memo"),
+        }
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let long = HandleMsg::StageBatch {
+            msgs: payload.clone(),
+            memo: "x".repeat(MAX_BATCH_MEMO_LEN + 1),
+            receipt_contract: None,
+            tags: vec![],
+        };
+        match handle(&mut deps, env, long) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject a long memo"),
+        }
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let res = handle(&mut deps, env, stage).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let res = query(
+            &deps,
+            QueryMsg::ListStagedBatches {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: StagedBatchesResponse = from_slice(&res).unwrap();
+        assert_eq!(1, value.batches.len());
+        assert_eq!("cosmos1ops", value.batches[0].operator.as_str());
+        assert_eq!("monthly bills", value.batches[0].memo);
+
+        // operators cannot execute what they staged
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1ops"),
+        };
+        assert_contract_error(
+            handle(&mut deps, env, HandleMsg::ExecuteStaged { id: 1 }),
+            expected,
+        );
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ExecuteStaged { id: 1 }).unwrap();
+        assert_eq!(payload, res.messages);
+        assert_eq!(log("batch_id", "1"), res.log[1]);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ExecuteStaged { id: 1 }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not execute twice"),
+        }
+        let res = query(
+            &deps,
+            QueryMsg::ListStagedBatches {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: StagedBatchesResponse = from_slice(&res).unwrap();
+        assert_eq!(0, value.batches.len());
+    }
+
+    #[test]
+    fn operator_manifest_replaces_set() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetOperators {
+            operators: vec![HumanAddr::from("cosmos1old"), HumanAddr::from("cosmos1ops")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let manifest = |nonce: u64, addrs: &[&str]| HandleMsg::ApplyOperatorManifest {
+            operators: addrs
+                .iter()
+                .map(|a| OperatorSpec {
+                    addr: HumanAddr::from(*a),
+                })
+                .collect(),
+            manifest_nonce: nonce,
+        };
+
+        // only admins apply manifests
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        match handle(&mut deps, env, manifest(1, &["cosmos1ops"])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, manifest(2, &["cosmos1ops", "cosmos1new"])).unwrap();
+        let res = query(&deps, QueryMsg::Operators {}).unwrap();
+        let value: OperatorsResponse = from_slice(&res).unwrap();
+        assert_eq!(
+            vec![HumanAddr::from("cosmos1ops"), HumanAddr::from("cosmos1new")],
+            value.operators
+        );
+        assert_eq!(2, value.manifest_nonce);
+
+        // replaying the same or an older manifest is rejected
+        for nonce in &[1, 2] {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            match handle(&mut deps, env, manifest(*nonce, &["cosmos1old"])) {
+                Err(Error::ContractErr { .. }) => {}
+                _ => panic!("Must reject stale manifest"),
+            }
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, manifest(3, &["cosmos1ops", "cosmos1ops"])) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject duplicates"),
+        }
+    }
+
+    #[test]
+    fn staged_batch_receipts() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetOperators {
+            operators: vec![HumanAddr::from("cosmos1ops")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let payload = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+
+        // receipts may not loop back into the contract
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let msg = HandleMsg::StageBatch {
+            msgs: vec![payload.clone()],
+            memo: "payroll".to_string(),
+            receipt_contract: Some(HumanAddr::from("cosmos2contract")),
+            tags: vec![],
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject receipts to self"),
+        }
+
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let msg = HandleMsg::StageBatch {
+            msgs: vec![payload.clone()],
+            memo: "payroll".to_string(),
+            receipt_contract: Some(HumanAddr::from("cosmos1automation")),
+            tags: vec![],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ExecuteStaged { id: 1 }).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert_eq!(payload, res.messages[0]);
+        let receipt = ReceiptMsg::ExecutionReceipt {
+            batch_id: 1,
+            execution_id: 1,
+            height: 12_345,
+        };
+        assert_eq!(
+            CosmosMsg::Contract {
+                contract_addr: HumanAddr::from("cosmos1automation"),
+                msg: Binary(to_vec(&receipt).unwrap()),
+                send: None,
+            },
+            res.messages[1]
+        );
+
+        // the receipt is not counted as part of the execution
+        let res = query(&deps, QueryMsg::Execution { id: 1 }).unwrap();
+        let value: ExecutionResponse = from_slice(&res).unwrap();
+        assert_eq!(1, value.msgs.len());
+        assert_eq!(1, value.msgs[0].count);
+
+        // a batch waiting for the co-signer reports once it is confirmed
+        let set = HandleMsg::SetCosigner {
+            cosigner: Some(HumanAddr::from("cosmos1cosigner")),
+            thresholds: vec![Amount::from(coin("0", "token").remove(0))],
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let msg = HandleMsg::StageBatch {
+            msgs: vec![payload.clone()],
+            memo: "payroll".to_string(),
+            receipt_contract: Some(HumanAddr::from("cosmos1automation")),
+            tags: vec![],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ExecuteStaged { id: 2 }).unwrap();
+        assert_eq!(0, res.messages.len());
+        let mut env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+        env.block.height = 12_350;
+        let res = handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 1 }).unwrap();
+        let receipt = ReceiptMsg::ExecutionReceipt {
+            batch_id: 2,
+            execution_id: 2,
+            height: 12_350,
+        };
+        assert_eq!(
+            vec![
+                payload,
+                CosmosMsg::Contract {
+                    contract_addr: HumanAddr::from("cosmos1automation"),
+                    msg: Binary(to_vec(&receipt).unwrap()),
+                    send: None,
+                },
+            ],
+            res.messages
+        );
+    }
+
+    #[test]
+    fn tagged_executions() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["Payroll".to_string()],
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject invalid tag names"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string(), "grants".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let reflect = |tags: &[&str]| HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            memo: None,
+        };
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, reflect(&["ops"])) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject unknown tags"),
+        }
+
+        // executions 1, 3 and 4 are payroll
+        for tags in &[
+            &["payroll"][..],
+            &["grants"],
+            &["payroll", "grants"],
+            &["payroll"],
+        ] {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            let _res = handle(&mut deps, env, reflect(tags)).unwrap();
+        }
+
+        let list =
+            |start_after: Option<String>, limit: Option<u32>| QueryMsg::ListTaggedExecutions {
+                tag: "payroll".to_string(),
+                start_after,
+                limit,
+            };
+        let res = query(&deps, list(None, None)).unwrap();
+        let value: TaggedExecutionsResponse = from_slice(&res).unwrap();
+        let ids: Vec<u64> = value.executions.iter().map(|e| e.id).collect();
+        assert_eq!(vec![1, 3, 4], ids);
+        assert!(!value.is_truncated);
+
+        // page through them one by one
+        let res = query(&deps, list(None, Some(1))).unwrap();
+        let value: TaggedExecutionsResponse = from_slice(&res).unwrap();
+        assert_eq!(1, value.executions[0].id);
+        assert!(value.is_truncated);
+        let res = query(&deps, list(value.next, Some(1))).unwrap();
+        let value: TaggedExecutionsResponse = from_slice(&res).unwrap();
+        assert_eq!(1, value.executions.len());
+        assert_eq!(3, value.executions[0].id);
+        assert_eq!(
+            vec!["payroll".to_string(), "grants".to_string()],
+            value.executions[0].tags
+        );
+        let res = query(&deps, list(value.next, Some(1))).unwrap();
+        let value: TaggedExecutionsResponse = from_slice(&res).unwrap();
+        assert_eq!(4, value.executions[0].id);
+        assert!(!value.is_truncated);
+        assert_eq!(None, value.next);
+
+        match query(&deps, list(Some("nonsense".to_string()), None)) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject invalid cursors"),
+        }
+    }
+
+    #[test]
+    fn tag_budgets() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let limit = coin("100", "token").pop().unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTagBudget {
+            tag: "payroll".to_string(),
+            budget: Some(Budget {
+                limit: limit.clone(),
+                period: Duration::Height(10),
+                enforce: true,
+            }),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // other denoms do not count against the budget
+        let pay = |amount: &str| {
+            let mut amount = coin(amount, "token");
+            amount.extend(coin("500", "other"));
+            HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount,
+                }],
+                tags: vec!["payroll".to_string()],
+                memo: None,
+            }
+        };
+        let spend = QueryMsg::TagSpend {
+            tag: "payroll".to_string(),
+        };
+        let budget_logs = |res: &Response| -> Vec<LogAttribute> {
+            res.log
+                .iter()
+                .filter(|l| l.key.starts_with("budget"))
+                .cloned()
+                .collect()
+        };
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, pay("50")).unwrap();
+        assert_eq!(0, budget_logs(&res).len());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, pay("30")).unwrap();
+        assert_eq!(vec![log("budget_warning", "payroll")], budget_logs(&res));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, pay("30"));
+        assert_contract_error(
+            res,
+            ContractError::BudgetExceeded {
+                tag: "payroll".to_string(),
+                limit: limit.clone(),
+                spent: "80".to_string(),
+            },
+        );
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, pay("20")).unwrap();
+        assert_eq!(vec![log("budget_reached", "payroll")], budget_logs(&res));
+
+        let res = query(&deps, spend.clone()).unwrap();
+        let value: TagSpendResponse = from_slice(&res).unwrap();
+        assert_eq!("100", value.spent);
+        assert_eq!(Some(Expiration::AtHeight(12_355)), value.resets_at);
+
+        // a new period starts from zero
+        let mut env = mock_env(&deps.api, "creator", &[], &[]);
+        env.block.height += 10;
+        let res = handle(&mut deps, env, pay("90")).unwrap();
+        assert_eq!(vec![log("budget_warning", "payroll")], budget_logs(&res));
+        let res = query(&deps, spend.clone()).unwrap();
+        let value: TagSpendResponse = from_slice(&res).unwrap();
+        assert_eq!("90", value.spent);
+        assert_eq!(Some(Expiration::AtHeight(12_365)), value.resets_at);
+    }
+
+    #[test]
+    fn evacuate_and_resume() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCoOwners {
+            co_owners: vec![HumanAddr::from("cosmos1partner")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // nowhere to go yet
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::Evacuate { to: None }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must require a recovery address"),
+        }
+
+        // on a joint account, setting the recovery address needs the contract as signer
+        let env = mock_env(&deps.api, "cosmos2contract", &[], &[]);
+        let msg = HandleMsg::SetRecovery {
+            recovery: Some(HumanAddr::from("cosmos1vault")),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // a single owner cannot redirect the funds elsewhere
+        let mut balance = coin("1000", "token");
+        balance.extend(coin("7", "other"));
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &balance);
+        let msg = HandleMsg::Evacuate {
+            to: Some(HumanAddr::from("cosmos1thief")),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &balance);
+        let res = handle(&mut deps, env, HandleMsg::Evacuate { to: None }).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1vault"),
+                amount: [coin("7", "other"), coin("1000", "token")].concat(),
+            }],
+            res.messages
+        );
+        let res = query(&deps, QueryMsg::Recovery {}).unwrap();
+        let value: RecoveryResponse = from_slice(&res).unwrap();
+        assert!(value.paused);
+
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect.clone());
+        assert_contract_error(
+            res,
+            ContractError::ContractPaused {
+                action: "reflect".to_string(),
+            },
+        );
+
+        let env = mock_env(&deps.api, "cosmos2contract", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Unpause {}).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect).unwrap();
+    }
+
+    #[test]
+    fn decommission_after_evacuation() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetOperators {
+            operators: vec![HumanAddr::from("cosmos1ops")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::Decommission { successor: None }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must evacuate first"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &coin("5", "token"));
+        let msg = HandleMsg::Evacuate {
+            to: Some(HumanAddr::from("cosmos1vault")),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Decommission { successor: None }).unwrap();
+
+        let res = query(&deps, QueryMsg::Operators {}).unwrap();
+        let value: OperatorsResponse = from_slice(&res).unwrap();
+        assert_eq!(0, value.operators.len());
+        let res = query(&deps, QueryMsg::Recovery {}).unwrap();
+        let value: RecoveryResponse = from_slice(&res).unwrap();
+        assert!(value.decommissioned);
+
+        // not even admin actions go through anymore
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::Unpause {});
+        assert_contract_error(res, ContractError::Decommissioned {});
+    }
+
+    #[test]
+    fn decommission_to_successor() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::Evacuate {
+            to: Some(HumanAddr::from("cosmos1newmask")),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::Decommission {
+            successor: Some(HumanAddr::from("cosmos1newmask")),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let res = query(&deps, QueryMsg::Successor {}).unwrap();
+        let value: SuccessorResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(HumanAddr::from("cosmos1newmask")), value.successor);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1someone"),
+        };
+        let res = handle(&mut deps, env, msg);
+        assert_contract_error(
+            res,
+            ContractError::MovedPermanently {
+                successor: HumanAddr::from("cosmos1newmask"),
+            },
+        );
+    }
+
+    #[test]
+    fn simulate_tighter_budget() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let payment = vec![CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("60", "token"),
+        }];
+        let simulate =
+            |budgets: Option<Vec<TagBudget>>, sender: &str| QueryMsg::SimulateWithConfig {
+                config_override: ConfigOverride {
+                    tags: None,
+                    budgets,
+                    cooldowns: None,
+                },
+                sender: HumanAddr::from(sender),
+                msgs: payment.clone(),
+                tags: vec!["payroll".to_string()],
+            };
+
+        let res = query(&deps, simulate(None, "creator")).unwrap();
+        let value: SimulationResponse = from_slice(&res).unwrap();
+        assert_eq!(None, value.error);
+
+        let res = query(&deps, simulate(None, "anyone")).unwrap();
+        let value: SimulationResponse = from_slice(&res).unwrap();
+        assert!(value.error.is_some());
+
+        let tight = vec![TagBudget {
+            tag: "payroll".to_string(),
+            budget: Budget {
+                limit: coin("50", "token").pop().unwrap(),
+                period: Duration::Height(100),
+                enforce: true,
+            },
+        }];
+        let res = query(&deps, simulate(Some(tight), "creator")).unwrap();
+        let value: SimulationResponse = from_slice(&res).unwrap();
+        let expected = ContractError::BudgetExceeded {
+            tag: "payroll".to_string(),
+            limit: coin("50", "token").pop().unwrap(),
+            spent: "0".to_string(),
+        };
+        assert!(value.error.unwrap().contains(&expected.to_string()));
+
+        // nothing was applied
+        let res = query(
+            &deps,
+            QueryMsg::TagSpend {
+                tag: "payroll".to_string(),
+            },
+        )
+        .unwrap();
+        let value: TagSpendResponse = from_slice(&res).unwrap();
+        assert_eq!(None, value.budget);
+    }
+
+    #[test]
+    fn scheduled_config_change() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let change = ConfigOverride {
+            tags: Some(vec!["payroll".to_string()]),
+            budgets: None,
+            cooldowns: Some(vec![Cooldown {
+                action: "reflect".to_string(),
+                period: Duration::Height(100),
+            }]),
+        };
+        let schedule = |activate_at: u64| HandleMsg::ScheduleConfig {
+            change: change.clone(),
+            activate_at: Expiration::AtHeight(activate_at),
+        };
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, schedule(12_345)) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must be in the future"),
+        }
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        match handle(&mut deps, env, schedule(12_400)) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // cancel and schedule again
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, schedule(12_400)).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::CancelPending {}).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, schedule(12_400)).unwrap();
+
+        let res = query(&deps, QueryMsg::PendingConfig {}).unwrap();
+        let value: PendingConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(
+            Expiration::AtHeight(12_400),
+            value.pending.unwrap().activate_at
+        );
+
+        // not due yet
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ActivatePending {}) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not activate early"),
+        }
+        let res = query(&deps, QueryMsg::Tags {}).unwrap();
+        let value: TagsResponse = from_slice(&res).unwrap();
+        assert_eq!(0, value.tags.len());
+
+        let mut env = mock_env(&deps.api, "anyone", &[], &[]);
+        env.block.height = 12_400;
+        let res = handle(&mut deps, env, HandleMsg::ActivatePending {}).unwrap();
+        assert_eq!(log("config_activated", "12400"), res.log[1]);
+        let res = query(&deps, QueryMsg::Tags {}).unwrap();
+        let value: TagsResponse = from_slice(&res).unwrap();
+        assert_eq!(vec!["payroll".to_string()], value.tags);
+        let res = query(&deps, QueryMsg::PendingConfig {}).unwrap();
+        let value: PendingConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(None, value.pending);
+
+        // the next change is applied by whatever handle comes after it is due
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, schedule(12_500)).unwrap();
+        let mut env = mock_env(&deps.api, "creator", &[], &[]);
+        env.block.height = 12_600;
+        let msg = HandleMsg::RegisterDenom {
+            denom: "uatom".to_string(),
+            symbol: "ATOM".to_string(),
+            exponent: 6,
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert!(res.log.contains(&log("config_activated", "12600")));
+    }
+
+    #[test]
+    fn pending_config_diff() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string(), "grants".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let budget = Budget {
+            limit: coin("100", "token").pop().unwrap(),
+            period: Duration::Height(10),
+            enforce: false,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTagBudget {
+            tag: "grants".to_string(),
+            budget: Some(budget.clone()),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let res = query(&deps, QueryMsg::ConfigDiff {}).unwrap();
+        let value: ConfigDiffResponse = from_slice(&res).unwrap();
+        assert_eq!(None, value.activate_at);
+        assert_eq!(0, value.budgets.len());
+
+        // dropping grants also drops its budget
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ScheduleConfig {
+            change: ConfigOverride {
+                tags: Some(vec!["payroll".to_string(), "ops".to_string()]),
+                budgets: None,
+                cooldowns: Some(vec![Cooldown {
+                    action: "reflect".to_string(),
+                    period: Duration::Time(60),
+                }]),
+            },
+            activate_at: Expiration::AtHeight(13_000),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let res = query(&deps, QueryMsg::ConfigDiff {}).unwrap();
+        let value: ConfigDiffResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(Expiration::AtHeight(13_000)), value.activate_at);
+        assert_eq!(vec!["ops".to_string()], value.tags_added);
+        assert_eq!(vec!["grants".to_string()], value.tags_removed);
+        assert_eq!(
+            vec![BudgetChange {
+                tag: "grants".to_string(),
+                old: Some(budget),
+                new: None,
+            }],
+            value.budgets
+        );
+        assert_eq!(
+            vec![CooldownChange {
+                action: "reflect".to_string(),
+                old: None,
+                new: Some(Duration::Time(60)),
+            }],
+            value.cooldowns
+        );
+    }
+
+    #[test]
+    fn error_payloads() {
+        let err = ContractError::CooldownActive {
+            action: "reflect".to_string(),
+            earliest: Expiration::AtHeight(12_400),
+        };
+        assert_eq!(
+            "Cooldown active for reflect, next allowed at height 12400 | code=1001;action=reflect;earliest=height 12400",
+            err.payload()
+        );
+        assert_eq!(
+            "Contract is decommissioned | code=1004",
+            ContractError::Decommissioned {}.payload()
+        );
+    }
+
+    #[test]
+    fn deprecation_warnings() {
+        let registry = [Deprecation {
+            name: "set_operators",
+            sunset_height: 2_000_000,
+        }];
+        assert_eq!(
+            vec![
+                log("deprecated", "set_operators"),
+                log("sunset_height", "2000000")
+            ],
+            deprecation_logs(&registry, "set_operators")
+        );
+        assert_eq!(0, deprecation_logs(&registry, "reflect").len());
+
+        // actions listed in the registry must exist
+        for d in DEPRECATIONS.iter() {
+            assert!(HandleMsg::ACTIONS.contains(&d.name), "{}", d.name);
+        }
+    }
+
+    #[test]
+    fn list_executions_by_height() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        // executions 1 to 6 at heights 100, 100, 101, 102, 102, 103
+        for height in &[100, 100, 101, 102, 102, 103] {
+            let mut env = mock_env(&deps.api, "creator", &[], &[]);
+            env.block.height = *height;
+            let msg = HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("1", "token"),
+                }],
+                tags: vec![],
+                memo: None,
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        let list = |start_after: Option<String>, order, min_height, max_height| {
+            let msg = QueryMsg::ListExecutions {
+                start_after,
+                limit: Some(2),
+                order,
+                min_height,
+                max_height,
+                sender: None,
+                tag: None,
+                kind: None,
+            };
+            let value: ExecutionsResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+            let ids: Vec<u64> = value.executions.iter().map(|e| e.id).collect();
+            (ids, value.next)
+        };
+
+        let (ids, next) = list(None, None, None, None);
+        assert_eq!(vec![1, 2], ids);
+        assert!(next.is_some());
+        let (ids, next) = list(None, Some(Order::Desc), None, None);
+        assert_eq!(vec![6, 5], ids);
+        let (ids, _) = list(next, Some(Order::Desc), None, None);
+        assert_eq!(vec![4, 3], ids);
+
+        // latest in range
+        let (ids, next) = list(None, Some(Order::Desc), Some(100), Some(102));
+        assert_eq!(vec![5, 4], ids);
+        let (ids, next) = list(next, Some(Order::Desc), Some(100), Some(102));
+        assert_eq!(vec![3, 2], ids);
+        let (ids, next) = list(next, Some(Order::Desc), Some(100), Some(102));
+        assert_eq!(vec![1], ids);
+        assert_eq!(None, next);
+
+        let (ids, next) = list(None, Some(Order::Asc), Some(101), None);
+        assert_eq!(vec![3, 4], ids);
+        let (ids, _) = list(next, Some(Order::Asc), Some(101), None);
+        assert_eq!(vec![5, 6], ids);
+
+        // empty ranges
+        let (ids, _) = list(None, None, Some(104), None);
+        assert_eq!(0, ids.len());
+        let (ids, _) = list(None, Some(Order::Desc), None, Some(99));
+        assert_eq!(0, ids.len());
+
+        // nothing follows a cursor at the last possible key
+        let last = Some(encode_cursor(u64::max_value()));
+        let (ids, next) = list(last.clone(), Some(Order::Asc), None, None);
+        assert_eq!((vec![], None), (ids, next));
+        let msg = QueryMsg::ListScheduled {
+            start_after: last.clone(),
+            limit: None,
+        };
+        let value: ScheduledBatchesResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert!(value.batches.is_empty());
+        let msg = QueryMsg::VerifyState {
+            section: VerifySection::Executions,
+            start_after: last,
+            limit: None,
+        };
+        let value: VerifyStateResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!(None, value.next);
+    }
+
+    #[test]
+    fn list_executions_with_filters() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCoOwners {
+            co_owners: vec![HumanAddr::from("cosmos1partner")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos2contract", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["ops".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let call = CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1other"),
+            msg: Binary(vec![]),
+            send: None,
+        };
+        // only execution 4 is a tagged send by the partner
+        let runs = vec![
+            ("cosmos1partner", send.clone(), vec![]),
+            ("creator", send.clone(), vec!["ops".to_string()]),
+            ("cosmos1partner", call, vec!["ops".to_string()]),
+            ("cosmos1partner", send, vec!["ops".to_string()]),
+        ];
+        for (sender, msg, tags) in runs.into_iter() {
+            let env = mock_env(&deps.api, sender, &[], &[]);
+            let msg = HandleMsg::ReflectMsg {
+                msgs: vec![msg],
+                tags,
+                memo: None,
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        let msg = QueryMsg::ListExecutions {
+            start_after: None,
+            limit: None,
+            order: None,
+            min_height: None,
+            max_height: None,
+            sender: Some(HumanAddr::from("cosmos1partner")),
+            tag: Some("ops".to_string()),
+            kind: Some(MsgKind::Send),
+        };
+        let value: ExecutionsResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        let ids: Vec<u64> = value.executions.iter().map(|e| e.id).collect();
+        assert_eq!(vec![4], ids);
+        assert!(!value.is_truncated);
+    }
+
+    #[test]
+    fn export_executions_in_chunks() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        for _ in 0..5 {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            let msg = HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("1", "token"),
+                }],
+                tags: vec![],
+                memo: None,
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        // the budget fits two records, which are all the same size
+        let record = executions_read(&deps.storage)
+            .load(&1u64.to_be_bytes())
+            .unwrap();
+        let record_size = 8 + to_vec(&record).unwrap().len() as u32;
+        let export = |start_after, max_bytes| {
+            let msg = QueryMsg::ExportChunk {
+                namespace: ExportNamespace::Executions,
+                start_after,
+                max_bytes,
+            };
+            let value: ExportChunkResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+            value
+        };
+
+        let mut exported = vec![];
+        let mut start_after = None;
+        loop {
+            let chunk = export(start_after, Some(record_size * 2 + 1));
+            assert!(chunk.entries.len() <= 2);
+            exported.extend(chunk.entries);
+            if !chunk.is_truncated {
+                break;
+            }
+            start_after = chunk.next;
+        }
+        assert_eq!(5, exported.len());
+        for (i, entry) in exported.iter().enumerate() {
+            let id = i as u64 + 1;
+            assert_eq!(id.to_be_bytes().to_vec(), entry.key.0);
+            let execution: Execution = from_slice(&entry.value.0).unwrap();
+            assert_eq!(id, execution.id);
+        }
+
+        // a record over the budget is still returned on its own
+        let chunk = export(None, Some(1));
+        assert_eq!(1, chunk.entries.len());
+        assert!(chunk.is_truncated);
+
+        let msg = QueryMsg::ExportChunk {
+            namespace: ExportNamespace::StagedBatches,
+            start_after: None,
+            max_bytes: None,
+        };
+        let value: ExportChunkResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!(0, value.entries.len());
+        assert!(!value.is_truncated);
+    }
+
+    #[test]
+    fn verify_state_finds_violations() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["ops".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        for height in &[100, 101, 102] {
+            let mut env = mock_env(&deps.api, "creator", &[], &[]);
+            env.block.height = *height;
+            let msg = HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("1", "token"),
+                }],
+                tags: vec!["ops".to_string()],
+                memo: None,
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        fn verify<S: Storage, A: Api>(
+            deps: &Extern<S, A>,
+            section: VerifySection,
+            start_after: Option<String>,
+        ) -> VerifyStateResponse {
+            let msg = QueryMsg::VerifyState {
+                section,
+                start_after,
+                limit: Some(2),
+            };
+            from_slice(&query(deps, msg).unwrap()).unwrap()
+        }
+        let ops = || VerifySection::TagIndex {
+            tag: "ops".to_string(),
+        };
+        let value = verify(&deps, VerifySection::Executions, None);
+        assert_eq!(0, value.violations.len());
+        assert!(value.is_truncated);
+        let value = verify(&deps, VerifySection::Executions, value.next);
+        assert_eq!(0, value.violations.len());
+        assert!(!value.is_truncated);
+        let value = verify(&deps, ops(), Some(encode_cursor(1)));
+        assert_eq!(0, value.violations.len());
+
+        // break things the way a bad migration could
+        let mut execution = executions_read(&deps.storage)
+            .load(&3u64.to_be_bytes())
+            .unwrap();
+        execution.height = 99;
+        execution.tags = vec![];
+        executions(&mut deps.storage)
+            .save(&3u64.to_be_bytes(), &execution)
+            .unwrap();
+        tag_index(&mut deps.storage)
+            .save(&tag_index_key("ops", 3), &7)
+            .unwrap();
+
+        let value = verify(&deps, VerifySection::Executions, Some(encode_cursor(1)));
+        let kinds: Vec<ViolationKind> = value.violations.iter().map(|v| v.kind).collect();
+        assert_eq!(vec![ViolationKind::HeightOrder], kinds);
+        assert_eq!("executions/3", value.violations[0].key);
+
+        let value = verify(&deps, ops(), Some(encode_cursor(1)));
+        let kinds: Vec<ViolationKind> = value.violations.iter().map(|v| v.kind).collect();
+        assert_eq!(
+            vec![
+                ViolationKind::DanglingReference,
+                ViolationKind::CounterMismatch
+            ],
+            kinds
+        );
+        assert_eq!("tag_index/ops/2", value.violations[0].key);
+        assert_eq!("tag_counts/ops", value.violations[1].key);
+
+        tag_counts(&mut deps.storage).save(b"ops", &5).unwrap();
+        let value = verify(&deps, ops(), Some(encode_cursor(2)));
+        let kinds: Vec<ViolationKind> = value.violations.iter().map(|v| v.kind).collect();
+        assert_eq!(
+            vec![
+                ViolationKind::DanglingReference,
+                ViolationKind::MissingRecord
+            ],
+            kinds
+        );
+        assert_eq!("tag_index/ops/4", value.violations[1].key);
+    }
+
+    #[test]
+    fn repair_state_from_tokens() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["ops".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        for _ in 0..3 {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            let msg = HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("1", "token"),
+                }],
+                tags: vec!["ops".to_string()],
+                memo: None,
+            };
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        fn violations<S: Storage, A: Api>(
+            deps: &Extern<S, A>,
+            section: VerifySection,
+        ) -> Vec<Violation> {
+            let msg = QueryMsg::VerifyState {
+                section,
+                start_after: None,
+                limit: None,
+            };
+            let value: VerifyStateResponse = from_slice(&query(deps, msg).unwrap()).unwrap();
+            value.violations
+        }
+        let ops = || VerifySection::TagIndex {
+            tag: "ops".to_string(),
+        };
+        let repair = |token: &str| HandleMsg::RepairState {
+            token: token.to_string(),
+        };
+
+        // entry 1 points nowhere, entry 3 is missing and execution 2 has the wrong id
+        tag_index(&mut deps.storage)
+            .save(&tag_index_key("ops", 1), &9)
+            .unwrap();
+        tag_counts(&mut deps.storage).save(b"ops", &4).unwrap();
+        let mut execution = executions_read(&deps.storage)
+            .load(&2u64.to_be_bytes())
+            .unwrap();
+        execution.id = 5;
+        executions(&mut deps.storage)
+            .save(&2u64.to_be_bytes(), &execution)
+            .unwrap();
+
+        let found = violations(&deps, ops());
+        let tokens: Vec<&str> = found.iter().map(|v| v.token.as_str()).collect();
+        assert_eq!(
+            vec![
+                "dangling_reference@tag_index/ops/1",
+                "missing_record@tag_index/ops/3"
+            ],
+            tokens
+        );
+
+        // only admins repair, and only what is still reported
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, repair(&found[0].token)) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, repair(&found[1].token)).unwrap();
+        assert_eq!(log("repaired", "dropped entry 3, count is 3"), res.log[2]);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, repair(&found[1].token)) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must not repair twice"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, repair(&found[0].token)).unwrap();
+        assert_eq!(0, violations(&deps, ops()).len());
+        let msg = QueryMsg::ListTaggedExecutions {
+            tag: "ops".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let value: TaggedExecutionsResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        let ids: Vec<u64> = value.executions.iter().map(|e| e.id).collect();
+        assert_eq!(vec![1, 3], ids);
+
+        // a counter which lost entries is raised again
+        tag_counts(&mut deps.storage).save(b"ops", &1).unwrap();
+        let found = violations(&deps, ops());
+        assert_eq!("counter_mismatch@tag_counts/ops", found[0].token);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, repair(&found[0].token)).unwrap();
+        assert_eq!(0, violations(&deps, ops()).len());
+
+        let found = violations(&deps, VerifySection::Executions);
+        assert_eq!("id_mismatch@executions/2", found[0].token);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, repair(&found[0].token)).unwrap();
+        assert_eq!(0, violations(&deps, VerifySection::Executions).len());
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, repair("height_order@executions/2")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must not find the violation"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, repair("executions/2")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject invalid tokens"),
+        }
+    }
+
+    #[test]
+    fn msg_byte_limit() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let payment = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("60", "token"),
+        };
+        let size = to_vec(&payment).unwrap().len() as u64;
+        let reflect = |payment: &CosmosMsg| HandleMsg::ReflectMsg {
+            msgs: vec![payment.clone()],
+            tags: vec![],
+            memo: None,
+        };
+        let set_limit = |limit| HandleMsg::SetMsgByteLimit { limit };
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, set_limit(Some(size))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, set_limit(Some(0))) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject a zero limit"),
+        }
+
+        // exactly at the limit passes
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set_limit(Some(size))).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect(&payment)).unwrap();
+        assert_eq!(log("msg_bytes", &size.to_string()), res.log[5]);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set_limit(Some(size - 1))).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect(&payment));
+        let expected = ContractError::MessagesTooLarge {
+            size,
+            limit: size - 1,
+        };
+        assert_contract_error(res, expected.clone());
+
+        let msg = QueryMsg::SimulateWithConfig {
+            config_override: ConfigOverride {
+                tags: None,
+                budgets: None,
+                cooldowns: None,
+            },
+            sender: HumanAddr::from("creator"),
+            msgs: vec![payment.clone()],
+            tags: vec![],
+        };
+        let value: SimulationResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert!(value.error.unwrap().contains(&expected.payload()));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set_limit(None)).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect(&payment)).unwrap();
+    }
+
+    #[test]
+    fn build_features() {
+        let deps = dependencies(20);
+
+        let res = query(&deps, QueryMsg::Features {}).unwrap();
+        let value: FeaturesResponse = from_slice(&res).unwrap();
+        assert!(value.features.contains(&"multisig".to_string()));
+        assert!(!value.features.contains(&"ibc".to_string()));
+        let backtraces = value.features.contains(&"backtraces".to_string());
+        assert_eq!(cfg!(feature = "backtraces"), backtraces);
+    }
+
+    #[test]
+    fn execute_many_calls_every_target() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let payload = Binary(b"{\"claim\":{}}".to_vec());
+        let execute_many = |targets: &[&str]| HandleMsg::ExecuteMany {
+            targets: targets.iter().map(|t| HumanAddr::from(*t)).collect(),
+            msg: payload.clone(),
+            send: None,
+            tags: vec![],
+        };
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1stranger"),
+        };
+        assert_contract_error(
+            handle(&mut deps, env, execute_many(&["cosmos1pool"])),
+            expected,
+        );
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, execute_many(&[])) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject an empty target list"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(
+            &mut deps,
+            env,
+            execute_many(&["cosmos1pool", "cosmos1pool"]),
+        ) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject duplicate targets"),
+        }
+        let too_many: Vec<String> = (0..=MAX_TARGETS)
+            .map(|i| format!("cosmos1pool{}", i))
+            .collect();
+        let too_many: Vec<&str> = too_many.iter().map(|t| t.as_str()).collect();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, execute_many(&too_many)) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject too many targets"),
+        }
+        // the contract may not call itself through one of the targets
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(
+            &mut deps,
+            env,
+            execute_many(&["cosmos1pool", "cosmos2contract"])
+        )
+        .is_err());
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(
+            &mut deps,
+            env,
+            execute_many(&["cosmos1pool", "cosmos1vault"]),
+        )
+        .unwrap();
+        assert_eq!(2, res.messages.len());
+        assert_eq!(
+            CosmosMsg::Contract {
+                contract_addr: HumanAddr::from("cosmos1vault"),
+                msg: payload.clone(),
+                send: None,
+            },
+            res.messages[1]
+        );
+        assert_eq!(log("action", "execute_many"), res.log[0]);
+        assert_eq!(log("targets", "2"), res.log[1]);
+        let result: ExecuteManyResult = from_slice(&res.data.unwrap().0).unwrap();
+        assert_eq!(Some(1), result.execution_id);
+        assert_eq!(
+            vec![
+                HumanAddr::from("cosmos1pool"),
+                HumanAddr::from("cosmos1vault")
+            ],
+            result.targets
+        );
+    }
+
+    #[test]
+    fn run_payroll_as_one_execution() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let limit = coin("100", "token").pop().unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTagBudget {
+            tag: "payroll".to_string(),
+            budget: Some(Budget {
+                limit: limit.clone(),
+                period: Duration::Height(10),
+                enforce: true,
+            }),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let entry = |recipient: &str, amount: &str| PayrollEntry {
+            recipient: HumanAddr::from(recipient),
+            amount: coin(amount, "token"),
+        };
+        let run = |entries: Vec<PayrollEntry>, tag: &str| HandleMsg::RunPayroll {
+            entries,
+            tag: tag.to_string(),
+        };
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1stranger"),
+        };
+        let res = handle(
+            &mut deps,
+            env,
+            run(vec![entry("cosmos1alice", "10")], "payroll"),
+        );
+        assert_contract_error(res, expected);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, run(vec![], "payroll")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject an empty payroll"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let empty = PayrollEntry {
+            recipient: HumanAddr::from("cosmos1alice"),
+            amount: vec![],
+        };
+        match handle(&mut deps, env, run(vec![empty], "payroll")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject an entry without amount"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(
+            &mut deps,
+            env,
+            run(vec![entry("cosmos1alice", "10")], "grants")
+        )
+        .is_err());
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let entries = vec![entry("cosmos1alice", "40"), entry("cosmos1bob", "30")];
+        let res = handle(&mut deps, env, run(entries, "payroll")).unwrap();
+        assert_eq!(log("action", "run_payroll"), res.log[0]);
+        assert_eq!(log("recipients", "2"), res.log[1]);
+        assert_eq!(log("total", "70token"), res.log[2]);
+        assert_eq!(
+            CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1bob"),
+                amount: coin("30", "token"),
+            },
+            res.messages[1]
+        );
+        let res = query(&deps, QueryMsg::Execution { id: 1 }).unwrap();
+        let value: ExecutionResponse = from_slice(&res).unwrap();
+        assert_eq!(vec!["payroll".to_string()], value.tags);
+
+        // each recipient gets its own denoms, the totals are per denom
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let mut mixed = entry("cosmos1alice", "5");
+        mixed.amount.extend(coin("700", "other"));
+        let carol = PayrollEntry {
+            recipient: HumanAddr::from("cosmos1carol"),
+            amount: coin("300", "other"),
+        };
+        let entries = vec![entry("cosmos1bob", "5"), carol, mixed];
+        let res = handle(&mut deps, env, run(entries, "payroll")).unwrap();
+        assert_eq!(log("total", "1000other"), res.log[2]);
+        assert_eq!(log("total", "10token"), res.log[3]);
+        assert_eq!(3, res.messages.len());
+
+        // the whole payroll counts against the budget
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let entries = vec![entry("cosmos1alice", "20"), entry("cosmos1bob", "20")];
+        let res = handle(&mut deps, env, run(entries, "payroll"));
+        assert_contract_error(
+            res,
+            ContractError::BudgetExceeded {
+                tag: "payroll".to_string(),
+                limit,
+                spent: "80".to_string(),
+            },
+        );
+    }
+
+    #[test]
+    fn grant_milestones() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let milestone = |description: &str, amount: &str| Milestone {
+            description: description.to_string(),
+            amount: coin(amount, "token"),
+        };
+        let create = |milestones: Vec<Milestone>| HandleMsg::CreateGrant {
+            grantee: HumanAddr::from("cosmos1grantee"),
+            approver: HumanAddr::from("cosmos1approver"),
+            milestones,
+        };
+        let grant = |deps: &Extern<_, _>, id: u64| -> GrantResponse {
+            from_slice(&query(deps, QueryMsg::Grant { id }).unwrap()).unwrap()
+        };
+
+        let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+        match handle(&mut deps, env, create(vec![milestone("design", "10")])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, create(vec![])) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject a grant without milestones"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let free = Milestone {
+            description: "design".to_string(),
+            amount: vec![],
+        };
+        match handle(&mut deps, env, create(vec![free])) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject a milestone paying nothing"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let milestones = vec![milestone("design", "10"), milestone("launch", "30")];
+        let res = handle(&mut deps, env, create(milestones.clone())).unwrap();
+        assert_eq!(log("grant_id", "1"), res.log[1]);
+        let value = grant(&deps, 1);
+        assert_eq!(HumanAddr::from("cosmos1approver"), value.approver);
+        assert_eq!(0, value.released);
+        assert_eq!(coin("40", "token"), value.remaining);
+
+        // only the approver releases milestones, in order
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ApproveMilestone { id: 1 }) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ApproveMilestone { id: 1 }).unwrap();
+        assert_eq!(log("milestone", "1"), res.log[3]);
+        assert_eq!(
+            vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1grantee"),
+                amount: coin("10", "token"),
+            }],
+            res.messages
+        );
+        let value = grant(&deps, 1);
+        assert_eq!(1, value.released);
+        assert_eq!(coin("30", "token"), value.remaining);
+
+        let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::ApproveMilestone { id: 1 }).unwrap();
+        let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ApproveMilestone { id: 1 }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject releasing more than all milestones"),
+        }
+        assert_eq!(Vec::<Coin>::new(), grant(&deps, 1).remaining);
+
+        // grants can be listed per grantee
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let other = HandleMsg::CreateGrant {
+            grantee: HumanAddr::from("cosmos1other"),
+            approver: HumanAddr::from("cosmos1approver"),
+            milestones,
+        };
+        let _res = handle(&mut deps, env, other).unwrap();
+        let list = |grantee: Option<&str>| -> GrantsResponse {
+            let msg = QueryMsg::ListGrants {
+                grantee: grantee.map(HumanAddr::from),
+                start_after: None,
+                limit: None,
+            };
+            from_slice(&query(&deps, msg).unwrap()).unwrap()
+        };
+        assert_eq!(2, list(None).grants.len());
+        let value = list(Some("cosmos1other"));
+        assert_eq!(1, value.grants.len());
+        assert_eq!(2, value.grants[0].id);
+        assert!(!value.is_truncated);
+
+        // the admin can cancel what is not paid yet
+        let clawback = |reason: &str| HandleMsg::Clawback {
+            id: 2,
+            reason: reason.to_string(),
+        };
+        let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+        match handle(&mut deps, env, clawback("fraud")) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, clawback("")) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must require a reason"),
+        }
+        let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::ApproveMilestone { id: 2 }).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, clawback("missed launch")).unwrap();
+        assert_eq!(
+            vec![
+                log("action", "clawback"),
+                log("grant_id", "2"),
+                log("ref", "grant-2"),
+                log("reason", "missed launch"),
+                log("returned", "30token"),
+            ],
+            &res.log[..5]
+        );
+        assert!(res.messages.is_empty());
+        let value = grant(&deps, 2);
+        assert!(value.clawed_back);
+        assert_eq!(Vec::<Coin>::new(), value.remaining);
+        let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ApproveMilestone { id: 2 }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not release a clawed back grant"),
+        }
+        // completed grants have nothing left
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::Clawback {
+            id: 1,
+            reason: "late".to_string(),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject clawing back a completed grant"),
+        }
+    }
+
+    #[test]
+    fn two_step_ownership_transfer() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let propose = |expires| HandleMsg::ProposeNewOwner {
+            owner: HumanAddr::from("cosmos1friend"),
+            expires,
+        };
+        let pending = |deps: &Extern<_, _>| -> PendingOwnerResponse {
+            from_slice(&query(deps, QueryMsg::GetPendingOwner {}).unwrap()).unwrap()
+        };
+        let owner = |deps: &Extern<_, _>| -> HumanAddr {
+            let value: OwnerResponse =
+                from_slice(&query(deps, QueryMsg::GetOwner {}).unwrap()).unwrap();
+            value.owner
+        };
+        assert_eq!(None, pending(&deps).owner);
+
+        let env = mock_env(&deps.api, "cosmos1friend", &[], &[]);
+        match handle(&mut deps, env, propose(Expiration::AtHeight(12_400))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, propose(Expiration::AtHeight(12_345))) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject an expiration in the past"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, propose(Expiration::AtHeight(12_400))).unwrap();
+        let value = pending(&deps);
+        assert_eq!(Some(HumanAddr::from("cosmos1friend")), value.owner);
+        assert_eq!(Some(Expiration::AtHeight(12_400)), value.expires);
+        // nothing changes until the new owner accepts
+        assert_eq!(HumanAddr::from("creator"), owner(&deps));
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::AcceptOwnership {}) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only the proposed owner may accept"),
+        }
+        let mut env = mock_env(&deps.api, "cosmos1friend", &[], &[]);
+        env.block.height = 12_400;
+        let expected = ContractError::Expired {
+            what: "Ownership transfer".to_string(),
+            at: Expiration::AtHeight(12_400),
+        };
+        assert_contract_error(
+            handle(&mut deps, env, HandleMsg::AcceptOwnership {}),
+            expected,
+        );
+
+        let env = mock_env(&deps.api, "cosmos1friend", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::AcceptOwnership {}).unwrap();
+        assert_eq!(log("owner", "cosmos1friend"), res.log[1]);
+        assert_eq!(HumanAddr::from("cosmos1friend"), owner(&deps));
+        assert_eq!(None, pending(&deps).owner);
+
+        let env = mock_env(&deps.api, "cosmos1friend", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::AcceptOwnership {}) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not accept twice"),
+        }
+    }
+
+    #[test]
+    fn add_and_remove_admins() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let admins = |deps: &Extern<_, _>, start_after: Option<String>| -> AdminsResponse {
+            let msg = QueryMsg::ListAdmins {
+                start_after,
+                limit: Some(1),
+            };
+            from_slice(&query(deps, msg).unwrap()).unwrap()
+        };
+        let add = |addr: &str| HandleMsg::AddAdmin {
+            addr: HumanAddr::from(addr),
+        };
+        let remove = |addr: &str| HandleMsg::RemoveAdmin {
+            addr: HumanAddr::from(addr),
+        };
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, remove("creator")) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not remove the last admin"),
+        }
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        match handle(&mut deps, env, add("anyone")) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, add("creator")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject an existing admin"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, add("cosmos1partner")).unwrap();
+        assert_eq!(log("admin", "cosmos1partner"), res.log[1]);
+
+        // both admins may reflect now
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let first = admins(&deps, None);
+        assert_eq!(vec![HumanAddr::from("creator")], first.admins);
+        assert!(first.is_truncated);
+        let second = admins(&deps, first.next);
+        assert_eq!(vec![HumanAddr::from("cosmos1partner")], second.admins);
+        assert!(!second.is_truncated);
+
+        // the account is joint now, so admins change through proposals
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, remove("creator")) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        let msg = HandleMsg::ProposeAdmin {
+            msg: Box::new(remove("creator")),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ApproveAdmin { id: 1 }).unwrap();
+        assert!(res.log.contains(&log("action", "remove_admin")));
+
+        // the co-owner took over as the only admin
+        let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_slice(&res).unwrap();
+        assert_eq!("cosmos1partner", value.owner.as_str());
+        let value = admins(&deps, None);
+        assert_eq!(vec![HumanAddr::from("cosmos1partner")], value.admins);
+        assert!(!value.is_truncated);
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        match handle(&mut deps, env, remove("cosmos1partner")) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not remove the last admin"),
+        }
+    }
+
+    #[test]
+    fn allowed_targets() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let contract_call = |contract: &str| CosmosMsg::Contract {
+            contract_addr: HumanAddr::from(contract),
+            msg: Binary(b"{}".to_vec()),
+            send: None,
+        };
+        let call = |contract: &str| HandleMsg::ReflectMsg {
+            msgs: vec![contract_call(contract)],
+            tags: vec![],
+            memo: None,
+        };
+        let targets = |deps: &Extern<_, _>| -> Option<Vec<HumanAddr>> {
+            let res = query(deps, QueryMsg::AllowedTargets {}).unwrap();
+            let value: AllowedTargetsResponse = from_slice(&res).unwrap();
+            value.targets
+        };
+        let allow = |contract: &str| HandleMsg::AddAllowedTarget {
+            contract: HumanAddr::from(contract),
+        };
+        let disallow = |contract: &str| HandleMsg::RemoveAllowedTarget {
+            contract: HumanAddr::from(contract),
+        };
+
+        // without an allowlist, any contract can be called
+        assert_eq!(None, targets(&deps));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, call("cosmos1other")).unwrap();
+
+        let env = mock_env(&deps.api, "anyone", &[], &[]);
+        match handle(&mut deps, env, allow("cosmos1pool")) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, allow("cosmos1pool")).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, allow("cosmos1pool")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject a target allowed twice"),
+        }
+        assert_eq!(Some(vec![HumanAddr::from("cosmos1pool")]), targets(&deps));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, call("cosmos1pool")).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let expected = ContractError::TargetNotAllowed {
+            contract: HumanAddr::from("cosmos1other"),
+        };
+        assert_contract_error(
+            handle(&mut deps, env, call("cosmos1other")),
+            expected.clone(),
+        );
+        // sends are not restricted
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1other"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        // nor can the allowlist be bypassed by simulating or other handles
+        let msg = QueryMsg::SimulateWithConfig {
+            config_override: ConfigOverride {
+                tags: None,
+                budgets: None,
+                cooldowns: None,
+            },
+            sender: HumanAddr::from("creator"),
+            msgs: vec![contract_call("cosmos1other")],
+            tags: vec![],
+        };
+        let res = query(&deps, msg).unwrap();
+        let value: SimulationResponse = from_slice(&res).unwrap();
+        assert!(value.error.unwrap().contains(&expected.payload()));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ExecuteMany {
+            targets: vec![
+                HumanAddr::from("cosmos1pool"),
+                HumanAddr::from("cosmos1other"),
+            ],
+            msg: Binary(b"{}".to_vec()),
+            send: None,
+            tags: vec![],
+        };
+        assert_contract_error(handle(&mut deps, env, msg), expected);
+
+        // an empty allowlist blocks all contract calls
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, disallow("cosmos1other")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject removing a target which is not allowed"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, disallow("cosmos1pool")).unwrap();
+        assert_eq!(Some(vec![]), targets(&deps));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, call("cosmos1pool")).is_err());
+    }
+
+    #[test]
+    fn otc_swap() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let mut want = coin("300", "uatom");
+        want.extend(coin("5", "ujuno"));
+        let create = |expires| HandleMsg::CreateSwap {
+            counterparty: HumanAddr::from("cosmos1desk"),
+            give: coin("1000", "token"),
+            want: want.clone(),
+            expires,
+        };
+        let env = mock_env(&deps.api, "cosmos1desk", &[], &[]);
+        match handle(&mut deps, env, create(Expiration::AtHeight(12_400))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, create(Expiration::AtHeight(12_000))) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject an expiration in the past"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, create(Expiration::AtHeight(12_400))).unwrap();
+        assert_eq!(log("swap_id", "1"), res.log[1]);
+        assert!(res.messages.is_empty());
+
+        let fill = HandleMsg::FillSwap { id: 1 };
+        let env = mock_env(&deps.api, "cosmos1stranger", &want, &[]);
+        match handle(&mut deps, env, fill.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only the counterparty may fill"),
+        }
+        let env = mock_env(&deps.api, "cosmos1desk", &coin("300", "uatom"), &[]);
+        match handle(&mut deps, env, fill.clone()) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject paying less than wanted"),
+        }
+        let mut env = mock_env(&deps.api, "cosmos1desk", &want, &[]);
+        env.block.height = 12_400;
+        let expected = ContractError::Expired {
+            what: "Swap".to_string(),
+            at: Expiration::AtHeight(12_400),
+        };
+        assert_contract_error(handle(&mut deps, env, fill.clone()), expected);
+
+        // order of the sent coins does not matter
+        let mut sent = coin("5", "ujuno");
+        sent.extend(coin("300", "uatom"));
+        let env = mock_env(&deps.api, "cosmos1desk", &sent, &[]);
+        let res = handle(&mut deps, env, fill.clone()).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1desk"),
+                amount: coin("1000", "token"),
+            }],
+            res.messages
+        );
+        let res = query(&deps, QueryMsg::Swap { id: 1 }).unwrap();
+        let value: SwapResponse = from_slice(&res).unwrap();
+        assert!(value.filled);
+        assert_eq!(HumanAddr::from("cosmos1desk"), value.counterparty);
+
+        let env = mock_env(&deps.api, "cosmos1desk", &want, &[]);
+        match handle(&mut deps, env, fill) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not fill twice"),
+        }
+    }
+
+    #[test]
+    fn delegated_permissions() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let call = CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1other"),
+            msg: Binary(b"{}".to_vec()),
+            send: None,
+        };
+        let reflect = |msgs: Vec<CosmosMsg>| HandleMsg::ReflectMsg {
+            msgs,
+            tags: vec![],
+            memo: None,
+        };
+        let grant = HandleMsg::GrantPermission {
+            grantee: HumanAddr::from("cosmos1hotkey"),
+            msg_types: vec![MsgKind::Send, MsgKind::Send],
+            expires_at_height: 12_400,
+        };
+        let permissions = |deps: &Extern<_, _>| -> PermissionsResponse {
+            let msg = QueryMsg::Permissions {
+                grantee: HumanAddr::from("cosmos1hotkey"),
+            };
+            from_slice(&query(deps, msg).unwrap()).unwrap()
+        };
+
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        match handle(&mut deps, env, reflect(vec![send.clone()])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        match handle(&mut deps, env, grant.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must not grant itself permissions"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, grant).unwrap();
+        let value = permissions(&deps);
+        assert_eq!(vec![MsgKind::Send], value.msg_types);
+        assert_eq!(Some(12_400), value.expires_at_height);
+
+        // only the granted kinds, and only until it expires
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        let res = handle(&mut deps, env, reflect(vec![send.clone()])).unwrap();
+        assert_eq!(vec![send.clone()], res.messages);
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        match handle(&mut deps, env, reflect(vec![send.clone(), call])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must reject kinds which were not granted"),
+        }
+        let mut env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        env.block.height = 12_400;
+        match handle(&mut deps, env, reflect(vec![send.clone()])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must reject an expired permission"),
+        }
+
+        let revoke = HandleMsg::RevokePermission {
+            grantee: HumanAddr::from("cosmos1hotkey"),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, revoke.clone()).unwrap();
+        assert_eq!(Vec::<MsgKind>::new(), permissions(&deps).msg_types);
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        match handle(&mut deps, env, reflect(vec![send])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must reject a revoked permission"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, revoke) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject revoking twice"),
+        }
+    }
+
+    #[test]
+    fn dutch_auction() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let create = |start: &str| HandleMsg::CreateAuction {
+            sell: coin("1000", "token"),
+            start_price: coin(start, "uatom").remove(0),
+            end_price: coin("400", "uatom").remove(0),
+            duration: Duration::Height(100),
+        };
+        let env = mock_env(&deps.api, "cosmos1buyer", &[], &[]);
+        match handle(&mut deps, env, create("1000")) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, create("300")) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject a rising price"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, create("1000")).unwrap();
+        assert_eq!(log("auction_id", "1"), res.log[1]);
+
+        // a quarter of the way the price has fallen by a quarter
+        let buy = HandleMsg::BuyAuction { id: 1 };
+        let api = deps.api;
+        let at = |height: i64, sent: &[Coin]| {
+            let mut env = mock_env(&api, "cosmos1buyer", sent, &[]);
+            env.block.height += height;
+            env
+        };
+        match handle(&mut deps, at(25, &coin("849", "uatom")), buy.clone()) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject paying less than the price"),
+        }
+        match handle(&mut deps, at(25, &coin("900", "ujuno")), buy.clone()) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject paying in another denom"),
+        }
+        let res = handle(&mut deps, at(25, &coin("900", "uatom")), buy.clone()).unwrap();
+        assert_eq!(log("price", "850uatom"), res.log[3]);
+        let mut amount = coin("1000", "token");
+        amount.extend(coin("50", "uatom"));
+        assert_eq!(
+            vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1buyer"),
+                amount,
+            }],
+            res.messages
+        );
+        match handle(&mut deps, at(30, &coin("900", "uatom")), buy) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not sell twice"),
+        }
+        let res = query(&deps, QueryMsg::Auction { id: 1 }).unwrap();
+        let value: AuctionResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(HumanAddr::from("cosmos1buyer")), value.buyer);
+        assert_eq!(coin("850", "uatom").pop(), value.paid);
+        assert_eq!((Status::Sold, 10), (value.status, value.status_code));
+
+        // after the duration it stays at the end price, until cancelled
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, create("1000")).unwrap();
+        let buy = HandleMsg::BuyAuction { id: 2 };
+        match handle(&mut deps, at(500, &coin("399", "uatom")), buy.clone()) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must not fall below the end price"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::CancelAuction { id: 2 }).unwrap();
+        match handle(&mut deps, at(500, &coin("400", "uatom")), buy) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not sell a cancelled auction"),
+        }
+    }
+
+    #[test]
+    fn scheduled_execution() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let schedule = |height| HandleMsg::Schedule {
+            msgs: vec![send.clone()],
+            execute_after: Expiration::AtHeight(height),
+            calendar: None,
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1stranger"),
+        };
+        assert_contract_error(handle(&mut deps, env, schedule(12_400)), expected);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, schedule(12_345)) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject a batch which is already due"),
+        }
+        for _ in 0..2 {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            let _res = handle(&mut deps, env, schedule(12_400)).unwrap();
+        }
+        let list = |deps: &Extern<_, _>| -> Vec<u64> {
+            let msg = QueryMsg::ListScheduled {
+                start_after: None,
+                limit: None,
+            };
+            let value: ScheduledBatchesResponse = from_slice(&query(deps, msg).unwrap()).unwrap();
+            value.batches.iter().map(|b| b.id).collect()
+        };
+        assert_eq!(vec![1, 2], list(&deps));
+
+        // anyone can execute it, but only once it is due
+        let execute = HandleMsg::ExecuteScheduled { id: 1 };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, execute.clone()) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must not execute before it is due"),
+        }
+        let mut env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        env.block.height = 12_400;
+        let res = handle(&mut deps, env.clone(), execute.clone()).unwrap();
+        assert_eq!(vec![send.clone()], res.messages);
+        assert_eq!(log("batch_id", "1"), res.log[1]);
+        assert!(res.log.contains(&log("sender", "creator")));
+        assert!(handle(&mut deps, env.clone(), execute).is_err());
+
+        let cancel = HandleMsg::CancelScheduled { id: 2 };
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1stranger"),
+        };
+        assert_contract_error(handle(&mut deps, env.clone(), cancel.clone()), expected);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, cancel).unwrap();
+        assert_eq!(Vec::<u64>::new(), list(&deps));
+        let mut env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        env.block.height = 12_400;
+        assert!(handle(&mut deps, env, HandleMsg::ExecuteScheduled { id: 2 }).is_err());
+    }
+
+    #[test]
+    fn revenue_by_source() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let deposit = |tag: &str| HandleMsg::Deposit {
+            tag: tag.to_string(),
+        };
+        let env = mock_env(&deps.api, "cosmos1payer", &[], &[]);
+        match handle(&mut deps, env, deposit("fees")) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject a deposit without funds"),
+        }
+        let env = mock_env(&deps.api, "cosmos1payer", &coin("5", "uatom"), &[]);
+        match handle(&mut deps, env, deposit("Fees")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject an invalid tag"),
+        }
+
+        let env = mock_env(&deps.api, "cosmos1payer", &coin("5", "uatom"), &[]);
+        let res = handle(&mut deps, env, deposit("fees")).unwrap();
+        assert_eq!(log("period", "18192"), res.log[2]);
+        assert_eq!(log("amount", "5uatom"), res.log[3]);
+        let mut sent = coin("7", "uatom");
+        sent.extend(coin("1", "ujuno"));
+        let env = mock_env(&deps.api, "cosmos1other", &sent, &[]);
+        let _res = handle(&mut deps, env, deposit("fees")).unwrap();
+        let env = mock_env(&deps.api, "cosmos1payer", &coin("100", "uatom"), &[]);
+        let _res = handle(&mut deps, env, deposit("royalties")).unwrap();
+        // the next day counts separately
+        let mut env = mock_env(&deps.api, "cosmos1payer", &coin("9", "uatom"), &[]);
+        env.block.time += REVENUE_PERIOD_SECS as i64;
+        let _res = handle(&mut deps, env, deposit("fees")).unwrap();
+
+        let revenue = |period| -> RevenueResponse {
+            let msg = QueryMsg::Revenue {
+                tag: "fees".to_string(),
+                period,
+            };
+            from_slice(&query(&deps, msg).unwrap()).unwrap()
+        };
+        let mut totals = coin("12", "uatom");
+        totals.extend(coin("1", "ujuno"));
+        let value = revenue(18192);
+        assert_eq!(totals, value.totals);
+        assert_eq!(1_571_788_800, value.starts_at);
+        assert_eq!(coin("9", "uatom"), revenue(18193).totals);
+        assert_eq!(Vec::<Coin>::new(), revenue(18191).totals);
+    }
+
+    #[test]
+    fn pause_and_unpause() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let paused = |deps: &Extern<_, _>| -> bool {
+            let res = query(deps, QueryMsg::GetConfig {}).unwrap();
+            let value: ConfigResponse = from_slice(&res).unwrap();
+            value.paused
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::Pause {}) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Pause {}).unwrap();
+        assert!(paused(&deps));
+
+        // pausing keeps the funds where they are
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect.clone());
+        assert_contract_error(
+            res,
+            ContractError::ContractPaused {
+                action: "reflect".to_string(),
+            },
+        );
+        // nor does the configuration change, only the owner key
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string()],
+        };
+        assert_contract_error(
+            handle(&mut deps, env, msg),
+            ContractError::ContractPaused {
+                action: "set_tags".to_string(),
+            },
+        );
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1newkey"),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::Unpause {}) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only the admins may unpause"),
+        }
+        let env = mock_env(&deps.api, "cosmos1newkey", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::Unpause {}).unwrap();
+        assert_eq!(log("action", "unpause"), res.log[0]);
+        assert!(!paused(&deps));
+        let env = mock_env(&deps.api, "cosmos1newkey", &[], &[]);
+        let _res = handle(&mut deps, env, reflect).unwrap();
+
+        // the old name still works, with a warning
+        let env = mock_env(&deps.api, "cosmos1newkey", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::Resume {}).unwrap();
+        assert_eq!(log("deprecated", "resume"), res.log[1]);
+
+        // a paused contract may still be evacuated
+        let msg = HandleMsg::SetRecovery {
+            recovery: Some(HumanAddr::from("cosmos1vault")),
+        };
+        let env = mock_env(&deps.api, "cosmos1newkey", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos1newkey", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Pause {}).unwrap();
+        let env = mock_env(&deps.api, "cosmos1newkey", &[], &coin("7", "token"));
+        let res = handle(&mut deps, env, HandleMsg::Evacuate { to: None }).unwrap();
+        let evacuated = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1vault"),
+            amount: coin("7", "token"),
+        };
+        assert_eq!(vec![evacuated], res.messages);
+        assert!(paused(&deps));
+    }
+
+    #[test]
+    fn revenue_distribution() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let mut sent = coin("100", "uatom");
+        sent.extend(coin("3", "ujuno"));
+        let env = mock_env(&deps.api, "cosmos1payer", &sent, &[]);
+        let msg = HandleMsg::Deposit {
+            tag: "fees".to_string(),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let distribute = HandleMsg::DistributeRevenue {
+            tag: "fees".to_string(),
+            period: 18192,
+        };
+        let next_day = |deps: &Extern<_, _>| {
+            let mut env = mock_env(&deps.api, "cosmos1anyone", &[], &[]);
+            env.block.time += REVENUE_PERIOD_SECS as i64;
+            env
+        };
+        let env = next_day(&deps);
+        match handle(&mut deps, env, distribute.clone()) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must require stakeholders"),
+        }
+
+        let set = HandleMsg::SetStakeholders {
+            stakeholders: vec![
+                StakeholderMsg {
+                    addr: HumanAddr::from("cosmos1alice"),
+                    shares: 2,
+                },
+                StakeholderMsg {
+                    addr: HumanAddr::from("cosmos1bob"),
+                    shares: 1,
+                },
+            ],
+        };
+        let env = mock_env(&deps.api, "cosmos1alice", &[], &[]);
+        match handle(&mut deps, env, set.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+        let res = query(&deps, QueryMsg::Stakeholders {}).unwrap();
+        let value: StakeholdersResponse = from_slice(&res).unwrap();
+        assert_eq!(2, value.stakeholders[0].shares);
+
+        let env = mock_env(&deps.api, "cosmos1anyone", &[], &[]);
+        match handle(&mut deps, env, distribute.clone()) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must wait for the period to end"),
+        }
+        // shares round down, the dust stays in the contract
+        let env = next_day(&deps);
+        let res = handle(&mut deps, env, distribute.clone()).unwrap();
+        assert_eq!(
+            vec![
+                CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1alice"),
+                    amount: [coin("66", "uatom"), coin("2", "ujuno")].concat(),
+                },
+                CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1bob"),
+                    amount: [coin("33", "uatom"), coin("1", "ujuno")].concat(),
+                },
+            ],
+            res.messages
+        );
+
+        let env = next_day(&deps);
+        match handle(&mut deps, env, distribute) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must not distribute twice"),
+        }
+        let msg = QueryMsg::Revenue {
+            tag: "fees".to_string(),
+            period: 18192,
+        };
+        let value: RevenueResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert!(value.settled);
+    }
+
+    #[test]
+    fn deposit_hook() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let set = |contract: Option<&str>| HandleMsg::SetDepositHook {
+            contract: contract.map(HumanAddr::from),
+        };
+        let env = mock_env(&deps.api, "cosmos1payer", &[], &[]);
+        match handle(&mut deps, env, set(Some("cosmos1ledger"))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, set(Some("cosmos2contract"))) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not hook the contract itself"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(Some("cosmos1ledger"))).unwrap();
+        let res = query(&deps, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(HumanAddr::from("cosmos1ledger")), value.deposit_hook);
+
+        let deposit = HandleMsg::Deposit {
+            tag: "fees".to_string(),
+        };
+        let env = mock_env(&deps.api, "cosmos1payer", &coin("5", "uatom"), &[]);
+        let res = handle(&mut deps, env, deposit.clone()).unwrap();
+        let receipt = ReceiptMsg::DepositReceipt {
+            sender: HumanAddr::from("cosmos1payer"),
+            amount: coin("5", "uatom"),
+            tag: "fees".to_string(),
+        };
+        assert_eq!(
+            vec![CosmosMsg::Contract {
+                contract_addr: HumanAddr::from("cosmos1ledger"),
+                msg: Binary(to_vec(&receipt).unwrap()),
+                send: None,
+            }],
+            res.messages
+        );
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(None)).unwrap();
+        let env = mock_env(&deps.api, "cosmos1payer", &coin("5", "uatom"), &[]);
+        let res = handle(&mut deps, env, deposit).unwrap();
+        assert!(res.messages.is_empty());
+    }
+
+    #[test]
+    fn permissioned_deposits() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let deposit = HandleMsg::Deposit {
+            tag: "fees".to_string(),
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &coin("5", "uatom"), &[]);
+        let _res = handle(&mut deps, env, deposit.clone()).unwrap();
+
+        let msg = HandleMsg::SetDepositors {
+            depositors: Some(vec![HumanAddr::from("cosmos1client")]),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let res = query(&deps, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(
+            Some(vec![HumanAddr::from("cosmos1client")]),
+            value.depositors
+        );
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &coin("5", "uatom"), &[]);
+        let res = handle(&mut deps, env, deposit.clone());
+        assert_contract_error(
+            res,
+            ContractError::DepositorNotAllowed {
+                sender: HumanAddr::from("cosmos1stranger"),
+            },
+        );
+        // calls without funds are not affected
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, deposit.clone()) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must get to the handler"),
+        }
+        for sender in &["cosmos1client", "creator"] {
+            let env = mock_env(&deps.api, *sender, &coin("5", "uatom"), &[]);
+            let _res = handle(&mut deps, env, deposit.clone()).unwrap();
+        }
+
+        let msg = HandleMsg::SetDepositors { depositors: None };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos1stranger", &coin("5", "uatom"), &[]);
+        let _res = handle(&mut deps, env, deposit).unwrap();
+    }
+
+    #[test]
+    fn travel_rule_memo() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let set = HandleMsg::SetTravelRule {
+            thresholds: Some(vec![Amount::from(coin("1000", "uatom").remove(0))]),
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, set.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+
+        let send = |amount: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin(amount, "uatom"),
+        };
+        let reflect = |msgs: Vec<CosmosMsg>, memo: Option<TravelMemo>| HandleMsg::ReflectMsg {
+            msgs,
+            tags: vec![],
+            memo,
+        };
+        // the threshold applies to each message, not their sum
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(
+            &mut deps,
+            env,
+            reflect(vec![send("1000"), send("1000")], None),
+        )
+        .unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect(vec![send("1001")], None));
+        assert_contract_error(
+            res,
+            ContractError::MemoRequired {
+                threshold: coin("1000", "uatom").remove(0),
+            },
+        );
+
+        let memo = |originator: &str| TravelMemo {
+            originator: originator.to_string(),
+            beneficiary: "acct-7".to_string(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, reflect(vec![send("1001")], Some(memo(" ")))) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject an empty memo field"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = reflect(vec![send("1001")], Some(memo("client \"42\"")));
+        match handle(&mut deps, env, msg) {
+            Err(Error::DynContractErr { msg, .. }) => assert_eq!(
+                "Memo originator cannot contain quotes, backslashes or control characters",
+                msg
+            ),
+            _ => panic!("Must reject a quote in a memo field"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = reflect(vec![send("1001")], Some(memo("client-42")));
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let res = query(&deps, QueryMsg::Execution { id: 2 }).unwrap();
+        let value: ExecutionResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(memo("client-42")), value.memo);
+    }
+
+    #[test]
+    fn proposals_need_an_owner() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1vendor"),
+            amount: coin("5", "token"),
+        };
+        let propose = |description: &str| HandleMsg::Propose {
+            msgs: vec![send.clone()],
+            description: description.to_string(),
+            intents: None,
+        };
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        match handle(&mut deps, env, propose(" ")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must require a description"),
+        }
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        match handle(&mut deps, env, propose("invoice \"1\"")) {
+            Err(Error::DynContractErr { msg, .. }) => assert_eq!(
+                "Description cannot contain quotes, backslashes or control characters",
+                msg
+            ),
+            _ => panic!("Must reject a quote in the description"),
+        }
+        for description in &["invoice 1", "invoice 2"] {
+            let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+            let res = handle(&mut deps, env, propose(description)).unwrap();
+            assert_eq!(0, res.messages.len());
+        }
+        let list = |deps: &Extern<_, _>| -> Vec<u64> {
+            let msg = QueryMsg::ListProposals {
+                start_after: None,
+                limit: None,
+            };
+            let value: ProposalsResponse = from_slice(&query(deps, msg).unwrap()).unwrap();
+            value.proposals.iter().map(|p| p.id).collect()
+        };
+        assert_eq!(vec![1, 2], list(&deps));
+
+        // the proposer cannot approve its own proposal
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1vendor"),
+        };
+        assert_contract_error(
+            handle(&mut deps, env, HandleMsg::Approve { id: 1 }),
+            expected,
+        );
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env.clone(), HandleMsg::Approve { id: 1 }).unwrap();
+        assert_eq!(vec![send.clone()], res.messages);
+        assert_eq!(log("proposal_id", "1"), res.log[1]);
+        match handle(&mut deps, env.clone(), HandleMsg::Reject { id: 1 }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not decide twice"),
+        }
+
+        let res = handle(&mut deps, env.clone(), HandleMsg::Reject { id: 2 }).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert!(handle(&mut deps, env, HandleMsg::Approve { id: 2 }).is_err());
+        assert_eq!(Vec::<u64>::new(), list(&deps));
+
+        let value: ProposalResponse =
+            from_slice(&query(&deps, QueryMsg::Proposal { id: 2 }).unwrap()).unwrap();
+        assert_eq!(HumanAddr::from("cosmos1vendor"), value.proposer);
+        assert_eq!("invoice 2", value.description);
+        assert_eq!(ProposalStatus::Rejected, value.status);
+    }
+
+    #[test]
+    fn spend_limits_reset_each_period() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let set = HandleMsg::SetSpendLimit {
+            denom: "uatom".to_string(),
+            amount: "100".to_string(),
+            period_secs: 3600,
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, set.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+
+        let reflect = |amount: &str| HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: vec![
+                    coin("7", "ujuno")[0].clone(),
+                    coin(amount, "uatom")[0].clone(),
+                ],
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect("60")).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect("50"));
+        assert_contract_error(
+            res,
+            ContractError::SpendLimitExceeded {
+                limit: coin("100", "uatom").remove(0),
+                period_secs: 3600,
+                spent: "60".to_string(),
+            },
+        );
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect("40")).unwrap();
+
+        let msg = QueryMsg::SpendLimit {
+            denom: "uatom".to_string(),
+        };
+        let value: SpendLimitResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!("100", value.spent);
+        // periods are whole hours of block time
+        assert_eq!(Some(Expiration::AtTime(1_571_799_600)), value.resets_at);
+
+        let mut env = mock_env(&deps.api, "creator", &[], &[]);
+        env.block.time = 1_571_799_600;
+        let _res = handle(&mut deps, env.clone(), reflect("100")).unwrap();
+        assert!(handle(&mut deps, env.clone(), reflect("1")).is_err());
+
+        let remove = HandleMsg::RemoveSpendLimit {
+            denom: "uatom".to_string(),
+        };
+        let _res = handle(&mut deps, env.clone(), remove).unwrap();
+        let _res = handle(&mut deps, env, reflect("1000")).unwrap();
+    }
+
+    #[test]
+    fn history_lists_newest_first() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect.clone()).unwrap();
+        // failed calls leave no entry
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, reflect).is_err());
+        let change = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1successor"),
+        };
+        let mut env = mock_env(&deps.api, "creator", &[], &[]);
+        env.block.height = 12_400;
+        let _res = handle(&mut deps, env, change).unwrap();
+
+        let history = |deps: &Extern<_, _>, start_after| {
+            let msg = QueryMsg::History {
+                start_after,
+                limit: Some(1),
+            };
+            let value: HistoryResponse = from_slice(&query(deps, msg).unwrap()).unwrap();
+            value
+        };
+        let page = history(&deps, None);
+        assert!(page.is_truncated);
+        let entry = &page.entries[0];
+        assert_eq!(2, entry.seq);
+        assert_eq!("change_owner", entry.action);
+        assert_eq!(12_400, entry.height);
+        assert_eq!(Some(HumanAddr::from("cosmos1successor")), entry.owner);
+
+        let page = history(&deps, page.next);
+        assert!(!page.is_truncated);
+        let entry = &page.entries[0];
+        assert_eq!(1, entry.seq);
+        assert_eq!("reflect", entry.action);
+        assert_eq!(HumanAddr::from("creator"), entry.sender);
+        assert_eq!(
+            vec![KindCount {
+                kind: MsgKind::Send,
+                count: 1
+            }],
+            entry.msgs
+        );
+    }
+
+    #[test]
+    fn suspended_operators_cannot_stage() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetOperators {
+            operators: vec![
+                HumanAddr::from("cosmos1ops"),
+                HumanAddr::from("cosmos1intern"),
+            ],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let stage = HandleMsg::StageBatch {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            memo: "payout".to_string(),
+            receipt_contract: None,
+            tags: vec![],
+        };
+        let suspend = HandleMsg::SuspendOperator {
+            operator: HumanAddr::from("cosmos1intern"),
+        };
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        match handle(&mut deps, env, suspend.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only admins may suspend operators"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env.clone(), suspend.clone()).unwrap();
+        assert!(handle(&mut deps, env, suspend).is_err());
+
+        let env = mock_env(&deps.api, "cosmos1intern", &[], &[]);
+        match handle(&mut deps, env, stage.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Suspended operators must not stage batches"),
+        }
+        let env = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let _res = handle(&mut deps, env, stage.clone()).unwrap();
+
+        // it stays configured while suspended
+        let operators = |deps: &Extern<_, _>| -> OperatorsResponse {
+            from_slice(&query(deps, QueryMsg::Operators {}).unwrap()).unwrap()
+        };
+        let value = operators(&deps);
+        assert_eq!(2, value.operators.len());
+        assert_eq!(vec![HumanAddr::from("cosmos1intern")], value.suspended);
+
+        let reinstate = HandleMsg::ReinstateOperator {
+            operator: HumanAddr::from("cosmos1intern"),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env.clone(), reinstate.clone()).unwrap();
+        assert!(handle(&mut deps, env, reinstate).is_err());
+        let env = mock_env(&deps.api, "cosmos1intern", &[], &[]);
+        let _res = handle(&mut deps, env, stage).unwrap();
+        assert_eq!(Vec::<HumanAddr>::new(), operators(&deps).suspended);
+    }
+
+    #[test]
+    fn withdraw_and_lifetime_deposits() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let deposit = HandleMsg::Deposit {
+            tag: "fees".to_string(),
+        };
+        let env = mock_env(&deps.api, "cosmos1client", &coin("30", "token"), &[]);
+        let _res = handle(&mut deps, env, deposit.clone()).unwrap();
+        let mut sent = coin("12", "token");
+        sent.extend(coin("5", "other"));
+        let env = mock_env(&deps.api, "cosmos1client", &sent, &[]);
+        let _res = handle(&mut deps, env, deposit).unwrap();
+        let res = query(&deps, QueryMsg::Deposits {}).unwrap();
+        let value: DepositsResponse = from_slice(&res).unwrap();
+        let mut totals = coin("42", "token");
+        totals.extend(coin("5", "other"));
+        assert_eq!(totals, value.totals);
+
+        let withdraw = |amount: Option<Vec<Coin>>| HandleMsg::Withdraw {
+            to: HumanAddr::from("cosmos1treasury"),
+            amount,
+        };
+        let env = mock_env(&deps.api, "cosmos1client", &[], &totals);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1client"),
+        };
+        assert_contract_error(handle(&mut deps, env, withdraw(None)), expected);
+        let env = mock_env(&deps.api, "creator", &[], &totals);
+        let res = handle(&mut deps, env.clone(), withdraw(None)).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1treasury"),
+                amount: totals.clone(),
+            }],
+            res.messages
+        );
+        assert_eq!(log("to", "cosmos1treasury"), res.log[1]);
+
+        let res = handle(&mut deps, env.clone(), withdraw(Some(coin("10", "token")))).unwrap();
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1treasury"),
+            amount: coin("10", "token"),
+        };
+        assert_eq!(vec![send], res.messages);
+        match handle(&mut deps, env, withdraw(Some(coin("0", "token")))) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not withdraw nothing"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, withdraw(None)).is_err());
+    }
+
+    #[test]
+    fn operator_windows_follow_block_time() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetOperators {
+            operators: vec![
+                HumanAddr::from("cosmos1junior"),
+                HumanAddr::from("cosmos1boss"),
+            ],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let office_hours = ExecutionWindow {
+            days: vec![
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+            ],
+            start_hour: 9,
+            end_hour: 17,
+        };
+        let set_window = |operator: &str, window: ExecutionWindow| HandleMsg::SetOperatorWindow {
+            operator: HumanAddr::from(operator),
+            window: Some(window),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let invalid = ExecutionWindow {
+            end_hour: 9,
+            ..office_hours.clone()
+        };
+        assert!(handle(&mut deps, env.clone(), set_window("cosmos1junior", invalid)).is_err());
+        for operator in &["cosmos1junior", "cosmos1boss"] {
+            let msg = set_window(operator, office_hours.clone());
+            let _res = handle(&mut deps, env.clone(), msg).unwrap();
+        }
+
+        let stage = HandleMsg::StageBatch {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            memo: "payout".to_string(),
+            receipt_contract: None,
+            tags: vec![],
+        };
+        // the mock block is on a Wednesday at 02:23 UTC
+        let env = mock_env(&deps.api, "cosmos1junior", &[], &[]);
+        let res = handle(&mut deps, env, stage.clone());
+        assert_contract_error(
+            res,
+            ContractError::OutsideWindow {
+                operator: HumanAddr::from("cosmos1junior"),
+            },
+        );
+        let mut env = mock_env(&deps.api, "cosmos1junior", &[], &[]);
+        env.block.time = 1_571_824_800;
+        let _res = handle(&mut deps, env.clone(), stage.clone()).unwrap();
+        // the following Saturday at the same time
+        env.block.time += 3 * 86_400;
+        assert!(handle(&mut deps, env.clone(), stage.clone()).is_err());
+
+        let msg = QueryMsg::OperatorWindow {
+            operator: HumanAddr::from("cosmos1junior"),
+        };
+        let res = query(&deps, msg.clone()).unwrap();
+        let value: OperatorWindowResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(office_hours), value.window);
+        let lift = HandleMsg::SetOperatorWindow {
+            operator: HumanAddr::from("cosmos1junior"),
+            window: None,
+        };
+        let admin = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, admin, lift).unwrap();
+        let _res = handle(&mut deps, env, stage.clone()).unwrap();
+        let value: OperatorWindowResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!(None, value.window);
+
+        // owners are never limited
+        let env = mock_env(&deps.api, "cosmos1boss", &[], &[]);
+        assert!(handle(&mut deps, env.clone(), stage.clone()).is_err());
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1boss"),
+        };
+        let admin = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, admin, msg).unwrap();
+        let _res = handle(&mut deps, env, stage).unwrap();
+    }
+
+    #[test]
+    fn calendars_block_operators_and_scheduled_batches() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetOperators {
+            operators: vec![HumanAddr::from("cosmos1ops")],
+        };
+        let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+        // the mock block time falls into the first freeze
+        let freeze = TimeRange {
+            start: 1_571_790_000,
+            end: 1_571_800_000,
+        };
+        let later = TimeRange {
+            start: 1_580_000_000,
+            end: 1_580_100_000,
+        };
+        let set_calendar = |blackouts: Vec<TimeRange>| HandleMsg::SetCalendar {
+            name: "quarter_end".to_string(),
+            allowed: vec![],
+            blackouts,
+        };
+        let inverted = TimeRange {
+            start: later.end,
+            end: later.start,
+        };
+        assert!(handle(&mut deps, env.clone(), set_calendar(vec![inverted])).is_err());
+        let _res = handle(&mut deps, env.clone(), set_calendar(vec![later, freeze])).unwrap();
+
+        let assign = |calendar: &str| HandleMsg::SetOperatorCalendar {
+            operator: HumanAddr::from("cosmos1ops"),
+            calendar: Some(calendar.to_string()),
+        };
+        match handle(&mut deps, env.clone(), assign("unknown")) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must reject an unknown calendar"),
+        }
+        let _res = handle(&mut deps, env.clone(), assign("quarter_end")).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let stage = HandleMsg::StageBatch {
+            msgs: vec![send.clone()],
+            memo: "payout".to_string(),
+            receipt_contract: None,
+            tags: vec![],
+        };
+        let ops = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        let closed = ContractError::CalendarClosed {
+            calendar: "quarter_end".to_string(),
+        };
+        assert_contract_error(handle(&mut deps, ops, stage.clone()), closed.clone());
+
+        let schedule = HandleMsg::Schedule {
+            msgs: vec![send],
+            execute_after: Expiration::AtHeight(12_400),
+            calendar: Some("quarter_end".to_string()),
+        };
+        let _res = handle(&mut deps, env, schedule).unwrap();
+        let mut anyone = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        anyone.block.height = 12_400;
+        let execute = HandleMsg::ExecuteScheduled { id: 1 };
+        assert_contract_error(handle(&mut deps, anyone.clone(), execute.clone()), closed);
+
+        // both go through once the freeze is over
+        anyone.block.time = freeze.end as i64;
+        let _res = handle(&mut deps, anyone, execute).unwrap();
+        let mut ops = mock_env(&deps.api, "cosmos1ops", &[], &[]);
+        ops.block.time = freeze.end as i64;
+        let _res = handle(&mut deps, ops, stage).unwrap();
+
+        let msg = QueryMsg::UpcomingBlackouts {
+            calendar: "quarter_end".to_string(),
+            after: freeze.end,
+            limit: None,
+        };
+        let value: BlackoutsResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!(vec![later], value.blackouts);
+        let msg = QueryMsg::UpcomingBlackouts {
+            calendar: "quarter_end".to_string(),
+            after: 0,
+            limit: None,
+        };
+        let value: BlackoutsResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!(vec![freeze, later], value.blackouts);
+    }
+
+    #[test]
+    fn guardians_recover_a_lost_owner_key() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let set = |guardians: &[&str], threshold| HandleMsg::SetGuardians {
+            guardians: guardians.iter().map(|g| HumanAddr::from(*g)).collect(),
+            threshold,
+            window: Duration::Height(100),
+        };
+        let session = |deps: &Extern<_, _>| -> RecoverySessionResponse {
+            from_slice(&query(deps, QueryMsg::RecoverySession {}).unwrap()).unwrap()
+        };
+        let guardians = ["cosmos1alice", "cosmos1bob", "cosmos1carol"];
+
+        let env = mock_env(&deps.api, "cosmos1alice", &[], &[]);
+        match handle(&mut deps, env, set(&guardians, 2)) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only admins may set guardians"),
+        }
+        for bad in &[
+            set(&guardians, 0),
+            set(&guardians, 4),
+            set(&["cosmos1alice"; 2], 1),
+        ] {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            match handle(&mut deps, env, bad.clone()) {
+                Err(Error::ContractErr { .. }) => {}
+                _ => panic!("Must reject {:?}", bad),
+            }
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(&guardians, 2)).unwrap();
+        let res = query(&deps, QueryMsg::Guardians {}).unwrap();
+        let value: GuardiansResponse = from_slice(&res).unwrap();
+        assert_eq!(3, value.guardians.len());
+        assert_eq!(Some(2), value.threshold);
+
+        let initiate = HandleMsg::InitiateRecovery {
+            new_owner: HumanAddr::from("cosmos1newkey"),
+        };
+        let env = mock_env(&deps.api, "cosmos1mallory", &[], &[]);
+        match handle(&mut deps, env, initiate.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only guardians may initiate a recovery"),
+        }
+        let env = mock_env(&deps.api, "cosmos1alice", &[], &[]);
+        let _res = handle(&mut deps, env, initiate.clone()).unwrap();
+        let value = session(&deps);
+        assert_eq!(Some(HumanAddr::from("cosmos1newkey")), value.new_owner);
+        assert_eq!(vec![HumanAddr::from("cosmos1alice")], value.approvals);
+        assert_eq!(Some(Expiration::AtHeight(12_445)), value.expires);
+
+        let env = mock_env(&deps.api, "cosmos1bob", &[], &[]);
+        match handle(&mut deps, env, initiate.clone()) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not start a second recovery"),
+        }
+        let env = mock_env(&deps.api, "cosmos1alice", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ApproveRecovery {}) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not count the same guardian twice"),
+        }
+        let mut env = mock_env(&deps.api, "cosmos1bob", &[], &[]);
+        env.block.height = 12_445;
+        let expected = ContractError::Expired {
+            what: "Recovery".to_string(),
+            at: Expiration::AtHeight(12_445),
+        };
+        assert_contract_error(
+            handle(&mut deps, env, HandleMsg::ApproveRecovery {}),
+            expected,
+        );
+
+        // the owner can still stop it while they hold their key
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::CancelRecovery {}).unwrap();
+        assert_eq!(None, session(&deps).new_owner);
+        let env = mock_env(&deps.api, "cosmos1bob", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ApproveRecovery {}) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not approve a cancelled recovery"),
+        }
+
+        let env = mock_env(&deps.api, "cosmos1alice", &[], &[]);
+        let _res = handle(&mut deps, env, initiate).unwrap();
+        let env = mock_env(&deps.api, "cosmos1carol", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ApproveRecovery {}).unwrap();
+        assert_eq!(log("recovered", "cosmos1newkey"), res.log[2]);
+        let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_slice(&res).unwrap();
+        assert_eq!(HumanAddr::from("cosmos1newkey"), value.owner);
+        assert_eq!(None, session(&deps).new_owner);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::Pause {}) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("The lost key must no longer be an owner"),
+        }
+    }
+
+    #[test]
+    fn denylisted_recipients_cannot_be_paid() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let pay = |to: &str| HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from(to),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let denylist = |add: &[&str], remove: &[&str]| HandleMsg::Denylist {
+            add: add.iter().map(|a| HumanAddr::from(*a)).collect(),
+            remove: remove.iter().map(|a| HumanAddr::from(*a)).collect(),
+        };
+        let is_denied = |deps: &Extern<_, _>, addr: &str| -> bool {
+            let msg = QueryMsg::IsDenied {
+                addr: HumanAddr::from(addr),
+            };
+            let value: IsDeniedResponse = from_slice(&query(deps, msg).unwrap()).unwrap();
+            value.denied
+        };
+
+        let env = mock_env(&deps.api, "cosmos1mallory", &[], &[]);
+        match handle(&mut deps, env, denylist(&["cosmos1mallory"], &[])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only admins may change the deny-list"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(
+            &mut deps,
+            env,
+            denylist(&["cosmos1mallory"], &["cosmos1mallory"]),
+        ) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject adding and removing the same address"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, denylist(&["cosmos1mallory"], &[])).unwrap();
+        assert!(is_denied(&deps, "cosmos1mallory"));
+        assert!(!is_denied(&deps, "cosmos1friend"));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let expected = ContractError::RecipientDenied {
+            recipient: HumanAddr::from("cosmos1mallory"),
+        };
+        assert_contract_error(handle(&mut deps, env, pay("cosmos1mallory")), expected);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, pay("cosmos1friend")).unwrap();
+        // withdrawals go through the same checks
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::Withdraw {
+            to: HumanAddr::from("cosmos1mallory"),
+            amount: Some(coin("1", "token")),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must not withdraw to a denied address"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, denylist(&[], &["cosmos1mallory"])).unwrap();
+        assert!(!is_denied(&deps, "cosmos1mallory"));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, pay("cosmos1mallory")).unwrap();
+    }
+
+    #[test]
+    fn four_eyes_kinds_need_a_second_approver() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetCoOwners {
+            co_owners: vec![HumanAddr::from("cosmos1partner")],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let call = CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1pool"),
+            msg: Binary(b"{}".to_vec()),
+            send: None,
+        };
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let reflect = |msgs: Vec<CosmosMsg>| HandleMsg::ReflectMsg {
+            msgs,
+            tags: vec![],
+            memo: None,
+        };
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetFourEyes {
+            kinds: Some(vec![MsgKind::Contract]),
+        };
+        match handle(&mut deps, env, msg.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("A joint account must propose the change"),
+        }
+        let env = mock_env(&deps.api, "cosmos2contract", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let res = query(&deps, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(vec![MsgKind::Contract]), value.four_eyes);
+
+        // not even an owner may reflect a listed kind directly
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let expected = ContractError::SecondApproverRequired {
+            kind: MsgKind::Contract,
+        };
+        let res = handle(&mut deps, env, reflect(vec![send.clone(), call.clone()]));
+        assert_contract_error(res, expected);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect(vec![send])).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::Propose {
+            msgs: vec![call],
+            description: "rebalance pool".to_string(),
+            intents: None,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::Approve { id: 1 }) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("The proposer must not approve their own proposal"),
+        }
+        let env = mock_env(&deps.api, "cosmos1partner", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::Approve { id: 1 }).unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn proposed_calls_must_match_their_intents() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let call = |msg: &str, send: Option<Vec<Coin>>| CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1pool"),
+            msg: Binary(msg.as_bytes().to_vec()),
+            send,
+        };
+        let intent = WasmIntent {
+            contract_addr: HumanAddr::from("cosmos1pool"),
+            method: "swap".to_string(),
+            max_funds: coin("100", "uatom"),
+        };
+        let propose = |msgs: Vec<CosmosMsg>| HandleMsg::Propose {
+            msgs,
+            description: "swap to juno".to_string(),
+            intents: Some(vec![intent.clone()]),
+        };
+        let mismatch = |detail: &str| ContractError::IntentMismatch {
+            contract: HumanAddr::from("cosmos1pool"),
+            detail: detail.to_string(),
+        };
+
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let res = handle(&mut deps, env, propose(vec![call(r#"{"burn":{}}"#, None)]));
+        assert_contract_error(res, mismatch("calls burn instead of swap"));
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let msgs = vec![call(r#"{"swap":{}}"#, Some(coin("101", "uatom")))];
+        let res = handle(&mut deps, env, propose(msgs));
+        assert_contract_error(res, mismatch("sends 101uatom, at most 100uatom declared"));
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let msgs = vec![call(r#"{"swap":{}}"#, Some(coin("1", "ujuno")))];
+        let res = handle(&mut deps, env, propose(msgs));
+        assert_contract_error(res, mismatch("sends 1ujuno, at most 0ujuno declared"));
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let msgs = vec![call(r#"{"swap":{}}"#, None), call(r#"{"swap":{}}"#, None)];
+        match handle(&mut deps, env, propose(msgs)) {
+            Err(Error::DynContractErr { .. }) => {}
+            _ => panic!("Must declare an intent for every call"),
+        }
+
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let msgs = vec![call(
+            r#" { "swap" : {"min":"5"}}"#,
+            Some(coin("100", "uatom")),
+        )];
+        let _res = handle(&mut deps, env, propose(msgs)).unwrap();
+        let res = query(&deps, QueryMsg::Proposal { id: 1 }).unwrap();
+        let value: ProposalResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(vec![intent.clone()]), value.intents);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Approve { id: 1 }).unwrap();
+        let msg = QueryMsg::History {
+            start_after: None,
+            limit: Some(1),
+        };
+        let value: HistoryResponse = from_slice(&query(&deps, msg).unwrap()).unwrap();
+        assert_eq!(Some(vec![intent]), value.entries[0].intents);
+    }
+
+    #[test]
+    fn delegated_callers_pay_the_reflect_fee() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::GrantPermission {
+            grantee: HumanAddr::from("cosmos1hotkey"),
+            msg_types: vec![MsgKind::Send],
+            expires_at_height: 12_400,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let collected = |deps: &Extern<_, _>| -> Vec<Coin> {
+            let res = query(deps, QueryMsg::CollectedFees {}).unwrap();
+            let value: CollectedFeesResponse = from_slice(&res).unwrap();
+            value.collected
+        };
+
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        // in the native denom, as the chain has no default denom
+        let msg = HandleMsg::SetFee {
+            fee: vec![Amount {
+                denom: None,
+                amount: "5".to_string(),
+            }],
+        };
+        match handle(&mut deps, env, msg.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Only admins may set the fee"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&deps.api, "cosmos1hotkey", &coin("4", "uatom"), &[]);
+        let expected = ContractError::FeeRequired {
+            fee: coin("5", "uatom").remove(0),
+        };
+        assert_contract_error(handle(&mut deps, env, reflect.clone()), expected);
+        let env = mock_env(&deps.api, "cosmos1hotkey", &coin("5", "uatom"), &[]);
+        let res = handle(&mut deps, env, reflect.clone()).unwrap();
+        assert!(res.log.contains(&log("fee", "5uatom")));
+        // owners reflect for free
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect.clone()).unwrap();
+        let env = mock_env(&deps.api, "cosmos1hotkey", &coin("7", "uatom"), &[]);
+        let _res = handle(&mut deps, env, reflect).unwrap();
+        assert_eq!(coin("10", "uatom"), collected(&deps));
+
+        let withdraw = HandleMsg::WithdrawFees {
+            to: HumanAddr::from("cosmos1treasury"),
+        };
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1hotkey"),
+        };
+        assert_contract_error(handle(&mut deps, env, withdraw.clone()), expected);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, withdraw.clone()).unwrap();
+        match &res.messages[0] {
+            CosmosMsg::Send { amount, .. } => assert_eq!(coin("10", "uatom"), *amount),
+            other => panic!("Unexpected message {:?}", other),
+        }
+        assert_eq!(Vec::<Coin>::new(), collected(&deps));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, withdraw) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not withdraw the fees twice"),
+        }
+    }
+
+    #[test]
+    fn validate_msg_explains_rejections() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::GrantPermission {
+            grantee: HumanAddr::from("cosmos1hotkey"),
+            msg_types: vec![MsgKind::Send],
+            expires_at_height: 12_400,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::Denylist {
+            add: vec![HumanAddr::from("cosmos1mallory")],
+            remove: vec![],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let send = |to: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from(to),
+            amount: coin("1", "token"),
+        };
+        let call = CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1pool"),
+            msg: Binary(b"{}".to_vec()),
+            send: None,
+        };
+        let validate = |sender: &str, msg: CosmosMsg| -> ValidationResponse {
+            let msg = QueryMsg::ValidateMsg {
+                sender: HumanAddr::from(sender),
+                msg,
+            };
+            from_slice(&query(&deps, msg).unwrap()).unwrap()
+        };
+
+        let value = validate("creator", send("cosmos1friend"));
+        assert!(value.valid);
+        assert_eq!(None, value.reason);
+        assert!(validate("cosmos1hotkey", send("cosmos1friend")).valid);
+        assert!(!validate("cosmos1hotkey", call.clone()).valid);
+        assert!(!validate("cosmos1stranger", send("cosmos1friend")).valid);
+        assert!(validate("creator", call).valid);
+
+        let value = validate("creator", send("cosmos1mallory"));
+        assert!(!value.valid);
+        let expected = ContractError::RecipientDenied {
+            recipient: HumanAddr::from("cosmos1mallory"),
+        };
+        assert!(value.reason.unwrap().contains(&expected.to_string()));
+    }
+
+    #[test]
+    fn rulesets_check_reflected_batches_first() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let set = |contracts: &[&str]| HandleMsg::SetRulesets {
+            contracts: contracts.iter().map(|c| HumanAddr::from(*c)).collect(),
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, set(&["cosmos1risk"])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, set(&["cosmos1risk", "cosmos2contract"])) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must not be its own ruleset"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, set(&["cosmos1risk", "cosmos1risk"])) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject duplicate rulesets"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(&["cosmos1compliance", "cosmos1risk"])).unwrap();
+        let res = query(&deps, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_slice(&res).unwrap();
+        let expected = vec![
+            HumanAddr::from("cosmos1compliance"),
+            HumanAddr::from("cosmos1risk"),
+        ];
+        assert_eq!(Some(expected), value.rulesets);
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![send.clone()],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env.clone(), reflect.clone()).unwrap();
+        let check = RulesetMsg::CheckReflect {
+            sender: HumanAddr::from("creator"),
+            msgs: vec![send.clone()],
+            tags: vec![],
+            memo: None,
+            height: env.block.height,
+            time: env.block.time,
+        };
+        let asks = |contract: &str| CosmosMsg::Contract {
+            contract_addr: HumanAddr::from(contract),
+            msg: Binary(to_vec(&check).unwrap()),
+            send: None,
+        };
+        let expected = vec![asks("cosmos1compliance"), asks("cosmos1risk"), send.clone()];
+        assert_eq!(expected, res.messages);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(&[])).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect).unwrap();
+        assert_eq!(vec![send], res.messages);
+    }
+
+    #[test]
+    fn stats_count_successful_calls() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![send.clone(), send],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect.clone()).unwrap();
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, reflect).is_err());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("cosmos1successor"),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let stats = |addr: &str| -> StatsResponse {
+            let msg = QueryMsg::Stats {
+                addr: HumanAddr::from(addr),
+            };
+            from_slice(&query(&deps, msg).unwrap()).unwrap()
+        };
+        let value = stats("creator");
+        assert_eq!(1, value.reflections);
+        assert_eq!(Some(12_345), value.last_height);
+        let value = stats("cosmos1stranger");
+        assert_eq!(0, value.reflections);
+        assert_eq!(None, value.last_height);
+
+        let res = query(&deps, QueryMsg::GlobalStats {}).unwrap();
+        let value: GlobalStatsResponse = from_slice(&res).unwrap();
+        assert_eq!(2, value.msgs_reflected);
+        assert_eq!(1, value.owner_changes);
+    }
+
+    #[test]
+    fn renounced_ownership_locks_admin_actions() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let renounce = |confirm: &str| HandleMsg::RenounceOwnership {
+            confirm: confirm.to_string(),
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, renounce("cosmos2contract")) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, renounce("creator")) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must confirm with the contract address"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, renounce("cosmos2contract")).unwrap();
+        assert_eq!(log("action", "renounce_ownership"), res.log[0]);
+
+        let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_slice(&res).unwrap();
+        assert_eq!(HumanAddr::from("creator"), value.owner);
+        assert!(value.renounced);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ChangeOwner {
+            owner: HumanAddr::from("creator"),
+        };
+        let res = handle(&mut deps, env, msg);
+        assert_contract_error(res, ContractError::OwnershipRenounced {});
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let res = handle(&mut deps, env, msg);
+        assert_contract_error(res, ContractError::OwnershipRenounced {});
+    }
+
+    #[test]
+    fn metrics_follow_executions_and_queues() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let metrics = |deps: &Extern<_, _>| -> Vec<u64> {
+            let res = query(deps, QueryMsg::Metrics {}).unwrap();
+            let value: MetricsResponse = from_slice(&res).unwrap();
+            value.metrics.into_iter().map(|m| m.value).collect()
+        };
+        assert_eq!(vec![0, 0, 0], metrics(&deps));
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let msg = HandleMsg::Propose {
+            msgs: vec![send.clone()],
+            description: "invoice 42".to_string(),
+            intents: None,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::Schedule {
+            msgs: vec![send.clone()],
+            execute_after: Expiration::AtHeight(12_400),
+            calendar: None,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(vec![0, 2, 0], metrics(&deps));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Approve { id: 1 }).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Pause {}).unwrap();
+        assert_eq!(vec![1, 1, 1], metrics(&deps));
+
+        let res = query(&deps, QueryMsg::Metrics {}).unwrap();
+        let value: MetricsResponse = from_slice(&res).unwrap();
+        let names: Vec<&str> = value.metrics.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(vec!["executions_total", "queue_depth", "paused"], names);
+        assert_eq!(MetricKind::Counter, value.metrics[0].kind);
+    }
+
+    #[test]
+    fn responses_end_with_a_heartbeat() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = init(&mut deps, env, msg).unwrap();
+        assert_eq!(vec![log("height", "12345"), log("seq", "1")], res.log);
+
+        let mut env = mock_env(&deps.api, "creator", &[], &[]);
+        env.block.height = 12_346;
+        let res = handle(&mut deps, env, HandleMsg::Resume {}).unwrap();
+        assert_eq!(log("deprecated", "resume"), res.log[1]);
+        assert_eq!(&[log("height", "12346"), log("seq", "2")], &res.log[3..]);
+
+        // failed calls keep no sequence number
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, HandleMsg::Unpause {}).is_err());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::Pause {}).unwrap();
+        assert_eq!(Some(&log("seq", "3")), res.log.last());
+    }
+
+    #[test]
+    fn rules_decide_top_down() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let friend_up_to_100 = Rule {
+            kind: Some(MsgKind::Send),
+            target: Some(HumanAddr::from("cosmos1friend")),
+            max_amount: Some(Amount::from(coin("100", "token").remove(0))),
+            action: RuleAction::Allow,
+        };
+        let no_sends = Rule {
+            kind: Some(MsgKind::Send),
+            target: None,
+            max_amount: None,
+            action: RuleAction::Deny,
+        };
+        let set = |rules: Vec<Rule>| HandleMsg::SetRules { rules };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, set(vec![no_sends.clone()])) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let rules = vec![friend_up_to_100.clone(), no_sends.clone()];
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(rules.clone())).unwrap();
+        let res = query(&deps, QueryMsg::Rules {}).unwrap();
+        let value: RulesResponse = from_slice(&res).unwrap();
+        assert_eq!(rules, value.rules);
+
+        let reflect = |to: &str, amount: &str| HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from(to),
+                amount: coin(amount, "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect("cosmos1friend", "100")).unwrap();
+        let denied_by = |rule| ContractError::RuleDenied { msg: 0, rule };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect("cosmos1friend", "101"));
+        assert_contract_error(res, denied_by(1));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect("cosmos1other", "1"));
+        assert_contract_error(res, denied_by(1));
+        // no rule matches contract calls
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Contract {
+                contract_addr: HumanAddr::from("cosmos1pool"),
+                msg: Binary(b"{}".to_vec()),
+                send: None,
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // the deny now comes first and shadows the allow
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(vec![no_sends, friend_up_to_100])).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect("cosmos1friend", "1"));
+        assert_contract_error(res, denied_by(0));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(vec![])).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect("cosmos1other", "1")).unwrap();
+    }
+
+    #[test]
+    fn data_round_trips_as_raw_bytes() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        // not valid json, not even utf-8
+        let raw = Binary(vec![0, 159, 146, 150, b'{']);
+        let set = |key: &str, value: &Binary| HandleMsg::SetData {
+            key: key.to_string(),
+            value: value.clone(),
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1stranger"),
+        };
+        assert_contract_error(handle(&mut deps, env, set("blob", &raw)), expected);
+        for key in &["blob", "alpha", "empty"] {
+            let value = if *key == "empty" {
+                Binary(vec![])
+            } else {
+                raw.clone()
+            };
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            let _res = handle(&mut deps, env, set(key, &value)).unwrap();
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, set("", &raw));
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("Data keys must have 1 to 64 bytes"));
+
+        let get = |deps: &Extern<_, _>, key: &str| -> Option<Binary> {
+            let msg = QueryMsg::GetData {
+                key: key.to_string(),
+            };
+            let value: DataResponse = from_slice(&query(deps, msg).unwrap()).unwrap();
+            value.value
+        };
+        assert_eq!(Some(raw.clone()), get(&deps, "blob"));
+        assert_eq!(Some(Binary(vec![])), get(&deps, "empty"));
+        assert_eq!(None, get(&deps, "missing"));
+
+        // in key order, continuing after the last key
+        let list = |deps: &Extern<_, _>, start_after: Option<String>| -> DataEntriesResponse {
+            let msg = QueryMsg::ListData {
+                start_after,
+                limit: Some(2),
+            };
+            from_slice(&query(deps, msg).unwrap()).unwrap()
+        };
+        let page = list(&deps, None);
+        let keys: Vec<&str> = page.entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(vec!["alpha", "blob"], keys);
+        assert_eq!(raw, page.entries[0].value);
+        assert_eq!(Some("blob".to_string()), page.next);
+
+        // removing the last key of a page does not shift the next one
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::RemoveData {
+            key: "blob".to_string(),
+        };
+        let _res = handle(&mut deps, env, msg.clone()).unwrap();
+        assert_eq!(None, get(&deps, "blob"));
+        let page = list(&deps, page.next);
+        assert_eq!(1, page.entries.len());
+        assert_eq!("empty", page.entries[0].key);
+        assert!(!page.is_truncated);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg);
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("No data stored under blob"));
+    }
+
+    #[test]
+    fn sessions_spend_a_msg_budget_until_expiry() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let reflect = |count: usize| HandleMsg::ReflectMsg {
+            msgs: vec![send.clone(); count],
+            tags: vec![],
+            memo: None,
+        };
+        let create = HandleMsg::CreateSession {
+            key_addr: HumanAddr::from("cosmos1session"),
+            max_msgs: 3,
+            expires: Expiration::AtHeight(12_400),
+        };
+        let session = |deps: &Extern<_, _>| -> SessionResponse {
+            let msg = QueryMsg::Session {
+                key_addr: HumanAddr::from("cosmos1session"),
+            };
+            from_slice(&query(deps, msg).unwrap()).unwrap()
+        };
+        assert_eq!(0, session(&deps).remaining_msgs);
+        assert_eq!(None, session(&deps).expires);
+
+        let env = mock_env(&deps.api, "cosmos1session", &[], &[]);
+        match handle(&mut deps, env, create.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must not create its own session"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, create).unwrap();
+        assert_eq!(3, session(&deps).remaining_msgs);
+        assert_eq!(Some(Expiration::AtHeight(12_400)), session(&deps).expires);
+
+        let env = mock_env(&deps.api, "cosmos1session", &[], &[]);
+        let res = handle(&mut deps, env, reflect(2)).unwrap();
+        assert_eq!(vec![send.clone(), send.clone()], res.messages);
+        assert_eq!(1, session(&deps).remaining_msgs);
+        // a batch over the budget is rejected as a whole
+        let env = mock_env(&deps.api, "cosmos1session", &[], &[]);
+        let res = handle(&mut deps, env, reflect(2));
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("Session has 1 messages left, 2 given"));
+        assert_eq!(1, session(&deps).remaining_msgs);
+        let mut env = mock_env(&deps.api, "cosmos1session", &[], &[]);
+        env.block.height = 12_400;
+        let expected = ContractError::Expired {
+            what: "Session".to_string(),
+            at: Expiration::AtHeight(12_400),
+        };
+        assert_contract_error(handle(&mut deps, env, reflect(1)), expected);
+        let env = mock_env(&deps.api, "cosmos1session", &[], &[]);
+        let _res = handle(&mut deps, env, reflect(1)).unwrap();
+        assert_eq!(0, session(&deps).remaining_msgs);
+        let env = mock_env(&deps.api, "cosmos1session", &[], &[]);
+        let res = handle(&mut deps, env, reflect(1));
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("Session has 0 messages left, 1 given"));
+    }
+
+    #[test]
+    fn address_sets_are_bounded() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let addrs = |prefix: &str, count: usize| -> Vec<HumanAddr> {
+            (0..count)
+                .map(|i| HumanAddr(format!("cosmos1{}{:02}", prefix, i)))
+                .collect()
+        };
+        let too_many = |set: &str, max| ContractError::LimitExceeded {
+            set: set.to_string(),
+            max,
+        };
+
+        let operators = |count| HandleMsg::SetOperators {
+            operators: addrs("operator", count),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, operators(OPERATORS.max + 1));
+        assert_contract_error(res, too_many("operators", OPERATORS.max as u32));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, operators(OPERATORS.max)).unwrap();
+
+        let co_owners = |count| HandleMsg::SetCoOwners {
+            co_owners: addrs("partner", count),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, co_owners(CO_OWNERS.max + 1));
+        assert_contract_error(res, too_many("co_owners", CO_OWNERS.max as u32));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, co_owners(CO_OWNERS.max)).unwrap();
+        // the set is full, so no more admins join
+        let env = mock_env(&deps.api, "cosmos2contract", &[], &[]);
+        let msg = HandleMsg::AddAdmin {
+            addr: HumanAddr::from("cosmos1latecomer"),
+        };
+        let res = handle(&mut deps, env, msg);
+        assert_contract_error(res, too_many("co_owners", CO_OWNERS.max as u32));
+    }
+
+    #[test]
+    fn large_reflections_wait_for_the_cosigner() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let set = HandleMsg::SetCosigner {
+            cosigner: Some(HumanAddr::from("cosmos1cosigner")),
+            thresholds: vec![Amount::from(coin("1000", "token").remove(0))],
+        };
+        let env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+        match handle(&mut deps, env, set.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+        let res = query(&deps, QueryMsg::Cosigner {}).unwrap();
+        let value: CosignerResponse = from_slice(&res).unwrap();
+        assert_eq!(Some(HumanAddr::from("cosmos1cosigner")), value.cosigner);
+
+        let send = |amount: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin(amount, "token"),
+        };
+        let reflect = |amount: &str| HandleMsg::ReflectMsg {
+            msgs: vec![send(amount)],
+            tags: vec![],
+            memo: None,
+        };
+        // up to the threshold, messages go out directly
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect("1000")).unwrap();
+        assert_eq!(vec![send("1000")], res.messages);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect("1001")).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(log("queued_id", "1"), res.log[1]);
+        let result: ReflectResult = from_slice(&res.data.unwrap().0).unwrap();
+        assert_eq!(None, result.execution_id);
+        let queued = |deps: &Extern<_, _>| -> QueuedExecutionResponse {
+            let res = query(deps, QueryMsg::QueuedExecution { id: 1 }).unwrap();
+            from_slice(&res).unwrap()
+        };
+        assert_eq!(HumanAddr::from("creator"), queued(&deps).sender);
+        assert_eq!(vec![send("1001")], queued(&deps).msgs);
+
+        // not even the owner confirms its own batch
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 1 }) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 1 }).unwrap();
+        assert_eq!(vec![send("1001")], res.messages);
+        assert!(queued(&deps).closed);
+        let res = query(&deps, QueryMsg::Execution { id: 2 }).unwrap();
+        let value: ExecutionResponse = from_slice(&res).unwrap();
+        assert_eq!(HumanAddr::from("creator"), value.sender);
+        let env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 1 });
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("Execution was already confirmed or cancelled"));
+
+        // owners may cancel, others may not, even if they sent the batch
+        let msg = HandleMsg::GrantPermission {
+            grantee: HumanAddr::from("cosmos1hotkey"),
+            msg_types: vec![MsgKind::Send],
+            expires_at_height: 12_400,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        let _res = handle(&mut deps, env, reflect("5000")).unwrap();
+        for sender in ["cosmos1stranger", "cosmos1hotkey"].iter().copied() {
+            let env = mock_env(&deps.api, sender, &[], &[]);
+            match handle(&mut deps, env, HandleMsg::CancelExecution { id: 2 }) {
+                Err(Error::Unauthorized { .. }) => {}
+                _ => panic!("Must return unauthorized error"),
+            }
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::CancelExecution { id: 2 }).unwrap();
+        let env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 2 });
+        assert!(res.is_err());
+
+        // the messages of a batch count together
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![send("600"), send("600")],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(log("queued_id", "3"), res.log[1]);
+
+        // so do the other handlers sending funds, and confirming does not queue again
+        let msg = HandleMsg::Withdraw {
+            to: HumanAddr::from("cosmos1friend"),
+            amount: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &coin("1500", "token"));
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert!(res.log.contains(&log("queued_id", "4")));
+        let env = mock_env(&deps.api, "cosmos1cosigner", &[], &coin("1500", "token"));
+        let res = handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 4 }).unwrap();
+        assert_eq!(vec![send("1500")], res.messages);
+
+        // the sender needs its permission still when the batch is confirmed
+        let env = mock_env(&deps.api, "cosmos1hotkey", &[], &[]);
+        let res = handle(&mut deps, env, reflect("5000")).unwrap();
+        assert!(res.log.contains(&log("queued_id", "5")));
+        let msg = HandleMsg::RevokePermission {
+            grantee: HumanAddr::from("cosmos1hotkey"),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+        match handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 5 }) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+    }
+
+    #[test]
+    fn cancelled_batches_take_back_what_their_handler_changed() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let set = HandleMsg::SetCosigner {
+            cosigner: Some(HumanAddr::from("cosmos1cosigner")),
+            thresholds: vec![Amount::from(coin("1000", "token").remove(0))],
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+        let cancel = |deps: &mut Extern<_, _>, id| {
+            let env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+            handle(deps, env, HandleMsg::CancelExecution { id }).unwrap();
+        };
+
+        // withdrawn fees count as collected again
+        collected_fees(&mut deps.storage)
+            .save(&coin("1500", "token"))
+            .unwrap();
+        let msg = HandleMsg::WithdrawFees {
+            to: HumanAddr::from("cosmos1friend"),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert!(res.log.contains(&log("queued_id", "1")));
+        let collected = |deps: &Extern<_, _>| -> Vec<Coin> {
+            let res = query(deps, QueryMsg::CollectedFees {}).unwrap();
+            from_slice::<CollectedFeesResponse>(&res).unwrap().collected
+        };
+        assert!(collected(&deps).is_empty());
+        cancel(&mut deps, 1);
+        assert_eq!(coin("1500", "token"), collected(&deps));
+
+        // a released milestone holds the next one back, and may be approved again
+        let msg = HandleMsg::CreateGrant {
+            grantee: HumanAddr::from("cosmos1grantee"),
+            approver: HumanAddr::from("cosmos1approver"),
+            milestones: vec![
+                Milestone {
+                    description: "design".to_string(),
+                    amount: coin("2000", "token"),
+                },
+                Milestone {
+                    description: "build".to_string(),
+                    amount: coin("10", "token"),
+                },
+            ],
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let approve = |deps: &mut Extern<_, _>| {
+            let env = mock_env(&deps.api, "cosmos1approver", &[], &[]);
+            handle(deps, env, HandleMsg::ApproveMilestone { id: 1 })
+        };
+        let released = |deps: &Extern<_, _>| -> u32 {
+            let res = query(deps, QueryMsg::Grant { id: 1 }).unwrap();
+            from_slice::<GrantResponse>(&res).unwrap().released
+        };
+        let res = approve(&mut deps).unwrap();
+        assert!(res.log.contains(&log("queued_id", "2")));
+        assert_eq!(1, released(&deps));
+        let res = approve(&mut deps);
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("The last milestone still waits for the co-signer"));
+        cancel(&mut deps, 2);
+        assert_eq!(0, released(&deps));
+        let res = approve(&mut deps).unwrap();
+        assert!(res.log.contains(&log("queued_id", "3")));
+        let env = mock_env(&deps.api, "cosmos1cosigner", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::ConfirmExecution { id: 3 }).unwrap();
+        let res = approve(&mut deps).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(2, released(&deps));
+
+        // a distributed period may be distributed again
+        let env = mock_env(&deps.api, "cosmos1payer", &coin("3000", "token"), &[]);
+        let msg = HandleMsg::Deposit {
+            tag: "fees".to_string(),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let msg = HandleMsg::SetStakeholders {
+            stakeholders: vec![StakeholderMsg {
+                addr: HumanAddr::from("cosmos1bob"),
+                shares: 1,
+            }],
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let distribute = |deps: &mut Extern<_, _>| {
+            let mut env = mock_env(&deps.api, "cosmos1anyone", &[], &[]);
+            env.block.time += REVENUE_PERIOD_SECS as i64;
+            let msg = HandleMsg::DistributeRevenue {
+                tag: "fees".to_string(),
+                period: 18192,
+            };
+            handle(deps, env, msg).unwrap()
+        };
+        let res = distribute(&mut deps);
+        assert!(res.log.contains(&log("queued_id", "4")));
+        cancel(&mut deps, 4);
+        let res = distribute(&mut deps);
+        assert!(res.log.contains(&log("queued_id", "5")));
+
+        // coins owed for a payment are never held back
+        let msg = HandleMsg::CreateSwap {
+            counterparty: HumanAddr::from("cosmos1trader"),
+            give: coin("5000", "token"),
+            want: coin("7", "uatom"),
+            expires: Expiration::AtHeight(20_000),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "cosmos1trader", &coin("7", "uatom"), &[]);
+        let res = handle(&mut deps, env, HandleMsg::FillSwap { id: 1 }).unwrap();
+        let payment = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1trader"),
+            amount: coin("5000", "token"),
+        };
+        assert_eq!(vec![payment], res.messages);
+    }
+
+    #[test]
+    fn composite_sends_are_ordered_by_recipient() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let send = |to: &str, amount: Vec<Coin>| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from(to),
+            amount,
+        };
+
+        // entries for the same recipient keep their order
+        let entries = vec![
+            PayrollEntry {
+                recipient: HumanAddr::from("cosmos1carol"),
+                amount: [coin("3", "ustake"), coin("1", "uatom")].concat(),
+            },
+            PayrollEntry {
+                recipient: HumanAddr::from("cosmos1alice"),
+                amount: coin("20", "uatom"),
+            },
+            PayrollEntry {
+                recipient: HumanAddr::from("cosmos1alice"),
+                amount: coin("10", "uatom"),
+            },
+        ];
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::RunPayroll {
+            entries,
+            tag: "payroll".to_string(),
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(
+            vec![
+                send("cosmos1alice", coin("20", "uatom")),
+                send("cosmos1alice", coin("10", "uatom")),
+                send(
+                    "cosmos1carol",
+                    [coin("1", "uatom"), coin("3", "ustake")].concat()
+                ),
+            ],
+            res.messages
+        );
+        assert_eq!(log("total", "31uatom"), res.log[2]);
+        assert_eq!(log("total", "3ustake"), res.log[3]);
+
+        let mut sent = coin("30", "ujuno");
+        sent.extend(coin("60", "uatom"));
+        let env = mock_env(&deps.api, "cosmos1payer", &sent, &[]);
+        let msg = HandleMsg::Deposit {
+            tag: "fees".to_string(),
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let msg = HandleMsg::SetStakeholders {
+            stakeholders: vec![
+                StakeholderMsg {
+                    addr: HumanAddr::from("cosmos1bob"),
+                    shares: 1,
+                },
+                StakeholderMsg {
+                    addr: HumanAddr::from("cosmos1alice"),
+                    shares: 2,
+                },
+            ],
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let mut env = mock_env(&deps.api, "cosmos1anyone", &[], &[]);
+        env.block.time += REVENUE_PERIOD_SECS as i64;
+        let msg = HandleMsg::DistributeRevenue {
+            tag: "fees".to_string(),
+            period: 18192,
+        };
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(
+            vec![
+                send(
+                    "cosmos1alice",
+                    [coin("40", "uatom"), coin("20", "ujuno")].concat()
+                ),
+                send(
+                    "cosmos1bob",
+                    [coin("20", "uatom"), coin("10", "ujuno")].concat()
+                ),
+            ],
+            res.messages
+        );
+    }
+
+    #[test]
+    fn overflows_fail_with_a_typed_error() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        // one max amount is fine, two of them cannot be summed up
+        let max = u128::max_value().to_string();
+        let entry = |recipient: &str| PayrollEntry {
+            recipient: HumanAddr::from(recipient),
+            amount: coin(&max, "token"),
+        };
+        let run = |entries| HandleMsg::RunPayroll {
+            entries,
+            tag: "payroll".to_string(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, run(vec![entry("cosmos1alice")])).unwrap();
+        assert_eq!(log("total", &format!("{}token", max)), res.log[2]);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(
+            &mut deps,
+            env,
+            run(vec![entry("cosmos1alice"), entry("cosmos1bob")]),
+        );
+        let expected = ContractError::Overflow {
+            op: "+".to_string(),
+            left: max.clone(),
+            right: max.clone(),
+        };
+        assert_contract_error(res, expected);
+
+        let msg = QueryMsg::Revenue {
+            tag: "fees".to_string(),
+            period: u64::max_value(),
+        };
+        let expected = ContractError::Overflow {
+            op: "*".to_string(),
+            left: u64::max_value().to_string(),
+            right: REVENUE_PERIOD_SECS.to_string(),
+        };
+        assert_contract_error(query(&deps, msg), expected);
+    }
+
+    #[test]
+    fn one_time_authorizations_cannot_be_replayed() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = |amount: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1vendor"),
+            amount: coin(amount, "token"),
+        };
+        let authorize = |msg: &CosmosMsg, expires| HandleMsg::AuthorizeOnce {
+            grantee: HumanAddr::from("cosmos1relayer"),
+            msg_hash: msg_hash(msg).unwrap(),
+            expires,
+        };
+        let authorize_ten = authorize(&send("10"), Expiration::AtHeight(12_400));
+        let execute = |msg: CosmosMsg| HandleMsg::ExecuteAuthorized { msg };
+        let list = |deps: &Extern<_, _>| -> AuthorizationsResponse {
+            let msg = QueryMsg::ListAuthorizations {
+                start_after: None,
+                limit: None,
+            };
+            from_slice(&query(deps, msg).unwrap()).unwrap()
+        };
+
+        let env = mock_env(&deps.api, "cosmos1relayer", &[], &[]);
+        let res = handle(&mut deps, env, authorize_ten.clone());
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1relayer"),
+        };
+        assert_contract_error(res, expected);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::AuthorizeOnce {
+            grantee: HumanAddr::from("cosmos1relayer"),
+            msg_hash: Binary(vec![1, 2, 3]),
+            expires: Expiration::AtHeight(12_400),
+        };
+        match handle(&mut deps, env, msg) {
+            Err(Error::ContractErr { .. }) => {}
+            _ => panic!("Must reject a hash of the wrong length"),
+        }
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, authorize_ten.clone());
+        assert_eq!(log("authorization_id", "1"), res.unwrap().log[1]);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, authorize_ten.clone()).is_err());
+        let value = list(&deps);
+        assert_eq!(1, value.authorizations.len());
+        assert_eq!(
+            HumanAddr::from("cosmos1relayer"),
+            value.authorizations[0].grantee
+        );
+        assert_eq!(
+            msg_hash(&send("10")).unwrap(),
+            value.authorizations[0].msg_hash
+        );
+
+        // only the grantee, and only for the exact message
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, execute(send("10"))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "cosmos1relayer", &[], &[]);
+        match handle(&mut deps, env, execute(send("11"))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must not execute another message"),
+        }
+
+        let env = mock_env(&deps.api, "cosmos1relayer", &[], &[]);
+        let res = handle(&mut deps, env, execute(send("10"))).unwrap();
+        assert_eq!(vec![send("10")], res.messages);
+        assert_eq!(log("action", "execute_authorized"), res.log[0]);
+        assert_eq!(log("authorization_id", "1"), res.log[1]);
+        assert_eq!(log("ref", "auth-1"), res.log[2]);
+        assert_eq!(log("sender", "cosmos1relayer"), res.log[5]);
+        let env = mock_env(&deps.api, "cosmos1relayer", &[], &[]);
+        match handle(&mut deps, env, execute(send("10"))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must not replay the message"),
+        }
+        assert_eq!(0, list(&deps).authorizations.len());
+
+        // the owner may authorize it again, but not past the expiration
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, authorize_ten);
+        assert_eq!(2, list(&deps).authorizations[0].id);
+        let mut env = mock_env(&deps.api, "cosmos1relayer", &[], &[]);
+        env.block.height = 12_400;
+        let expected = ContractError::Expired {
+            what: "Authorization".to_string(),
+            at: Expiration::AtHeight(12_400),
+        };
+        assert_contract_error(handle(&mut deps, env, execute(send("10"))), expected);
+    }
+
+    #[test]
+    fn migrate_records_upgrades_in_steps() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1vendor"),
+                amount: coin("10", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        for _ in 0..3 {
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            handle(&mut deps, env, reflect.clone()).unwrap();
+        }
+        // store them the way they were before records had a layout version
+        for id in 1..=3u64 {
+            let execution = executions_read(&deps.storage)
+                .load(&id.to_be_bytes())
+                .unwrap();
+            let json = to_vec(&execution).unwrap();
+            prefixed(EXECUTION_PREFIX, &mut deps.storage).set(&id.to_be_bytes(), &json);
+        }
+        let first_byte = |deps: &Extern<_, _>, id: u64| {
+            prefixed_read(EXECUTION_PREFIX, &deps.storage)
+                .get(&id.to_be_bytes())
+                .unwrap()[0]
+        };
+        let migrate = |limit| HandleMsg::MigrateRecords {
+            namespace: MigrateNamespace::Executions,
+            limit,
+        };
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let res = handle(&mut deps, env, migrate(Some(2)));
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1stranger"),
+        };
+        assert_contract_error(res, expected);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, migrate(Some(2))).unwrap();
+        let result: MigrateRecordsResult = from_slice(&res.data.unwrap().0).unwrap();
+        let expected = MigrateRecordsResult {
+            upgraded: 2,
+            unreadable: 0,
+            migrated_up_to: 2,
+            last: 3,
+            done: false,
+        };
+        assert_eq!(expected, result);
+        assert_eq!(Execution::VERSION, first_byte(&deps, 2));
+        assert_ne!(Execution::VERSION, first_byte(&deps, 3));
+
+        // the next call goes on from there
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, migrate(None)).unwrap();
+        let result: MigrateRecordsResult = from_slice(&res.data.unwrap().0).unwrap();
+        assert_eq!(
+            (1, 3, true),
+            (result.upgraded, result.migrated_up_to, result.done)
+        );
+        assert_eq!(Execution::VERSION, first_byte(&deps, 3));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, migrate(None)).unwrap();
+        let result: MigrateRecordsResult = from_slice(&res.data.unwrap().0).unwrap();
+        assert_eq!(
+            (0, 3, true),
+            (result.upgraded, result.migrated_up_to, result.done)
+        );
+    }
+
+    #[test]
+    fn reflected_batches_run_through_the_middlewares() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = |to: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from(to),
+            amount: coin("10", "token"),
+        };
+        let reflect = |msgs: Vec<CosmosMsg>| HandleMsg::ReflectMsg {
+            msgs,
+            tags: vec![],
+            memo: None,
+        };
+        let set = HandleMsg::SetMiddlewares {
+            middlewares: vec![
+                MiddlewareConfig::Recipients {
+                    allowed: vec![HumanAddr::from("cosmos1payroll")],
+                },
+                MiddlewareConfig::MaxMsgs { max: 1 },
+                MiddlewareConfig::LogKinds {},
+            ],
+        };
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, set.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let invalid = HandleMsg::SetMiddlewares {
+            middlewares: vec![MiddlewareConfig::MaxMsgs { max: 0 }],
+        };
+        assert!(handle(&mut deps, env, invalid).is_err());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, set).unwrap();
+        assert_eq!(log("middlewares", "3"), res.log[1]);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect(vec![send("cosmos1vendor")]));
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("Sending to cosmos1vendor"));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msgs = vec![send("cosmos1payroll"), send("cosmos1payroll")];
+        let res = handle(&mut deps, env, reflect(msgs));
+        assert!(res.unwrap_err().to_string().contains("At most 1 messages"));
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect(vec![send("cosmos1payroll")])).unwrap();
+        assert_eq!(vec![send("cosmos1payroll")], res.messages);
+        assert!(res.log.contains(&log("msgs_send", "1")));
+
+        // so do the batches of the other handlers
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["payroll".to_string()],
+        };
+        handle(&mut deps, env, msg).unwrap();
+        let entry = || PayrollEntry {
+            recipient: HumanAddr::from("cosmos1payroll"),
+            amount: coin("10", "token"),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::RunPayroll {
+            entries: vec![entry(), entry()],
+            tag: "payroll".to_string(),
+        };
+        let res = handle(&mut deps, env, msg);
+        assert!(res.unwrap_err().to_string().contains("At most 1 messages"));
+        let env = mock_env(&deps.api, "cosmos1vendor", &[], &[]);
+        let msg = HandleMsg::Propose {
+            msgs: vec![send("cosmos1vendor")],
+            description: "invoice".to_string(),
+            intents: None,
+        };
+        handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::Approve { id: 1 });
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("Sending to cosmos1vendor"));
+
+        let res = query(&deps, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(3, value.middlewares.unwrap().len());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let clear = HandleMsg::SetMiddlewares {
+            middlewares: vec![],
+        };
+        handle(&mut deps, env, clear).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, reflect(vec![send("cosmos1vendor")])).is_ok());
+    }
+
+    #[test]
+    fn metrics_count_what_each_policy_did() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = |to: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from(to),
+            amount: coin("10", "token"),
+        };
+        let reflect = |msgs: Vec<CosmosMsg>| HandleMsg::ReflectMsg {
+            msgs,
+            tags: vec![],
+            memo: None,
+        };
+        let set = HandleMsg::SetMiddlewares {
+            middlewares: vec![
+                MiddlewareConfig::Shadow {
+                    middleware: Box::new(MiddlewareConfig::MaxMsgs { max: 1 }),
+                },
+                MiddlewareConfig::LogKinds {},
+            ],
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+        let rules = vec![
+            Rule {
+                kind: Some(MsgKind::Contract),
+                target: None,
+                max_amount: None,
+                action: RuleAction::Allow,
+            },
+            Rule {
+                kind: Some(MsgKind::Send),
+                target: None,
+                max_amount: None,
+                action: RuleAction::Allow,
+            },
+        ];
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::SetRules { rules }).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, reflect(vec![send("cosmos1a")])).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msgs = vec![send("cosmos1a"), send("cosmos1b")];
+        let _res = handle(&mut deps, env, reflect(msgs)).unwrap();
+
+        let res = query(&deps, QueryMsg::Metrics {}).unwrap();
+        let value: MetricsResponse = from_slice(&res).unwrap();
+        let counted: Vec<(&str, u64)> = value.metrics[3..]
+            .iter()
+            .map(|m| (m.name.as_str(), m.value))
+            .collect();
+        let expected = vec![
+            ("middleware_0_evaluations", 2),
+            ("middleware_0_shadow_blocks", 1),
+            ("middleware_1_evaluations", 2),
+            ("middleware_1_shadow_blocks", 0),
+            ("rule_0_matches", 0),
+            ("rule_1_matches", 3),
+        ];
+        assert_eq!(expected, counted);
+
+        // replacing the chain starts its counters over
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let set = HandleMsg::SetMiddlewares {
+            middlewares: vec![MiddlewareConfig::LogKinds {}],
+        };
+        let _res = handle(&mut deps, env, set).unwrap();
+        let res = query(&deps, QueryMsg::Metrics {}).unwrap();
+        let value: MetricsResponse = from_slice(&res).unwrap();
+        assert_eq!(7, value.metrics.len());
+        assert_eq!("middleware_0_evaluations", value.metrics[3].name);
+        assert_eq!(0, value.metrics[3].value);
+        assert_eq!(3, value.metrics[6].value);
+    }
+
+    #[test]
+    fn executions_log_the_config_checksum() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let checksum = |deps: &Extern<_, _>| -> String {
+            let res = query(deps, QueryMsg::GetConfig {}).unwrap();
+            let value: ConfigResponse = from_slice(&res).unwrap();
+            value.checksum
+        };
+        let reflect = || HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let initial = checksum(&deps);
+        assert_eq!(64, initial.len());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect()).unwrap();
+        assert!(res.log.contains(&log("config_checksum", &initial)));
+        assert_eq!(initial, checksum(&deps));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let set = HandleMsg::SetMiddlewares {
+            middlewares: vec![MiddlewareConfig::LogKinds {}],
+        };
+        let _res = handle(&mut deps, env, set).unwrap();
+        let changed = checksum(&deps);
+        assert_ne!(initial, changed);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect()).unwrap();
+        assert!(res.log.contains(&log("config_checksum", &changed)));
+    }
+
+    #[test]
+    fn executions_need_a_fresh_attestation_once_required() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let reflect = || HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("1", "token"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let checksum = |deps: &Extern<_, _>| -> String {
+            let res = query(deps, QueryMsg::GetConfig {}).unwrap();
+            let value: ConfigResponse = from_slice(&res).unwrap();
+            value.checksum
+        };
+        let attest = |checksum: String| HandleMsg::Attest {
+            checksum,
+            signer: "compliance".to_string(),
+            signature: Binary(b"signed".to_vec()),
+        };
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let require = HandleMsg::RequireAttestation {
+            max_age: Some(Duration::Height(100)),
+        };
+        match handle(&mut deps, env, require.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, require).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect());
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("needs an attestation"));
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, attest("0badc0de".to_string())).is_err());
+        let active = checksum(&deps);
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, attest(active.clone())).is_err());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, attest(active)).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, reflect()).is_ok());
+        let res = query(&deps, QueryMsg::Attestation {}).unwrap();
+        let value: AttestationResponse = from_slice(&res).unwrap();
+        assert!(value.current);
+        assert_eq!(Some(Duration::Height(100)), value.max_age);
+        assert_eq!("compliance", value.attestation.unwrap().signer);
+
+        let mut env = mock_env(&deps.api, "creator", &[], &[]);
+        env.block.height += 100;
+        let res = handle(&mut deps, env, reflect());
+        assert!(res.unwrap_err().to_string().contains("outdated"));
+
+        // any change of the configuration needs a new sign-off
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let set = HandleMsg::SetMiddlewares {
+            middlewares: vec![MiddlewareConfig::LogKinds {}],
+        };
+        let _res = handle(&mut deps, env, set).unwrap();
+        let res = query(&deps, QueryMsg::Attestation {}).unwrap();
+        let value: AttestationResponse = from_slice(&res).unwrap();
+        assert!(!value.current);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, reflect()).is_err());
+        let active = checksum(&deps);
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, attest(active)).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, reflect()).is_ok());
+    }
+
+    #[test]
+    fn executions_are_archived_by_hash() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![send.clone()],
+            tags: vec![],
+            memo: None,
+        };
+        let set = |contract: Option<&str>| HandleMsg::SetArchive {
+            contract: contract.map(HumanAddr::from),
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        match handle(&mut deps, env, set(Some("cosmos1archive"))) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, set(Some("cosmos2contract"))).is_err());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(Some("cosmos1archive"))).unwrap();
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env.clone(), reflect.clone()).unwrap();
+        let payload = to_vec(&vec![send.clone()]).unwrap();
+        let archive = ArchiveMsg::Archive {
+            execution_id: 1,
+            height: env.block.height,
+            payload: Binary(payload.clone()),
+        };
+        let archived = CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1archive"),
+            msg: Binary(to_vec(&archive).unwrap()),
+            send: None,
+        };
+        assert_eq!(vec![send.clone(), archived], res.messages);
+        let res = query(&deps, QueryMsg::Execution { id: 1 }).unwrap();
+        let value: ExecutionResponse = from_slice(&res).unwrap();
+        let hash = Binary(Sha256::digest(&payload).to_vec());
+        assert_eq!(Some(hash), value.payload_hash);
+
+        // turned off, eg. while the archive is down, executions keep no hash
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set(None)).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, reflect).unwrap();
+        assert_eq!(vec![send], res.messages);
+        let res = query(&deps, QueryMsg::Execution { id: 2 }).unwrap();
+        let value: ExecutionResponse = from_slice(&res).unwrap();
+        assert_eq!(None, value.payload_hash);
+    }
+
+    #[test]
+    fn storage_usage_is_counted_per_namespace() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let usage = |deps: &Extern<_, _>, namespace: &str| -> Option<NamespaceUsage> {
+            let res = query(deps, QueryMsg::StorageUsage {}).unwrap();
+            let value: StorageUsageResponse = from_slice(&res).unwrap();
+            value
+                .namespaces
+                .into_iter()
+                .find(|n| n.namespace == namespace)
+        };
+        assert_eq!(None, usage(&deps, "data"));
+        let set = |key: &str, value: &[u8]| HandleMsg::SetData {
+            key: key.to_string(),
+            value: Binary(value.to_vec()),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set("invoice", b"1234")).unwrap();
+        let first = usage(&deps, "data").unwrap();
+        assert_eq!(1, first.entries);
+
+        // overwriting adds no entry, a failed call counts nothing
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set("invoice", b"12345678")).unwrap();
+        let second = usage(&deps, "data").unwrap();
+        assert_eq!(1, second.entries);
+        assert!(second.bytes > first.bytes);
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, set("other", b"1")).is_err());
+        assert_eq!(Some(second), usage(&deps, "data"));
+
+        let res = query(&deps, QueryMsg::StorageUsage {}).unwrap();
+        let value: StorageUsageResponse = from_slice(&res).unwrap();
+        let bytes: Vec<u64> = value.namespaces.iter().map(|n| n.bytes).collect();
+        let mut sorted = bytes.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+        assert_eq!(sorted, bytes);
+    }
+
+    #[test]
+    fn batching_suggestions_fit_the_gas_budget() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("1", "token"),
+        };
+        let call = CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1pool"),
+            msg: Binary(b"{}".to_vec()),
+            send: None,
+        };
+        let suggest = |deps: &Extern<_, _>, msgs: Vec<CosmosMsg>, gas_budget| {
+            let res = query(deps, QueryMsg::SuggestBatching { msgs, gas_budget })?;
+            let value: BatchingResponse = from_slice(&res).unwrap();
+            Ok(value.chunks)
+        };
+        let chunk = |start, end, gas, over_budget| BatchChunk {
+            start,
+            end,
+            gas,
+            over_budget,
+        };
+        let res: Result<Vec<BatchChunk>> = suggest(&deps, vec![send.clone()], 100_000);
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("No gas estimate for send"));
+
+        let heuristics = vec![
+            GasHeuristic {
+                kind: MsgKind::Send,
+                gas_per_msg: 40_000,
+            },
+            GasHeuristic {
+                kind: MsgKind::Contract,
+                gas_per_msg: 100_000,
+            },
+        ];
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let set = HandleMsg::SetGasHeuristics { heuristics };
+        match handle(&mut deps, env, set.clone()) {
+            Err(Error::Unauthorized { .. }) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, set).unwrap();
+
+        let msgs = vec![
+            send.clone(),
+            send.clone(),
+            send.clone(),
+            call.clone(),
+            send.clone(),
+        ];
+        let expected = vec![chunk(0, 3, 120_000, false), chunk(3, 5, 140_000, false)];
+        assert_eq!(expected, suggest(&deps, msgs, 150_000).unwrap());
+        let expected = vec![chunk(0, 1, 40_000, false), chunk(1, 2, 100_000, true)];
+        assert_eq!(expected, suggest(&deps, vec![send, call], 50_000).unwrap());
+        assert_eq!(
+            Vec::<BatchChunk>::new(),
+            suggest(&deps, vec![], 50_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn aggregate_runs_each_query_on_its_own() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let entry = |query| AggregateQuery {
+            query,
+            pointer: None,
+        };
+        let queries = vec![
+            entry(QueryMsg::GetOwner {}),
+            entry(QueryMsg::Execution { id: 7 }),
+            entry(QueryMsg::Aggregate { queries: vec![] }),
+            entry(QueryMsg::Metrics {}),
+        ];
+        let res = query(&deps, QueryMsg::Aggregate { queries }).unwrap();
+        let value: AggregateResponse = from_slice(&res).unwrap();
+        assert_eq!(4, value.results.len());
+        let owner = query(&deps, QueryMsg::GetOwner {}).unwrap();
+        assert_eq!(Some(Binary(owner)), value.results[0].data);
+        assert_eq!(None, value.results[1].data);
+        assert!(value.results[1].error.is_some());
+        let nested = value.results[2].error.as_ref().unwrap();
+        assert!(nested.contains("cannot be nested"));
+        let metrics = query(&deps, QueryMsg::Metrics {}).unwrap();
+        assert_eq!(Some(Binary(metrics)), value.results[3].data);
+
+        let queries = vec![entry(QueryMsg::GetOwner {}); MAX_AGGREGATE_QUERIES + 1];
+        assert!(query(&deps, QueryMsg::Aggregate { queries }).is_err());
+
+        // a pointer to nothing fails the entry only
+        let queries = vec![
+            AggregateQuery {
+                query: QueryMsg::GetOwner {},
+                pointer: Some("/no_such_field".to_string()),
+            },
+            entry(QueryMsg::GetOwner {}),
+        ];
+        let res = query(&deps, QueryMsg::Aggregate { queries }).unwrap();
+        let value: AggregateResponse = from_slice(&res).unwrap();
+        assert!(value.results[0].error.is_some());
+        assert!(value.results[1].data.is_some());
+    }
+
+    #[test]
+    fn fill_lookup_into_template() {
+        let send = |amount: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin(amount, "uatom"),
+        };
+        assert_eq!(
+            send("1250"),
+            fill_template(send("$lookup"), b"\"1250\"").unwrap()
+        );
+        assert_eq!(send("7"), fill_template(send("$lookup"), b"7").unwrap());
+        assert!(fill_template(send("$lookup"), b"\"12uatom\"").is_err());
+        assert!(fill_template(send("100"), b"7").is_err());
+
+        let call = |msg: &[u8]| CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1pool"),
+            msg: Binary(msg.to_vec()),
+            send: None,
+        };
+        let template = call(br#"{"deposit":{"amount":"$lookup","memo":"$lookup!"}}"#);
+        let filled = fill_template(template, br#""1250""#).unwrap();
+        assert_eq!(
+            call(br#"{"deposit":{"amount":"1250","memo":"$lookup!"}}"#),
+            filled
+        );
+        assert!(fill_template(call(b"{}"), b"7").is_err());
+        let opaque = CosmosMsg::Opaque {
+            data: Binary(vec![]),
+        };
+        assert!(fill_template(opaque, b"7").is_err());
+    }
+
+    #[test]
+    fn execute_with_lookup_reflects_like_reflect_msg() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let msg = HandleMsg::ExecuteWithLookup {
+            lookup: Lookup {
+                query: QueryMsg::CollectedFees {},
+                pointer: "/collected/0/amount".to_string(),
+                expr: Some("x * 90 / 100".to_string()),
+                on_empty: None,
+            },
+            template: CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("$lookup", "uatom"),
+            },
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, msg.clone()).is_err());
+
+        // nothing was collected, so there is no amount to send
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, msg.clone()) {
+            Err(Error::DynContractErr { msg, .. }) => assert!(msg.contains("code=1023"), "{}", msg),
+            res => panic!("Unexpected {:?}", res),
+        }
+        let with_policy = |on_empty| match msg.clone() {
+            HandleMsg::ExecuteWithLookup {
+                lookup,
+                template,
+                tags,
+                memo,
+            } => HandleMsg::ExecuteWithLookup {
+                lookup: Lookup {
+                    on_empty: Some(on_empty),
+                    ..lookup
+                },
+                template,
+                tags,
+                memo,
+            },
+            _ => unreachable!(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, with_policy(OnEmpty::Skip {})).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(0, last_execution_id(&deps.storage).unwrap());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let default = OnEmpty::Default {
+            amount: "5".to_string(),
+        };
+        let res = handle(&mut deps, env, with_policy(default)).unwrap();
+        let sent = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("5", "uatom"),
+        };
+        assert_eq!(vec![sent], res.messages);
+        assert_eq!(1, last_execution_id(&deps.storage).unwrap());
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        handle(&mut deps, env, HandleMsg::Pause {}).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, msg) {
+            Err(Error::DynContractErr { msg, .. }) => assert!(msg.contains("paused"), "{}", msg),
+            res => panic!("Unexpected {:?}", res),
+        }
+        assert_eq!(1, last_execution_id(&deps.storage).unwrap());
+    }
+
+    #[test]
+    fn macros_run_with_bounded_args() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let payout = |amount: &str| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin(amount, "uatom"),
+        };
+        let param = |name: &str| MacroParam {
+            name: name.to_string(),
+            max: Some("100".to_string()),
+        };
+        let save = |params| HandleMsg::SaveMacro {
+            name: "payout".to_string(),
+            msgs: vec![payout("$amount")],
+            params,
+            runners: vec![HumanAddr::from("cosmos1operator")],
+            quota: None,
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, save(vec![param("amount")])).is_err());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let unused = vec![param("amount"), param("fee")];
+        assert!(handle(&mut deps, env, save(unused)).is_err());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        handle(&mut deps, env, save(vec![param("amount")])).unwrap();
+
+        let res = query(&deps, QueryMsg::Macros {}).unwrap();
+        let value: MacrosResponse = from_slice(&res).unwrap();
+        assert_eq!(1, value.macros.len());
+        assert_eq!(vec![param("amount")], value.macros[0].params);
+        assert_eq!(
+            vec![HumanAddr::from("cosmos1operator")],
+            value.macros[0].runners
+        );
+
+        let run = |args: &[(&str, &str)]| HandleMsg::RunMacro {
+            name: "payout".to_string(),
+            args: args
+                .iter()
+                .map(|(name, amount)| MacroArg {
+                    name: name.to_string(),
+                    amount: amount.to_string(),
+                })
+                .collect(),
+        };
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        assert!(handle(&mut deps, env, run(&[("amount", "60")])).is_err());
+        for args in &[
+            vec![("amount", "150")],
+            vec![],
+            vec![("amount", "60"), ("fee", "1")],
+        ] {
+            let env = mock_env(&deps.api, "cosmos1operator", &[], &[]);
+            assert!(handle(&mut deps, env, run(args)).is_err(), "{:?}", args);
+        }
+        let env = mock_env(&deps.api, "cosmos1operator", &[], &[]);
+        let res = handle(&mut deps, env, run(&[("amount", "60")])).unwrap();
+        assert_eq!(vec![payout("60")], res.messages);
+        assert_eq!(log("macro", "payout"), res.log[1]);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let remove = HandleMsg::RemoveMacro {
+            name: "payout".to_string(),
+        };
+        handle(&mut deps, env, remove).unwrap();
+        let env = mock_env(&deps.api, "cosmos1operator", &[], &[]);
+        assert!(handle(&mut deps, env, run(&[("amount", "60")])).is_err());
+        let res = query(&deps, QueryMsg::Macros {}).unwrap();
+        let value: MacrosResponse = from_slice(&res).unwrap();
+        assert!(value.macros.is_empty());
+    }
+
+    #[test]
+    fn macro_quotas_reset_each_period() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let quota = MacroQuota {
+            runs: 2,
+            period: Duration::Time(3_600),
+        };
+        let save = HandleMsg::SaveMacro {
+            name: "rebalance".to_string(),
+            msgs: vec![CosmosMsg::Contract {
+                contract_addr: HumanAddr::from("cosmos1pool"),
+                msg: Binary(br#"{"rebalance":{}}"#.to_vec()),
+                send: None,
+            }],
+            params: vec![],
+            runners: vec![HumanAddr::from("cosmos1operator")],
+            quota: Some(quota),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        handle(&mut deps, env, save).unwrap();
+
+        let run = || HandleMsg::RunMacro {
+            name: "rebalance".to_string(),
+            args: vec![],
+        };
+        let remaining = |deps: &Extern<_, _>| -> Option<u32> {
+            let grantee = HumanAddr::from("cosmos1operator");
+            let res = query(deps, QueryMsg::Permissions { grantee }).unwrap();
+            let value: PermissionsResponse = from_slice(&res).unwrap();
+            assert_eq!(Some(quota), value.macros[0].quota);
+            value.macros[0].remaining
+        };
+        assert_eq!(Some(2), remaining(&deps));
+        for _ in 0..2 {
+            let env = mock_env(&deps.api, "cosmos1operator", &[], &[]);
+            handle(&mut deps, env, run()).unwrap();
+        }
+        assert_eq!(Some(0), remaining(&deps));
+        let env = mock_env(&deps.api, "cosmos1operator", &[], &[]);
+        match handle(&mut deps, env, run()) {
+            Err(Error::DynContractErr { msg, .. }) => assert!(msg.contains("code=1024"), "{}", msg),
+            res => panic!("Unexpected {:?}", res),
+        }
+        // owners are not held to the quota
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        handle(&mut deps, env, run()).unwrap();
+
+        let mut env = mock_env(&deps.api, "cosmos1operator", &[], &[]);
+        env.block.time += 3_600;
+        handle(&mut deps, env, run()).unwrap();
+        assert_eq!(Some(1), remaining(&deps));
+    }
+
+    #[test]
+    fn stale_config_version_rejects_the_update() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let version = |deps: &Extern<_, _>| -> u64 {
+            let res = query(deps, QueryMsg::GetConfig {}).unwrap();
+            let value: ConfigResponse = from_slice(&res).unwrap();
+            value.config_version
+        };
+        let set_tags = |config_version, tag: &str| HandleMsg::IfConfigVersion {
+            config_version,
+            msg: Box::new(HandleMsg::SetTags {
+                tags: vec![tag.to_string()],
+            }),
+        };
+        let read = version(&deps);
+        assert_eq!(0, read);
+
+        // both admins read version 0, the first one to write wins
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, set_tags(read, "ops")).unwrap();
+        assert!(res.log.contains(&log("config_version", "1")));
+        assert!(res.log.contains(&log("config_changed", "tags")));
+        // the wrapped message counts as part of the one call
+        assert_eq!(1, res.log.iter().filter(|l| l.key == "seq").count());
+        assert_eq!(Some(&log("seq", "2")), res.log.last());
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        match handle(&mut deps, env, set_tags(read, "payroll")) {
+            Err(Error::DynContractErr { msg, .. }) => assert!(msg.contains("code=1025"), "{}", msg),
+            res => panic!("Unexpected {:?}", res),
+        }
+        let res = query(&deps, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(1, value.config_version);
+
+        // only changes to the configuration count
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("10", "uatom"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        handle(&mut deps, env, msg).unwrap();
+        assert_eq!(1, version(&deps));
+        let env = mock_env(&deps.api, "creator", &coin("5", "uatom"), &[]);
+        handle(&mut deps, env, set_tags(1, "payroll")).unwrap();
+        assert_eq!(2, version(&deps));
+        let res = query(&deps, QueryMsg::Deposits {}).unwrap();
+        let value: DepositsResponse = from_slice(&res).unwrap();
+        assert_eq!(coin("5", "uatom"), value.totals);
+    }
+
+    #[test]
+    #[cfg(feature = "dry-run")]
+    fn dry_run_sends_and_keeps_nothing() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let reflect = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("10", "uatom"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let msg = HandleMsg::DryRun {
+            msg: Box::new(reflect.clone()),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(log("dry_run", "true"), res.log[0]);
+        let sent = log(
+            "dry_run_msg_0",
+            "kind=send;target=cosmos1friend;amount=10uatom",
+        );
+        assert!(res.log.contains(&sent), "{:?}", res.log);
+        let res = query(&deps, QueryMsg::GlobalStats {}).unwrap();
+        let value: GlobalStatsResponse = from_slice(&res).unwrap();
+        assert_eq!(0, value.msgs_reflected);
+
+        // the checks still run
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let msg = HandleMsg::DryRun {
+            msg: Box::new(reflect),
+        };
+        assert!(handle(&mut deps, env, msg).is_err());
+
+        let msg = HandleMsg::DryRun {
+            msg: Box::new(HandleMsg::SetTags {
+                tags: vec!["ops".to_string()],
+            }),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg.clone()).unwrap();
+        assert!(res.log.contains(&log("config_changed", "tags")));
+        let res = query(&deps, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_slice(&res).unwrap();
+        assert_eq!(0, value.config_version);
+
+        let msg = HandleMsg::IfConfigVersion {
+            config_version: 0,
+            msg: Box::new(msg),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        assert!(handle(&mut deps, env, msg).is_err());
+    }
+
+    #[test]
+    fn references_tell_entities_apart() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let send = CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1friend"),
+            amount: coin("10", "uatom"),
+        };
+        let msg = HandleMsg::Schedule {
+            msgs: vec![send.clone()],
+            execute_after: Expiration::AtHeight(12_400),
+            calendar: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(log("ref", "sched-1"), res.log[2]);
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![send],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, msg).unwrap();
+        assert_eq!(log("ref", "exec-1"), res.log[2]);
+
+        let resolve = |deps: &Extern<_, _>, reference: &str| {
+            let msg = QueryMsg::Resolve {
+                reference: reference.to_string(),
+            };
+            query(deps, msg).map(|res| from_slice::<ResolveResponse>(&res).unwrap())
+        };
+        let value = resolve(&deps, "sched-1").unwrap();
+        assert_eq!((EntityKind::ScheduledBatch, 1), (value.kind, value.id));
+        let value = resolve(&deps, "exec-1").unwrap();
+        assert_eq!((EntityKind::Execution, 1), (value.kind, value.id));
+        let expected = ContractError::NotFound {
+            reference: "staged-1".to_string(),
+        };
+        assert_contract_error(resolve(&deps, "staged-1"), expected);
+        assert!(resolve(&deps, "batch-1").is_err());
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, HandleMsg::CancelScheduled { id: 9 });
+        let expected = ContractError::NotFound {
+            reference: "sched-9".to_string(),
+        };
+        assert_contract_error(res, expected);
+    }
+
+    #[test]
+    fn search_finds_references_addresses_and_tags() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let msg = HandleMsg::SetTags {
+            tags: vec!["ops".to_string()],
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+        for _ in 0..2 {
+            let msg = HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("10", "uatom"),
+                }],
+                tags: vec!["ops".to_string()],
+                memo: None,
+            };
+            let env = mock_env(&deps.api, "creator", &[], &[]);
+            let _res = handle(&mut deps, env, msg).unwrap();
+        }
+
+        let search = |deps: &Extern<_, _>, term: &str, limit| {
+            let msg = QueryMsg::Search {
+                term: term.to_string(),
+                limit,
+            };
+            let value: SearchResponse = from_slice(&query(deps, msg).unwrap()).unwrap();
+            value
+                .groups
+                .into_iter()
+                .map(|g| {
+                    (
+                        g.kind,
+                        g.hits
+                            .into_iter()
+                            .map(|h| (h.reference, h.summary))
+                            .collect(),
+                    )
+                })
+                .collect::<Vec<(String, Vec<_>)>>()
+        };
+        let exec = |id| Some(format!("exec-{}", id));
+        let sent = "1 msgs at height 12345".to_string();
+
+        let found = search(&deps, "exec-2", None);
+        assert_eq!(
+            vec![("execution".to_string(), vec![(exec(2), sent.clone())])],
+            found
+        );
+        let found = search(&deps, "ops", Some(1));
+        assert_eq!(
+            vec![
+                ("tag".to_string(), vec![(None, "2 executions".to_string())]),
+                ("execution".to_string(), vec![(exec(2), sent)]),
+            ],
+            found
+        );
+        let found = search(&deps, "creator", None);
+        let kinds: Vec<&str> = found.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(vec!["owner", "sender"], kinds);
+        assert!(search(&deps, "exec-3", None).is_empty());
+        assert!(search(&deps, "payroll", None).is_empty());
+    }
+
+    #[test]
+    fn statuses_come_with_codes() {
+        let deps = dependencies(20);
+        let res = query(&deps, QueryMsg::Statuses {}).unwrap();
+        let value: StatusesResponse = from_slice(&res).unwrap();
+        let swap = value
+            .kinds
+            .iter()
+            .find(|k| k.kind == EntityKind::Swap)
+            .unwrap();
+        let expected = vec![
+            StatusCode {
+                status: Status::Open,
+                code: 8,
+            },
+            StatusCode {
+                status: Status::Filled,
+                code: 9,
+            },
+        ];
+        assert_eq!(expected, swap.statuses);
+        assert!(value.kinds.iter().all(|k| k.kind != EntityKind::Execution));
+    }
+
+    #[test]
+    fn outflows_never_exceed_the_funds() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+
+        let msg = HandleMsg::ReflectMsg {
+            msgs: vec![CosmosMsg::Send {
+                from_address: HumanAddr::from("cosmos2contract"),
+                to_address: HumanAddr::from("cosmos1friend"),
+                amount: coin("10", "uatom"),
+            }],
+            tags: vec![],
+            memo: None,
+        };
+        let env = mock_env(&deps.api, "creator", &[], &coin("9", "uatom"));
+        let expected = ContractError::OutflowExceedsFunds {
+            denom: "uatom".to_string(),
+            outflow: "10".to_string(),
+            available: "9".to_string(),
+        };
+        assert_contract_error(handle(&mut deps, env, msg.clone()), expected);
+
+        // the balance already holds the funds sent along, they are not counted twice
+        let env = mock_env(
+            &deps.api,
+            "creator",
+            &coin("4", "uatom"),
+            &coin("9", "uatom"),
+        );
+        let expected = ContractError::OutflowExceedsFunds {
+            denom: "uatom".to_string(),
+            outflow: "10".to_string(),
+            available: "9".to_string(),
+        };
+        assert_contract_error(handle(&mut deps, env, msg.clone()), expected);
+
+        let env = mock_env(
+            &deps.api,
+            "creator",
+            &coin("4", "uatom"),
+            &coin("10", "uatom"),
+        );
+        handle(&mut deps, env, msg).unwrap();
+    }
+
+    #[test]
+    fn self_test_reports_each_subsystem() {
+        let mut deps = dependencies(20);
+
+        let msg = InitMsg {
+            chain: mock_chain(),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = init(&mut deps, env, msg).unwrap();
+        let self_test = |echo: Option<&str>| HandleMsg::SelfTest {
+            echo: echo.map(HumanAddr::from),
+        };
+        let result = |res: &Response| -> SelfTestResult {
+            from_slice(&res.data.as_ref().unwrap().0).unwrap()
+        };
+
+        let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+        let expected = ContractError::NotOwner {
+            sender: HumanAddr::from("cosmos1stranger"),
+        };
+        assert_contract_error(handle(&mut deps, env, self_test(None)), expected);
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, self_test(None)).unwrap();
+        let subsystems: Vec<_> = result(&res)
+            .checks
+            .into_iter()
+            .map(|c| c.subsystem)
+            .collect();
+        assert_eq!(vec!["reflect", "schedule", "data", "history"], subsystems);
+        assert!(result(&res)
+            .checks
+            .iter()
+            .all(|c| c.passed && c.error.is_none()));
+        assert_eq!(log("passed", "4/4"), res.log[5]);
+        assert!(res.messages.is_empty());
+        // the probes kept nothing
+        assert_eq!(0, last_scheduled_batch_id(&deps.storage).unwrap());
+        assert_eq!(0, last_execution_id(&deps.storage).unwrap());
+        assert_eq!(0, last_history_seq(&deps.storage).unwrap());
+        let keys = data_keys_read(&deps.storage).may_load().unwrap();
+        assert!(keys.unwrap_or_default().is_empty());
+
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, self_test(Some("cosmos1echo"))).unwrap();
+        match &res.messages[..] {
+            [CosmosMsg::Contract {
+                contract_addr,
+                send,
+                ..
+            }] => {
+                assert_eq!("cosmos1echo", contract_addr.as_str());
+                assert_eq!(&None, send);
+            }
+            _ => panic!("Must send the probe to the echo contract"),
+        }
+        assert_eq!(Some(&log("seq", "3")), res.log.last());
+
+        // a probe must pass the checks of any reflected message to go out
+        let msg = HandleMsg::AddAllowedTarget {
+            contract: HumanAddr::from("cosmos1other"),
+        };
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let res = handle(&mut deps, env, self_test(Some("cosmos1echo"))).unwrap();
+        assert_eq!(log("reflect", "fail"), res.log[1]);
+        assert!(res.messages.is_empty());
+
+        // paused, it is rejected like any other call
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let _res = handle(&mut deps, env, HandleMsg::Pause {}).unwrap();
+        let env = mock_env(&deps.api, "creator", &[], &[]);
+        let expected = ContractError::ContractPaused {
+            action: "self_test".to_string(),
+        };
+        assert_contract_error(handle(&mut deps, env, self_test(None)), expected);
+    }
+
+    #[test]
+    fn display_amount_scaling() {
+        let six = DenomMetadata {
+            symbol: "ATOM".to_string(),
+            exponent: 6,
+        };
+        assert_eq!("0", six.display_amount("0").unwrap());
+        assert_eq!("0.000001", six.display_amount("1").unwrap());
+        assert_eq!("12", six.display_amount("12000000").unwrap());
+        assert_eq!("12.000345", six.display_amount("12000345").unwrap());
+        assert!(six.display_amount("12.5").is_err());
+        assert!(six.display_amount("").is_err());
+
+        let eighteen = DenomMetadata {
+            symbol: "ETH".to_string(),
+            exponent: 18,
+        };
+        assert_eq!(
+            "1.25",
+            eighteen.display_amount("1250000000000000000").unwrap()
+        );
+
+        let zero = DenomMetadata {
+            symbol: "RAW".to_string(),
+            exponent: 0,
+        };
+        assert_eq!("0", zero.display_amount("000").unwrap());
+        assert_eq!("77", zero.display_amount("77").unwrap());
+    }
+
+    #[test]
+    fn chain_profile_timeout_math() {
+        let chain = mock_chain();
+        assert_eq!(0, chain.blocks_for_secs(0));
+        assert_eq!(1, chain.blocks_for_secs(1));
+        assert_eq!(1, chain.blocks_for_secs(5));
+        assert_eq!(2, chain.blocks_for_secs(6));
+        assert_eq!(50, chain.secs_for_blocks(10));
     }
 }
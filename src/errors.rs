@@ -0,0 +1,372 @@
+use std::fmt;
+
+use cosmwasm::errors::{dyn_contract_err, Result};
+
+use cosmwasm::types::{Coin, HumanAddr};
+
+use crate::state::{Expiration, MsgKind};
+
+/// ContractError holds the failures specific to this contract.
+/// They surface as `Error::DynContractErr` with the payload as message:
+/// the display string, followed by ` | code=<code>` and `;<key>=<value>` for each param.
+/// Codes are stable and never reused, so clients should branch on them rather than the text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractError {
+    CooldownActive {
+        action: String,
+        earliest: Expiration,
+    },
+    BudgetExceeded {
+        tag: String,
+        limit: Coin,
+        spent: String,
+    },
+    ContractPaused {
+        action: String,
+    },
+    Decommissioned {},
+    MovedPermanently {
+        successor: HumanAddr,
+    },
+    MessagesTooLarge {
+        size: u64,
+        limit: u64,
+    },
+    TargetNotAllowed {
+        contract: HumanAddr,
+    },
+    DepositorNotAllowed {
+        sender: HumanAddr,
+    },
+    MemoRequired {
+        threshold: Coin,
+    },
+    SpendLimitExceeded {
+        limit: Coin,
+        period_secs: u64,
+        spent: String,
+    },
+    OutsideWindow {
+        operator: HumanAddr,
+    },
+    CalendarClosed {
+        calendar: String,
+    },
+    RecipientDenied {
+        recipient: HumanAddr,
+    },
+    SecondApproverRequired {
+        kind: MsgKind,
+    },
+    IntentMismatch {
+        contract: HumanAddr,
+        detail: String,
+    },
+    FeeRequired {
+        fee: Coin,
+    },
+    OwnershipRenounced {},
+    RuleDenied {
+        msg: u32,
+        rule: u32,
+    },
+    LimitExceeded {
+        set: String,
+        max: u32,
+    },
+    NotOwner {
+        sender: HumanAddr,
+    },
+    Expired {
+        what: String,
+        at: Expiration,
+    },
+    Overflow {
+        op: String,
+        left: String,
+        right: String,
+    },
+    EmptyLookup {
+        placeholder: String,
+        value: String,
+    },
+    QuotaExhausted {
+        name: String,
+        resets_at: Expiration,
+    },
+    ConfigChanged {
+        expected: u64,
+        current: u64,
+    },
+    NotFound {
+        reference: String,
+    },
+    OutflowExceedsFunds {
+        denom: String,
+        outflow: String,
+        available: String,
+    },
+}
+
+impl ContractError {
+    pub fn code(&self) -> u32 {
+        match self {
+            ContractError::CooldownActive { .. } => 1001,
+            ContractError::BudgetExceeded { .. } => 1002,
+            ContractError::ContractPaused { .. } => 1003,
+            ContractError::Decommissioned { .. } => 1004,
+            ContractError::MovedPermanently { .. } => 1005,
+            ContractError::MessagesTooLarge { .. } => 1006,
+            ContractError::TargetNotAllowed { .. } => 1007,
+            ContractError::DepositorNotAllowed { .. } => 1008,
+            ContractError::MemoRequired { .. } => 1009,
+            ContractError::SpendLimitExceeded { .. } => 1010,
+            ContractError::OutsideWindow { .. } => 1011,
+            ContractError::CalendarClosed { .. } => 1012,
+            ContractError::RecipientDenied { .. } => 1013,
+            ContractError::SecondApproverRequired { .. } => 1014,
+            ContractError::IntentMismatch { .. } => 1015,
+            ContractError::FeeRequired { .. } => 1016,
+            ContractError::OwnershipRenounced { .. } => 1017,
+            ContractError::RuleDenied { .. } => 1018,
+            ContractError::LimitExceeded { .. } => 1019,
+            ContractError::NotOwner { .. } => 1020,
+            ContractError::Expired { .. } => 1021,
+            ContractError::Overflow { .. } => 1022,
+            ContractError::EmptyLookup { .. } => 1023,
+            ContractError::QuotaExhausted { .. } => 1024,
+            ContractError::ConfigChanged { .. } => 1025,
+            ContractError::NotFound { .. } => 1026,
+            ContractError::OutflowExceedsFunds { .. } => 1027,
+        }
+    }
+
+    /// The values the message is built from, for clients to localize it
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ContractError::CooldownActive { action, earliest } => vec![
+                ("action", action.clone()),
+                ("earliest", earliest.to_string()),
+            ],
+            ContractError::BudgetExceeded { tag, limit, spent } => vec![
+                ("tag", tag.clone()),
+                ("limit", limit.amount.clone()),
+                ("denom", limit.denom.clone()),
+                ("spent", spent.clone()),
+            ],
+            ContractError::ContractPaused { action } => vec![("action", action.clone())],
+            ContractError::Decommissioned {} => vec![],
+            ContractError::MovedPermanently { successor } => {
+                vec![("successor", successor.as_str().to_string())]
+            }
+            ContractError::MessagesTooLarge { size, limit } => {
+                vec![("size", size.to_string()), ("limit", limit.to_string())]
+            }
+            ContractError::TargetNotAllowed { contract } => {
+                vec![("contract", contract.as_str().to_string())]
+            }
+            ContractError::DepositorNotAllowed { sender } => {
+                vec![("sender", sender.as_str().to_string())]
+            }
+            ContractError::MemoRequired { threshold } => vec![
+                ("threshold", threshold.amount.clone()),
+                ("denom", threshold.denom.clone()),
+            ],
+            ContractError::SpendLimitExceeded {
+                limit,
+                period_secs,
+                spent,
+            } => vec![
+                ("limit", limit.amount.clone()),
+                ("denom", limit.denom.clone()),
+                ("period_secs", period_secs.to_string()),
+                ("spent", spent.clone()),
+            ],
+            ContractError::OutsideWindow { operator } => {
+                vec![("operator", operator.as_str().to_string())]
+            }
+            ContractError::CalendarClosed { calendar } => vec![("calendar", calendar.clone())],
+            ContractError::RecipientDenied { recipient } => {
+                vec![("recipient", recipient.as_str().to_string())]
+            }
+            ContractError::SecondApproverRequired { kind } => {
+                vec![("kind", kind.as_str().to_string())]
+            }
+            ContractError::IntentMismatch { contract, detail } => vec![
+                ("contract", contract.as_str().to_string()),
+                ("detail", detail.clone()),
+            ],
+            ContractError::FeeRequired { fee } => {
+                vec![("fee", fee.amount.clone()), ("denom", fee.denom.clone())]
+            }
+            ContractError::OwnershipRenounced {} => vec![],
+            ContractError::RuleDenied { msg, rule } => {
+                vec![("msg", msg.to_string()), ("rule", rule.to_string())]
+            }
+            ContractError::LimitExceeded { set, max } => {
+                vec![("set", set.clone()), ("max", max.to_string())]
+            }
+            ContractError::NotOwner { sender } => vec![("sender", sender.as_str().to_string())],
+            ContractError::Expired { what, at } => {
+                vec![("what", what.clone()), ("at", at.to_string())]
+            }
+            ContractError::Overflow { op, left, right } => vec![
+                ("op", op.clone()),
+                ("left", left.clone()),
+                ("right", right.clone()),
+            ],
+            ContractError::EmptyLookup { placeholder, value } => {
+                vec![
+                    ("placeholder", placeholder.clone()),
+                    ("value", value.clone()),
+                ]
+            }
+            ContractError::QuotaExhausted { name, resets_at } => {
+                vec![("name", name.clone()), ("resets_at", resets_at.to_string())]
+            }
+            ContractError::ConfigChanged { expected, current } => {
+                vec![
+                    ("expected", expected.to_string()),
+                    ("current", current.to_string()),
+                ]
+            }
+            ContractError::NotFound { reference } => vec![("ref", reference.clone())],
+            ContractError::OutflowExceedsFunds {
+                denom,
+                outflow,
+                available,
+            } => vec![
+                ("denom", denom.clone()),
+                ("outflow", outflow.clone()),
+                ("available", available.clone()),
+            ],
+        }
+    }
+
+    pub fn payload(&self) -> String {
+        let mut payload = format!("{} | code={}", self, self.code());
+        for (key, value) in self.params() {
+            payload.push_str(&format!(";{}={}", key, value));
+        }
+        payload
+    }
+
+    pub fn fail<T>(self) -> Result<T> {
+        dyn_contract_err(self.payload())
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContractError::CooldownActive { action, earliest } => write!(
+                f,
+                "Cooldown active for {}, next allowed at {}",
+                action, earliest
+            ),
+            ContractError::BudgetExceeded { tag, limit, spent } => write!(
+                f,
+                "Budget for {} exceeded, already spent {} of {}{}",
+                tag, spent, limit.amount, limit.denom
+            ),
+            ContractError::ContractPaused { action } => {
+                write!(f, "Contract is paused, {} is not allowed", action)
+            }
+            ContractError::Decommissioned {} => write!(f, "Contract is decommissioned"),
+            ContractError::MovedPermanently { successor } => {
+                write!(f, "Contract moved permanently to {}", successor.as_str())
+            }
+            ContractError::MessagesTooLarge { size, limit } => write!(
+                f,
+                "Messages take {} bytes, at most {} are allowed",
+                size, limit
+            ),
+            ContractError::TargetNotAllowed { contract } => {
+                write!(f, "Contract {} is not an allowed target", contract.as_str())
+            }
+            ContractError::DepositorNotAllowed { sender } => {
+                write!(f, "{} may not send funds to this contract", sender.as_str())
+            }
+            ContractError::MemoRequired { threshold } => write!(
+                f,
+                "Sending more than {}{} needs a travel rule memo",
+                threshold.amount, threshold.denom
+            ),
+            ContractError::SpendLimitExceeded {
+                limit,
+                period_secs,
+                spent,
+            } => write!(
+                f,
+                "Spend limit of {}{} per {}s exceeded, already spent {}",
+                limit.amount, limit.denom, period_secs, spent
+            ),
+            ContractError::OutsideWindow { operator } => write!(
+                f,
+                "{} may not act outside its execution window",
+                operator.as_str()
+            ),
+            ContractError::CalendarClosed { calendar } => {
+                write!(f, "Calendar {} is closed at this time", calendar)
+            }
+            ContractError::RecipientDenied { recipient } => {
+                write!(f, "{} is on the deny-list", recipient.as_str())
+            }
+            ContractError::SecondApproverRequired { kind } => write!(
+                f,
+                "{} messages need a second approver, propose them instead",
+                kind.as_str()
+            ),
+            ContractError::IntentMismatch { contract, detail } => write!(
+                f,
+                "Call to {} does not match its intent: {}",
+                contract.as_str(),
+                detail
+            ),
+            ContractError::FeeRequired { fee } => write!(
+                f,
+                "Reflecting with a permission costs a fee of {}{}",
+                fee.amount, fee.denom
+            ),
+            ContractError::OwnershipRenounced {} => write!(f, "Ownership has been renounced"),
+            ContractError::RuleDenied { msg, rule } => {
+                write!(f, "Message {} is denied by rule {}", msg, rule)
+            }
+            ContractError::LimitExceeded { set, max } => {
+                write!(f, "At most {} {} are allowed", max, set)
+            }
+            ContractError::NotOwner { sender } => {
+                write!(f, "{} is not an owner of this contract", sender.as_str())
+            }
+            ContractError::Expired { what, at } => write!(f, "{} expired at {}", what, at),
+            ContractError::Overflow { op, left, right } => {
+                write!(f, "Arithmetic overflow in {} {} {}", left, op, right)
+            }
+            ContractError::EmptyLookup { placeholder, value } => {
+                write!(
+                    f,
+                    "The lookup for {} came back empty: {}",
+                    placeholder, value
+                )
+            }
+            ContractError::QuotaExhausted { name, resets_at } => {
+                write!(f, "No runs of {} left until {}", name, resets_at)
+            }
+            ContractError::ConfigChanged { expected, current } => write!(
+                f,
+                "The configuration changed since version {}, it is at {}",
+                expected, current
+            ),
+            ContractError::NotFound { reference } => write!(f, "{} does not exist", reference),
+            ContractError::OutflowExceedsFunds {
+                denom,
+                outflow,
+                available,
+            } => write!(
+                f,
+                "Internal invariant broken: {}{} would leave, but only {}{} are available",
+                outflow, denom, available, denom
+            ),
+        }
+    }
+}
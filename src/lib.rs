@@ -1,15 +1,27 @@
+pub mod bounded;
+pub mod builders;
 pub mod contract;
+pub mod errors;
+pub mod math;
+pub mod middleware;
 pub mod msg;
+pub mod pagination;
+pub mod pointer;
+pub mod reference;
+pub mod select;
+pub mod staging;
 pub mod state;
+pub mod usage;
+pub mod versioned;
 
 /** Below we expose wasm exports * **/
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", not(feature = "library")))]
 pub use cosmwasm::exports::{allocate, deallocate};
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", not(feature = "library")))]
 pub use wasm::{handle, init};
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", not(feature = "library")))]
 mod wasm {
     use super::contract;
     use cosmwasm::{exports, imports};
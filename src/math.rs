@@ -0,0 +1,246 @@
+//! Checked arithmetic on amounts, counters and weights.
+//!
+//! Release builds keep overflow checks, so an overflow panics, which in wasm aborts the call
+//! with a useless message. Values which must be exact (amounts, weights) go through SafeMath
+//! and fail with Overflow. Statistics and far away expirations saturate.
+
+use cosmwasm::errors::{dyn_contract_err, Result};
+
+use crate::errors::ContractError;
+
+/// SafeMath fails with ContractError::Overflow where the std operators would panic or wrap
+pub trait SafeMath: Sized {
+    fn safe_add(self, rhs: Self) -> Result<Self>;
+    fn safe_sub(self, rhs: Self) -> Result<Self>;
+    fn safe_mul(self, rhs: Self) -> Result<Self>;
+    /// Fails on a division by zero
+    fn safe_div(self, rhs: Self) -> Result<Self>;
+}
+
+macro_rules! impl_safe_math {
+    ($($t:ty),*) => {
+        $(
+            impl SafeMath for $t {
+                fn safe_add(self, rhs: Self) -> Result<Self> {
+                    self.checked_add(rhs).map_or_else(|| overflow(self, "+", rhs), Ok)
+                }
+
+                fn safe_sub(self, rhs: Self) -> Result<Self> {
+                    self.checked_sub(rhs).map_or_else(|| overflow(self, "-", rhs), Ok)
+                }
+
+                fn safe_mul(self, rhs: Self) -> Result<Self> {
+                    self.checked_mul(rhs).map_or_else(|| overflow(self, "*", rhs), Ok)
+                }
+
+                fn safe_div(self, rhs: Self) -> Result<Self> {
+                    self.checked_div(rhs).map_or_else(|| overflow(self, "/", rhs), Ok)
+                }
+            }
+        )*
+    };
+}
+
+impl_safe_math!(u32, u64, u128);
+
+fn overflow<T: ToString, R>(left: T, op: &str, right: T) -> Result<R> {
+    ContractError::Overflow {
+        op: op.to_string(),
+        left: left.to_string(),
+        right: right.to_string(),
+    }
+    .fail()
+}
+
+/// amount * numerator / denominator, without overflowing in the product where the result fits
+pub fn mul_ratio(amount: u128, numerator: u128, denominator: u128) -> Result<u128> {
+    if denominator == 0 {
+        return overflow(amount, "/", denominator);
+    }
+    // split off the whole multiples, so only the remainder is multiplied
+    let whole = (amount / denominator).safe_mul(numerator)?;
+    let rest = (amount % denominator).safe_mul(numerator)? / denominator;
+    whole.safe_add(rest)
+}
+
+/// Sums up the values, failing with Overflow rather than wrapping
+pub fn safe_sum<T: SafeMath + Default, I: IntoIterator<Item = T>>(values: I) -> Result<T> {
+    values
+        .into_iter()
+        .try_fold(T::default(), |total, value| total.safe_add(value))
+}
+
+/// Longest expression eval takes, which also bounds how deep parentheses nest
+pub const MAX_EXPR_LEN: usize = 100;
+
+/// Evaluates an expression of x, eg. x * 90 / 100 - 1000. It has integers, x, + - * /
+/// and parentheses, with the usual precedence. Divisions round down, and everything is checked,
+/// so a result below zero fails rather than wrapping.
+pub fn eval(expr: &str, x: u128) -> Result<u128> {
+    if expr.len() > MAX_EXPR_LEN {
+        return dyn_contract_err(format!("Expressions have at most {} bytes", MAX_EXPR_LEN));
+    }
+    let mut parser = Parser {
+        expr: expr.as_bytes(),
+        pos: 0,
+        x,
+    };
+    let value = parser.sum()?;
+    parser.skip_ws();
+    if parser.pos < parser.expr.len() {
+        return parser.unexpected();
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    expr: &'a [u8],
+    pos: usize,
+    x: u128,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_ws(&mut self) {
+        while self.expr.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips the next byte if it is one of ops
+    fn op(&mut self, ops: &[u8]) -> Option<u8> {
+        self.skip_ws();
+        let op = *self.expr.get(self.pos).filter(|b| ops.contains(b))?;
+        self.pos += 1;
+        Some(op)
+    }
+
+    fn sum(&mut self) -> Result<u128> {
+        let mut value = self.product()?;
+        while let Some(op) = self.op(b"+-") {
+            let rhs = self.product()?;
+            value = if op == b'+' {
+                value.safe_add(rhs)?
+            } else {
+                value.safe_sub(rhs)?
+            };
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<u128> {
+        let mut value = self.factor()?;
+        while let Some(op) = self.op(b"*/") {
+            let rhs = self.factor()?;
+            value = if op == b'*' {
+                value.safe_mul(rhs)?
+            } else {
+                value.safe_div(rhs)?
+            };
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<u128> {
+        if self.op(b"(").is_some() {
+            let value = self.sum()?;
+            return match self.op(b")") {
+                Some(_) => Ok(value),
+                None => self.unexpected(),
+            };
+        }
+        if self.op(b"x").is_some() {
+            return Ok(self.x);
+        }
+        let start = self.pos;
+        while self.expr.get(self.pos).map_or(false, u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        // only digits, so it is valid UTF-8
+        let digits = std::str::from_utf8(&self.expr[start..self.pos]).unwrap_or_default();
+        match digits.parse::<u128>() {
+            Ok(value) => Ok(value),
+            Err(_) if digits.is_empty() => self.unexpected(),
+            Err(_) => dyn_contract_err(format!("Number too large: {}", digits)),
+        }
+    }
+
+    fn unexpected<T>(&self) -> Result<T> {
+        match self.expr.get(self.pos) {
+            Some(&b) => dyn_contract_err(format!("Unexpected {} at {}", b as char, self.pos)),
+            None => dyn_contract_err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_overflow<T: std::fmt::Debug>(res: Result<T>, op: &str, left: &str, right: &str) {
+        let expected = ContractError::Overflow {
+            op: op.to_string(),
+            left: left.to_string(),
+            right: right.to_string(),
+        };
+        assert!(res.unwrap_err().to_string().contains(&expected.payload()));
+    }
+
+    #[test]
+    fn fails_at_the_u128_boundaries() {
+        let max = u128::max_value();
+        assert_eq!(max, (max - 1).safe_add(1).unwrap());
+        assert_overflow(max.safe_add(1), "+", &max.to_string(), "1");
+        assert_eq!(0, 1u128.safe_sub(1).unwrap());
+        assert_overflow(0u128.safe_sub(1), "-", "0", "1");
+        assert_eq!(max, (max / 2).safe_mul(2).unwrap() + 1);
+        assert_overflow(
+            (max / 2 + 1).safe_mul(2),
+            "*",
+            &(max / 2 + 1).to_string(),
+            "2",
+        );
+        assert_eq!(max, max.safe_div(1).unwrap());
+        assert_overflow(max.safe_div(0), "/", &max.to_string(), "0");
+        assert_overflow(
+            u64::max_value().safe_add(1),
+            "+",
+            "18446744073709551615",
+            "1",
+        );
+    }
+
+    #[test]
+    fn mul_ratio_avoids_the_intermediate_overflow() {
+        let max = u128::max_value();
+        assert_eq!(max / 3 * 2, mul_ratio(max, 2, 3).unwrap());
+        assert_eq!(max, mul_ratio(max, 7, 7).unwrap());
+        assert_eq!(66, mul_ratio(100, 2, 3).unwrap());
+        assert_eq!(0, mul_ratio(0, 2, 3).unwrap());
+        assert!(mul_ratio(max, 3, 2).is_err());
+        assert_overflow(mul_ratio(1, 1, 0), "/", "1", "0");
+    }
+
+    #[test]
+    fn sums_fail_instead_of_wrapping() {
+        assert_eq!(6u64, safe_sum(vec![1u64, 2, 3]).unwrap());
+        assert_eq!(0u128, safe_sum(Vec::<u128>::new()).unwrap());
+        let max = u128::max_value();
+        assert_overflow(safe_sum(vec![max, 1]), "+", &max.to_string(), "1");
+    }
+
+    #[test]
+    fn evaluates_expressions_of_x() {
+        assert_eq!(8000, eval("x * 90 / 100 - 1000", 10000).unwrap());
+        assert_eq!(7, eval("1 + 2 * 3", 0).unwrap());
+        assert_eq!(9, eval("(1 + 2) * 3", 0).unwrap());
+        assert_eq!(3, eval("x/3", 10).unwrap());
+        assert_eq!(10, eval(" x ", 10).unwrap());
+        assert_eq!(1, eval("10 - 4 - 5", 0).unwrap());
+        assert_overflow(eval("x - 1000", 999), "-", "999", "1000");
+        assert_overflow(eval("x / (x - 10)", 10), "/", "10", "0");
+        for bad in &["", "x +", "(x", "x)", "2x", "y", "-1", "x ** 2"] {
+            assert!(eval(bad, 1).is_err(), "{}", bad);
+        }
+        assert!(eval(&"(".repeat(MAX_EXPR_LEN + 1), 1).is_err());
+    }
+}
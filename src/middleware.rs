@@ -0,0 +1,698 @@
+//! Middlewares every reflected batch passes through, in the order they are configured.
+//!
+//! A middleware may reject the batch, rewrite its messages or add to the response log.
+//! Admins compose the chain from the built-in ones with SetMiddlewares, which stores a
+//! MiddlewareConfig for each in the State. Every batch the contract sends runs through the
+//! chain before anything else looks at it, so permissions and limits see the messages as they
+//! will go out. Batches queued for the co-signer run through it again on confirmation.
+//!
+//! A middleware in shadow mode only logs what it would reject and leaves the messages alone,
+//! so admins can trial a stricter policy against real traffic before enforcing it. During a
+//! rollout, a middleware is enforced for a share of the executions and shadowed for the rest.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm::errors::{contract_err, dyn_contract_err, Result};
+use cosmwasm::types::{log, Coin, CosmosMsg, Env, HumanAddr, LogAttribute};
+
+use crate::select;
+use crate::state::{count_kinds, sum_coins, ChainProfile, Expiration, MsgKind};
+
+/// Most middlewares a chain may have
+pub const MAX_MIDDLEWARES: usize = 10;
+/// Most recipients a Recipients middleware may allow
+pub const MAX_ALLOWED_RECIPIENTS: usize = 50;
+/// Most aliases a DenomAliases middleware may map
+pub const MAX_DENOM_ALIASES: usize = 20;
+
+/// Batch is a reflected batch on its way through the chain
+#[derive(Clone, Debug, PartialEq)]
+pub struct Batch<'a> {
+    pub env: &'a Env,
+    /// The id the execution gets if the batch goes out directly
+    pub execution_id: u64,
+    pub msgs: Vec<CosmosMsg>,
+    pub tags: &'a [String],
+}
+
+pub trait Middleware {
+    /// Fails to reject the batch
+    fn validate(&self, _batch: &Batch) -> Result<()> {
+        Ok(())
+    }
+
+    /// May rewrite the messages, the middlewares after this one see the result
+    fn transform(&self, _batch: &mut Batch) -> Result<()> {
+        Ok(())
+    }
+
+    /// Adds to the log of the response, once the whole chain accepted the batch
+    fn observe(&self, _batch: &Batch) -> Vec<LogAttribute> {
+        vec![]
+    }
+
+    /// Whether a rejection by this middleware stops the batch, or is only logged
+    fn enforcement(&self, _batch: &Batch) -> Enforcement {
+        Enforcement::Enforce
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Enforcement {
+    Enforce,
+    Shadow,
+}
+
+/// Verdict is what a middleware decided about a batch
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Accepted,
+    /// Rejected by a middleware in shadow mode, the batch goes on
+    Shadowed {
+        reason: String,
+    },
+}
+
+/// Outcome is what the chain decided about a batch it let through
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome {
+    /// One for each middleware, in order
+    pub verdicts: Vec<Verdict>,
+    pub logs: Vec<LogAttribute>,
+}
+
+/// Validates and transforms the batch with each middleware in turn, then lets all observe
+/// the final batch. Rejections in shadow mode are logged as shadow_rejected.
+pub fn run(chain: &[Box<dyn Middleware>], batch: &mut Batch) -> Result<Outcome> {
+    let mut verdicts = Vec::with_capacity(chain.len());
+    let mut logs = vec![];
+    for (i, middleware) in chain.iter().enumerate() {
+        let verdict = judge(middleware.as_ref(), batch)?;
+        if let Verdict::Shadowed { reason } = &verdict {
+            logs.push(log("shadow_rejected", &format!("{}: {}", i, reason)));
+        }
+        verdicts.push(verdict);
+    }
+    logs.extend(chain.iter().flat_map(|m| m.observe(batch)));
+    Ok(Outcome { verdicts, logs })
+}
+
+/// Runs one middleware, failing if it rejects the batch in earnest
+fn judge(middleware: &dyn Middleware, batch: &mut Batch) -> Result<Verdict> {
+    if let Err(err) = middleware.validate(batch) {
+        return match middleware.enforcement(batch) {
+            Enforcement::Enforce => Err(err),
+            Enforcement::Shadow => Ok(Verdict::Shadowed {
+                reason: err.to_string(),
+            }),
+        };
+    }
+    middleware.transform(batch)?;
+    Ok(Verdict::Accepted)
+}
+
+/// MiddlewareConfig picks a built-in middleware and its settings
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MiddlewareConfig {
+    /// Logs how many messages of each kind the batch has
+    LogKinds {},
+    /// Logs every message on its own, for indexers. Costs gas with every message.
+    LogMsgs {},
+    /// Rejects batches of more than max messages
+    MaxMsgs { max: u32 },
+    /// Only lets sends go to these recipients
+    Recipients { allowed: Vec<HumanAddr> },
+    /// Replaces the aliases in the coins of every message by the denoms they stand for
+    DenomAliases { aliases: Vec<DenomAlias> },
+    /// Runs the middleware in shadow mode: it logs what it would reject and changes nothing
+    Shadow { middleware: Box<MiddlewareConfig> },
+    /// Until ends, only enforces the middleware for percent of the executions, picked by the
+    /// hash of their id, and shadows it for the others. Enforces it for all of them after.
+    Rollout {
+        middleware: Box<MiddlewareConfig>,
+        percent: u32,
+        ends: Expiration,
+    },
+}
+
+/// DenomAlias is a name operators may use for a denom, eg. "usdc" for its ibc/ hash
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomAlias {
+    pub alias: String,
+    pub denom: String,
+}
+
+impl MiddlewareConfig {
+    pub fn validate(&self, chain: &ChainProfile) -> Result<()> {
+        match self {
+            MiddlewareConfig::LogKinds {} | MiddlewareConfig::LogMsgs {} => Ok(()),
+            MiddlewareConfig::MaxMsgs { max } => match max {
+                0 => contract_err("MaxMsgs must allow at least one message"),
+                _ => Ok(()),
+            },
+            MiddlewareConfig::Recipients { allowed } => {
+                if allowed.is_empty() || allowed.len() > MAX_ALLOWED_RECIPIENTS {
+                    return dyn_contract_err(format!(
+                        "Recipients must allow between 1 and {} addresses",
+                        MAX_ALLOWED_RECIPIENTS
+                    ));
+                }
+                allowed
+                    .iter()
+                    .try_for_each(|addr| chain.validate_address(addr))
+            }
+            MiddlewareConfig::DenomAliases { aliases } => validate_aliases(aliases),
+            MiddlewareConfig::Shadow { middleware } => validate_wrapped(middleware, chain),
+            MiddlewareConfig::Rollout {
+                middleware,
+                percent,
+                ..
+            } => {
+                if *percent > 100 {
+                    return contract_err("Rollout percent must be at most 100");
+                }
+                validate_wrapped(middleware, chain)
+            }
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn Middleware> {
+        match self {
+            MiddlewareConfig::LogKinds {} => Box::new(LogKinds),
+            MiddlewareConfig::LogMsgs {} => Box::new(LogMsgs),
+            MiddlewareConfig::MaxMsgs { max } => Box::new(MaxMsgs(*max)),
+            MiddlewareConfig::Recipients { allowed } => Box::new(Recipients(allowed.clone())),
+            MiddlewareConfig::DenomAliases { aliases } => Box::new(DenomAliases(aliases.clone())),
+            MiddlewareConfig::Shadow { middleware } => Box::new(Shadow(middleware.build())),
+            MiddlewareConfig::Rollout {
+                middleware,
+                percent,
+                ends,
+            } => Box::new(Rollout {
+                middleware: middleware.build(),
+                percent: *percent,
+                ends: *ends,
+            }),
+        }
+    }
+}
+
+/// Shadow and Rollout both decide the enforcement, so neither may wrap the other
+fn validate_wrapped(middleware: &MiddlewareConfig, chain: &ChainProfile) -> Result<()> {
+    match middleware {
+        MiddlewareConfig::Shadow { .. } | MiddlewareConfig::Rollout { .. } => {
+            contract_err("Shadow and Rollout cannot be nested")
+        }
+        middleware => middleware.validate(chain),
+    }
+}
+
+/// Every alias must be unique and stand for a denom which is not an alias itself,
+/// so resolving once gives the same result as resolving again
+fn validate_aliases(aliases: &[DenomAlias]) -> Result<()> {
+    if aliases.is_empty() || aliases.len() > MAX_DENOM_ALIASES {
+        return dyn_contract_err(format!(
+            "DenomAliases must map between 1 and {} aliases",
+            MAX_DENOM_ALIASES
+        ));
+    }
+    for (i, entry) in aliases.iter().enumerate() {
+        if entry.alias.is_empty() || entry.denom.is_empty() {
+            return contract_err("Aliases and denoms must not be empty");
+        }
+        if aliases[..i].iter().any(|a| a.alias == entry.alias) {
+            return dyn_contract_err(format!("Duplicate alias {}", entry.alias));
+        }
+        if aliases.iter().any(|a| a.alias == entry.denom) {
+            return dyn_contract_err(format!("Denom {} is an alias itself", entry.denom));
+        }
+    }
+    Ok(())
+}
+
+/// Builds the chain of the configured middlewares, in order
+pub fn chain(configs: &[MiddlewareConfig]) -> Vec<Box<dyn Middleware>> {
+    configs.iter().map(MiddlewareConfig::build).collect()
+}
+
+pub struct LogKinds;
+
+impl Middleware for LogKinds {
+    fn observe(&self, batch: &Batch) -> Vec<LogAttribute> {
+        count_kinds(&batch.msgs)
+            .iter()
+            .map(|k| log(&format!("msgs_{}", k.kind.as_str()), &k.count.to_string()))
+            .collect()
+    }
+}
+
+/// LogMsgs logs msg_<index> for each message, eg. kind=send;target=cosmos1...;amount=10uatom
+pub struct LogMsgs;
+
+impl Middleware for LogMsgs {
+    fn observe(&self, batch: &Batch) -> Vec<LogAttribute> {
+        batch
+            .msgs
+            .iter()
+            .enumerate()
+            .map(|(i, msg)| log(&format!("msg_{}", i), &describe(msg)))
+            .collect()
+    }
+}
+
+pub fn describe(msg: &CosmosMsg) -> String {
+    let (target, coins) = match msg {
+        CosmosMsg::Send {
+            to_address, amount, ..
+        } => (to_address.as_str(), &amount[..]),
+        CosmosMsg::Contract {
+            contract_addr,
+            send,
+            ..
+        } => (
+            contract_addr.as_str(),
+            send.as_ref().map_or(&[][..], |s| &s[..]),
+        ),
+        CosmosMsg::Opaque { .. } => ("", &[][..]),
+    };
+    let amount: Vec<String> = coins
+        .iter()
+        .map(|c| format!("{}{}", c.amount, c.denom))
+        .collect();
+    format!(
+        "kind={};target={};amount={}",
+        MsgKind::of(msg).as_str(),
+        target,
+        amount.join(",")
+    )
+}
+
+pub struct MaxMsgs(pub u32);
+
+impl Middleware for MaxMsgs {
+    fn validate(&self, batch: &Batch) -> Result<()> {
+        if batch.msgs.len() > self.0 as usize {
+            return dyn_contract_err(format!(
+                "At most {} messages may be reflected at once, {} given",
+                self.0,
+                batch.msgs.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub struct Recipients(pub Vec<HumanAddr>);
+
+impl Middleware for Recipients {
+    fn validate(&self, batch: &Batch) -> Result<()> {
+        for msg in batch.msgs.iter() {
+            if let CosmosMsg::Send { to_address, .. } = msg {
+                if !self.0.contains(to_address) {
+                    let msg = format!("Sending to {} is not allowed", to_address.as_str());
+                    return dyn_contract_err(msg);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct DenomAliases(pub Vec<DenomAlias>);
+
+impl DenomAliases {
+    /// Resolves the aliases, adding up coins which turn out to be of the same denom
+    fn resolve(&self, coins: &mut Vec<Coin>) -> Result<()> {
+        for coin in coins.iter_mut() {
+            if let Some(entry) = self.0.iter().find(|a| a.alias == coin.denom) {
+                coin.denom = entry.denom.clone();
+            }
+        }
+        let duplicate = coins
+            .iter()
+            .enumerate()
+            .any(|(i, c)| coins[..i].iter().any(|o| o.denom == c.denom));
+        if duplicate {
+            *coins = sum_coins(coins.iter())?;
+        }
+        Ok(())
+    }
+}
+
+impl Middleware for DenomAliases {
+    fn transform(&self, batch: &mut Batch) -> Result<()> {
+        for msg in batch.msgs.iter_mut() {
+            match msg {
+                CosmosMsg::Send { amount, .. } => self.resolve(amount)?,
+                CosmosMsg::Contract {
+                    send: Some(send), ..
+                } => self.resolve(send)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shadow runs a middleware without enforcing it or letting it rewrite the batch
+pub struct Shadow(pub Box<dyn Middleware>);
+
+impl Middleware for Shadow {
+    fn validate(&self, batch: &Batch) -> Result<()> {
+        self.0.validate(batch)
+    }
+
+    fn observe(&self, batch: &Batch) -> Vec<LogAttribute> {
+        self.0.observe(batch)
+    }
+
+    fn enforcement(&self, _batch: &Batch) -> Enforcement {
+        Enforcement::Shadow
+    }
+}
+
+/// Rollout enforces a middleware for a deterministic share of the executions
+pub struct Rollout {
+    pub middleware: Box<dyn Middleware>,
+    pub percent: u32,
+    pub ends: Expiration,
+}
+
+impl Middleware for Rollout {
+    fn validate(&self, batch: &Batch) -> Result<()> {
+        self.middleware.validate(batch)
+    }
+
+    fn transform(&self, batch: &mut Batch) -> Result<()> {
+        match self.enforcement(batch) {
+            Enforcement::Enforce => self.middleware.transform(batch),
+            Enforcement::Shadow => Ok(()),
+        }
+    }
+
+    fn observe(&self, batch: &Batch) -> Vec<LogAttribute> {
+        self.middleware.observe(batch)
+    }
+
+    fn enforcement(&self, batch: &Batch) -> Enforcement {
+        if self.ends.is_expired(batch.env) || rollout_bucket(batch.execution_id) < self.percent {
+            Enforcement::Enforce
+        } else {
+            Enforcement::Shadow
+        }
+    }
+}
+
+/// Spreads execution ids evenly over 0..100, so consecutive ones do not land together
+pub fn rollout_bucket(execution_id: u64) -> u32 {
+    select::index(&execution_id.to_be_bytes(), 100) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm::mock::{mock_env, MockApi};
+    use cosmwasm::types::coin;
+
+    fn send(to: &str, amount: &str) -> CosmosMsg {
+        CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from(to),
+            amount: coin(amount, "uatom"),
+        }
+    }
+
+    /// Drops sends of nothing
+    struct DropEmpty;
+
+    impl Middleware for DropEmpty {
+        fn transform(&self, batch: &mut Batch) -> Result<()> {
+            batch.msgs.retain(|msg| match msg {
+                CosmosMsg::Send { amount, .. } => amount.iter().any(|c| c.amount != "0"),
+                _ => true,
+            });
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn later_middlewares_see_the_transformed_batch() {
+        let env = mock_env(&MockApi::new(20), "cosmos1owner", &[], &[]);
+        let mut batch = Batch {
+            env: &env,
+            execution_id: 1,
+            msgs: vec![
+                send("cosmos1a", "0"),
+                send("cosmos1b", "5"),
+                send("cosmos1b", "0"),
+            ],
+            tags: &[],
+        };
+        let chain: Vec<Box<dyn Middleware>> = vec![
+            Box::new(DropEmpty),
+            Box::new(MaxMsgs(1)),
+            Box::new(LogKinds),
+        ];
+        let logs = run(&chain, &mut batch).unwrap().logs;
+        assert_eq!(vec![send("cosmos1b", "5")], batch.msgs);
+        assert_eq!(vec![log("msgs_send", "1")], logs);
+
+        // without the transform, there are too many messages
+        let mut batch = Batch {
+            env: &env,
+            execution_id: 1,
+            msgs: vec![send("cosmos1a", "0"), send("cosmos1b", "5")],
+            tags: &[],
+        };
+        assert!(run(&chain[1..], &mut batch).is_err());
+    }
+
+    #[test]
+    fn recipients_only_allows_listed_sends() {
+        let env = mock_env(&MockApi::new(20), "cosmos1owner", &[], &[]);
+        let recipients = Recipients(vec![HumanAddr::from("cosmos1a")]);
+        let batch = Batch {
+            env: &env,
+            execution_id: 1,
+            msgs: vec![send("cosmos1a", "5")],
+            tags: &[],
+        };
+        assert!(recipients.validate(&batch).is_ok());
+        let batch = Batch {
+            msgs: vec![send("cosmos1a", "5"), send("cosmos1b", "5")],
+            ..batch
+        };
+        let err = recipients.validate(&batch).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Sending to cosmos1b is not allowed"));
+    }
+
+    #[test]
+    fn denom_aliases_resolve_all_coins() {
+        let usdc = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        let aliases = DenomAliases(vec![DenomAlias {
+            alias: "usdc".to_string(),
+            denom: usdc.to_string(),
+        }]);
+        let env = mock_env(&MockApi::new(20), "cosmos1owner", &[], &[]);
+        let pay = |coins: Vec<Coin>| CosmosMsg::Send {
+            from_address: HumanAddr::from("cosmos2contract"),
+            to_address: HumanAddr::from("cosmos1vendor"),
+            amount: coins,
+        };
+        let call = |coins: Vec<Coin>| CosmosMsg::Contract {
+            contract_addr: HumanAddr::from("cosmos1pool"),
+            msg: cosmwasm::encoding::Binary(b"{}".to_vec()),
+            send: Some(coins),
+        };
+        let mut both = coin("5", "usdc");
+        both.extend(coin("7", usdc));
+        both.extend(coin("1", "uatom"));
+        let mut batch = Batch {
+            env: &env,
+            execution_id: 1,
+            msgs: vec![pay(coin("5", "usdc")), call(coin("2", "usdc")), pay(both)],
+            tags: &[],
+        };
+        aliases.transform(&mut batch).unwrap();
+
+        let mut merged = coin("12", usdc);
+        merged.extend(coin("1", "uatom"));
+        let expected = vec![pay(coin("5", usdc)), call(coin("2", usdc)), pay(merged)];
+        assert_eq!(expected, batch.msgs);
+        // resolving again changes nothing
+        aliases.transform(&mut batch).unwrap();
+        assert_eq!(expected, batch.msgs);
+    }
+
+    #[test]
+    fn log_msgs_describes_each_message() {
+        let env = mock_env(&MockApi::new(20), "cosmos1owner", &[], &[]);
+        let mut send_two = send("cosmos1a", "5");
+        if let CosmosMsg::Send { amount, .. } = &mut send_two {
+            amount.extend(coin("1", "token"));
+        }
+        let batch = Batch {
+            env: &env,
+            execution_id: 1,
+            msgs: vec![
+                send_two,
+                CosmosMsg::Contract {
+                    contract_addr: HumanAddr::from("cosmos1pool"),
+                    msg: cosmwasm::encoding::Binary(b"{}".to_vec()),
+                    send: None,
+                },
+            ],
+            tags: &[],
+        };
+        let expected = vec![
+            log("msg_0", "kind=send;target=cosmos1a;amount=5uatom,1token"),
+            log("msg_1", "kind=contract;target=cosmos1pool;amount="),
+        ];
+        assert_eq!(expected, LogMsgs.observe(&batch));
+    }
+
+    #[test]
+    fn shadowed_middlewares_only_log() {
+        let env = mock_env(&MockApi::new(20), "cosmos1owner", &[], &[]);
+        let msgs = vec![send("cosmos1a", "0"), send("cosmos1b", "5")];
+        let mut batch = Batch {
+            env: &env,
+            execution_id: 1,
+            msgs: msgs.clone(),
+            tags: &[],
+        };
+        let chain: Vec<Box<dyn Middleware>> = vec![
+            Box::new(Shadow(Box::new(DropEmpty))),
+            Box::new(Shadow(Box::new(MaxMsgs(1)))),
+            Box::new(Shadow(Box::new(Recipients(vec![HumanAddr::from(
+                "cosmos1a",
+            )])))),
+        ];
+        let outcome = run(&chain, &mut batch).unwrap();
+        assert_eq!(msgs, batch.msgs);
+        assert_eq!(Verdict::Accepted, outcome.verdicts[0]);
+        assert_eq!(3, outcome.verdicts.len());
+        let logs = outcome.logs;
+        assert_eq!(2, logs.len());
+        assert_eq!("shadow_rejected", logs[0].key);
+        assert!(logs[0].value.starts_with("1: "));
+        assert!(logs[0].value.contains("At most 1 messages"));
+        assert!(logs[1].value.starts_with("2: "));
+        assert!(logs[1].value.contains("Sending to cosmos1b"));
+
+        // verdicts of enforced middlewares stop the batch
+        let mut batch = Batch {
+            env: &env,
+            execution_id: 1,
+            msgs,
+            tags: &[],
+        };
+        let shadowed = judge(chain[1].as_ref(), &mut batch).unwrap();
+        assert!(match shadowed {
+            Verdict::Shadowed { .. } => true,
+            _ => false,
+        });
+        assert!(judge(&MaxMsgs(1), &mut batch).is_err());
+        assert_eq!(Verdict::Accepted, judge(&MaxMsgs(2), &mut batch).unwrap());
+    }
+
+    #[test]
+    fn rollouts_enforce_a_share_of_executions() {
+        let mut env = mock_env(&MockApi::new(20), "cosmos1owner", &[], &[]);
+        let rollout = |percent| Rollout {
+            middleware: Box::new(MaxMsgs(1)),
+            percent,
+            ends: Expiration::AtHeight(12_400),
+        };
+        let msgs = vec![send("cosmos1a", "5"), send("cosmos1b", "5")];
+        let count_enforced = |env: &Env, rollout: &Rollout| {
+            (1..=1000u64)
+                .filter(|id| {
+                    let mut batch = Batch {
+                        env,
+                        execution_id: *id,
+                        msgs: msgs.clone(),
+                        tags: &[],
+                    };
+                    judge(rollout, &mut batch).is_err()
+                })
+                .count()
+        };
+        assert_eq!(0, count_enforced(&env, &rollout(0)));
+        assert_eq!(1000, count_enforced(&env, &rollout(100)));
+        let quarter = count_enforced(&env, &rollout(25));
+        assert!(quarter > 200 && quarter < 300, "{} enforced", quarter);
+
+        // once the rollout ends, the middleware is enforced for every execution
+        env.block.height = 12_400;
+        assert_eq!(1000, count_enforced(&env, &rollout(0)));
+    }
+
+    #[test]
+    fn configs_are_validated() {
+        let chain = ChainProfile {
+            bech32_prefix: "cosmos".to_string(),
+            native_denom: "uatom".to_string(),
+            block_time_secs: 5,
+            default_denom: None,
+        };
+        assert!(MiddlewareConfig::LogKinds {}.validate(&chain).is_ok());
+        assert!(MiddlewareConfig::MaxMsgs { max: 0 }
+            .validate(&chain)
+            .is_err());
+        assert!(MiddlewareConfig::MaxMsgs { max: 3 }
+            .validate(&chain)
+            .is_ok());
+        let recipients = |allowed: Vec<&str>| MiddlewareConfig::Recipients {
+            allowed: allowed.into_iter().map(HumanAddr::from).collect(),
+        };
+        assert!(recipients(vec![]).validate(&chain).is_err());
+        assert!(recipients(vec!["cosmos1a"]).validate(&chain).is_ok());
+        assert!(recipients(vec!["terra1a"]).validate(&chain).is_err());
+
+        let aliases = |pairs: Vec<(&str, &str)>| MiddlewareConfig::DenomAliases {
+            aliases: pairs
+                .into_iter()
+                .map(|(alias, denom)| DenomAlias {
+                    alias: alias.to_string(),
+                    denom: denom.to_string(),
+                })
+                .collect(),
+        };
+        assert!(aliases(vec![("usdc", "ibc/27"), ("atom", "uatom")])
+            .validate(&chain)
+            .is_ok());
+        assert!(aliases(vec![]).validate(&chain).is_err());
+        assert!(aliases(vec![("usdc", "ibc/27"), ("usdc", "ibc/28")])
+            .validate(&chain)
+            .is_err());
+        assert!(aliases(vec![("usdc", "ibc/27"), ("ibc/27", "ibc/28")])
+            .validate(&chain)
+            .is_err());
+        assert!(aliases(vec![("usdc", "")]).validate(&chain).is_err());
+
+        let shadow = |middleware| MiddlewareConfig::Shadow {
+            middleware: Box::new(middleware),
+        };
+        assert!(shadow(MiddlewareConfig::MaxMsgs { max: 3 })
+            .validate(&chain)
+            .is_ok());
+        assert!(shadow(MiddlewareConfig::MaxMsgs { max: 0 })
+            .validate(&chain)
+            .is_err());
+        let nested = shadow(shadow(MiddlewareConfig::LogKinds {}));
+        assert!(nested.validate(&chain).is_err());
+        let rollout = |percent| MiddlewareConfig::Rollout {
+            middleware: Box::new(MiddlewareConfig::MaxMsgs { max: 3 }),
+            percent,
+            ends: Expiration::AtHeight(12_400),
+        };
+        assert!(rollout(100).validate(&chain).is_ok());
+        assert!(rollout(101).validate(&chain).is_err());
+        assert!(shadow(rollout(50)).validate(&chain).is_err());
+    }
+}
@@ -1,26 +1,1975 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm::types::{HumanAddr, CosmosMsg};
+use cosmwasm::encoding::Binary;
+use cosmwasm::types::{log, Coin, CosmosMsg, HumanAddr, LogAttribute};
+
+use crate::middleware::MiddlewareConfig;
+use crate::reference::EntityKind;
+use crate::state::{
+    Amount, Attestation, Budget, ChainProfile, ContractVersion, Cooldown, DenomMetadata, Duration,
+    ExecutionWindow, Expiration, GasHeuristic, KindCount, MacroParam, MacroQuota, Milestone,
+    MsgKind, PendingConfig, ProposalStatus, Rule, SpendLimit, Status, TagBudget, TimeRange,
+    TravelMemo, WasmIntent,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InitMsg {}
+pub struct InitMsg {
+    pub chain: ChainProfile,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum HandleMsg {
-    ReflectMsg { msg: CosmosMsg},
-    ChangeOwner { owner: HumanAddr },
+    /// Emits the msgs in order as one execution.
+    /// Tags must be part of the taxonomy set by SetTags.
+    /// The memo is kept with the execution, and required for transfers above the travel rule.
+    /// The response data is a ReflectResult.
+    ReflectMsg {
+        msgs: Vec<CosmosMsg>,
+        tags: Vec<String>,
+        memo: Option<TravelMemo>,
+    },
+    ChangeOwner {
+        owner: HumanAddr,
+    },
+    RegisterDenom {
+        denom: String,
+        symbol: String,
+        exponent: u32,
+    },
+    /// Stores the gas an execution actually used, as observed off-chain
+    ReportGas {
+        execution_id: u64,
+        gas_used: u64,
+    },
+    /// Turns this into a joint account with the given co-owners (or back again if empty)
+    SetCoOwners {
+        co_owners: Vec<HumanAddr>,
+    },
+    /// Switches admin proposals from unanimity to a weighted threshold (or back if None)
+    SetAdminWeights {
+        weights: Vec<WeightMsg>,
+        threshold: Option<u64>,
+    },
+    /// On joint accounts, admin actions are proposed by one owner and run once all approved
+    ProposeAdmin {
+        msg: Box<HandleMsg>,
+    },
+    ApproveAdmin {
+        id: u64,
+    },
+    /// Limits how often the action may be used, None removes the limit
+    SetCooldown {
+        action: String,
+        period: Option<Duration>,
+    },
+    SetOperators {
+        operators: Vec<HumanAddr>,
+    },
+    /// Replaces the whole operator set. The nonce must increase with every manifest,
+    /// so an older manifest cannot be replayed.
+    ApplyOperatorManifest {
+        operators: Vec<OperatorSpec>,
+        manifest_nonce: u64,
+    },
+    /// Operators prepare a batch, which an owner may later execute as is.
+    /// If receipt_contract is set, it is sent a ReceiptMsg once the batch is executed.
+    StageBatch {
+        msgs: Vec<CosmosMsg>,
+        memo: String,
+        receipt_contract: Option<HumanAddr>,
+        tags: Vec<String>,
+    },
+    ExecuteStaged {
+        id: u64,
+    },
+    /// Replaces the taxonomy executions can be tagged with
+    SetTags {
+        tags: Vec<String>,
+    },
+    /// Limits what executions with the tag may send per period, None removes the budget
+    SetTagBudget {
+        tag: String,
+        budget: Option<Budget>,
+    },
+    SetRecovery {
+        recovery: Option<HumanAddr>,
+    },
+    /// Pauses the contract and sends its whole balance to the recovery address.
+    /// Any owner may evacuate to the recovery address, only admins may choose another one.
+    /// The coins sent are ordered by denom.
+    Evacuate {
+        to: Option<HumanAddr>,
+    },
+    /// Deprecated, use Unpause
+    Resume {},
+    /// Stops everything but changing the owner, until an admin unpauses. Any owner may pause.
+    Pause {},
+    /// Lifts the pause Pause or Evacuate put in place
+    Unpause {},
+    /// Announces a config change which takes effect at activate_at.
+    /// Only one change may be pending at a time.
+    ScheduleConfig {
+        change: ConfigOverride,
+        activate_at: Expiration,
+    },
+    /// Drops the pending config change, as long as it is not active yet
+    CancelPending {},
+    /// Applies the pending config change once it is due, anyone may trigger this.
+    /// Any other handle applies a due change first as well.
+    ActivatePending {},
+    /// Retires the contract after an evacuation: clears the configuration and
+    /// rejects all further handles. Queries keep working.
+    /// If a successor is given, handles fail with MovedPermanently pointing there.
+    Decommission {
+        successor: Option<HumanAddr>,
+    },
+    /// Fixes a violation VerifyState reported, as long as it is still there.
+    /// Only some kinds of violations can be repaired.
+    RepairState {
+        token: String,
+    },
+    /// Limits the serialized size of the messages of one call, None removes the limit
+    SetMsgByteLimit {
+        limit: Option<u64>,
+    },
+    /// Calls each target contract with the same msg and funds, as one execution.
+    /// Messages are not isolated from each other: if one call fails, all fail.
+    /// The response data is an ExecuteManyResult.
+    ExecuteMany {
+        targets: Vec<HumanAddr>,
+        msg: Binary,
+        send: Option<Vec<Coin>>,
+        tags: Vec<String>,
+    },
+    /// Pays every entry from the contract balance as one execution tagged with tag.
+    /// The sends are ordered by recipient (entries for the same recipient keep their order),
+    /// the coins of each send by denom.
+    RunPayroll {
+        entries: Vec<PayrollEntry>,
+        tag: String,
+    },
+    /// Sets up a grant whose milestones the approver releases one by one
+    CreateGrant {
+        grantee: HumanAddr,
+        approver: HumanAddr,
+        milestones: Vec<Milestone>,
+    },
+    /// Pays the next milestone of the grant to the grantee, only the approver may call this
+    ApproveMilestone {
+        id: u64,
+    },
+    /// Offers ownership to owner, who has to accept it before it expires.
+    /// Replaces any transfer offered before.
+    ProposeNewOwner {
+        owner: HumanAddr,
+        expires: Expiration,
+    },
+    /// Makes the sender the owner, if ownership was offered to it and has not expired
+    AcceptOwnership {},
+    /// Cancels the milestones of the grant not yet paid, their funds stay with the contract
+    Clawback {
+        id: u64,
+        reason: String,
+    },
+    /// Adds a co-owner, turning this into a joint account if it was not one yet
+    AddAdmin {
+        addr: HumanAddr,
+    },
+    /// Removes the owner or a co-owner, the last remaining owner cannot be removed.
+    /// If the main owner is removed, the first co-owner takes its place.
+    RemoveAdmin {
+        addr: HumanAddr,
+    },
+    /// Allows reflected calls to the contract. The first one added turns on the allowlist,
+    /// from then on calls to any other contract are rejected.
+    AddAllowedTarget {
+        contract: HumanAddr,
+    },
+    /// Removing the last allowed target leaves no contract that may be called
+    RemoveAllowedTarget {
+        contract: HumanAddr,
+    },
+    /// Offers give to the counterparty in exchange for want, until the swap expires
+    CreateSwap {
+        counterparty: HumanAddr,
+        give: Vec<Coin>,
+        want: Vec<Coin>,
+        expires: Expiration,
+    },
+    /// Sent by the counterparty with exactly the wanted coins, pays out the given ones
+    FillSwap {
+        id: u64,
+    },
+    /// Lets grantee reflect messages of the given kinds until expires_at_height,
+    /// replacing any permission it had before
+    GrantPermission {
+        grantee: HumanAddr,
+        msg_types: Vec<MsgKind>,
+        expires_at_height: u64,
+    },
+    RevokePermission {
+        grantee: HumanAddr,
+    },
+    /// Offers sell at a price falling from start_price to end_price over the duration
+    CreateAuction {
+        sell: Vec<Coin>,
+        start_price: Coin,
+        end_price: Coin,
+        duration: Duration,
+    },
+    /// Sent by anyone with at least the current price, pays out the sold coins and any excess
+    BuyAuction {
+        id: u64,
+    },
+    CancelAuction {
+        id: u64,
+    },
+    /// Queues msgs, which anyone may execute with ExecuteScheduled once execute_after has passed,
+    /// and while the calendar is open if one is given
+    Schedule {
+        msgs: Vec<CosmosMsg>,
+        execute_after: Expiration,
+        calendar: Option<String>,
+    },
+    ExecuteScheduled {
+        id: u64,
+    },
+    CancelScheduled {
+        id: u64,
+    },
+    /// Sent by anyone with funds, which are counted as revenue from the source tag
+    Deposit {
+        tag: String,
+    },
+    /// Replaces who revenue is distributed to, and in which proportions
+    SetStakeholders {
+        stakeholders: Vec<StakeholderMsg>,
+    },
+    /// Pays out the revenue of the source tag in a period which has ended, once only.
+    /// The sends are ordered by recipient, the coins of each send by denom.
+    DistributeRevenue {
+        tag: String,
+        period: u64,
+    },
+    /// None stops sending deposit receipts
+    SetDepositHook {
+        contract: Option<HumanAddr>,
+    },
+    /// Only lets these addresses (and the owners) send funds along with a call.
+    /// None accepts funds from everyone again.
+    SetDepositors {
+        depositors: Option<Vec<HumanAddr>>,
+    },
+    /// Requires a memo for messages sending more than one of these amounts, None lifts it
+    SetTravelRule {
+        thresholds: Option<Vec<Amount>>,
+    },
+    /// Sent by anyone to suggest msgs, which an owner may then approve or reject.
+    /// If intents are given, there must be one for each contract call, in order.
+    Propose {
+        msgs: Vec<CosmosMsg>,
+        description: String,
+        intents: Option<Vec<WasmIntent>>,
+    },
+    /// Dispatches the msgs of a pending proposal
+    Approve {
+        id: u64,
+    },
+    Reject {
+        id: u64,
+    },
+    /// Caps what reflected messages may send of the denom per period_secs of block time,
+    /// replacing any limit it had before
+    SetSpendLimit {
+        denom: String,
+        amount: String,
+        period_secs: u64,
+    },
+    RemoveSpendLimit {
+        denom: String,
+    },
+    /// Stops an operator from staging batches, keeping it configured
+    SuspendOperator {
+        operator: HumanAddr,
+    },
+    ReinstateOperator {
+        operator: HumanAddr,
+    },
+    /// Only lets the operator act inside the window, None lifts it. Owners are never limited.
+    SetOperatorWindow {
+        operator: HumanAddr,
+        window: Option<ExecutionWindow>,
+    },
+    /// Creates or replaces the named calendar
+    SetCalendar {
+        name: String,
+        allowed: Vec<TimeRange>,
+        blackouts: Vec<TimeRange>,
+    },
+    /// Only lets the operator act while the calendar is open, None unassigns it.
+    /// Owners are never limited.
+    SetOperatorCalendar {
+        operator: HumanAddr,
+        calendar: Option<String>,
+    },
+    /// Sends the coins, or the whole balance if None, to the given address
+    Withdraw {
+        to: HumanAddr,
+        amount: Option<Vec<Coin>>,
+    },
+    /// Lets threshold of the guardians replace the owner, if they agree within window.
+    /// An empty list removes the guardians. Either way, a recovery in progress is dropped.
+    SetGuardians {
+        guardians: Vec<HumanAddr>,
+        threshold: u64,
+        window: Duration,
+    },
+    /// Starts a recovery to new_owner, counting as the approval of the calling guardian.
+    /// Only one recovery may be in progress at a time.
+    InitiateRecovery {
+        new_owner: HumanAddr,
+    },
+    /// Approves the recovery in progress, which replaces the owner once threshold is reached
+    ApproveRecovery {},
+    /// Drops the recovery in progress, any owner still holding their key may do so
+    CancelRecovery {},
+    /// Adds and removes addresses reflected messages may not send funds to
+    Denylist {
+        add: Vec<HumanAddr>,
+        remove: Vec<HumanAddr>,
+    },
+    /// Sends messages of these kinds through the proposal flow, where someone other than the
+    /// proposer must approve them. None lets owners reflect them directly again.
+    SetFourEyes {
+        kinds: Option<Vec<MsgKind>>,
+    },
+    /// Charges callers which reflect through a permission this fee per call.
+    /// Owners never pay, an empty fee removes it.
+    SetFee {
+        fee: Vec<Amount>,
+    },
+    /// Sends the fees collected so far to the given address
+    WithdrawFees {
+        to: HumanAddr,
+    },
+    /// Replaces the rules contracts, which are sent a RulesetMsg ahead of every reflected
+    /// batch in the given order. Each one may veto the batch by failing, so all must allow it.
+    /// An empty list removes them.
+    SetRulesets {
+        contracts: Vec<HumanAddr>,
+    },
+    /// Leaves the contract without owners for good. To confirm, pass the contract address.
+    /// Guardians and a pending owner are dropped too, permissions keep working until they expire.
+    RenounceOwnership {
+        confirm: String,
+    },
+    /// Replaces the rules reflected messages are checked against. A message is decided by the
+    /// first rule matching it, messages no rule matches are allowed. An empty list removes them.
+    SetRules {
+        rules: Vec<Rule>,
+    },
+    /// Stores the value under the key as given, eg. for integration tests to keep data
+    /// between calls. Overwrites any value stored under the key before.
+    SetData {
+        key: String,
+        value: Binary,
+    },
+    RemoveData {
+        key: String,
+    },
+    /// Lets key_addr reflect up to max_msgs messages in total until expires,
+    /// replacing any session it had before
+    CreateSession {
+        key_addr: HumanAddr,
+        max_msgs: u64,
+        expires: Expiration,
+    },
+    /// Holds back batches whose messages together send more than one of the thresholds,
+    /// until the co-signer confirms them. None lets every batch go out directly again.
+    SetCosigner {
+        cosigner: Option<HumanAddr>,
+        thresholds: Vec<Amount>,
+    },
+    /// Sent by the co-signer, dispatches a queued batch as its sender
+    ConfirmExecution {
+        id: u64,
+    },
+    /// Sent by the co-signer or the sender, drops a queued batch
+    CancelExecution {
+        id: u64,
+    },
+    /// Lets grantee execute one exact message once, until expires.
+    /// msg_hash is the SHA-256 of the message as the contract serializes it, see state::msg_hash.
+    AuthorizeOnce {
+        grantee: HumanAddr,
+        msg_hash: Binary,
+        expires: Expiration,
+    },
+    /// Sent by the grantee of a one-time authorization, executes the message it was given for
+    ExecuteAuthorized {
+        msg: CosmosMsg,
+    },
+    /// Upgrades up to limit records of the namespace to their current layout, going on where
+    /// the last call stopped. Records are also upgraded when they are written on access, this
+    /// finishes a migration ahead of that. The response data is a MigrateRecordsResult.
+    MigrateRecords {
+        namespace: MigrateNamespace,
+        limit: Option<u32>,
+    },
+    /// Replaces the middlewares every reflected batch runs through, in order.
+    /// An empty list removes them all.
+    SetMiddlewares {
+        middlewares: Vec<MiddlewareConfig>,
+    },
+    /// Stores the signature of signer over the config checksum, replacing the last one.
+    /// It must be the checksum of the active configuration.
+    Attest {
+        checksum: String,
+        signer: String,
+        signature: Binary,
+    },
+    /// If max_age is set, batches are only dispatched under an attested configuration,
+    /// attested at most that long ago. Changing anything else needs a new attestation.
+    RequireAttestation {
+        max_age: Option<Duration>,
+    },
+    /// Sends the full batch of every execution to this contract as an ArchiveMsg, keeping only
+    /// its hash. None stops archiving, eg. while the archive is down.
+    SetArchive {
+        contract: Option<HumanAddr>,
+    },
+    /// Replaces the gas assumed per message kind by SuggestBatching. An empty list leaves
+    /// it to the reported gas.
+    SetGasHeuristics {
+        heuristics: Vec<GasHeuristic>,
+    },
+    /// Runs the lookup and reflects the template with its value in place of "$lookup", in the
+    /// same transaction. In a send it replaces a coin amount, in a contract call the JSON
+    /// string "$lookup" in msg. Otherwise it reflects like ReflectMsg.
+    ExecuteWithLookup {
+        lookup: Lookup,
+        template: CosmosMsg,
+        tags: Vec<String>,
+        memo: Option<TravelMemo>,
+    },
+    /// Saves msgs under name, replacing the macro saved there. Each param must be used,
+    /// as a coin amount of "$name" or the JSON string "$name" in the msg of a contract call.
+    SaveMacro {
+        name: String,
+        msgs: Vec<CosmosMsg>,
+        params: Vec<MacroParam>,
+        runners: Vec<HumanAddr>,
+        quota: Option<MacroQuota>,
+    },
+    RemoveMacro {
+        name: String,
+    },
+    /// Sent by an owner or a runner of the macro, reflects its msgs with the args filled in.
+    /// Every param needs an arg.
+    RunMacro {
+        name: String,
+        args: Vec<MacroArg>,
+    },
+    /// Runs msg only if the configuration is still at the config_version read from GetConfig,
+    /// so two admins editing at once cannot overwrite each other's change unknowingly
+    IfConfigVersion {
+        config_version: u64,
+        msg: Box<HandleMsg>,
+    },
+    /// Testnet builds only. Runs msg with all its checks and logs, but logs the messages it
+    /// would send instead of sending them and keeps none of its writes.
+    #[cfg(feature = "dry-run")]
+    DryRun {
+        msg: Box<HandleMsg>,
+    },
+    /// Sent by an owner after deploying, runs a probe through reflecting, scheduling, the
+    /// data entries and the history, and reports in a SelfTestResult which of them work.
+    /// Nothing is kept.
+    /// The probe is a call to echo, which is also sent for real if reflecting it passed,
+    /// or else a send of nothing to the contract itself.
+    SelfTest {
+        echo: Option<HumanAddr>,
+    },
+}
+
+impl HandleMsg {
+    /// Names every handle action, eg. for cooldowns
+    pub const ACTIONS: &'static [&'static str] = &[
+        "reflect",
+        "change_owner",
+        "register_denom",
+        "report_gas",
+        "set_co_owners",
+        "set_admin_weights",
+        "propose_admin",
+        "approve_admin",
+        "set_cooldown",
+        "set_operators",
+        "apply_operator_manifest",
+        "stage_batch",
+        "execute_staged",
+        "set_tags",
+        "set_tag_budget",
+        "set_recovery",
+        "evacuate",
+        "resume",
+        "pause",
+        "unpause",
+        "schedule_config",
+        "cancel_pending",
+        "activate_pending",
+        "decommission",
+        "repair_state",
+        "set_msg_byte_limit",
+        "execute_many",
+        "run_payroll",
+        "create_grant",
+        "approve_milestone",
+        "propose_new_owner",
+        "accept_ownership",
+        "clawback",
+        "add_admin",
+        "remove_admin",
+        "add_allowed_target",
+        "remove_allowed_target",
+        "create_swap",
+        "fill_swap",
+        "grant_permission",
+        "revoke_permission",
+        "create_auction",
+        "buy_auction",
+        "cancel_auction",
+        "schedule",
+        "execute_scheduled",
+        "cancel_scheduled",
+        "deposit",
+        "set_stakeholders",
+        "distribute_revenue",
+        "set_deposit_hook",
+        "set_depositors",
+        "set_travel_rule",
+        "propose",
+        "approve",
+        "reject",
+        "set_spend_limit",
+        "remove_spend_limit",
+        "suspend_operator",
+        "reinstate_operator",
+        "set_operator_window",
+        "set_calendar",
+        "set_operator_calendar",
+        "withdraw",
+        "set_guardians",
+        "initiate_recovery",
+        "approve_recovery",
+        "cancel_recovery",
+        "denylist",
+        "set_four_eyes",
+        "set_fee",
+        "withdraw_fees",
+        "set_rulesets",
+        "renounce_ownership",
+        "set_rules",
+        "set_data",
+        "remove_data",
+        "create_session",
+        "set_cosigner",
+        "confirm_execution",
+        "cancel_execution",
+        "authorize_once",
+        "execute_authorized",
+        "migrate_records",
+        "set_middlewares",
+        "attest",
+        "require_attestation",
+        "set_archive",
+        "set_gas_heuristics",
+        "execute_with_lookup",
+        "save_macro",
+        "remove_macro",
+        "run_macro",
+        "if_config_version",
+        #[cfg(feature = "dry-run")]
+        "dry_run",
+        "self_test",
+    ];
+
+    pub fn action(&self) -> &'static str {
+        match self {
+            HandleMsg::ReflectMsg { .. } => "reflect",
+            HandleMsg::ChangeOwner { .. } => "change_owner",
+            HandleMsg::RegisterDenom { .. } => "register_denom",
+            HandleMsg::ReportGas { .. } => "report_gas",
+            HandleMsg::SetCoOwners { .. } => "set_co_owners",
+            HandleMsg::SetAdminWeights { .. } => "set_admin_weights",
+            HandleMsg::ProposeAdmin { .. } => "propose_admin",
+            HandleMsg::ApproveAdmin { .. } => "approve_admin",
+            HandleMsg::SetCooldown { .. } => "set_cooldown",
+            HandleMsg::SetOperators { .. } => "set_operators",
+            HandleMsg::ApplyOperatorManifest { .. } => "apply_operator_manifest",
+            HandleMsg::StageBatch { .. } => "stage_batch",
+            HandleMsg::ExecuteStaged { .. } => "execute_staged",
+            HandleMsg::SetTags { .. } => "set_tags",
+            HandleMsg::SetTagBudget { .. } => "set_tag_budget",
+            HandleMsg::SetRecovery { .. } => "set_recovery",
+            HandleMsg::Evacuate { .. } => "evacuate",
+            HandleMsg::Resume { .. } => "resume",
+            HandleMsg::Pause { .. } => "pause",
+            HandleMsg::Unpause { .. } => "unpause",
+            HandleMsg::ScheduleConfig { .. } => "schedule_config",
+            HandleMsg::CancelPending { .. } => "cancel_pending",
+            HandleMsg::ActivatePending { .. } => "activate_pending",
+            HandleMsg::Decommission { .. } => "decommission",
+            HandleMsg::RepairState { .. } => "repair_state",
+            HandleMsg::SetMsgByteLimit { .. } => "set_msg_byte_limit",
+            HandleMsg::ExecuteMany { .. } => "execute_many",
+            HandleMsg::RunPayroll { .. } => "run_payroll",
+            HandleMsg::CreateGrant { .. } => "create_grant",
+            HandleMsg::ApproveMilestone { .. } => "approve_milestone",
+            HandleMsg::ProposeNewOwner { .. } => "propose_new_owner",
+            HandleMsg::AcceptOwnership { .. } => "accept_ownership",
+            HandleMsg::Clawback { .. } => "clawback",
+            HandleMsg::AddAdmin { .. } => "add_admin",
+            HandleMsg::RemoveAdmin { .. } => "remove_admin",
+            HandleMsg::AddAllowedTarget { .. } => "add_allowed_target",
+            HandleMsg::RemoveAllowedTarget { .. } => "remove_allowed_target",
+            HandleMsg::CreateSwap { .. } => "create_swap",
+            HandleMsg::FillSwap { .. } => "fill_swap",
+            HandleMsg::GrantPermission { .. } => "grant_permission",
+            HandleMsg::RevokePermission { .. } => "revoke_permission",
+            HandleMsg::CreateAuction { .. } => "create_auction",
+            HandleMsg::BuyAuction { .. } => "buy_auction",
+            HandleMsg::CancelAuction { .. } => "cancel_auction",
+            HandleMsg::Schedule { .. } => "schedule",
+            HandleMsg::ExecuteScheduled { .. } => "execute_scheduled",
+            HandleMsg::CancelScheduled { .. } => "cancel_scheduled",
+            HandleMsg::Deposit { .. } => "deposit",
+            HandleMsg::SetStakeholders { .. } => "set_stakeholders",
+            HandleMsg::DistributeRevenue { .. } => "distribute_revenue",
+            HandleMsg::SetDepositHook { .. } => "set_deposit_hook",
+            HandleMsg::SetDepositors { .. } => "set_depositors",
+            HandleMsg::SetTravelRule { .. } => "set_travel_rule",
+            HandleMsg::Propose { .. } => "propose",
+            HandleMsg::Approve { .. } => "approve",
+            HandleMsg::Reject { .. } => "reject",
+            HandleMsg::SetSpendLimit { .. } => "set_spend_limit",
+            HandleMsg::RemoveSpendLimit { .. } => "remove_spend_limit",
+            HandleMsg::SuspendOperator { .. } => "suspend_operator",
+            HandleMsg::ReinstateOperator { .. } => "reinstate_operator",
+            HandleMsg::SetOperatorWindow { .. } => "set_operator_window",
+            HandleMsg::SetCalendar { .. } => "set_calendar",
+            HandleMsg::SetOperatorCalendar { .. } => "set_operator_calendar",
+            HandleMsg::Withdraw { .. } => "withdraw",
+            HandleMsg::SetGuardians { .. } => "set_guardians",
+            HandleMsg::InitiateRecovery { .. } => "initiate_recovery",
+            HandleMsg::ApproveRecovery { .. } => "approve_recovery",
+            HandleMsg::CancelRecovery { .. } => "cancel_recovery",
+            HandleMsg::Denylist { .. } => "denylist",
+            HandleMsg::SetFourEyes { .. } => "set_four_eyes",
+            HandleMsg::SetFee { .. } => "set_fee",
+            HandleMsg::WithdrawFees { .. } => "withdraw_fees",
+            HandleMsg::SetRulesets { .. } => "set_rulesets",
+            HandleMsg::RenounceOwnership { .. } => "renounce_ownership",
+            HandleMsg::SetRules { .. } => "set_rules",
+            HandleMsg::SetData { .. } => "set_data",
+            HandleMsg::RemoveData { .. } => "remove_data",
+            HandleMsg::CreateSession { .. } => "create_session",
+            HandleMsg::SetCosigner { .. } => "set_cosigner",
+            HandleMsg::ConfirmExecution { .. } => "confirm_execution",
+            HandleMsg::CancelExecution { .. } => "cancel_execution",
+            HandleMsg::AuthorizeOnce { .. } => "authorize_once",
+            HandleMsg::ExecuteAuthorized { .. } => "execute_authorized",
+            HandleMsg::MigrateRecords { .. } => "migrate_records",
+            HandleMsg::SetMiddlewares { .. } => "set_middlewares",
+            HandleMsg::Attest { .. } => "attest",
+            HandleMsg::RequireAttestation { .. } => "require_attestation",
+            HandleMsg::SetArchive { .. } => "set_archive",
+            HandleMsg::SetGasHeuristics { .. } => "set_gas_heuristics",
+            HandleMsg::ExecuteWithLookup { .. } => "execute_with_lookup",
+            HandleMsg::SaveMacro { .. } => "save_macro",
+            HandleMsg::RemoveMacro { .. } => "remove_macro",
+            HandleMsg::RunMacro { .. } => "run_macro",
+            HandleMsg::IfConfigVersion { .. } => "if_config_version",
+            #[cfg(feature = "dry-run")]
+            HandleMsg::DryRun { .. } => "dry_run",
+            HandleMsg::SelfTest { .. } => "self_test",
+        }
+    }
+
+    /// While paused, only the owner key may be rotated and the pause itself changed.
+    /// Evacuating pauses anyway, and Decommission needs an evacuated, so paused, contract.
+    pub fn runs_while_paused(&self) -> bool {
+        match self {
+            HandleMsg::Pause {}
+            | HandleMsg::Unpause {}
+            | HandleMsg::Resume {}
+            | HandleMsg::ChangeOwner { .. }
+            | HandleMsg::Evacuate { .. }
+            | HandleMsg::Decommission { .. } => true,
+            // joint accounts unpause through a proposal, whose action is checked again as it runs
+            HandleMsg::ProposeAdmin { msg } => msg.runs_while_paused(),
+            HandleMsg::ApproveAdmin { .. } => true,
+            HandleMsg::IfConfigVersion { msg, .. } => msg.runs_while_paused(),
+            #[cfg(feature = "dry-run")]
+            HandleMsg::DryRun { msg } => msg.runs_while_paused(),
+            _ => false,
+        }
+    }
+}
+
+/// Deprecation announces that a handle action (or legacy field alias) is going away
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deprecation {
+    pub name: &'static str,
+    /// From this height on, the action may be removed
+    pub sunset_height: u64,
+}
+
+/// Every deprecated action, callers using them get a warning in the response log
+pub const DEPRECATIONS: &[Deprecation] = &[Deprecation {
+    name: "resume",
+    sunset_height: 2_000_000,
+}];
+
+/// Warns about the use of a deprecated action, if it is listed in the registry
+pub fn deprecation_logs(registry: &[Deprecation], name: &str) -> Vec<LogAttribute> {
+    match registry.iter().find(|d| d.name == name) {
+        Some(d) => vec![
+            log("deprecated", d.name),
+            log("sunset_height", &d.sunset_height.to_string()),
+        ],
+        None => vec![],
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum QueryMsg {
     GetOwner {},
+    GetConfig {},
+    GetChainProfile {},
+    DenomMetadata {
+        denom: String,
+    },
+    /// Returns the given coins along with their amounts in display units, if known
+    DescribeCoins {
+        coins: Vec<Coin>,
+    },
+    Execution {
+        id: u64,
+    },
+    /// Lists executions by id, optionally newest first and only within a height range.
+    /// The other filters are applied while scanning, which stops after a bounded number of
+    /// executions. A truncated page may then hold fewer items than the limit, or none.
+    ListExecutions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<Order>,
+        min_height: Option<i64>,
+        max_height: Option<i64>,
+        sender: Option<HumanAddr>,
+        tag: Option<String>,
+        kind: Option<MsgKind>,
+    },
+    /// Estimates the gas a message of this kind typically costs, based on reported executions
+    EstimateCost {
+        kind: MsgKind,
+    },
+    GetCoOwners {},
+    AdminProposal {
+        id: u64,
+    },
+    /// Lists admin proposals which were not executed yet
+    ListAdminProposals {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    AdminVote {
+        id: u64,
+        owner: HumanAddr,
+    },
+    Cooldowns {},
+    Operators {},
+    StagedBatch {
+        id: u64,
+    },
+    /// Lists staged batches which were not executed yet
+    ListStagedBatches {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Tags {},
+    /// Lists executions with this tag, oldest first
+    ListTaggedExecutions {
+        tag: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the budget of the tag along with the spend in the current period
+    TagSpend {
+        tag: String,
+    },
+    Recovery {},
+    /// Returns where a decommissioned contract moved to, if anywhere
+    Successor {},
+    PendingConfig {},
+    /// Shows what the pending config change would change, field by field
+    ConfigDiff {},
+    /// Checks whether a reflect call would pass with the config changed as given.
+    /// Nothing is stored, so admins can try out a policy before applying it.
+    SimulateWithConfig {
+        config_override: ConfigOverride,
+        sender: HumanAddr,
+        msgs: Vec<CosmosMsg>,
+        tags: Vec<String>,
+    },
+    /// Returns the stored records of a collection as raw key/value pairs, so off-chain tools
+    /// can mirror it. A chunk holds as many records as fit into max_bytes, but at least one.
+    ExportChunk {
+        namespace: ExportNamespace,
+        start_after: Option<String>,
+        max_bytes: Option<u32>,
+    },
+    /// Checks a slice of one storage section for broken invariants, such as records missing
+    /// below their sequence or index entries pointing nowhere. At most limit records are
+    /// checked per query, continue with next until it is not truncated.
+    VerifyState {
+        section: VerifySection,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lists the optional features this build was compiled with
+    Features {},
+    Grant {
+        id: u64,
+    },
+    /// Grants in the order they were created, optionally only those of one grantee
+    ListGrants {
+        grantee: Option<HumanAddr>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetPendingOwner {},
+    /// Lists the owner followed by the co-owners
+    ListAdmins {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    AllowedTargets {},
+    Swap {
+        id: u64,
+    },
+    Permissions {
+        grantee: HumanAddr,
+    },
+    /// Queries get no block, so clients compute the current price from the schedule
+    Auction {
+        id: u64,
+    },
+    /// Lists scheduled batches which were neither executed nor cancelled
+    ListScheduled {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Deposits from the source tag in the period, which is the block time divided by
+    /// REVENUE_PERIOD_SECS
+    Revenue {
+        tag: String,
+        period: u64,
+    },
+    Stakeholders {},
+    Proposal {
+        id: u64,
+    },
+    /// Lists proposals which were neither approved nor rejected
+    ListProposals {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    SpendLimit {
+        denom: String,
+    },
+    /// Instances created before versions were stored have none
+    ContractVersion {},
+    /// The execution window and calendar of the operator
+    OperatorWindow {
+        operator: HumanAddr,
+    },
+    Calendar {
+        name: String,
+    },
+    /// Lists the blackouts of a calendar which end after the given block time, soonest first.
+    /// Queries get no block, so clients pass the current time.
+    UpcomingBlackouts {
+        calendar: String,
+        after: u64,
+        limit: Option<u32>,
+    },
+    /// Sums up the funds ever sent to the contract along with a call, per denom.
+    /// Queries cannot read the current balance, as there is no querier.
+    Deposits {},
+    /// The audit log of reflect calls and owner changes, newest first
+    History {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Guardians {},
+    /// Queries get no block, so clients check whether the recovery has expired
+    RecoverySession {},
+    IsDenied {
+        addr: HumanAddr,
+    },
+    /// The reflect fees paid since they were last withdrawn
+    CollectedFees {},
+    /// Checks whether the sender could reflect the message now, and if not, why.
+    /// Like SimulateWithConfig, it cannot check what depends on the block.
+    ValidateMsg {
+        sender: HumanAddr,
+        msg: CosmosMsg,
+    },
+    /// Usage counters of one sender
+    Stats {
+        addr: HumanAddr,
+    },
+    GlobalStats {},
+    /// All metrics at once, for exporters scraping every block. Includes what each middleware
+    /// and rule did, by position.
+    Metrics {},
+    /// The rules in the order they are evaluated
+    Rules {},
+    /// The value stored under the key, none if there is none
+    GetData {
+        key: String,
+    },
+    /// Stored data in key order
+    ListData {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Queries get no block, so clients check whether the session has expired
+    Session {
+        key_addr: HumanAddr,
+    },
+    Cosigner {},
+    /// A reflected batch held back for the co-signer
+    QueuedExecution {
+        id: u64,
+    },
+    /// One-time authorizations which were not used yet, in the order they were given.
+    /// Queries get no block, so clients check whether they have expired.
+    ListAuthorizations {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The latest attestation, and whether executions would accept it.
+    /// Queries get no block, so fresh only compares the checksum.
+    Attestation {},
+    /// Entries and bytes per storage namespace, counted as they are written. Entries from
+    /// before this was counted are missing, so it is an estimate.
+    StorageUsage {},
+    /// Splits the batch into consecutive chunks whose estimated gas fits the budget. Uses the
+    /// gas heuristics, or the reported gas of kinds without one.
+    SuggestBatching {
+        msgs: Vec<CosmosMsg>,
+        gas_budget: u64,
+    },
+    /// Runs several queries of this contract at once, eg. to fill a dashboard in one round
+    /// trip. A failing one does not fail the others. Aggregates cannot be nested.
+    Aggregate {
+        queries: Vec<AggregateQuery>,
+    },
+    /// Lists the saved macros by name
+    Macros {},
+    /// The kind and stored record of the entity a reference such as sched-3 names
+    Resolve {
+        reference: String,
+    },
+    /// Every status each kind of entity may be in, with its code, for translation tables
+    Statuses {},
+    /// Looks term up as a reference, an address and a tag at once, for support staff who do
+    /// not know which list to check. Returns at most limit hits of each kind.
+    Search {
+        term: String,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// ExportNamespace names the collections which can be exported, all of them keyed by id
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportNamespace {
+    Executions,
+    StagedBatches,
+    AdminProposals,
+}
+
+/// MigrateNamespace names the collections with versioned records
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrateNamespace {
+    Executions,
+}
+
+/// VerifySection names the parts of storage VerifyState can check
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifySection {
+    Executions,
+    StagedBatches,
+    AdminProposals,
+    /// The index of executions with this tag along with its counter
+    TagIndex {
+        tag: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeholderMsg {
+    pub addr: HumanAddr,
+    pub shares: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WeightMsg {
+    pub addr: HumanAddr,
+    pub weight: u64,
+}
+
+/// ConfigOverride replaces the parts of the config which are set
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigOverride {
+    pub tags: Option<Vec<String>>,
+    pub budgets: Option<Vec<TagBudget>>,
+    pub cooldowns: Option<Vec<Cooldown>>,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+/// Once renounced, owner is the last owner, who cannot act anymore
 pub struct OwnerResponse {
     pub owner: HumanAddr,
+    pub renounced: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: HumanAddr,
+    pub co_owners: Vec<HumanAddr>,
+    /// While paused, only the pause and the owner key can change
+    pub paused: bool,
+    pub decommissioned: bool,
+    pub deposit_hook: Option<HumanAddr>,
+    /// If set, only these addresses and the owners may send funds
+    pub depositors: Option<Vec<HumanAddr>>,
+    pub travel_rule: Option<Vec<Coin>>,
+    /// Message kinds which need a second approver
+    pub four_eyes: Option<Vec<MsgKind>>,
+    pub reflect_fee: Option<Vec<Coin>>,
+    /// The rules contracts in the order they check a batch
+    pub rulesets: Option<Vec<HumanAddr>>,
+    /// The middlewares in the order reflected batches run through them
+    pub middlewares: Option<Vec<MiddlewareConfig>>,
+    pub archive: Option<HumanAddr>,
+    /// Of the active configuration, as logged with every execution
+    pub checksum: String,
+    /// Goes up with every change to the configuration, see IfConfigVersion
+    pub config_version: u64,
+}
+
+/// Both are None if there is no ownership transfer waiting to be accepted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub owner: Option<HumanAddr>,
+    pub expires: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainProfileResponse {
+    pub chain: ChainProfile,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomMetadataResponse {
+    pub denom: String,
+    pub metadata: Option<DenomMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DescribeCoinsResponse {
+    pub coins: Vec<CoinDescription>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CoinDescription {
+    pub denom: String,
+    pub amount: String,
+    /// These are only set if metadata was registered for the denom
+    pub symbol: Option<String>,
+    pub display_amount: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecutionResponse {
+    pub id: u64,
+    pub sender: HumanAddr,
+    pub height: i64,
+    pub msgs: Vec<KindCount>,
+    pub gas_used: Option<u64>,
+    pub tags: Vec<String>,
+    pub memo: Option<TravelMemo>,
+    pub payload_hash: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecutionsResponse {
+    pub executions: Vec<ExecutionResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CostEstimateResponse {
+    pub kind: MsgKind,
+    /// Number of reported messages of this kind the estimate is based on
+    pub samples: u64,
+    pub gas_per_msg: Option<u64>,
+}
+
+/// MacroArg is the amount to pass for a param of a macro
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MacroArg {
+    pub name: String,
+    pub amount: String,
+}
+
+/// Lookup is a value ExecuteWithLookup reads from a query of this contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Lookup {
+    pub query: QueryMsg,
+    /// The part of the response to take, eg. /collected/0/amount
+    pub pointer: String,
+    /// If set, the part must be an amount x, and the value is expr evaluated on it,
+    /// eg. x * 90 / 100 - 1000 (see math::eval)
+    pub expr: Option<String>,
+    /// What to do if the value is null, zero or empty. Without it, the execution aborts.
+    pub on_empty: Option<OnEmpty>,
+}
+
+/// OnEmpty is what ExecuteWithLookup does when its lookup finds nothing to send
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OnEmpty {
+    /// Fails with ContractError::EmptyLookup
+    Abort {},
+    /// Reflects nothing and succeeds
+    Skip {},
+    /// Uses this amount instead, without applying expr
+    Default { amount: String },
+}
+
+/// AggregateQuery is one query of an Aggregate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AggregateQuery {
+    pub query: QueryMsg,
+    /// If set, only this part of the response comes back, eg. /metrics/0/value
+    pub pointer: Option<String>,
+}
+
+/// AggregateResult is the response or the error of one query of an Aggregate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AggregateResult {
+    pub data: Option<Binary>,
+    pub error: Option<String>,
+}
+
+/// AggregateResponse holds a result for each query, in order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AggregateResponse {
+    pub results: Vec<AggregateResult>,
+}
+
+/// BatchChunk holds the messages from start up to (but excluding) end
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchChunk {
+    pub start: u32,
+    pub end: u32,
+    pub gas: u64,
+    /// A single message over the budget gets a chunk of its own
+    pub over_budget: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchingResponse {
+    pub chunks: Vec<BatchChunk>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CoOwnersResponse {
+    pub owner: HumanAddr,
+    pub co_owners: Vec<HumanAddr>,
+    pub admin_threshold: Option<u64>,
+    /// Weight of every owner, starting with the main owner
+    pub weights: Vec<WeightMsg>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminsResponse {
+    pub admins: Vec<HumanAddr>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminProposalResponse {
+    pub id: u64,
+    pub msg: HandleMsg,
+    pub approvals: Vec<HumanAddr>,
+    pub approved_weight: u64,
+    pub executed: bool,
+    pub status: Status,
+    pub status_code: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminProposalsResponse {
+    pub proposals: Vec<AdminProposalResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminVoteResponse {
+    pub id: u64,
+    pub owner: HumanAddr,
+    pub approved: bool,
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CooldownsResponse {
+    pub cooldowns: Vec<CooldownInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CooldownInfo {
+    pub cooldown: Cooldown,
+    /// Set if the action was used before
+    pub next_allowed: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorSpec {
+    pub addr: HumanAddr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayrollEntry {
+    pub recipient: HumanAddr,
+    pub amount: Vec<Coin>,
+}
+
+/// ExecuteManyResult is the data of an ExecuteMany response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecuteManyResult {
+    /// None if the execution waits for the cosigner
+    pub execution_id: Option<u64>,
+    /// The targets in the order they are called
+    pub targets: Vec<HumanAddr>,
+}
+
+/// ReflectResult is the data of a ReflectMsg response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReflectResult {
+    /// None if the execution waits for the cosigner
+    pub execution_id: Option<u64>,
+    pub msg_count: u32,
+}
+
+/// MigrateRecordsResult is the data of a MigrateRecords response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateRecordsResult {
+    /// How many records of this call had an older layout
+    pub upgraded: u32,
+    /// Records which cannot be read are left as they are, VerifyState reports them
+    pub unreadable: u32,
+    /// Every record up to this id is in the current layout
+    pub migrated_up_to: u64,
+    pub last: u64,
+    pub done: bool,
+}
+
+/// SelfTestResult is the data of a SelfTest response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SelfTestResult {
+    pub checks: Vec<SubsystemCheck>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubsystemCheck {
+    pub subsystem: String,
+    pub passed: bool,
+    /// Why the probe failed
+    pub error: Option<String>,
+}
+
+/// ReceiptMsg is sent to the receipt_contract of a staged batch after execution,
+/// and to the deposit hook after a deposit.
+/// The receipt is part of the same transaction, so it is only delivered if all messages succeed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReceiptMsg {
+    ExecutionReceipt {
+        batch_id: u64,
+        execution_id: u64,
+        height: i64,
+    },
+    DepositReceipt {
+        sender: HumanAddr,
+        amount: Vec<Coin>,
+        tag: String,
+    },
+}
+
+/// RulesetMsg is the interface a rules contract implements to extend the policy of the mask.
+/// It is sent ahead of the messages of every reflected batch, once the built-in checks passed.
+/// The rules contract rejects the batch by returning an error, which fails the whole transaction,
+/// so the rules contracts after it are not asked.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RulesetMsg {
+    CheckReflect {
+        sender: HumanAddr,
+        msgs: Vec<CosmosMsg>,
+        tags: Vec<String>,
+        memo: Option<TravelMemo>,
+        height: i64,
+        time: i64,
+    },
+}
+
+/// ArchiveMsg is the interface an archive contract implements to store the executed batches.
+/// It is sent after the messages of the batch; payload is the JSON of those messages, and its
+/// SHA-256 is the payload_hash of the execution. Without submessages, a failing archive fails
+/// the whole transaction, so the admins turn archiving off while it is down.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveMsg {
+    Archive {
+        execution_id: u64,
+        height: i64,
+        payload: Binary,
+    },
+}
+
+/// EchoMsg is the probe a SelfTest sends its echo contract, which accepts it without effect
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EchoMsg {
+    Ping { height: i64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorsResponse {
+    pub operators: Vec<HumanAddr>,
+    pub manifest_nonce: u64,
+    /// The operators which are suspended
+    pub suspended: Vec<HumanAddr>,
+}
+
+/// targets is None while there is no allowlist, so any contract may be called
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowedTargetsResponse {
+    pub targets: Option<Vec<HumanAddr>>,
+}
+
+/// msg_types is empty if the grantee has no permission, or it was revoked
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PermissionsResponse {
+    pub grantee: HumanAddr,
+    pub msg_types: Vec<MsgKind>,
+    pub expires_at_height: Option<u64>,
+    /// The macros the grantee may run
+    pub macros: Vec<MacroRunsResponse>,
+}
+
+/// Queries get no block, so remaining counts the runs left in the current period,
+/// which ends at resets_at. After that the full quota is available again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MacroRunsResponse {
+    pub name: String,
+    pub quota: Option<MacroQuota>,
+    pub remaining: Option<u32>,
+    pub resets_at: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StagedBatchResponse {
+    pub id: u64,
+    pub operator: HumanAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub memo: String,
+    pub receipt_contract: Option<HumanAddr>,
+    pub tags: Vec<String>,
+    pub staged_height: i64,
+    pub executed: bool,
+    pub status: Status,
+    pub status_code: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StagedBatchesResponse {
+    pub batches: Vec<StagedBatchResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevenueResponse {
+    pub tag: String,
+    pub period: u64,
+    /// Block time the period starts at, in seconds
+    pub starts_at: u64,
+    pub totals: Vec<Coin>,
+    /// Whether the totals were distributed to the stakeholders
+    pub settled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeholdersResponse {
+    pub stakeholders: Vec<StakeholderMsg>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduledBatchResponse {
+    pub id: u64,
+    pub owner: HumanAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub execute_after: Expiration,
+    pub calendar: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduledBatchesResponse {
+    pub batches: Vec<ScheduledBatchResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub proposer: HumanAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub description: String,
+    pub status: ProposalStatus,
+    pub status_code: u32,
+    pub intents: Option<Vec<WasmIntent>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalsResponse {
+    pub proposals: Vec<ProposalResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantResponse {
+    pub id: u64,
+    pub grantee: HumanAddr,
+    pub approver: HumanAddr,
+    pub milestones: Vec<Milestone>,
+    /// How many milestones were paid so far
+    pub released: u32,
+    /// What the milestones not yet paid add up to
+    pub remaining: Vec<Coin>,
+    pub created_height: i64,
+    pub clawed_back: bool,
+    pub status: Status,
+    pub status_code: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantsResponse {
+    pub grants: Vec<GrantResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapResponse {
+    pub id: u64,
+    pub counterparty: HumanAddr,
+    pub give: Vec<Coin>,
+    pub want: Vec<Coin>,
+    pub expires: Expiration,
+    pub filled: bool,
+    pub status: Status,
+    pub status_code: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionResponse {
+    pub id: u64,
+    pub sell: Vec<Coin>,
+    pub start_price: Coin,
+    pub end_price: Coin,
+    pub duration: Duration,
+    pub start_height: u64,
+    pub start_time: u64,
+    pub buyer: Option<HumanAddr>,
+    pub paid: Option<Coin>,
+    pub cancelled: bool,
+    pub status: Status,
+    pub status_code: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TagsResponse {
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TaggedExecutionsResponse {
+    pub tag: String,
+    pub executions: Vec<ExecutionResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TagSpendResponse {
+    pub tag: String,
+    pub budget: Option<Budget>,
+    pub spent: String,
+    /// When the current period ends, if there is one
+    pub resets_at: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorWindowResponse {
+    pub operator: HumanAddr,
+    pub window: Option<ExecutionWindow>,
+    pub calendar: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CalendarResponse {
+    pub name: String,
+    pub allowed: Vec<TimeRange>,
+    pub blackouts: Vec<TimeRange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackoutsResponse {
+    pub calendar: String,
+    pub blackouts: Vec<TimeRange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositsResponse {
+    pub totals: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractVersionResponse {
+    pub version: Option<ContractVersion>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryEntryResponse {
+    pub seq: u64,
+    pub sender: HumanAddr,
+    pub height: u64,
+    pub action: String,
+    pub msgs: Vec<KindCount>,
+    /// The new owner, for owner changes
+    pub owner: Option<HumanAddr>,
+    pub intents: Option<Vec<WasmIntent>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryResponse {
+    pub entries: Vec<HistoryEntryResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next (older) page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendLimitResponse {
+    pub denom: String,
+    pub limit: Option<SpendLimit>,
+    pub spent: String,
+    /// When the current period ends, if there is one
+    pub resets_at: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecoveryResponse {
+    pub recovery: Option<HumanAddr>,
+    pub paused: bool,
+    pub evacuated: bool,
+    pub decommissioned: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectedFeesResponse {
+    pub collected: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsDeniedResponse {
+    pub addr: HumanAddr,
+    pub denied: bool,
+}
+
+/// last_height is None if the sender never did anything recorded in the history
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub addr: HumanAddr,
+    pub reflections: u64,
+    pub last_height: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricKind {
+    /// Only ever goes up
+    Counter,
+    /// Goes up and down, paused is 1 or 0
+    Gauge,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Metric {
+    pub name: String,
+    pub kind: MetricKind,
+    pub value: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MetricsResponse {
+    pub metrics: Vec<Metric>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RulesResponse {
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CosignerResponse {
+    pub cosigner: Option<HumanAddr>,
+    pub thresholds: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AttestationResponse {
+    pub attestation: Option<Attestation>,
+    pub max_age: Option<Duration>,
+    /// Whether the attestation is for the active configuration
+    pub current: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NamespaceUsage {
+    /// Empty for keys outside a namespace
+    pub namespace: String,
+    pub entries: u64,
+    /// Keys included
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MacroResponse {
+    pub name: String,
+    pub msgs: Vec<CosmosMsg>,
+    pub params: Vec<MacroParam>,
+    pub runners: Vec<HumanAddr>,
+    pub quota: Option<MacroQuota>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MacrosResponse {
+    pub macros: Vec<MacroResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatusesResponse {
+    pub kinds: Vec<KindStatuses>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KindStatuses {
+    pub kind: EntityKind,
+    pub statuses: Vec<StatusCode>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatusCode {
+    pub status: Status,
+    pub code: u32,
+}
+
+/// SearchResponse groups what matched the term by kind, e.g. operator, tag or execution
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SearchResponse {
+    pub term: String,
+    pub groups: Vec<SearchGroup>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SearchGroup {
+    pub kind: String,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SearchHit {
+    /// The reference of the entity, for hits which are stored entities
+    pub reference: Option<String>,
+    pub summary: String,
+}
+
+/// ResolveResponse holds the stored record of a referenced entity, serialized as it is stored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ResolveResponse {
+    pub reference: String,
+    pub kind: EntityKind,
+    pub id: u64,
+    pub record: Binary,
+}
+
+/// StorageUsageResponse lists the namespaces by the bytes they use, the biggest first
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StorageUsageResponse {
+    pub namespaces: Vec<NamespaceUsage>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueuedExecutionResponse {
+    pub id: u64,
+    pub sender: HumanAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub tags: Vec<String>,
+    pub memo: Option<TravelMemo>,
+    pub queued_height: u64,
+    pub closed: bool,
+    pub status: Status,
+    pub status_code: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuthorizationResponse {
+    pub id: u64,
+    pub grantee: HumanAddr,
+    pub msg_hash: Binary,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuthorizationsResponse {
+    pub authorizations: Vec<AuthorizationResponse>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SessionResponse {
+    pub key_addr: HumanAddr,
+    pub remaining_msgs: u64,
+    /// None if the key never had a session
+    pub expires: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DataResponse {
+    pub key: String,
+    pub value: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DataEntry {
+    pub key: String,
+    pub value: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DataEntriesResponse {
+    pub entries: Vec<DataEntry>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next page
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GlobalStatsResponse {
+    /// Messages of all successful reflect calls, not counting the reflect calls themselves
+    pub msgs_reflected: u64,
+    pub owner_changes: u64,
+}
+
+/// Both are None if there are no guardians
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GuardiansResponse {
+    pub guardians: Vec<HumanAddr>,
+    pub threshold: Option<u64>,
+    pub window: Option<Duration>,
+}
+
+/// new_owner and expires are None if no recovery is in progress
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecoverySessionResponse {
+    pub new_owner: Option<HumanAddr>,
+    pub approvals: Vec<HumanAddr>,
+    pub expires: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SuccessorResponse {
+    pub successor: Option<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulationResponse {
+    /// Why the call would fail, None if it would pass
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidationResponse {
+    pub valid: bool,
+    /// Why the message would be rejected, None if it is valid
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfigResponse {
+    pub pending: Option<PendingConfig>,
+}
+
+/// ConfigDiffResponse lists what the pending config would change, empty if none is pending
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigDiffResponse {
+    pub activate_at: Option<Expiration>,
+    pub tags_added: Vec<String>,
+    pub tags_removed: Vec<String>,
+    pub budgets: Vec<BudgetChange>,
+    pub cooldowns: Vec<CooldownChange>,
+}
+
+/// A budget which is added (no old), removed (no new) or modified
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BudgetChange {
+    pub tag: String,
+    pub old: Option<Budget>,
+    pub new: Option<Budget>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CooldownChange {
+    pub action: String,
+    pub old: Option<Duration>,
+    pub new: Option<Duration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportEntry {
+    /// The key within the namespace, as stored
+    pub key: Binary,
+    /// The record, as stored
+    pub value: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportChunkResponse {
+    pub entries: Vec<ExportEntry>,
+    pub is_truncated: bool,
+    /// Pass as start_after to get the next chunk
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeaturesResponse {
+    pub features: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyStateResponse {
+    pub violations: Vec<Violation>,
+    /// Whether there is more to check in this section
+    pub is_truncated: bool,
+    /// Pass as start_after to check the next slice
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// The affected record, like "executions/4" or "tag_index/ops/2"
+    pub key: String,
+    pub detail: String,
+    /// Pass to RepairState to fix this violation
+    pub token: String,
+}
+
+impl Violation {
+    pub fn new(kind: ViolationKind, key: String, detail: String) -> Self {
+        let token = format!("{}@{}", kind.as_str(), key);
+        Violation {
+            kind,
+            key,
+            detail,
+            token,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// No record although the sequence or counter says there is one
+    MissingRecord,
+    /// The record cannot be read
+    CorruptRecord,
+    /// The record has another id than it is stored under
+    IdMismatch,
+    /// An execution is at a lower height than the one before
+    HeightOrder,
+    /// An index entry points to a missing execution or one without the tag
+    DanglingReference,
+    /// There are index entries beyond the counter
+    CounterMismatch,
+}
+
+impl ViolationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ViolationKind::MissingRecord => "missing_record",
+            ViolationKind::CorruptRecord => "corrupt_record",
+            ViolationKind::IdMismatch => "id_mismatch",
+            ViolationKind::HeightOrder => "height_order",
+            ViolationKind::DanglingReference => "dangling_reference",
+            ViolationKind::CounterMismatch => "counter_mismatch",
+        }
+    }
 }
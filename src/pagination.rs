@@ -0,0 +1,194 @@
+//! Pagination shared by all list queries.
+//!
+//! Lists are returned in the order they are stored. To get the next page, pass the `next`
+//! cursor of a response as `start_after`. Cursors are opaque, clients must not build them.
+
+use cosmwasm::errors::{dyn_contract_err, Result};
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+
+/// Page holds one page of a list query
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Whether there are more items after this page
+    pub is_truncated: bool,
+    /// Cursor to continue after this page, only set if it is truncated
+    pub next: Option<String>,
+}
+
+/// Applies the default limit and clamps it between 1 and MAX_LIMIT
+pub fn clamp_limit(limit: Option<u32>) -> usize {
+    match limit.unwrap_or(DEFAULT_LIMIT) {
+        0 => 1,
+        limit => limit.min(MAX_LIMIT) as usize,
+    }
+}
+
+pub fn encode_cursor(key: u64) -> String {
+    format!("{:016x}", key)
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<u64> {
+    if cursor.len() != 16 || !cursor.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return dyn_contract_err(format!("Invalid cursor: {}", cursor));
+    }
+    match u64::from_str_radix(cursor, 16) {
+        Ok(key) => Ok(key),
+        Err(_) => dyn_contract_err(format!("Invalid cursor: {}", cursor)),
+    }
+}
+
+/// Decodes an optional start_after cursor
+pub fn decode_start_after(start_after: Option<String>) -> Result<Option<u64>> {
+    match start_after {
+        Some(cursor) => Ok(Some(decode_cursor(&cursor)?)),
+        None => Ok(None),
+    }
+}
+
+/// The key a page starts at: the one after the decoded cursor, but at least `first`.
+/// None for a cursor at u64::MAX, after which nothing can follow, so the page is empty.
+pub fn start_key(cursor: Option<u64>, first: u64) -> Option<u64> {
+    match cursor {
+        Some(key) => key.checked_add(1).map(|next| next.max(first)),
+        None => Some(first),
+    }
+}
+
+/// Loads up to `limit` items for the keys in order, skipping keys for which `load` returns None.
+/// It reads one matching item ahead to know whether the list is truncated.
+pub fn paginate<T, I, F>(keys: I, limit: usize, load: F) -> Result<Page<T>>
+where
+    I: IntoIterator<Item = u64>,
+    F: FnMut(u64) -> Result<Option<T>>,
+{
+    scan(keys, limit, None, load)
+}
+
+/// Like paginate, but stops after looking at `max_scan` keys, so sparse filters do bounded work.
+/// The page may then be truncated with fewer than `limit` items, even none.
+pub fn paginate_bounded<T, I, F>(keys: I, limit: usize, max_scan: usize, load: F) -> Result<Page<T>>
+where
+    I: IntoIterator<Item = u64>,
+    F: FnMut(u64) -> Result<Option<T>>,
+{
+    scan(keys, limit, Some(max_scan.max(1)), load)
+}
+
+fn scan<T, I, F>(keys: I, limit: usize, max_scan: Option<usize>, mut load: F) -> Result<Page<T>>
+where
+    I: IntoIterator<Item = u64>,
+    F: FnMut(u64) -> Result<Option<T>>,
+{
+    let mut items = vec![];
+    // the next page continues after the last key looked at
+    let mut last = None;
+    let mut scanned = 0;
+    for key in keys {
+        if max_scan == Some(scanned) {
+            return Ok(Page {
+                items,
+                is_truncated: true,
+                next: last.map(encode_cursor),
+            });
+        }
+        scanned += 1;
+        if let Some(item) = load(key)? {
+            if items.len() == limit {
+                return Ok(Page {
+                    items,
+                    is_truncated: true,
+                    next: last.map(encode_cursor),
+                });
+            }
+            items.push(item);
+        }
+        last = Some(key);
+    }
+    Ok(Page {
+        items,
+        is_truncated: false,
+        next: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evens(keys: std::ops::Range<u64>, limit: usize) -> Page<u64> {
+        paginate(keys, limit, |k| Ok(if k % 2 == 0 { Some(k) } else { None })).unwrap()
+    }
+
+    #[test]
+    fn limits_are_clamped() {
+        assert_eq!(DEFAULT_LIMIT as usize, clamp_limit(None));
+        assert_eq!(1, clamp_limit(Some(0)));
+        assert_eq!(7, clamp_limit(Some(7)));
+        assert_eq!(MAX_LIMIT as usize, clamp_limit(Some(MAX_LIMIT + 1)));
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        for key in &[0, 1, 255, 0xffff_ffff_ffff_ffff] {
+            assert_eq!(*key, decode_cursor(&encode_cursor(*key)).unwrap());
+        }
+        assert_eq!(None, decode_start_after(None).unwrap());
+        assert!(decode_cursor("").is_err());
+        assert!(decode_cursor("12").is_err());
+        assert!(decode_cursor("+00000000000000f").is_err());
+        assert!(decode_cursor("000000000000000g").is_err());
+    }
+
+    #[test]
+    fn pages_start_after_the_cursor() {
+        assert_eq!(Some(1), start_key(None, 1));
+        assert_eq!(Some(8), start_key(Some(7), 1));
+        assert_eq!(Some(5), start_key(Some(2), 5));
+        let last = decode_cursor("ffffffffffffffff").unwrap();
+        assert_eq!(None, start_key(Some(last), 1));
+    }
+
+    #[test]
+    fn pages_are_truncated_only_if_more_follow() {
+        let empty = evens(0..0, 3);
+        assert_eq!(Vec::<u64>::new(), empty.items);
+        assert!(!empty.is_truncated);
+
+        // exactly full, with skipped keys after the last match
+        let full = evens(0..7, 4);
+        assert_eq!(vec![0, 2, 4, 6], full.items);
+        assert!(!full.is_truncated);
+        assert_eq!(None, full.next);
+
+        let first = evens(0..7, 3);
+        assert_eq!(vec![0, 2, 4], first.items);
+        assert!(first.is_truncated);
+        let start = decode_cursor(&first.next.unwrap()).unwrap();
+        let second = evens(start + 1..7, 3);
+        assert_eq!(vec![6], second.items);
+        assert!(!second.is_truncated);
+    }
+
+    #[test]
+    fn bounded_scans_continue_where_they_stopped() {
+        let every_fifth = |k: u64| Ok(if k % 5 == 0 { Some(k) } else { None });
+
+        // runs out of budget before finding anything
+        let page = paginate_bounded(1..20, 3, 4, every_fifth).unwrap();
+        assert_eq!(Vec::<u64>::new(), page.items);
+        assert!(page.is_truncated);
+        let start = decode_cursor(&page.next.unwrap()).unwrap();
+        assert_eq!(4, start);
+
+        let page = paginate_bounded(start + 1..20, 3, 4, every_fifth).unwrap();
+        assert_eq!(vec![5], page.items);
+        assert!(page.is_truncated);
+
+        // the budget is not exceeded by the last key
+        let page = paginate_bounded(16..20, 3, 4, every_fifth).unwrap();
+        assert!(!page.is_truncated);
+    }
+}
@@ -0,0 +1,230 @@
+//! JSON pointers (RFC 6901) into serialized query responses.
+//!
+//! The contract has no JSON value type, so `select` walks the JSON text itself and returns
+//! the bytes of the value the pointer refers to, exactly as they were serialized.
+
+use cosmwasm::errors::{contract_err, dyn_contract_err, Result};
+
+/// The value at pointer in json. The empty pointer selects the whole document.
+pub fn select<'a>(json: &'a [u8], pointer: &str) -> Result<&'a [u8]> {
+    find(json, pointer)?.map_or_else(|| dyn_contract_err(format!("Nothing at {}", pointer)), Ok)
+}
+
+/// Like select, but None if there is nothing at pointer
+pub fn find<'a>(json: &'a [u8], pointer: &str) -> Result<Option<&'a [u8]>> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return dyn_contract_err(format!("Pointer must start with /: {}", pointer));
+    }
+    let mut start = skip_ws(json, 0);
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        let found = match json.get(start) {
+            Some(b'{') => member(json, start, &token)?,
+            Some(b'[') => element(json, start, &token)?,
+            _ => None,
+        };
+        start = match found {
+            Some(start) => start,
+            None => return Ok(None),
+        };
+    }
+    let end = value_end(json, start)?;
+    Ok(Some(&json[start..end]))
+}
+
+fn invalid<T>() -> Result<T> {
+    contract_err("Invalid JSON")
+}
+
+fn skip_ws(json: &[u8], mut pos: usize) -> usize {
+    while let Some(b' ') | Some(b'\n') | Some(b'\r') | Some(b'\t') = json.get(pos) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Where the value of the member named key starts, if the object at start has one
+fn member(json: &[u8], start: usize, key: &str) -> Result<Option<usize>> {
+    let mut pos = skip_ws(json, start + 1);
+    if json.get(pos) == Some(&b'}') {
+        return Ok(None);
+    }
+    loop {
+        let key_end = string_end(json, pos)?;
+        let found = decode_string(&json[pos + 1..key_end - 1])? == key;
+        pos = skip_ws(json, key_end);
+        if json.get(pos) != Some(&b':') {
+            return invalid();
+        }
+        pos = skip_ws(json, pos + 1);
+        if found {
+            return Ok(Some(pos));
+        }
+        pos = skip_ws(json, value_end(json, pos)?);
+        match json.get(pos) {
+            Some(b',') => pos = skip_ws(json, pos + 1),
+            Some(b'}') => return Ok(None),
+            _ => return invalid(),
+        }
+    }
+}
+
+/// Where the element at index token starts, if the array at start is long enough
+fn element(json: &[u8], start: usize, token: &str) -> Result<Option<usize>> {
+    // no signs or leading zeros, so every index has one spelling
+    let digits = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit());
+    if !digits || (token.len() > 1 && token.starts_with('0')) {
+        return Ok(None);
+    }
+    let index: usize = match token.parse() {
+        Ok(index) => index,
+        Err(_) => return Ok(None),
+    };
+    let mut pos = skip_ws(json, start + 1);
+    if json.get(pos) == Some(&b']') {
+        return Ok(None);
+    }
+    for _ in 0..index {
+        pos = skip_ws(json, value_end(json, pos)?);
+        match json.get(pos) {
+            Some(b',') => pos = skip_ws(json, pos + 1),
+            Some(b']') => return Ok(None),
+            _ => return invalid(),
+        }
+    }
+    Ok(Some(pos))
+}
+
+/// The end of the value starting at pos
+fn value_end(json: &[u8], pos: usize) -> Result<usize> {
+    match json.get(pos) {
+        Some(b'"') => string_end(json, pos),
+        Some(b'{') | Some(b'[') => {
+            let mut depth = 0;
+            let mut pos = pos;
+            loop {
+                match json.get(pos) {
+                    Some(b'"') => {
+                        pos = string_end(json, pos)?;
+                        continue;
+                    }
+                    Some(b'{') | Some(b'[') => depth += 1,
+                    Some(b'}') | Some(b']') => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(pos + 1);
+                        }
+                    }
+                    Some(_) => {}
+                    None => return invalid(),
+                }
+                pos += 1;
+            }
+        }
+        Some(_) => {
+            let len = json[pos..]
+                .iter()
+                .position(|b| b"{}[],:\" \n\r\t".contains(b))
+                .unwrap_or(json.len() - pos);
+            if len == 0 {
+                return invalid();
+            }
+            Ok(pos + len)
+        }
+        None => invalid(),
+    }
+}
+
+/// The end of the string starting at pos, after its closing quote
+fn string_end(json: &[u8], pos: usize) -> Result<usize> {
+    if json.get(pos) != Some(&b'"') {
+        return invalid();
+    }
+    let mut pos = pos + 1;
+    loop {
+        match json.get(pos) {
+            Some(b'"') => return Ok(pos + 1),
+            Some(b'\\') => pos += 2,
+            Some(_) => pos += 1,
+            None => return invalid(),
+        }
+    }
+}
+
+/// Unescapes the inside of a JSON string
+fn decode_string(raw: &[u8]) -> Result<String> {
+    let raw = match std::str::from_utf8(raw) {
+        Ok(raw) => raw,
+        Err(_) => return invalid(),
+    };
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let unescaped = match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let code = u32::from_str_radix(&hex, 16).ok();
+                // surrogate pairs never match a key of the contract, so they decode to U+FFFD
+                code.and_then(std::char::from_u32).unwrap_or('\u{fffd}')
+            }
+            Some(c) => c,
+            None => return invalid(),
+        };
+        out.push(unescaped);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: &[u8] = br#"{"metrics": [
+        {"name": "executions_total", "kind": "counter", "value": 3},
+        {"name": "a/b~c", "kind": "gauge", "value": 0}
+    ], "owner": "cosmos1owner", "n\"ested": {"deep": [true, null]}, "x/y~": 5}"#;
+
+    fn selected(pointer: &str) -> String {
+        String::from_utf8(select(METRICS, pointer).unwrap().to_vec()).unwrap()
+    }
+
+    #[test]
+    fn selects_by_key_and_index() {
+        assert_eq!(METRICS, select(METRICS, "").unwrap());
+        assert_eq!(r#""cosmos1owner""#, selected("/owner"));
+        assert_eq!("3", selected("/metrics/0/value"));
+        assert_eq!(r#""a/b~c""#, selected("/metrics/1/name"));
+        assert_eq!("null", selected("/n\"ested/deep/1"));
+        assert_eq!("5", selected("/x~1y~0"));
+        let second = selected("/metrics/1");
+        assert!(second.starts_with('{') && second.ends_with('}'));
+    }
+
+    #[test]
+    fn reports_what_is_missing() {
+        for pointer in &[
+            "/missing",
+            "/metrics/2",
+            "/metrics/01",
+            "/metrics/-",
+            "/owner/0",
+        ] {
+            let err = select(METRICS, pointer).unwrap_err();
+            assert!(err.to_string().contains("Nothing at"), "{}", pointer);
+        }
+        assert!(select(METRICS, "owner").is_err());
+        assert_eq!(None, find(METRICS, "/metrics/2").unwrap());
+        assert!(select(br#"{"a": [1, 2"#, "/a/1").is_ok());
+        assert!(select(br#"{"a": [1, 2"#, "/a").is_err());
+        assert!(select(br#"{"a" 1}"#, "/a").is_err());
+    }
+}
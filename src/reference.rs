@@ -0,0 +1,168 @@
+//! References name a stored entity across subsystems, as its kind's prefix and its id.
+//!
+//! Ids are counted per kind, so "batch 3" may be a staged or a scheduled batch. A reference
+//! such as sched-3 says which, and the Resolve query turns it back into the entity.
+
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm::errors::{dyn_contract_err, Result};
+use cosmwasm::types::{log, LogAttribute};
+
+use crate::errors::ContractError;
+use crate::state::Status;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Execution,
+    StagedBatch,
+    ScheduledBatch,
+    Proposal,
+    AdminProposal,
+    Grant,
+    Swap,
+    QueuedExecution,
+    Authorization,
+    Auction,
+}
+
+impl EntityKind {
+    pub const ALL: &'static [EntityKind] = &[
+        EntityKind::Execution,
+        EntityKind::StagedBatch,
+        EntityKind::ScheduledBatch,
+        EntityKind::Proposal,
+        EntityKind::AdminProposal,
+        EntityKind::Grant,
+        EntityKind::Swap,
+        EntityKind::QueuedExecution,
+        EntityKind::Authorization,
+        EntityKind::Auction,
+    ];
+
+    /// The name of the kind, as it is serialized
+    pub fn name(self) -> &'static str {
+        match self {
+            EntityKind::Execution => "execution",
+            EntityKind::StagedBatch => "staged_batch",
+            EntityKind::ScheduledBatch => "scheduled_batch",
+            EntityKind::Proposal => "proposal",
+            EntityKind::AdminProposal => "admin_proposal",
+            EntityKind::Grant => "grant",
+            EntityKind::Swap => "swap",
+            EntityKind::QueuedExecution => "queued_execution",
+            EntityKind::Authorization => "authorization",
+            EntityKind::Auction => "auction",
+        }
+    }
+
+    /// The statuses responses may give an entity of the kind, none if they give no status
+    pub fn statuses(self) -> &'static [Status] {
+        match self {
+            EntityKind::StagedBatch => &[Status::Staged, Status::Executed],
+            EntityKind::Proposal => &[Status::Pending, Status::Approved, Status::Rejected],
+            EntityKind::AdminProposal => &[Status::Pending, Status::Executed],
+            EntityKind::Grant => &[Status::Active, Status::Completed, Status::ClawedBack],
+            EntityKind::Swap => &[Status::Open, Status::Filled],
+            EntityKind::QueuedExecution => &[Status::Queued, Status::Closed],
+            EntityKind::Auction => &[Status::Open, Status::Sold, Status::Cancelled],
+            EntityKind::Execution | EntityKind::ScheduledBatch | EntityKind::Authorization => &[],
+        }
+    }
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            EntityKind::Execution => "exec",
+            EntityKind::StagedBatch => "staged",
+            EntityKind::ScheduledBatch => "sched",
+            EntityKind::Proposal => "prop",
+            EntityKind::AdminProposal => "admin",
+            EntityKind::Grant => "grant",
+            EntityKind::Swap => "swap",
+            EntityKind::QueuedExecution => "queued",
+            EntityKind::Authorization => "auth",
+            EntityKind::Auction => "auction",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reference {
+    pub kind: EntityKind,
+    pub id: u64,
+}
+
+impl Reference {
+    pub fn new(kind: EntityKind, id: u64) -> Self {
+        Reference { kind, id }
+    }
+
+    /// Parses prefix-id, e.g. sched-3
+    pub fn parse(reference: &str) -> Result<Reference> {
+        let mut parts = reference.splitn(2, '-');
+        let prefix = parts.next().unwrap_or_default();
+        let kind = EntityKind::ALL.iter().find(|k| k.prefix() == prefix);
+        let id = parts.next().and_then(|id| id.parse().ok());
+        match (kind, id) {
+            (Some(&kind), Some(id)) => Ok(Reference { kind, id }),
+            _ => dyn_contract_err(format!("Invalid reference: {}", reference)),
+        }
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.kind.prefix(), self.id)
+    }
+}
+
+/// Logs the reference of an entity a handle created or acted on, next to its id
+pub fn ref_log(kind: EntityKind, id: u64) -> LogAttribute {
+    log("ref", &Reference::new(kind, id).to_string())
+}
+
+/// The entity loaded for kind and id, or a NotFound error naming its reference
+pub fn found<T>(entity: Option<T>, kind: EntityKind, id: u64) -> Result<T> {
+    match entity {
+        Some(entity) => Ok(entity),
+        None => ContractError::NotFound {
+            reference: Reference::new(kind, id).to_string(),
+        }
+        .fail(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_round_trip() {
+        for &kind in EntityKind::ALL {
+            let reference = Reference::new(kind, 42);
+            assert_eq!(reference, Reference::parse(&reference.to_string()).unwrap());
+        }
+        assert_eq!(
+            "sched-3",
+            Reference::new(EntityKind::ScheduledBatch, 3).to_string()
+        );
+        for invalid in &["sched", "sched-", "sched-x", "batch-3", "-3", "sched-+3x"] {
+            assert!(Reference::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn status_codes_name_one_status() {
+        let mut seen: Vec<Status> = vec![];
+        for kind in EntityKind::ALL {
+            for &status in kind.statuses() {
+                let same_code = seen.iter().find(|s| s.code() == status.code());
+                assert!(same_code.map_or(true, |&s| s == status), "{:?}", status);
+                seen.push(status);
+            }
+        }
+    }
+}
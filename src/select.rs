@@ -0,0 +1,122 @@
+//! Deterministic pseudo-random selection, for features which rotate through candidates.
+//!
+//! Every validator must come to the same choice, so the randomness is a hash of what they all
+//! agree on: the block and a salt naming what is chosen. Anyone can predict the choice once the
+//! block is known, so it is fine for spreading work evenly, but not for anything worth gaming.
+
+use sha2::{Digest, Sha256};
+
+use cosmwasm::types::BlockInfo;
+
+/// An index in 0..n, spread evenly by the hash of seed. 0 if n is 0 or 1.
+pub fn index(seed: &[u8], n: u64) -> u64 {
+    if n <= 1 {
+        return 0;
+    }
+    // the values above the last full multiple of n would favour the low indexes, so redraw them
+    let zone = u64::max_value() - u64::max_value() % n;
+    let mut hash = Sha256::digest(seed);
+    loop {
+        let mut head = [0u8; 8];
+        head.copy_from_slice(&hash[..8]);
+        let value = u64::from_be_bytes(head);
+        if value < zone {
+            return value % n;
+        }
+        hash = Sha256::digest(&hash);
+    }
+}
+
+/// The seed of a choice made in block, salted so different choices in a block differ
+pub fn block_seed(block: &BlockInfo, salt: &[u8]) -> Vec<u8> {
+    let mut seed = Vec::with_capacity(16 + salt.len());
+    seed.extend_from_slice(&(block.height as u64).to_be_bytes());
+    seed.extend_from_slice(&(block.time as u64).to_be_bytes());
+    seed.extend_from_slice(salt);
+    seed
+}
+
+/// Picks one of n candidates for block
+pub fn pick(block: &BlockInfo, salt: &[u8], n: u64) -> u64 {
+    index(&block_seed(block, salt), n)
+}
+
+/// The items in an order shuffled for block, e.g. which approver to ping first
+pub fn shuffle<T: Clone>(block: &BlockInfo, salt: &[u8], items: &[T]) -> Vec<T> {
+    let mut items = items.to_vec();
+    let mut seed = block_seed(block, salt);
+    // Fisher-Yates, with a seed per swap
+    for i in (1..items.len()).rev() {
+        seed.extend_from_slice(&(i as u64).to_be_bytes());
+        let j = index(&seed, i as u64 + 1) as usize;
+        seed.truncate(seed.len() - 8);
+        items.swap(i, j);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(height: i64) -> BlockInfo {
+        BlockInfo {
+            height,
+            time: 1_571_797_419,
+            chain_id: "cosmos-testnet-14002".to_string(),
+        }
+    }
+
+    #[test]
+    fn same_block_and_salt_same_choice() {
+        assert_eq!(
+            pick(&block(12_345), b"keeper", 7),
+            pick(&block(12_345), b"keeper", 7)
+        );
+        let picks: Vec<u64> = (0..20).map(|h| pick(&block(h), b"keeper", 1000)).collect();
+        assert!(picks.iter().any(|&p| p != picks[0]));
+        let salted: Vec<u64> = (0..20)
+            .map(|h| pick(&block(h), b"approver", 1000))
+            .collect();
+        assert_ne!(picks, salted);
+        assert_eq!(0, pick(&block(1), b"keeper", 0));
+        assert_eq!(0, pick(&block(1), b"keeper", 1));
+    }
+
+    #[test]
+    fn picks_are_uniform() {
+        // chi-squared over 10 buckets, 9 degrees of freedom: 27.9 is exceeded 0.1% of the time
+        let draws = 10_000;
+        let mut counts = [0u64; 10];
+        for h in 0..draws {
+            counts[pick(&block(h), b"keeper", 10) as usize] += 1;
+        }
+        let expected = draws as f64 / 10.0;
+        let chi2: f64 = counts
+            .iter()
+            .map(|&c| (c as f64 - expected).powi(2) / expected)
+            .sum();
+        assert!(chi2 < 27.9, "{:?}", counts);
+    }
+
+    #[test]
+    fn shuffles_are_uniform() {
+        let items = ["alice", "bob", "carol", "dave"];
+        let mut first = [0u64; 4];
+        for h in 0..4_000 {
+            let shuffled = shuffle(&block(h), b"approver", &items);
+            let mut sorted = shuffled.clone();
+            sorted.sort();
+            assert_eq!(items.to_vec(), sorted);
+            let at = items.iter().position(|i| *i == shuffled[0]).unwrap();
+            first[at] += 1;
+        }
+        // 3 degrees of freedom: 16.3 is exceeded 0.1% of the time
+        let chi2: f64 = first
+            .iter()
+            .map(|&c| (c as f64 - 1000.0).powi(2) / 1000.0)
+            .sum();
+        assert!(chi2 < 16.3, "{:?}", first);
+        assert!(shuffle::<u8>(&block(1), b"approver", &[]).is_empty());
+    }
+}
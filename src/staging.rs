@@ -0,0 +1,82 @@
+//! Staged storage for dry runs on testnets and for self tests.
+//!
+//! A dry run goes through everything a handle does, but must leave the contract as it was, and
+//! so must the probes of a self test.
+//! Failing the call would roll the writes back along with the response, so the handle runs on
+//! Staged storage instead, which keeps its writes in memory and drops them at the end. Reads
+//! see the staged writes, so the handle behaves exactly as it would for real.
+
+use std::collections::BTreeMap;
+
+use cosmwasm::errors::Result;
+use cosmwasm::traits::{Api, Extern, ReadonlyStorage, Storage};
+
+/// Staged is storage which reads through to storage, but keeps its writes to itself
+pub struct Staged<'a, S: Storage> {
+    storage: &'a S,
+    writes: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl<'a, S: Storage> Staged<'a, S> {
+    pub fn new(storage: &'a S) -> Self {
+        Staged {
+            storage,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// How many keys were written
+    pub fn writes(&self) -> usize {
+        self.writes.len()
+    }
+}
+
+impl<'a, S: Storage> ReadonlyStorage for Staged<'a, S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(value) => Some(value.clone()),
+            None => self.storage.get(key),
+        }
+    }
+}
+
+impl<'a, S: Storage> Storage for Staged<'a, S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), value.to_vec());
+    }
+}
+
+/// Runs a call on staged storage and drops what it wrote. Returns the result of the call and
+/// how many keys it would have written.
+pub fn staged<S: Storage, A: Api, T>(
+    deps: &Extern<S, A>,
+    call: impl FnOnce(&mut Extern<Staged<S>, A>) -> Result<T>,
+) -> Result<(T, usize)> {
+    let mut inner = Extern {
+        storage: Staged::new(&deps.storage),
+        api: deps.api,
+    };
+    let res = call(&mut inner)?;
+    Ok((res, inner.storage.writes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm::mock::MockStorage;
+
+    #[test]
+    fn reads_its_writes_and_drops_them() {
+        let mut storage = MockStorage::new();
+        storage.set(b"a", b"1");
+        let mut staged = Staged::new(&storage);
+        assert_eq!(Some(b"1".to_vec()), staged.get(b"a"));
+        staged.set(b"a", b"2");
+        staged.set(b"b", b"3");
+        assert_eq!(Some(b"2".to_vec()), staged.get(b"a"));
+        assert_eq!(Some(b"3".to_vec()), staged.get(b"b"));
+        assert_eq!(2, staged.writes());
+        assert_eq!(Some(b"1".to_vec()), storage.get(b"a"));
+        assert_eq!(None, storage.get(b"b"));
+    }
+}
@@ -1,15 +1,551 @@
+use std::fmt;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snafu::ResultExt;
 
+use crate::math::{mul_ratio, safe_sum, SafeMath};
+use crate::middleware::MiddlewareConfig;
+use crate::msg::{ConfigOverride, HandleMsg};
+use crate::versioned::{ReadonlyVersionedBucket, Versioned, VersionedBucket};
+use cosmwasm::encoding::Binary;
+use cosmwasm::errors::{contract_err, dyn_contract_err, ParseErr, Result, SerializeErr};
+use cosmwasm::serde::{from_slice, to_vec};
 use cosmwasm::traits::Storage;
-use cosmwasm::types::CanonicalAddr;
-use cw_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
+use cosmwasm::types::{CanonicalAddr, Coin, CosmosMsg, Env, HumanAddr};
+use cw_storage::{
+    bucket, bucket_read, prefixed, prefixed_read, singleton, singleton_read, Bucket,
+    PrefixedStorage, ReadonlyBucket, ReadonlyPrefixedStorage, ReadonlySingleton, Singleton,
+};
 
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static PENDING_OWNER_KEY: &[u8] = b"pending_owner";
+pub static DENOM_PREFIX: &[u8] = b"denoms";
+pub static EXECUTION_PREFIX: &[u8] = b"executions";
+pub static EXECUTION_SEQ_KEY: &[u8] = b"execution_seq";
+pub static GAS_STATS_PREFIX: &[u8] = b"gas_stats";
+pub static ADMIN_PROPOSAL_PREFIX: &[u8] = b"admin_proposals";
+pub static ADMIN_PROPOSAL_SEQ_KEY: &[u8] = b"admin_proposal_seq";
+pub static LAST_USED_PREFIX: &[u8] = b"last_used";
+pub static STAGED_BATCH_PREFIX: &[u8] = b"staged_batches";
+pub static STAGED_BATCH_SEQ_KEY: &[u8] = b"staged_batch_seq";
+pub static TAG_INDEX_PREFIX: &[u8] = b"tag_index";
+pub static TAG_COUNT_PREFIX: &[u8] = b"tag_counts";
+pub static TAG_SPEND_PREFIX: &[u8] = b"tag_spend";
+pub static GRANT_PREFIX: &[u8] = b"grants";
+pub static GRANT_SEQ_KEY: &[u8] = b"grant_seq";
+pub static SWAP_PREFIX: &[u8] = b"swaps";
+pub static SWAP_SEQ_KEY: &[u8] = b"swap_seq";
+pub static PERMISSION_PREFIX: &[u8] = b"permissions";
+pub static DENYLIST_PREFIX: &[u8] = b"denylist";
+pub static AUCTION_PREFIX: &[u8] = b"auctions";
+pub static AUCTION_SEQ_KEY: &[u8] = b"auction_seq";
+pub static SCHEDULED_BATCH_PREFIX: &[u8] = b"scheduled_batches";
+pub static SCHEDULED_BATCH_SEQ_KEY: &[u8] = b"scheduled_batch_seq";
+pub static REVENUE_PREFIX: &[u8] = b"revenue";
+pub static REVENUE_SETTLED_PREFIX: &[u8] = b"revenue_settled";
+pub static STAKEHOLDERS_KEY: &[u8] = b"stakeholders";
+pub static PROPOSAL_PREFIX: &[u8] = b"proposals";
+pub static PROPOSAL_SEQ_KEY: &[u8] = b"proposal_seq";
+pub static SPEND_TRACKER_PREFIX: &[u8] = b"spend_tracker";
+pub static HISTORY_PREFIX: &[u8] = b"history";
+pub static HISTORY_SEQ_KEY: &[u8] = b"history_seq";
+pub static CONTRACT_VERSION_KEY: &[u8] = b"contract_version";
+pub static GUARDIANS_KEY: &[u8] = b"guardians";
+pub static RECOVERY_SESSION_KEY: &[u8] = b"recovery_session";
+pub static LIFETIME_DEPOSITS_KEY: &[u8] = b"lifetime_deposits";
+pub static COLLECTED_FEES_KEY: &[u8] = b"collected_fees";
+pub static OPERATOR_WINDOW_PREFIX: &[u8] = b"operator_windows";
+pub static CALENDAR_PREFIX: &[u8] = b"calendars";
+pub static OPERATOR_CALENDAR_PREFIX: &[u8] = b"operator_calendars";
+pub static STATS_PREFIX: &[u8] = b"stats";
+pub static GLOBAL_STATS_KEY: &[u8] = b"global_stats";
+pub static METRICS_PREFIX: &[u8] = b"metrics";
+pub static HEARTBEAT_SEQ_KEY: &[u8] = b"heartbeat_seq";
+pub static DATA_PREFIX: &[u8] = b"data";
+pub static DATA_KEYS_KEY: &[u8] = b"data_keys";
+pub static SESSION_PREFIX: &[u8] = b"sessions";
+pub static QUEUED_EXECUTION_PREFIX: &[u8] = b"queued_executions";
+pub static QUEUED_EXECUTION_SEQ_KEY: &[u8] = b"queued_execution_seq";
+pub static ONE_TIME_AUTH_PREFIX: &[u8] = b"one_time_auths";
+pub static ONE_TIME_AUTH_HASH_PREFIX: &[u8] = b"one_time_auth_hashes";
+pub static ONE_TIME_AUTH_SEQ_KEY: &[u8] = b"one_time_auth_seq";
+pub static MIGRATION_CURSOR_PREFIX: &[u8] = b"migration_cursors";
+pub static ATTESTATION_KEY: &[u8] = b"attestation";
+pub static USAGE_PREFIX: &[u8] = b"storage_usage";
+pub static USAGE_NAMESPACES_KEY: &[u8] = b"storage_usage_namespaces";
+pub static MACRO_PREFIX: &[u8] = b"macros";
+pub static MACRO_NAMES_KEY: &[u8] = b"macro_names";
+pub static MACRO_RUNS_PREFIX: &[u8] = b"macro_runs";
+pub static CONFIG_VERSION_KEY: &[u8] = b"config_version";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub owner: CanonicalAddr,
+    /// If set, this is a joint account: any owner may reflect,
+    /// but admin actions need the approval of all owners
+    pub co_owners: Vec<CanonicalAddr>,
+    /// If set, admin proposals pass once the approving owners carry this much weight,
+    /// rather than needing all owners
+    pub admin_threshold: Option<u64>,
+    /// Voting weights of the owners, those not listed here have weight 1
+    pub weights: Vec<OwnerWeight>,
+    /// Minimum time between two uses of the same handle action
+    pub cooldowns: Vec<Cooldown>,
+    /// Operators prepare batches, but cannot execute anything themselves
+    pub operators: Vec<CanonicalAddr>,
+    /// Nonce of the last applied operator manifest, 0 if none was applied
+    pub operator_manifest_nonce: u64,
+    /// Taxonomy of tags executions may be classified with
+    pub tags: Vec<String>,
+    pub budgets: Vec<TagBudget>,
+    /// Evacuate sends all funds here, unless the admins pick another address
+    pub recovery: Option<CanonicalAddr>,
+    /// Set by Pause or Evacuate, nothing but the owner key can change until an admin unpauses
+    pub paused: bool,
+    /// Whether the balance was swept since the contract was last unpaused
+    pub evacuated: bool,
+    /// Once decommissioned, the contract rejects every handle for good
+    pub decommissioned: bool,
+    /// Once renounced, no one is an owner anymore and admin actions are impossible
+    pub renounced: bool,
+    /// Where a decommissioned contract points its users to
+    pub successor: Option<CanonicalAddr>,
+    /// A config change which takes effect once its activation point is reached
+    pub pending: Option<PendingConfig>,
+    pub chain: ChainProfile,
+    /// Most bytes the messages of one reflect call may serialize to
+    pub max_msg_bytes: Option<u64>,
+    /// If set, reflected contract calls may only go to these contracts
+    pub allowed_targets: Option<Vec<HumanAddr>>,
+    /// If set, this contract is sent a DepositReceipt for every deposit
+    pub deposit_hook: Option<HumanAddr>,
+    /// If set, only these addresses, the owners and the contract itself may send funds along
+    pub depositors: Option<Vec<CanonicalAddr>>,
+    /// If set, sending more than one of these amounts in a single message needs a TravelMemo
+    pub travel_rule: Option<Vec<Coin>>,
+    /// If set, reflected messages may send at most the limit of each denom per period
+    pub spend_limits: Option<Vec<SpendLimit>>,
+    /// Operators which stay configured, but may not stage batches until reinstated
+    pub suspended_operators: Option<Vec<CanonicalAddr>>,
+    /// Messages of these kinds only go out through a proposal approved by someone other than
+    /// its proposer, even owners cannot reflect them directly
+    pub four_eyes: Option<Vec<MsgKind>>,
+    /// What callers reflecting through a permission pay per call
+    pub reflect_fee: Option<Vec<Coin>>,
+    /// If set, these contracts are sent a RulesetMsg before every reflected batch, in order,
+    /// and each may veto it
+    pub rulesets: Option<Vec<HumanAddr>>,
+    /// Every reflected message is checked against these rules top-down, the first match decides
+    pub rules: Option<Vec<Rule>>,
+    /// If set, reflected batches with a message over a threshold wait for the co-signer
+    pub cosign: Option<Cosign>,
+    /// If set, every reflected batch runs through these middlewares first, in order
+    pub middlewares: Option<Vec<MiddlewareConfig>>,
+    /// If set, batches are only dispatched under a configuration attested at most this long ago
+    pub attestation_max_age: Option<Duration>,
+    /// If set, this contract is sent an ArchiveMsg with the full batch of every execution
+    pub archive: Option<HumanAddr>,
+    /// Gas per message of a kind, to plan batches with. Reported gas fills in the others.
+    pub gas_heuristics: Option<Vec<GasHeuristic>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerWeight {
+    pub addr: CanonicalAddr,
+    pub weight: u64,
+}
+
+impl State {
+    /// Hex SHA-256 of the active configuration, without staged changes. Any setting changes
+    /// it, so an execution can be matched to the policy it ran under.
+    pub fn checksum(&self) -> Result<String> {
+        let active = State {
+            pending: None,
+            ..self.clone()
+        };
+        let json = to_vec(&active).context(SerializeErr { kind: "State" })?;
+        let hash = Sha256::digest(&json);
+        Ok(hash.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    pub fn is_joint(&self) -> bool {
+        !self.co_owners.is_empty()
+    }
+
+    /// True for the owner and all co-owners, unless they renounced
+    pub fn is_owner(&self, addr: &CanonicalAddr) -> bool {
+        !self.renounced && (self.owner == *addr || self.co_owners.contains(addr))
+    }
+
+    /// True for operators which are not suspended
+    pub fn is_active_operator(&self, addr: &CanonicalAddr) -> bool {
+        let suspended = self.suspended_operators.iter().flatten().any(|a| a == addr);
+        self.operators.contains(addr) && !suspended
+    }
+
+    /// Forgets the suspensions of addresses which are no longer operators
+    pub fn prune_suspended(&mut self) {
+        let operators = &self.operators;
+        if let Some(suspended) = self.suspended_operators.as_mut() {
+            suspended.retain(|a| operators.contains(a));
+        }
+        if self
+            .suspended_operators
+            .as_ref()
+            .map_or(false, |s| s.is_empty())
+        {
+            self.suspended_operators = None;
+        }
+    }
+
+    /// All owners, which for a joint account must all approve admin proposals
+    pub fn owners(&self) -> Vec<CanonicalAddr> {
+        let mut owners = vec![self.owner.clone()];
+        owners.extend(self.co_owners.iter().cloned());
+        owners
+    }
+
+    pub fn weight_of(&self, addr: &CanonicalAddr) -> u64 {
+        self.weights
+            .iter()
+            .find(|w| w.addr == *addr)
+            .map(|w| w.weight)
+            .unwrap_or(1)
+    }
+
+    /// Sums up the weight of all current owners among the approvals
+    pub fn approved_weight(&self, approvals: &[CanonicalAddr]) -> u64 {
+        self.owners()
+            .iter()
+            .filter(|owner| approvals.contains(owner))
+            .fold(0u64, |total, owner| {
+                total.saturating_add(self.weight_of(owner))
+            })
+    }
+
+    /// Ensures admin proposals can still pass with the current owners and weights
+    pub fn validate_threshold(&self) -> Result<()> {
+        if let Some(threshold) = self.admin_threshold {
+            let total = safe_sum(self.owners().iter().map(|o| self.weight_of(o)))?;
+            if threshold == 0 || threshold > total {
+                return dyn_contract_err(format!(
+                    "Threshold must be between 1 and the total weight {}",
+                    total
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether an admin proposal with these approvals may be executed
+    pub fn is_approved(&self, approvals: &[CanonicalAddr]) -> bool {
+        match self.admin_threshold {
+            Some(threshold) => self.approved_weight(approvals) >= threshold,
+            None => self.owners().iter().all(|owner| approvals.contains(owner)),
+        }
+    }
+
+    /// Admin actions are done by the owner directly, unless this is a joint account.
+    /// There they must be approved by all owners, and the contract then executes them
+    /// as signer on their behalf.
+    pub fn can_admin(&self, env: &Env) -> bool {
+        if self.renounced {
+            false
+        } else if self.is_joint() {
+            env.message.signer == env.contract.address
+        } else {
+            env.message.signer == self.owner
+        }
+    }
+
+    /// Whether the signer may send the funds of this call, if there are any
+    pub fn accepts_funds_from(&self, env: &Env) -> bool {
+        let signer = &env.message.signer;
+        match (&env.message.sent_funds, &self.depositors) {
+            (Some(funds), Some(depositors)) if !funds.is_empty() => {
+                depositors.contains(signer)
+                    || self.is_owner(signer)
+                    || *signer == env.contract.address
+            }
+            _ => true,
+        }
+    }
+
+    /// Ensures all tags are part of the taxonomy and none is given twice
+    pub fn validate_tags(&self, tags: &[String]) -> Result<()> {
+        for (i, tag) in tags.iter().enumerate() {
+            if !self.tags.contains(tag) {
+                return dyn_contract_err(format!("Unknown tag {}", tag));
+            }
+            if tags[..i].contains(tag) {
+                return dyn_contract_err(format!("Duplicate tag {}", tag));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Budget limits how much of one denom executions with a tag may send per period
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Budget {
+    pub limit: Coin,
+    pub period: Duration,
+    /// If set, executions which would go over the limit are rejected.
+    /// Otherwise crossing it is only reported.
+    pub enforce: bool,
+}
+
+impl Budget {
+    pub fn validate(&self) -> Result<()> {
+        if self.limit.denom.is_empty() || parse_amount(&self.limit.amount)? == 0 {
+            return contract_err("Budget limit must be a positive amount of a denom");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TagBudget {
+    pub tag: String,
+    pub budget: Budget,
+}
+
+/// TagSpend sums up what executions with a tag sent since the period started
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TagSpend {
+    pub since_height: u64,
+    pub since_time: u64,
+    pub spent: String,
+}
+
+impl TagSpend {
+    pub fn starting(env: &Env) -> Self {
+        TagSpend {
+            since_height: env.block.height as u64,
+            since_time: env.block.time as u64,
+            spent: "0".to_string(),
+        }
+    }
+
+    pub fn resets_at(&self, period: Duration) -> Expiration {
+        period.after(self.since_height, self.since_time)
+    }
+}
+
+/// SpendLimit caps what reflected messages send of a denom in each period of block time.
+/// Periods are aligned to multiples of period_secs, so they all start at the same times.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendLimit {
+    pub limit: Coin,
+    pub period_secs: u64,
+}
+
+impl SpendLimit {
+    /// The start of the period which contains this block time
+    pub fn period_start(&self, time: u64) -> u64 {
+        time - time % self.period_secs
+    }
+}
+
+/// SpendTracker sums up what was sent of a denom since the period started
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendTracker {
+    pub since: u64,
+    pub spent: String,
+}
+
+pub fn parse_amount(amount: &str) -> Result<u128> {
+    match amount.parse::<u128>() {
+        Ok(value) => Ok(value),
+        Err(_) => dyn_contract_err(format!("Invalid amount: {}", amount)),
+    }
+}
+
+/// Sums up how much of the denom the messages send, either directly or along with contract calls
+pub fn sent_amount(msgs: &[CosmosMsg], denom: &str) -> Result<u128> {
+    let mut total: u128 = 0;
+    for msg in msgs.iter() {
+        let coins: &[Coin] = match msg {
+            CosmosMsg::Send { amount, .. } => amount,
+            CosmosMsg::Contract {
+                send: Some(send), ..
+            } => send,
+            _ => &[],
+        };
+        for coin in coins.iter().filter(|c| c.denom == denom) {
+            total = total.safe_add(parse_amount(&coin.amount)?)?;
+        }
+    }
+    Ok(total)
+}
+
+/// Adds up the coins per denom, in the order the denoms first appear
+pub fn sum_coins<'a, I: IntoIterator<Item = &'a Coin>>(coins: I) -> Result<Vec<Coin>> {
+    let mut totals: Vec<(String, u128)> = vec![];
+    for coin in coins {
+        let amount = parse_amount(&coin.amount)?;
+        match totals.iter_mut().find(|(denom, _)| *denom == coin.denom) {
+            Some((_, total)) => *total = total.safe_add(amount)?,
+            None => totals.push((coin.denom.clone(), amount)),
+        }
+    }
+    Ok(totals
+        .into_iter()
+        .map(|(denom, amount)| Coin {
+            denom,
+            amount: amount.to_string(),
+        })
+        .collect())
+}
+
+pub const MAX_TAG_LEN: usize = 32;
+
+/// Tags are short lowercase identifiers, like "payroll" or "grants"
+pub fn validate_tag_name(tag: &str) -> Result<()> {
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+    if tag.is_empty() || tag.len() > MAX_TAG_LEN || !tag.chars().all(valid_char) {
+        return dyn_contract_err(format!(
+            "Tag {} must be 1 to {} characters of a-z, 0-9 or _",
+            tag, MAX_TAG_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Free text is written into responses as it is, and serde-json-wasm does not escape
+/// strings, so quotes, backslashes and control characters would break their JSON
+pub fn validate_plain_text(field: &str, text: &str) -> Result<()> {
+    if text
+        .chars()
+        .any(|c| c == '"' || c == '\\' || c.is_control())
+    {
+        return dyn_contract_err(format!(
+            "{} cannot contain quotes, backslashes or control characters",
+            field
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cooldown {
+    pub action: String,
+    pub period: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfig {
+    pub change: ConfigOverride,
+    pub activate_at: Expiration,
+}
+
+/// Duration is a span of either blocks or seconds
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Duration {
+    Height(u64),
+    Time(u64),
+}
+
+impl Duration {
+    /// The point this duration after the given block
+    pub fn after(self, height: u64, time: u64) -> Expiration {
+        match self {
+            Duration::Height(h) => Expiration::AtHeight(height.saturating_add(h)),
+            Duration::Time(t) => Expiration::AtTime(time.saturating_add(t)),
+        }
+    }
+}
+
+/// Expiration is a point in either block height or block time (in seconds)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Expiration {
+    AtHeight(u64),
+    AtTime(u64),
+}
+
+impl Expiration {
+    pub fn is_expired(self, env: &Env) -> bool {
+        match self {
+            Expiration::AtHeight(h) => env.block.height as u64 >= h,
+            Expiration::AtTime(t) => env.block.time as u64 >= t,
+        }
+    }
+}
+
+impl fmt::Display for Expiration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expiration::AtHeight(h) => write!(f, "height {}", h),
+            Expiration::AtTime(t) => write!(f, "time {}", t),
+        }
+    }
+}
+
+/// ChainProfile describes the chain this instance is deployed on.
+/// It is fixed at init, so the same code works on every chain we deploy to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainProfile {
+    pub bech32_prefix: String,
+    pub native_denom: String,
+    pub block_time_secs: u64,
+    /// The denom of settings which give an amount without one, native_denom if None
+    pub default_denom: Option<String>,
+}
+
+impl ChainProfile {
+    pub fn validate(&self) -> Result<()> {
+        if self.bech32_prefix.is_empty() {
+            return contract_err("bech32_prefix must not be empty");
+        }
+        if self.native_denom.is_empty() {
+            return contract_err("native_denom must not be empty");
+        }
+        if self.block_time_secs == 0 {
+            return contract_err("block_time_secs must be positive");
+        }
+        if self.default_denom.as_ref().map_or(false, |d| d.is_empty()) {
+            return contract_err("default_denom must not be empty");
+        }
+        Ok(())
+    }
+
+    pub fn default_denom(&self) -> &str {
+        match &self.default_denom {
+            Some(denom) => denom,
+            None => &self.native_denom,
+        }
+    }
+
+    /// Ensures the address was encoded for this chain
+    pub fn validate_address(&self, addr: &HumanAddr) -> Result<()> {
+        if !addr.as_str().starts_with(&self.bech32_prefix) {
+            return dyn_contract_err(format!(
+                "Address {} does not use prefix {}",
+                addr.as_str(),
+                self.bech32_prefix
+            ));
+        }
+        Ok(())
+    }
+
+    /// Estimates how many blocks it takes for `secs` seconds to pass (rounding up)
+    pub fn blocks_for_secs(&self, secs: u64) -> u64 {
+        let blocks = secs / self.block_time_secs;
+        if secs % self.block_time_secs == 0 {
+            blocks
+        } else {
+            blocks + 1
+        }
+    }
+
+    /// Estimates how many seconds it takes to produce `blocks` blocks
+    pub fn secs_for_blocks(&self, blocks: u64) -> u64 {
+        blocks.saturating_mul(self.block_time_secs)
+    }
 }
 
 pub fn config<S: Storage>(storage: &mut S) -> Singleton<S, State> {
@@ -19,3 +555,1525 @@ pub fn config<S: Storage>(storage: &mut S) -> Singleton<S, State> {
 pub fn config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, State> {
     singleton_read(storage, CONFIG_KEY)
 }
+
+/// PendingOwner becomes the owner once it accepts, if it does so before expires
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner: CanonicalAddr,
+    pub expires: Expiration,
+}
+
+/// pending_owner holds the ownership transfer waiting to be accepted, if any.
+/// Storage cannot remove keys, so a finished transfer is saved as None.
+pub fn pending_owner<S: Storage>(storage: &mut S) -> Singleton<S, Option<PendingOwner>> {
+    singleton(storage, PENDING_OWNER_KEY)
+}
+
+pub fn pending_owner_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Option<PendingOwner>> {
+    singleton_read(storage, PENDING_OWNER_KEY)
+}
+
+/// Most guardians a contract may have
+pub const MAX_GUARDIANS: usize = 20;
+
+/// Most co-owners a joint account may have, besides the owner
+pub const MAX_CO_OWNERS: usize = 10;
+
+/// Most operators a contract may have
+pub const MAX_OPERATORS: usize = 20;
+
+/// Most rules contracts every reflected batch is checked by
+pub const MAX_RULESETS: usize = 5;
+
+/// Guardians can hand the contract to a new owner if the owner key is lost
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Guardians {
+    pub addrs: Vec<CanonicalAddr>,
+    /// How many guardians must approve a recovery
+    pub threshold: u64,
+    /// How long a recovery stays open for approvals once it is initiated
+    pub window: Duration,
+}
+
+impl Guardians {
+    pub fn is_guardian(&self, addr: &CanonicalAddr) -> bool {
+        self.addrs.contains(addr)
+    }
+}
+
+pub fn guardians<S: Storage>(storage: &mut S) -> Singleton<S, Option<Guardians>> {
+    singleton(storage, GUARDIANS_KEY)
+}
+
+pub fn guardians_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Option<Guardians>> {
+    singleton_read(storage, GUARDIANS_KEY)
+}
+
+/// RecoverySession replaces the owner once threshold guardians approved it before expires
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecoverySession {
+    pub new_owner: CanonicalAddr,
+    /// The guardians which approved, starting with the one which initiated it
+    pub approvals: Vec<CanonicalAddr>,
+    pub expires: Expiration,
+}
+
+/// recovery_session holds the recovery guardians are approving, if any.
+/// Like pending_owner, a finished or cancelled session is saved as None.
+pub fn recovery_session<S: Storage>(storage: &mut S) -> Singleton<S, Option<RecoverySession>> {
+    singleton(storage, RECOVERY_SESSION_KEY)
+}
+
+pub fn recovery_session_read<S: Storage>(
+    storage: &S,
+) -> ReadonlySingleton<S, Option<RecoverySession>> {
+    singleton_read(storage, RECOVERY_SESSION_KEY)
+}
+
+/// ContractVersion names the code an instance was created with, for upgrades to check
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractVersion {
+    pub contract: String,
+    pub version: String,
+}
+
+impl ContractVersion {
+    /// The version of this build
+    pub fn current() -> Self {
+        ContractVersion {
+            contract: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+pub fn contract_version<S: Storage>(storage: &mut S) -> Singleton<S, ContractVersion> {
+    singleton(storage, CONTRACT_VERSION_KEY)
+}
+
+pub fn contract_version_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, ContractVersion> {
+    singleton_read(storage, CONTRACT_VERSION_KEY)
+}
+
+/// DenomMetadata lets queries render raw integer amounts in display units
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomMetadata {
+    pub symbol: String,
+    pub exponent: u32,
+}
+
+impl DenomMetadata {
+    /// Scales a raw integer amount down by the exponent, eg. "1500000" with exponent 6 is "1.5"
+    pub fn display_amount(&self, amount: &str) -> Result<String> {
+        if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+            return dyn_contract_err(format!("Invalid amount: {}", amount));
+        }
+        let exp = self.exponent as usize;
+        let digits = amount.trim_start_matches('0');
+        let padded = format!("{:0>width$}", digits, width = exp + 1);
+        let (whole, frac) = padded.split_at(padded.len() - exp);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            Ok(whole.to_string())
+        } else {
+            Ok(format!("{}.{}", whole, frac))
+        }
+    }
+}
+
+pub fn denoms<S: Storage>(storage: &mut S) -> Bucket<S, DenomMetadata> {
+    bucket(DENOM_PREFIX, storage)
+}
+
+pub fn denoms_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, DenomMetadata> {
+    bucket_read(DENOM_PREFIX, storage)
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MsgKind {
+    Send,
+    Contract,
+    Opaque,
+}
+
+impl MsgKind {
+    pub fn of(msg: &CosmosMsg) -> MsgKind {
+        match msg {
+            CosmosMsg::Send { .. } => MsgKind::Send,
+            CosmosMsg::Contract { .. } => MsgKind::Contract,
+            CosmosMsg::Opaque { .. } => MsgKind::Opaque,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MsgKind::Send => "send",
+            MsgKind::Contract => "contract",
+            MsgKind::Opaque => "opaque",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KindCount {
+    pub kind: MsgKind,
+    pub count: u32,
+}
+
+/// Counts the messages of each kind, in order of first appearance
+pub fn count_kinds(msgs: &[CosmosMsg]) -> Vec<KindCount> {
+    let mut counts: Vec<KindCount> = vec![];
+    for msg in msgs {
+        let kind = MsgKind::of(msg);
+        match counts.iter_mut().find(|c| c.kind == kind) {
+            Some(c) => c.count += 1,
+            None => counts.push(KindCount { kind, count: 1 }),
+        }
+    }
+    counts
+}
+
+/// Execution records one successful reflect call
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Execution {
+    pub id: u64,
+    pub sender: CanonicalAddr,
+    pub height: i64,
+    pub msgs: Vec<KindCount>,
+    /// Gas is only known after the fact and reported by the owner
+    pub gas_used: Option<u64>,
+    pub tags: Vec<String>,
+    pub memo: Option<TravelMemo>,
+    /// The SHA-256 of the payload sent to the archive, if there was one
+    pub payload_hash: Option<Binary>,
+}
+
+/// HistoryEntry is one record of the append-only audit log, of either a reflect call
+/// (with its messages) or an owner change (with the new owner)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryEntry {
+    pub seq: u64,
+    pub sender: CanonicalAddr,
+    pub height: u64,
+    pub action: String,
+    pub msgs: Vec<KindCount>,
+    pub owner: Option<CanonicalAddr>,
+    /// The intents the contract calls were checked against, if declared
+    pub intents: Option<Vec<WasmIntent>>,
+}
+
+pub fn history<S: Storage>(storage: &mut S) -> Bucket<S, HistoryEntry> {
+    bucket(HISTORY_PREFIX, storage)
+}
+
+pub fn history_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, HistoryEntry> {
+    bucket_read(HISTORY_PREFIX, storage)
+}
+
+pub fn last_history_seq<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, HISTORY_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_history_seq<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, HISTORY_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// Counts the successful init and handle calls, so each response carries the next number
+pub fn next_heartbeat_seq<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, HEARTBEAT_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+pub const MAX_MEMO_FIELD_LEN: usize = 128;
+
+/// TravelMemo names the parties of a large transfer, as the travel rule requires
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TravelMemo {
+    /// Reference of the originator, eg. a customer id
+    pub originator: String,
+    /// Reference of the beneficiary
+    pub beneficiary: String,
+}
+
+impl TravelMemo {
+    pub fn validate(&self) -> Result<()> {
+        let fields = [
+            ("originator", &self.originator),
+            ("beneficiary", &self.beneficiary),
+        ];
+        for (name, value) in fields.iter() {
+            if value.trim().is_empty() || value.len() > MAX_MEMO_FIELD_LEN {
+                return dyn_contract_err(format!(
+                    "Memo {} must be 1 to {} characters",
+                    name, MAX_MEMO_FIELD_LEN
+                ));
+            }
+            validate_plain_text(&format!("Memo {}", name), value)?;
+        }
+        Ok(())
+    }
+}
+
+/// WasmIntent declares what a contract call does, so reviewers can approve the intent
+/// rather than the raw message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WasmIntent {
+    pub contract_addr: HumanAddr,
+    /// The method the call invokes, like "transfer"
+    pub method: String,
+    /// Most the call may send along, denoms which are not listed may not be sent at all
+    pub max_funds: Vec<Coin>,
+}
+
+impl WasmIntent {
+    pub fn validate(&self) -> Result<()> {
+        if self.method.is_empty() {
+            return contract_err("Intent needs a method");
+        }
+        if sum_coins(&self.max_funds)?.len() != self.max_funds.len() {
+            return contract_err("Intent lists the same denom twice");
+        }
+        Ok(())
+    }
+
+    /// Describes how the message differs from the intent, None if it matches
+    pub fn mismatch(&self, msg: &CosmosMsg) -> Result<Option<String>> {
+        let (contract_addr, msg, send) = match msg {
+            CosmosMsg::Contract {
+                contract_addr,
+                msg,
+                send,
+            } => (contract_addr, msg, send),
+            _ => return Ok(Some("message is not a contract call".to_string())),
+        };
+        if *contract_addr != self.contract_addr {
+            return Ok(Some(format!(
+                "intent is for {}",
+                self.contract_addr.as_str()
+            )));
+        }
+        match call_method(&msg.0) {
+            Some(ref method) if *method == self.method => {}
+            Some(method) => {
+                return Ok(Some(format!("calls {} instead of {}", method, self.method)))
+            }
+            None => return Ok(Some("message names no method".to_string())),
+        }
+        for coin in sum_coins(send.iter().flatten())?.iter() {
+            let max = match self.max_funds.iter().find(|c| c.denom == coin.denom) {
+                Some(max) => parse_amount(&max.amount)?,
+                None => 0,
+            };
+            if parse_amount(&coin.amount)? > max {
+                return Ok(Some(format!(
+                    "sends {}{}, at most {}{} declared",
+                    coin.amount, coin.denom, max, coin.denom
+                )));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Most rules the owner may install
+pub const MAX_RULES: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Deny,
+}
+
+/// Amount is a single-coin setting, in the default denom of the chain unless it names another
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Amount {
+    pub denom: Option<String>,
+    pub amount: String,
+}
+
+impl Amount {
+    pub fn coin(&self, chain: &ChainProfile) -> Coin {
+        let denom = match &self.denom {
+            Some(denom) => denom.clone(),
+            None => chain.default_denom().to_string(),
+        };
+        Coin {
+            denom,
+            amount: self.amount.clone(),
+        }
+    }
+}
+
+impl From<Coin> for Amount {
+    fn from(coin: Coin) -> Self {
+        Amount {
+            denom: Some(coin.denom),
+            amount: coin.amount,
+        }
+    }
+}
+
+/// The coins of amounts, in the default denom of chain where they name none
+pub fn coins_of(amounts: &[Amount], chain: &ChainProfile) -> Vec<Coin> {
+    amounts.iter().map(|a| a.coin(chain)).collect()
+}
+
+/// Rule allows or denies the messages it matches. A message matches if it meets every
+/// criterion which is set, a rule without criteria matches everything.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Rule {
+    pub kind: Option<MsgKind>,
+    /// The recipient of a send or the contract called
+    pub target: Option<HumanAddr>,
+    /// Only matches messages sending at most this much of the denom
+    pub max_amount: Option<Amount>,
+    pub action: RuleAction,
+}
+
+impl Rule {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(max) = &self.max_amount {
+            parse_amount(&max.amount)?;
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, msg: &CosmosMsg, chain: &ChainProfile) -> Result<bool> {
+        if self.kind.map_or(false, |kind| kind != MsgKind::of(msg)) {
+            return Ok(false);
+        }
+        if let Some(target) = &self.target {
+            let addr = match msg {
+                CosmosMsg::Send { to_address, .. } => Some(to_address),
+                CosmosMsg::Contract { contract_addr, .. } => Some(contract_addr),
+                CosmosMsg::Opaque { .. } => None,
+            };
+            if addr != Some(target) {
+                return Ok(false);
+            }
+        }
+        if let Some(max) = &self.max_amount {
+            let max = max.coin(chain);
+            let sent = sent_amount(std::slice::from_ref(msg), &max.denom)?;
+            if sent > parse_amount(&max.amount)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// The method a contract call invokes, which is the first key of its JSON message
+pub fn call_method(msg: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(msg).ok()?.trim_start();
+    if !text.starts_with('{') {
+        return None;
+    }
+    let rest = text[1..].trim_start();
+    if !rest.starts_with('"') {
+        return None;
+    }
+    let rest = &rest[1..];
+    let end = rest.find('"')?;
+    let method = &rest[..end];
+    // escaped keys are not worth decoding, no contract uses them as method names
+    if method.is_empty() || method.contains('\\') {
+        return None;
+    }
+    if !rest[end + 1..].trim_start().starts_with(':') {
+        return None;
+    }
+    Some(method.to_string())
+}
+
+/// GasStats aggregates the reported gas apportioned to one message kind
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct GasStats {
+    pub msgs: u64,
+    pub gas: u64,
+}
+
+/// GasHeuristic is the gas a message of this kind is assumed to cost
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct GasHeuristic {
+    pub kind: MsgKind,
+    pub gas_per_msg: u64,
+}
+
+impl Versioned for Execution {
+    const VERSION: u8 = 2;
+    const KIND: &'static str = "Execution";
+
+    // version 2 added payload_hash, which the older layouts decode to None
+    fn upgrade(version: u8, data: &[u8]) -> Result<Self> {
+        match version {
+            0 | 1 => from_slice(data).context(ParseErr { kind: Self::KIND }),
+            _ => dyn_contract_err(format!("No upgrade of Execution from version {}", version)),
+        }
+    }
+}
+
+pub fn executions<S: Storage>(storage: &mut S) -> VersionedBucket<S, Execution> {
+    VersionedBucket::new(EXECUTION_PREFIX, storage)
+}
+
+pub fn executions_read<S: Storage>(storage: &S) -> ReadonlyVersionedBucket<S, Execution> {
+    ReadonlyVersionedBucket::new(EXECUTION_PREFIX, storage)
+}
+
+/// migration_cursors holds the last id MigrateRecords reached, by the prefix of the collection
+pub fn migration_cursors<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(MIGRATION_CURSOR_PREFIX, storage)
+}
+
+pub fn migration_cursors_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(MIGRATION_CURSOR_PREFIX, storage)
+}
+
+/// Returns the id to use for the next execution
+pub fn last_execution_id<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, EXECUTION_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_execution_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, EXECUTION_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+pub fn gas_stats<S: Storage>(storage: &mut S) -> Bucket<S, GasStats> {
+    bucket(GAS_STATS_PREFIX, storage)
+}
+
+pub fn gas_stats_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, GasStats> {
+    bucket_read(GAS_STATS_PREFIX, storage)
+}
+
+/// AdminProposal collects approvals for an admin action on a joint account
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminProposal {
+    pub id: u64,
+    pub msg: HandleMsg,
+    pub approvals: Vec<CanonicalAddr>,
+    pub executed: bool,
+}
+
+pub fn admin_proposals<S: Storage>(storage: &mut S) -> Bucket<S, AdminProposal> {
+    bucket(ADMIN_PROPOSAL_PREFIX, storage)
+}
+
+pub fn admin_proposals_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, AdminProposal> {
+    bucket_read(ADMIN_PROPOSAL_PREFIX, storage)
+}
+
+pub fn last_admin_proposal_id<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, ADMIN_PROPOSAL_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_admin_proposal_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, ADMIN_PROPOSAL_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// LastUsed remembers the block an action was last run in, to enforce cooldowns
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LastUsed {
+    pub height: u64,
+    pub time: u64,
+}
+
+pub fn last_used<S: Storage>(storage: &mut S) -> Bucket<S, LastUsed> {
+    bucket(LAST_USED_PREFIX, storage)
+}
+
+pub fn last_used_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, LastUsed> {
+    bucket_read(LAST_USED_PREFIX, storage)
+}
+
+pub const MAX_BATCH_MEMO_LEN: usize = 256;
+
+/// StagedBatch is prepared by an operator and can only be executed by an owner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StagedBatch {
+    pub id: u64,
+    pub operator: CanonicalAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub memo: String,
+    pub receipt_contract: Option<HumanAddr>,
+    pub tags: Vec<String>,
+    pub staged_height: i64,
+    pub executed: bool,
+}
+
+pub fn staged_batches<S: Storage>(storage: &mut S) -> Bucket<S, StagedBatch> {
+    bucket(STAGED_BATCH_PREFIX, storage)
+}
+
+pub fn staged_batches_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, StagedBatch> {
+    bucket_read(STAGED_BATCH_PREFIX, storage)
+}
+
+pub fn last_staged_batch_id<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, STAGED_BATCH_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_staged_batch_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, STAGED_BATCH_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// Key of the n-th execution with this tag in the tag index.
+/// Keys of different tags have different lengths, so they never collide.
+pub fn tag_index_key(tag: &str, n: u64) -> Vec<u8> {
+    let mut key = tag.as_bytes().to_vec();
+    key.extend_from_slice(&n.to_be_bytes());
+    key
+}
+
+/// tag_index maps tag_index_key to the execution id
+pub fn tag_index<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(TAG_INDEX_PREFIX, storage)
+}
+
+pub fn tag_index_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(TAG_INDEX_PREFIX, storage)
+}
+
+/// tag_counts holds the number of executions indexed per tag
+pub fn tag_counts<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(TAG_COUNT_PREFIX, storage)
+}
+
+pub fn tag_counts_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(TAG_COUNT_PREFIX, storage)
+}
+
+pub fn tag_spend<S: Storage>(storage: &mut S) -> Bucket<S, TagSpend> {
+    bucket(TAG_SPEND_PREFIX, storage)
+}
+
+pub fn tag_spend_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, TagSpend> {
+    bucket_read(TAG_SPEND_PREFIX, storage)
+}
+
+/// spend_tracker maps a denom to what was sent of it in the current period
+pub fn spend_tracker<S: Storage>(storage: &mut S) -> Bucket<S, SpendTracker> {
+    bucket(SPEND_TRACKER_PREFIX, storage)
+}
+
+pub fn spend_tracker_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, SpendTracker> {
+    bucket_read(SPEND_TRACKER_PREFIX, storage)
+}
+
+/// lifetime_deposits sums up all funds ever sent along with a call, per denom
+pub fn lifetime_deposits<S: Storage>(storage: &mut S) -> Singleton<S, Vec<Coin>> {
+    singleton(storage, LIFETIME_DEPOSITS_KEY)
+}
+
+pub fn lifetime_deposits_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<Coin>> {
+    singleton_read(storage, LIFETIME_DEPOSITS_KEY)
+}
+
+/// collected_fees sums up the reflect fees paid since the owner last withdrew them
+pub fn collected_fees<S: Storage>(storage: &mut S) -> Singleton<S, Vec<Coin>> {
+    singleton(storage, COLLECTED_FEES_KEY)
+}
+
+pub fn collected_fees_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<Coin>> {
+    singleton_read(storage, COLLECTED_FEES_KEY)
+}
+
+/// SenderStats counts what one sender did, as far as it made it into the history
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct SenderStats {
+    pub reflections: u64,
+    /// Height of the last history entry of the sender
+    pub last_height: u64,
+}
+
+/// stats holds the SenderStats by canonical sender address
+pub fn stats<S: Storage>(storage: &mut S) -> Bucket<S, SenderStats> {
+    bucket(STATS_PREFIX, storage)
+}
+
+pub fn stats_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, SenderStats> {
+    bucket_read(STATS_PREFIX, storage)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct GlobalStats {
+    pub msgs_reflected: u64,
+    pub owner_changes: u64,
+}
+
+pub fn global_stats<S: Storage>(storage: &mut S) -> Singleton<S, GlobalStats> {
+    singleton(storage, GLOBAL_STATS_KEY)
+}
+
+pub fn global_stats_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, GlobalStats> {
+    singleton_read(storage, GLOBAL_STATS_KEY)
+}
+
+/// Counts every reflected batch, whichever handle sent it
+pub const EXECUTIONS_TOTAL: &str = "executions_total";
+/// Staged and scheduled batches and proposals which are still waiting to go out
+pub const QUEUE_DEPTH: &str = "queue_depth";
+
+/// Counts the batches a middleware judged, by its position in the chain
+pub const MIDDLEWARE_EVALUATIONS: &str = "evaluations";
+/// Counts the batches a middleware in shadow mode would have rejected
+pub const MIDDLEWARE_SHADOW_BLOCKS: &str = "shadow_blocks";
+
+pub fn middleware_metric(index: usize, counter: &str) -> String {
+    format!("middleware_{}_{}", index, counter)
+}
+
+/// Counts the messages a rule allowed, by its position in the rules
+pub fn rule_metric(index: usize) -> String {
+    format!("rule_{}_matches", index)
+}
+
+/// metrics holds the stored metrics by name
+pub fn metrics<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(METRICS_PREFIX, storage)
+}
+
+pub fn metrics_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(METRICS_PREFIX, storage)
+}
+
+/// Raises or lowers a stored metric by one, gauges do not drop below zero
+pub fn adjust_metric<S: Storage>(storage: &mut S, name: &str, up: bool) -> Result<()> {
+    let value = metrics_read(storage)
+        .may_load(name.as_bytes())?
+        .unwrap_or(0);
+    let value = if up {
+        value.saturating_add(1)
+    } else {
+        value.saturating_sub(1)
+    };
+    metrics(storage).save(name.as_bytes(), &value)
+}
+
+/// Starts the metrics over, eg. when what they count was replaced
+pub fn reset_metrics<S: Storage, I: IntoIterator<Item = String>>(
+    storage: &mut S,
+    names: I,
+) -> Result<()> {
+    let mut bucket = metrics(storage);
+    for name in names {
+        bucket.save(name.as_bytes(), &0)?;
+    }
+    Ok(())
+}
+
+/// Most keys the owner may store data under
+pub const MAX_DATA_KEYS: usize = 100;
+/// Longest key data may be stored under, in bytes
+pub const MAX_DATA_KEY_LEN: usize = 64;
+
+/// data holds the owner's values by key, stored as given rather than serialized
+pub fn data<S: Storage>(storage: &mut S) -> PrefixedStorage<S> {
+    prefixed(DATA_PREFIX, storage)
+}
+
+pub fn data_read<S: Storage>(storage: &S) -> ReadonlyPrefixedStorage<S> {
+    prefixed_read(DATA_PREFIX, storage)
+}
+
+/// data_keys holds the sorted keys of data. Storage cannot delete, so a key is only set
+/// if it is in here.
+pub fn data_keys<S: Storage>(storage: &mut S) -> Singleton<S, Vec<String>> {
+    singleton(storage, DATA_KEYS_KEY)
+}
+
+pub fn data_keys_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<String>> {
+    singleton_read(storage, DATA_KEYS_KEY)
+}
+
+/// Milestone is one tranche of a grant
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Milestone {
+    pub description: String,
+    pub amount: Vec<Coin>,
+}
+
+/// Grant pays its milestones to the grantee in order, each once the approver approves it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Grant {
+    pub id: u64,
+    pub grantee: HumanAddr,
+    pub approver: CanonicalAddr,
+    pub milestones: Vec<Milestone>,
+    /// How many milestones were paid so far
+    pub released: u32,
+    pub created_height: i64,
+    /// Set once the admin cancelled the milestones not yet paid
+    pub clawed_back: bool,
+    /// The queued execution paying the last released milestone, which holds the next one back
+    pub queued: Option<u64>,
+}
+
+impl Grant {
+    pub fn is_complete(&self) -> bool {
+        self.clawed_back || self.released as usize == self.milestones.len()
+    }
+
+    /// What the milestones not yet paid add up to, nothing once it is clawed back
+    pub fn remaining(&self) -> Result<Vec<Coin>> {
+        if self.clawed_back {
+            return Ok(vec![]);
+        }
+        let pending = &self.milestones[self.released as usize..];
+        sum_coins(pending.iter().flat_map(|m| m.amount.iter()))
+    }
+}
+
+pub fn grants<S: Storage>(storage: &mut S) -> Bucket<S, Grant> {
+    bucket(GRANT_PREFIX, storage)
+}
+
+pub fn grants_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Grant> {
+    bucket_read(GRANT_PREFIX, storage)
+}
+
+pub fn last_grant_id<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, GRANT_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_grant_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, GRANT_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// Swap offers coins of the contract to a counterparty, in exchange for the coins it wants
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Swap {
+    pub id: u64,
+    pub counterparty: CanonicalAddr,
+    pub give: Vec<Coin>,
+    pub want: Vec<Coin>,
+    /// The counterparty can no longer fill the swap from here on
+    pub expires: Expiration,
+    pub filled: bool,
+}
+
+pub fn swaps<S: Storage>(storage: &mut S) -> Bucket<S, Swap> {
+    bucket(SWAP_PREFIX, storage)
+}
+
+pub fn swaps_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Swap> {
+    bucket_read(SWAP_PREFIX, storage)
+}
+
+pub fn next_swap_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, SWAP_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// Permission lets a key which is not an owner reflect messages of some kinds, for a while.
+/// Revoking saves it without any kinds, as storage cannot remove keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Permission {
+    pub msg_types: Vec<MsgKind>,
+    /// The permission no longer applies from this height on
+    pub expires_at_height: u64,
+}
+
+impl Permission {
+    pub fn allows(&self, env: &Env, msgs: &[CosmosMsg]) -> bool {
+        (env.block.height as u64) < self.expires_at_height
+            && msgs
+                .iter()
+                .all(|msg| self.msg_types.contains(&MsgKind::of(msg)))
+    }
+}
+
+/// permissions maps the canonical address of the grantee to its permission
+pub fn permissions<S: Storage>(storage: &mut S) -> Bucket<S, Permission> {
+    bucket(PERMISSION_PREFIX, storage)
+}
+
+pub fn permissions_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Permission> {
+    bucket_read(PERMISSION_PREFIX, storage)
+}
+
+/// Session lets a key reflect a limited number of messages until it expires
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Session {
+    pub remaining_msgs: u64,
+    pub expires: Expiration,
+}
+
+/// sessions maps the canonical address of the session key to its session
+pub fn sessions<S: Storage>(storage: &mut S) -> Bucket<S, Session> {
+    bucket(SESSION_PREFIX, storage)
+}
+
+pub fn sessions_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Session> {
+    bucket_read(SESSION_PREFIX, storage)
+}
+
+/// denylist marks the canonical addresses reflected messages may not send funds to.
+/// Storage cannot remove keys, so addresses taken off the list are saved as false.
+pub fn denylist<S: Storage>(storage: &mut S) -> Bucket<S, bool> {
+    bucket(DENYLIST_PREFIX, storage)
+}
+
+pub fn denylist_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, bool> {
+    bucket_read(DENYLIST_PREFIX, storage)
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    /// The UTC weekday of a block time
+    pub fn of(time: u64) -> Self {
+        const WEEK: [Weekday; 7] = [
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+            Weekday::Saturday,
+            Weekday::Sunday,
+        ];
+        // 1970-01-01 was a Thursday
+        WEEK[((time / 86_400 + 3) % 7) as usize]
+    }
+}
+
+/// ExecutionWindow limits when an operator may act, in UTC block time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecutionWindow {
+    pub days: Vec<Weekday>,
+    /// First hour of those days in which it is open
+    pub start_hour: u8,
+    /// Hour in which it is closed again, 24 to stay open until midnight
+    pub end_hour: u8,
+}
+
+impl ExecutionWindow {
+    pub fn validate(&self) -> Result<()> {
+        if self.days.is_empty() {
+            return contract_err("Execution window must be open on at least one day");
+        }
+        if self.start_hour >= self.end_hour || self.end_hour > 24 {
+            return contract_err("Execution window must open before it closes, by hour 24");
+        }
+        Ok(())
+    }
+
+    pub fn is_open(&self, env: &Env) -> bool {
+        let time = env.block.time as u64;
+        let hour = (time % 86_400 / 3_600) as u8;
+        self.days.contains(&Weekday::of(time)) && hour >= self.start_hour && hour < self.end_hour
+    }
+}
+
+/// operator_windows maps the canonical address of an operator to its execution window.
+/// Storage cannot remove keys, so a lifted window is saved as None.
+pub fn operator_windows<S: Storage>(storage: &mut S) -> Bucket<S, Option<ExecutionWindow>> {
+    bucket(OPERATOR_WINDOW_PREFIX, storage)
+}
+
+pub fn operator_windows_read<S: Storage>(
+    storage: &S,
+) -> ReadonlyBucket<S, Option<ExecutionWindow>> {
+    bucket_read(OPERATOR_WINDOW_PREFIX, storage)
+}
+
+/// TimeRange is a span of block time in seconds, from start up to (not including) end
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct TimeRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl TimeRange {
+    pub fn contains(&self, time: u64) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+/// Most allowed ranges, and most blackouts, a calendar may have
+pub const MAX_CALENDAR_RANGES: usize = 50;
+
+/// Calendar is open during its allowed ranges, or always if it has none,
+/// except during its blackouts, eg. a quarter-end freeze
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Calendar {
+    pub allowed: Vec<TimeRange>,
+    pub blackouts: Vec<TimeRange>,
+}
+
+impl Calendar {
+    pub fn validate(&self) -> Result<()> {
+        if self.allowed.len() > MAX_CALENDAR_RANGES || self.blackouts.len() > MAX_CALENDAR_RANGES {
+            return dyn_contract_err(format!(
+                "Calendar may have at most {} allowed ranges and {} blackouts",
+                MAX_CALENDAR_RANGES, MAX_CALENDAR_RANGES
+            ));
+        }
+        if self
+            .allowed
+            .iter()
+            .chain(self.blackouts.iter())
+            .any(|r| r.start >= r.end)
+        {
+            return contract_err("Calendar ranges must start before they end");
+        }
+        Ok(())
+    }
+
+    pub fn is_open(&self, time: u64) -> bool {
+        let allowed = self.allowed.is_empty() || self.allowed.iter().any(|r| r.contains(time));
+        allowed && !self.blackouts.iter().any(|r| r.contains(time))
+    }
+}
+
+/// Calendars are named like tags
+pub fn validate_calendar_name(name: &str) -> Result<()> {
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+    if name.is_empty() || name.len() > MAX_TAG_LEN || !name.chars().all(valid_char) {
+        return dyn_contract_err(format!(
+            "Calendar {} must be 1 to {} characters of a-z, 0-9 or _",
+            name, MAX_TAG_LEN
+        ));
+    }
+    Ok(())
+}
+
+pub fn calendars<S: Storage>(storage: &mut S) -> Bucket<S, Calendar> {
+    bucket(CALENDAR_PREFIX, storage)
+}
+
+pub fn calendars_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Calendar> {
+    bucket_read(CALENDAR_PREFIX, storage)
+}
+
+/// operator_calendars maps the canonical address of an operator to the name of its calendar.
+/// Storage cannot remove keys, so an unassigned calendar is saved as None.
+pub fn operator_calendars<S: Storage>(storage: &mut S) -> Bucket<S, Option<String>> {
+    bucket(OPERATOR_CALENDAR_PREFIX, storage)
+}
+
+pub fn operator_calendars_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Option<String>> {
+    bucket_read(OPERATOR_CALENDAR_PREFIX, storage)
+}
+
+/// Auction sells coins of the contract for a price which falls from start_price to end_price
+/// over its duration, and then stays at end_price until someone buys or it is cancelled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Auction {
+    pub id: u64,
+    pub sell: Vec<Coin>,
+    pub start_price: Coin,
+    pub end_price: Coin,
+    pub duration: Duration,
+    pub start_height: u64,
+    pub start_time: u64,
+    pub buyer: Option<CanonicalAddr>,
+    /// What the buyer paid, excess funds were returned
+    pub paid: Option<Coin>,
+    pub cancelled: bool,
+}
+
+impl Auction {
+    pub fn is_open(&self) -> bool {
+        self.buyer.is_none() && !self.cancelled
+    }
+
+    /// The price in the denom of start_price at the given block, falling linearly
+    pub fn price_at(&self, env: &Env) -> Result<u128> {
+        let start = parse_amount(&self.start_price.amount)?;
+        let end = parse_amount(&self.end_price.amount)?;
+        let (elapsed, total) = match self.duration {
+            Duration::Height(blocks) => (
+                (env.block.height as u64).saturating_sub(self.start_height),
+                blocks,
+            ),
+            Duration::Time(secs) => (
+                (env.block.time as u64).saturating_sub(self.start_time),
+                secs,
+            ),
+        };
+        if elapsed >= total {
+            return Ok(end);
+        }
+        let drop = mul_ratio(start.safe_sub(end)?, u128::from(elapsed), u128::from(total))?;
+        start.safe_sub(drop)
+    }
+}
+
+pub fn auctions<S: Storage>(storage: &mut S) -> Bucket<S, Auction> {
+    bucket(AUCTION_PREFIX, storage)
+}
+
+pub fn auctions_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Auction> {
+    bucket_read(AUCTION_PREFIX, storage)
+}
+
+pub fn next_auction_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, AUCTION_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// ScheduledBatch is queued by an owner, anyone can execute it once execute_after has passed.
+/// Executing or cancelling closes it, as storage cannot remove keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduledBatch {
+    pub id: u64,
+    pub owner: CanonicalAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub execute_after: Expiration,
+    pub closed: bool,
+    /// If set, the batch can only be executed while this calendar is open
+    pub calendar: Option<String>,
+}
+
+pub fn scheduled_batches<S: Storage>(storage: &mut S) -> Bucket<S, ScheduledBatch> {
+    bucket(SCHEDULED_BATCH_PREFIX, storage)
+}
+
+pub fn scheduled_batches_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, ScheduledBatch> {
+    bucket_read(SCHEDULED_BATCH_PREFIX, storage)
+}
+
+pub fn last_scheduled_batch_id<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, SCHEDULED_BATCH_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_scheduled_batch_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, SCHEDULED_BATCH_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// Cosign holds back batches sending a lot at once until a second key confirms them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cosign {
+    pub cosigner: CanonicalAddr,
+    /// A batch is queued if its messages together send more than one of these amounts
+    pub thresholds: Vec<Coin>,
+}
+
+/// QueuedExecution is a reflected batch waiting for the co-signer.
+/// Confirming or cancelling closes it, as storage cannot remove keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueuedExecution {
+    pub id: u64,
+    pub sender: CanonicalAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub tags: Vec<String>,
+    pub memo: Option<TravelMemo>,
+    pub queued_height: u64,
+    pub closed: bool,
+    /// What the handler queueing the batch already changed, taken back if it is cancelled
+    pub undo: Option<Undo>,
+    /// What let the sender send the batch, checked again when it is confirmed
+    pub standing: Option<Standing>,
+    /// Where to report the execution, for a staged batch with a receipt contract
+    pub receipt: Option<PendingReceipt>,
+}
+
+/// Standing is what let the sender of a queued batch send it
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Standing {
+    /// Being an owner
+    Owner,
+    /// A permission or session of someone else reflecting
+    Delegate,
+    /// The terms of the handler, such as approving a grant, which it checked already
+    Handler,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingReceipt {
+    pub contract_addr: HumanAddr,
+    pub batch_id: u64,
+}
+
+/// Undo is what a handler changed before its batch was queued
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Undo {
+    /// Withdrawn fees, which count as collected again
+    CollectedFees { fees: Vec<Coin> },
+    /// A distributed revenue period, which may be distributed again
+    RevenueSettled { tag: String, period: u64 },
+    /// The last released milestone of a grant, which may be approved again
+    Milestone { grant_id: u64 },
+}
+
+pub fn queued_executions<S: Storage>(storage: &mut S) -> Bucket<S, QueuedExecution> {
+    bucket(QUEUED_EXECUTION_PREFIX, storage)
+}
+
+pub fn queued_executions_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, QueuedExecution> {
+    bucket_read(QUEUED_EXECUTION_PREFIX, storage)
+}
+
+pub fn next_queued_execution_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, QUEUED_EXECUTION_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// OneTimeAuth lets the grantee execute the message with msg_hash once.
+/// Executing it marks it used, as storage cannot remove keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OneTimeAuth {
+    pub id: u64,
+    pub grantee: CanonicalAddr,
+    pub msg_hash: Binary,
+    pub expires: Expiration,
+    pub used: bool,
+}
+
+pub fn one_time_auths<S: Storage>(storage: &mut S) -> Bucket<S, OneTimeAuth> {
+    bucket(ONE_TIME_AUTH_PREFIX, storage)
+}
+
+pub fn one_time_auths_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, OneTimeAuth> {
+    bucket_read(ONE_TIME_AUTH_PREFIX, storage)
+}
+
+/// one_time_auth_ids maps a msg_hash to the id of the authorization given for it last
+pub fn one_time_auth_ids<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(ONE_TIME_AUTH_HASH_PREFIX, storage)
+}
+
+pub fn one_time_auth_ids_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(ONE_TIME_AUTH_HASH_PREFIX, storage)
+}
+
+pub fn last_one_time_auth_id<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, ONE_TIME_AUTH_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_one_time_auth_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, ONE_TIME_AUTH_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
+
+/// Attestation is a sign-off on the configuration with this checksum, signed off chain.
+/// The contract cannot check signatures, it keeps the signature for auditors to verify.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Attestation {
+    pub checksum: String,
+    /// Who signed, eg. the public key of the compliance officer
+    pub signer: String,
+    pub signature: Binary,
+    pub height: u64,
+    pub time: u64,
+}
+
+impl Attestation {
+    /// Whether it was given for this configuration, no longer than max_age ago
+    pub fn is_fresh(&self, state: &State, max_age: Duration, env: &Env) -> Result<bool> {
+        let expired = max_age.after(self.height, self.time).is_expired(env);
+        Ok(!expired && self.checksum == state.checksum()?)
+    }
+}
+
+/// config_version counts the handle calls which changed the State, 0 if none did yet
+pub fn config_version<S: Storage>(storage: &mut S) -> Singleton<S, u64> {
+    singleton(storage, CONFIG_VERSION_KEY)
+}
+
+pub fn config_version_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, u64> {
+    singleton_read(storage, CONFIG_VERSION_KEY)
+}
+
+/// attestation holds the latest attestation the owner stored
+pub fn attestation<S: Storage>(storage: &mut S) -> Singleton<S, Attestation> {
+    singleton(storage, ATTESTATION_KEY)
+}
+
+pub fn attestation_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Attestation> {
+    singleton_read(storage, ATTESTATION_KEY)
+}
+
+/// Most macros the admins may save
+pub const MAX_MACROS: usize = 20;
+/// Most parameters one macro may take
+pub const MAX_MACRO_PARAMS: usize = 10;
+
+/// MacroParam is an amount a macro takes, written "$name" where it goes in its msgs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MacroParam {
+    pub name: String,
+    /// If set, runners cannot pass more than this
+    pub max: Option<String>,
+}
+
+/// MacroQuota lets each runner run a macro this many times per period.
+/// The period starts with the first run after the last one ended.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct MacroQuota {
+    pub runs: u32,
+    pub period: Duration,
+}
+
+/// Macro is a batch saved by the admins, which its runners may reflect with the amounts
+/// of their choice filled in, but nothing else
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Macro {
+    pub msgs: Vec<CosmosMsg>,
+    pub params: Vec<MacroParam>,
+    pub runners: Vec<CanonicalAddr>,
+    /// Owners are not held to it
+    pub quota: Option<MacroQuota>,
+}
+
+/// MacroRuns counts the runs of a runner since its quota period started
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MacroRuns {
+    pub since_height: u64,
+    pub since_time: u64,
+    pub runs: u32,
+}
+
+impl MacroRuns {
+    pub fn starting(env: &Env) -> Self {
+        MacroRuns {
+            since_height: env.block.height as u64,
+            since_time: env.block.time as u64,
+            runs: 0,
+        }
+    }
+
+    pub fn resets_at(&self, period: Duration) -> Expiration {
+        period.after(self.since_height, self.since_time)
+    }
+}
+
+/// Key of the runs of a runner of a macro.
+/// Canonical addresses all have the same length, so keys never collide.
+pub fn macro_runs_key(runner: &CanonicalAddr, name: &str) -> Vec<u8> {
+    let mut key = runner.as_slice().to_vec();
+    key.extend_from_slice(name.as_bytes());
+    key
+}
+
+/// Macros and their parameters are named like tags
+pub fn validate_macro_name(what: &str, name: &str) -> Result<()> {
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+    if name.is_empty() || name.len() > MAX_TAG_LEN || !name.chars().all(valid_char) {
+        return dyn_contract_err(format!(
+            "{} {} must be 1 to {} characters of a-z, 0-9 or _",
+            what, name, MAX_TAG_LEN
+        ));
+    }
+    Ok(())
+}
+
+pub fn macros<S: Storage>(storage: &mut S) -> Bucket<S, Macro> {
+    bucket(MACRO_PREFIX, storage)
+}
+
+pub fn macros_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Macro> {
+    bucket_read(MACRO_PREFIX, storage)
+}
+
+/// macro_names holds the sorted names of the macros. Storage cannot delete, so a macro
+/// only exists if it is in here.
+pub fn macro_names<S: Storage>(storage: &mut S) -> Singleton<S, Vec<String>> {
+    singleton(storage, MACRO_NAMES_KEY)
+}
+
+pub fn macro_names_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<String>> {
+    singleton_read(storage, MACRO_NAMES_KEY)
+}
+
+/// macro_runs maps macro_runs_key to the runs counted against the quota
+pub fn macro_runs<S: Storage>(storage: &mut S) -> Bucket<S, MacroRuns> {
+    bucket(MACRO_RUNS_PREFIX, storage)
+}
+
+pub fn macro_runs_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, MacroRuns> {
+    bucket_read(MACRO_RUNS_PREFIX, storage)
+}
+
+/// The SHA-256 of the message as the contract serializes it, which AuthorizeOnce refers to
+pub fn msg_hash(msg: &CosmosMsg) -> Result<Binary> {
+    let json = to_vec(msg).context(SerializeErr { kind: "CosmosMsg" })?;
+    Ok(Binary(Sha256::digest(&json).to_vec()))
+}
+
+/// Revenue is counted in periods of this many seconds of block time, ie. UTC days
+pub const REVENUE_PERIOD_SECS: u64 = 86_400;
+
+/// The revenue period which contains this block time
+pub fn revenue_period(time: u64) -> u64 {
+    time / REVENUE_PERIOD_SECS
+}
+
+/// revenue maps tag_index_key of the source tag and period to the coins deposited then
+pub fn revenue<S: Storage>(storage: &mut S) -> Bucket<S, Vec<Coin>> {
+    bucket(REVENUE_PREFIX, storage)
+}
+
+pub fn revenue_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vec<Coin>> {
+    bucket_read(REVENUE_PREFIX, storage)
+}
+
+/// revenue_settled marks the tag_index_key of a source tag and period once it was distributed
+pub fn revenue_settled<S: Storage>(storage: &mut S) -> Bucket<S, bool> {
+    bucket(REVENUE_SETTLED_PREFIX, storage)
+}
+
+pub fn revenue_settled_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, bool> {
+    bucket_read(REVENUE_SETTLED_PREFIX, storage)
+}
+
+/// Stakeholder receives this many shares of every revenue distribution
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stakeholder {
+    pub addr: CanonicalAddr,
+    pub shares: u64,
+}
+
+pub fn stakeholders<S: Storage>(storage: &mut S) -> Singleton<S, Vec<Stakeholder>> {
+    singleton(storage, STAKEHOLDERS_KEY)
+}
+
+pub fn stakeholders_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<Stakeholder>> {
+    singleton_read(storage, STAKEHOLDERS_KEY)
+}
+
+pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// Status is where a stored entity is in its lifecycle. Responses carry it with its code next
+/// to the flags it follows from, so UIs can translate it rather than show a flag.
+/// Codes are stable and never reused.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Pending,
+    Approved,
+    Rejected,
+    Executed,
+    Staged,
+    Queued,
+    Closed,
+    Open,
+    Filled,
+    Sold,
+    Cancelled,
+    Active,
+    Completed,
+    ClawedBack,
+}
+
+impl Status {
+    pub fn code(self) -> u32 {
+        match self {
+            Status::Pending => 1,
+            Status::Approved => 2,
+            Status::Rejected => 3,
+            Status::Executed => 4,
+            Status::Staged => 5,
+            Status::Queued => 6,
+            Status::Closed => 7,
+            Status::Open => 8,
+            Status::Filled => 9,
+            Status::Sold => 10,
+            Status::Cancelled => 11,
+            Status::Active => 12,
+            Status::Completed => 13,
+            Status::ClawedBack => 14,
+        }
+    }
+}
+
+impl From<ProposalStatus> for Status {
+    fn from(status: ProposalStatus) -> Self {
+        match status {
+            ProposalStatus::Pending => Status::Pending,
+            ProposalStatus::Approved => Status::Approved,
+            ProposalStatus::Rejected => Status::Rejected,
+        }
+    }
+}
+
+/// Proposal holds msgs suggested by anyone, which an owner may approve or reject once
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: CanonicalAddr,
+    pub msgs: Vec<CosmosMsg>,
+    pub description: String,
+    pub status: ProposalStatus,
+    /// Intents the contract calls must match, checked again when the proposal is approved
+    pub intents: Option<Vec<WasmIntent>>,
+}
+
+pub fn proposals<S: Storage>(storage: &mut S) -> Bucket<S, Proposal> {
+    bucket(PROPOSAL_PREFIX, storage)
+}
+
+pub fn proposals_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Proposal> {
+    bucket_read(PROPOSAL_PREFIX, storage)
+}
+
+pub fn last_proposal_id<S: Storage>(storage: &S) -> Result<u64> {
+    let seq = singleton_read(storage, PROPOSAL_SEQ_KEY);
+    Ok(seq.may_load()?.unwrap_or(0u64))
+}
+
+pub fn next_proposal_id<S: Storage>(storage: &mut S) -> Result<u64> {
+    let mut seq = singleton(storage, PROPOSAL_SEQ_KEY);
+    let id = seq.may_load()?.unwrap_or(0u64) + 1;
+    seq.save(&id)?;
+    Ok(id)
+}
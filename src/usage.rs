@@ -0,0 +1,179 @@
+//! Storage usage per namespace, counted as it is written.
+//!
+//! Storage cannot be iterated, so the usage is kept in counters. init and handle run on a
+//! UsageTracker, which sees every write and adds up the new entries and bytes per namespace,
+//! then saves the changes once at the end. A failing call rolls them back with its writes.
+//!
+//! Entries written before the tracker existed are not counted, and as storage cannot remove
+//! keys, entries only ever grow. The counts are an estimate to decide what to prune first.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm::errors::Result;
+use cosmwasm::traits::{Api, Extern, ReadonlyStorage, Storage};
+use cw_storage::{bucket, bucket_read, singleton, singleton_read};
+
+use crate::state::{USAGE_NAMESPACES_KEY, USAGE_PREFIX};
+
+/// Usage is what a namespace takes up, keys included
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, JsonSchema)]
+pub struct Usage {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// The namespace of a key written through a bucket or prefixed storage, which put its
+/// length in front. Other keys belong to the empty namespace.
+pub fn namespace(key: &[u8]) -> &[u8] {
+    if key.len() < 2 {
+        return b"";
+    }
+    let len = (usize::from(key[0]) << 8) | usize::from(key[1]);
+    key.get(2..2 + len).unwrap_or(b"")
+}
+
+/// UsageTracker is storage which counts what its writes add, by namespace
+pub struct UsageTracker<'a, S: Storage> {
+    storage: &'a mut S,
+    /// Added entries and (possibly negative) bytes, by namespace
+    changes: BTreeMap<Vec<u8>, (u64, i64)>,
+}
+
+impl<'a, S: Storage> UsageTracker<'a, S> {
+    pub fn new(storage: &'a mut S) -> Self {
+        UsageTracker {
+            storage,
+            changes: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the changes to the stored usage
+    pub fn finish(self) -> Result<()> {
+        let mut known: Vec<String> = singleton_read(self.storage, USAGE_NAMESPACES_KEY)
+            .may_load()?
+            .unwrap_or_default();
+        let count = known.len();
+        for (name, (entries, bytes)) in self.changes {
+            let mut usage = bucket_read::<_, Usage>(USAGE_PREFIX, self.storage)
+                .may_load(&name)?
+                .unwrap_or_default();
+            usage.entries = usage.entries.saturating_add(entries);
+            usage.bytes = if bytes < 0 {
+                usage.bytes.saturating_sub(bytes.wrapping_neg() as u64)
+            } else {
+                usage.bytes.saturating_add(bytes as u64)
+            };
+            bucket(USAGE_PREFIX, self.storage).save(&name, &usage)?;
+            let name = String::from_utf8_lossy(&name).into_owned();
+            if !known.contains(&name) {
+                known.push(name);
+            }
+        }
+        if known.len() > count {
+            singleton(self.storage, USAGE_NAMESPACES_KEY).save(&known)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, S: Storage> ReadonlyStorage for UsageTracker<'a, S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.storage.get(key)
+    }
+}
+
+impl<'a, S: Storage> Storage for UsageTracker<'a, S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        let (entries, bytes) = match self.storage.get(key) {
+            Some(old) => (0, value.len() as i64 - old.len() as i64),
+            None => (1, (key.len() + value.len()) as i64),
+        };
+        let change = self.changes.entry(namespace(key).to_vec()).or_default();
+        change.0 += entries;
+        change.1 += bytes;
+        self.storage.set(key, value);
+    }
+}
+
+/// Runs a call on storage which counts the usage of its writes
+pub fn tracked<S: Storage, A: Api, T>(
+    deps: &mut Extern<S, A>,
+    call: impl FnOnce(&mut Extern<UsageTracker<S>, A>) -> Result<T>,
+) -> Result<T> {
+    let mut inner = Extern {
+        storage: UsageTracker::new(&mut deps.storage),
+        api: deps.api,
+    };
+    let res = call(&mut inner)?;
+    inner.storage.finish()?;
+    Ok(res)
+}
+
+/// The usage of every namespace written to so far, in the order they were first written
+pub fn usage<S: Storage>(storage: &S) -> Result<Vec<(String, Usage)>> {
+    let known: Vec<String> = singleton_read(storage, USAGE_NAMESPACES_KEY)
+        .may_load()?
+        .unwrap_or_default();
+    let usages = bucket_read::<_, Usage>(USAGE_PREFIX, storage);
+    let mut all = Vec::with_capacity(known.len());
+    for name in known {
+        let usage = usages.may_load(name.as_bytes())?.unwrap_or_default();
+        all.push((name, usage));
+    }
+    Ok(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm::mock::MockStorage;
+    use cw_storage::prefixed;
+
+    #[test]
+    fn finds_the_namespace_of_a_key() {
+        assert_eq!(b"data", namespace(b"\x00\x04datakey"));
+        assert_eq!(b"data", namespace(b"\x00\x04data"));
+        assert_eq!(b"", namespace(b"\x00\x09data"));
+        assert_eq!(b"", namespace(b"c"));
+    }
+
+    #[test]
+    fn counts_new_entries_and_changed_bytes() {
+        let mut storage = MockStorage::new();
+        let mut tracker = UsageTracker::new(&mut storage);
+        prefixed(b"data", &mut tracker).set(b"a", b"12345");
+        prefixed(b"data", &mut tracker).set(b"b", b"1");
+        tracker.finish().unwrap();
+        let data = Usage {
+            entries: 2,
+            bytes: 2 * 7 + 6,
+        };
+        assert_eq!(vec![("data".to_string(), data)], usage(&storage).unwrap());
+
+        let mut tracker = UsageTracker::new(&mut storage);
+        prefixed(b"data", &mut tracker).set(b"a", b"1");
+        prefixed(b"other", &mut tracker).set(b"x", b"1");
+        tracker.finish().unwrap();
+        let all = usage(&storage).unwrap();
+        assert_eq!(
+            Usage {
+                entries: 2,
+                bytes: 16
+            },
+            all[0].1
+        );
+        assert_eq!(
+            (
+                "other".to_string(),
+                Usage {
+                    entries: 1,
+                    bytes: 9
+                }
+            ),
+            all[1]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Records which carry the version of their layout.
+//!
+//! A versioned record is stored as one version byte followed by its JSON. When a layout
+//! changes, the type bumps its VERSION and decodes the older layouts in `upgrade`.
+//! Records are upgraded as they are read, and a mutable bucket writes the upgrade back on
+//! that first access, so a migration never has to rewrite a whole collection at once.
+//!
+//! Records written before they were versioned have no version byte. JSON never starts with
+//! a control character, so they are read as version 0.
+
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use snafu::ResultExt;
+
+use cosmwasm::errors::{dyn_contract_err, NotFound, ParseErr, Result, SerializeErr};
+use cosmwasm::serde::{from_slice, to_vec};
+use cosmwasm::traits::{ReadonlyStorage, Storage};
+use cw_storage::{prefixed, prefixed_read, PrefixedStorage, ReadonlyPrefixedStorage};
+
+/// Version bytes are below this, anything else starts an unversioned record
+const FIRST_JSON_BYTE: u8 = 0x20;
+
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// The layout written by this code, between 1 and 31
+    const VERSION: u8;
+    const KIND: &'static str;
+
+    /// Decodes a record of an older layout. Version 0 is the layout from before versioning,
+    /// by default it is read as the current one.
+    fn upgrade(version: u8, data: &[u8]) -> Result<Self> {
+        if version != 0 {
+            let msg = format!("No upgrade of {} from version {}", Self::KIND, version);
+            return dyn_contract_err(msg);
+        }
+        from_slice(data).context(ParseErr { kind: Self::KIND })
+    }
+}
+
+/// Splits off the version byte
+fn split_version(raw: &[u8]) -> (u8, &[u8]) {
+    match raw.first() {
+        Some(&version) if version < FIRST_JSON_BYTE => (version, &raw[1..]),
+        _ => (0, raw),
+    }
+}
+
+/// Decodes a stored record, telling whether it had to be upgraded
+fn decode<T: Versioned>(raw: &[u8]) -> Result<(T, bool)> {
+    let (version, data) = split_version(raw);
+    if version == T::VERSION {
+        let record = from_slice(data).context(ParseErr { kind: T::KIND })?;
+        Ok((record, false))
+    } else if version > T::VERSION {
+        dyn_contract_err(format!(
+            "{} has layout version {}, this code only knows up to {}",
+            T::KIND,
+            version,
+            T::VERSION
+        ))
+    } else {
+        Ok((T::upgrade(version, data)?, true))
+    }
+}
+
+fn encode<T: Versioned>(record: &T) -> Result<Vec<u8>> {
+    let mut raw = vec![T::VERSION];
+    raw.extend(to_vec(record).context(SerializeErr { kind: T::KIND })?);
+    Ok(raw)
+}
+
+/// VersionedBucket is a Bucket of versioned records, upgrading the ones it reads
+pub struct VersionedBucket<'a, S: Storage, T: Versioned> {
+    storage: PrefixedStorage<'a, S>,
+    record: PhantomData<T>,
+}
+
+impl<'a, S: Storage, T: Versioned> VersionedBucket<'a, S, T> {
+    pub fn new(namespace: &[u8], storage: &'a mut S) -> Self {
+        VersionedBucket {
+            storage: prefixed(namespace, storage),
+            record: PhantomData,
+        }
+    }
+
+    pub fn save(&mut self, key: &[u8], record: &T) -> Result<()> {
+        self.storage.set(key, &encode(record)?);
+        Ok(())
+    }
+
+    pub fn load(&mut self, key: &[u8]) -> Result<T> {
+        match self.may_load(key)? {
+            Some(record) => Ok(record),
+            None => NotFound { kind: T::KIND }.fail(),
+        }
+    }
+
+    /// Writes back records of an older layout, so they are only upgraded once
+    pub fn may_load(&mut self, key: &[u8]) -> Result<Option<T>> {
+        Ok(self.load_upgraded(key)?.map(|(record, _)| record))
+    }
+
+    /// Upgrades the record in storage, telling whether it had an older layout
+    pub fn upgrade(&mut self, key: &[u8]) -> Result<bool> {
+        Ok(self
+            .load_upgraded(key)?
+            .map_or(false, |(_, upgraded)| upgraded))
+    }
+
+    fn load_upgraded(&mut self, key: &[u8]) -> Result<Option<(T, bool)>> {
+        let raw = match self.storage.get(key) {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+        let (record, upgraded) = decode(&raw)?;
+        if upgraded {
+            self.save(key, &record)?;
+        }
+        Ok(Some((record, upgraded)))
+    }
+}
+
+/// ReadonlyVersionedBucket reads versioned records, upgrading them in memory only
+pub struct ReadonlyVersionedBucket<'a, S: Storage, T: Versioned> {
+    storage: ReadonlyPrefixedStorage<'a, S>,
+    record: PhantomData<T>,
+}
+
+impl<'a, S: Storage, T: Versioned> ReadonlyVersionedBucket<'a, S, T> {
+    pub fn new(namespace: &[u8], storage: &'a S) -> Self {
+        ReadonlyVersionedBucket {
+            storage: prefixed_read(namespace, storage),
+            record: PhantomData,
+        }
+    }
+
+    pub fn load(&self, key: &[u8]) -> Result<T> {
+        match self.may_load(key)? {
+            Some(record) => Ok(record),
+            None => NotFound { kind: T::KIND }.fail(),
+        }
+    }
+
+    pub fn may_load(&self, key: &[u8]) -> Result<Option<T>> {
+        match self.storage.get(key) {
+            Some(raw) => Ok(Some(decode(&raw)?.0)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm::mock::MockStorage;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Point {
+        x: u64,
+        y: u64,
+    }
+
+    impl Versioned for Point {
+        const VERSION: u8 = 2;
+        const KIND: &'static str = "Point";
+
+        // version 1 only had x
+        fn upgrade(version: u8, data: &[u8]) -> Result<Self> {
+            match version {
+                1 => {
+                    let x: u64 = from_slice(data).context(ParseErr { kind: "Point" })?;
+                    Ok(Point { x, y: 0 })
+                }
+                _ => dyn_contract_err(format!("No upgrade from version {}", version)),
+            }
+        }
+    }
+
+    #[test]
+    fn upgrades_on_first_read() {
+        let mut storage = MockStorage::new();
+        let mut old = vec![1u8];
+        old.extend(to_vec(&7u64).unwrap());
+        prefixed(b"points", &mut storage).set(b"a", &old);
+
+        // reading only upgrades in memory
+        let point = ReadonlyVersionedBucket::<_, Point>::new(b"points", &storage).load(b"a");
+        assert_eq!(Point { x: 7, y: 0 }, point.unwrap());
+        assert_eq!(Some(old), prefixed_read(b"points", &storage).get(b"a"));
+
+        let mut bucket = VersionedBucket::<_, Point>::new(b"points", &mut storage);
+        assert_eq!(Point { x: 7, y: 0 }, bucket.load(b"a").unwrap());
+        assert_eq!(false, bucket.upgrade(b"a").unwrap());
+        assert_eq!(false, bucket.upgrade(b"missing").unwrap());
+        let raw = prefixed_read(b"points", &storage).get(b"a").unwrap();
+        assert_eq!(
+            (2, &to_vec(&Point { x: 7, y: 0 }).unwrap()[..]),
+            split_version(&raw)
+        );
+    }
+
+    #[test]
+    fn reads_records_from_before_versioning() {
+        assert_eq!((0, &b"{}"[..]), split_version(b"{}"));
+        assert_eq!((3, &b"{}"[..]), split_version(b"\x03{}"));
+        assert_eq!((0, &b""[..]), split_version(b""));
+    }
+
+    #[test]
+    fn rejects_newer_layouts() {
+        let mut storage = MockStorage::new();
+        let mut bucket = VersionedBucket::<_, Point>::new(b"points", &mut storage);
+        assert_eq!(None, bucket.may_load(b"a").unwrap());
+        assert!(bucket.load(b"a").is_err());
+        bucket.save(b"a", &Point { x: 1, y: 2 }).unwrap();
+        assert_eq!(Point { x: 1, y: 2 }, bucket.load(b"a").unwrap());
+
+        prefixed(b"points", &mut storage).set(b"b", b"\x09{}");
+        let bucket = ReadonlyVersionedBucket::<_, Point>::new(b"points", &storage);
+        let err = bucket.load(b"b").unwrap_err();
+        assert!(err.to_string().contains("Point has layout version 9"));
+    }
+}
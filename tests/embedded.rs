@@ -0,0 +1,161 @@
+//! A contract built on the mask as a library: it takes over ownership and reflect, and adds
+//! a handle of its own. Everything it uses from the mask is public API embedders rely on,
+//! so a breaking change shows up here.
+//!
+//! Embedders depend on mask with the library feature, which leaves out its wasm exports.
+
+use cosmwasm::errors::Error;
+use cosmwasm::mock::{dependencies, mock_env};
+use cosmwasm::serde::from_slice;
+use cosmwasm::types::{coin, CosmosMsg, HumanAddr};
+
+use mask::msg::{InitMsg, OwnerResponse, QueryMsg};
+use mask::state::ChainProfile;
+
+use tipjar::{handle, init, query, HandleMsg};
+
+mod tipjar {
+    use serde::{Deserialize, Serialize};
+
+    use cosmwasm::errors::{contract_err, Result};
+    use cosmwasm::traits::{Api, Extern, Storage};
+    use cosmwasm::types::{log, CosmosMsg, Env, HumanAddr, Response};
+
+    use mask::contract;
+    use mask::msg;
+    use mask::state::config_read;
+
+    pub use mask::contract::{init, query};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum HandleMsg {
+        ReflectMsg {
+            msgs: Vec<CosmosMsg>,
+        },
+        ChangeOwner {
+            owner: HumanAddr,
+        },
+        /// Passes the tokens sent along on to the owner
+        Tip {},
+    }
+
+    pub fn handle<S: Storage, A: Api>(
+        deps: &mut Extern<S, A>,
+        env: Env,
+        msg: HandleMsg,
+    ) -> Result<Response> {
+        // the mask's own handle keeps the pause, deny-list and cooldown checks in place
+        match msg {
+            HandleMsg::ReflectMsg { msgs } => {
+                let msg = msg::HandleMsg::ReflectMsg {
+                    msgs,
+                    tags: vec![],
+                    memo: None,
+                };
+                contract::handle(deps, env, msg)
+            }
+            HandleMsg::ChangeOwner { owner } => {
+                contract::handle(deps, env, msg::HandleMsg::ChangeOwner { owner })
+            }
+            HandleMsg::Tip {} => try_tip(deps, env),
+        }
+    }
+
+    fn try_tip<S: Storage, A: Api>(deps: &mut Extern<S, A>, env: Env) -> Result<Response> {
+        let tip = env.message.sent_funds.unwrap_or_default();
+        if tip.is_empty() {
+            return contract_err("Send some tokens to tip");
+        }
+        let state = config_read(&deps.storage).load()?;
+        let owner = deps.api.human_address(&state.owner)?;
+        let from_address = deps.api.human_address(&env.contract.address)?;
+        Ok(Response {
+            messages: vec![CosmosMsg::Send {
+                from_address,
+                to_address: owner.clone(),
+                amount: tip,
+            }],
+            log: vec![log("action", "tip"), log("owner", owner.as_str())],
+            ..Response::default()
+        })
+    }
+}
+
+fn mock_chain() -> ChainProfile {
+    ChainProfile {
+        bech32_prefix: "cosmos".to_string(),
+        native_denom: "uatom".to_string(),
+        block_time_secs: 5,
+        default_denom: None,
+    }
+}
+
+fn send(amount: &str) -> CosmosMsg {
+    CosmosMsg::Send {
+        from_address: HumanAddr::from("cosmos2contract"),
+        to_address: HumanAddr::from("cosmos1friend"),
+        amount: coin(amount, "uatom"),
+    }
+}
+
+#[test]
+fn embedded_ownership_and_reflect() {
+    let mut deps = dependencies(20);
+
+    let msg = InitMsg {
+        chain: mock_chain(),
+    };
+    let env = mock_env(&deps.api, "creator", &[], &[]);
+    init(&mut deps, env, msg).unwrap();
+
+    let env = mock_env(&deps.api, "cosmos1stranger", &[], &[]);
+    let msg = HandleMsg::ReflectMsg {
+        msgs: vec![send("10")],
+    };
+    assert!(handle(&mut deps, env, msg.clone()).is_err());
+    let env = mock_env(&deps.api, "creator", &[], &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(vec![send("10")], res.messages);
+
+    let env = mock_env(&deps.api, "creator", &[], &[]);
+    let msg = HandleMsg::ChangeOwner {
+        owner: HumanAddr::from("cosmos1newowner"),
+    };
+    handle(&mut deps, env, msg).unwrap();
+    let res = query(&deps, QueryMsg::GetOwner {}).unwrap();
+    let value: OwnerResponse = from_slice(&res).unwrap();
+    assert_eq!(HumanAddr::from("cosmos1newowner"), value.owner);
+
+    let env = mock_env(&deps.api, "creator", &[], &[]);
+    let msg = HandleMsg::ReflectMsg {
+        msgs: vec![send("20")],
+    };
+    match handle(&mut deps, env, msg) {
+        Err(Error::Unauthorized { .. }) => {}
+        _ => panic!("The old owner must not reflect anymore"),
+    }
+}
+
+#[test]
+fn custom_handle_reads_mask_state() {
+    let mut deps = dependencies(20);
+
+    let msg = InitMsg {
+        chain: mock_chain(),
+    };
+    let env = mock_env(&deps.api, "creator", &[], &[]);
+    init(&mut deps, env, msg).unwrap();
+
+    let env = mock_env(&deps.api, "cosmos1fan", &[], &[]);
+    assert!(handle(&mut deps, env, HandleMsg::Tip {}).is_err());
+
+    let env = mock_env(&deps.api, "cosmos1fan", &coin("5", "uatom"), &[]);
+    let res = handle(&mut deps, env, HandleMsg::Tip {}).unwrap();
+    let expected = CosmosMsg::Send {
+        from_address: HumanAddr::from("cosmos2contract"),
+        to_address: HumanAddr::from("creator"),
+        amount: coin("5", "uatom"),
+    };
+    assert_eq!(vec![expected], res.messages);
+}
@@ -0,0 +1,2499 @@
+//! Fixtures for every message and response, in the exact form the contract and its
+//! clients exchange them. tests/golden.rs pins them and examples/vectors.rs writes them out.
+//!
+//! Every group is a list of (name, JSON) pairs, kept in a stable order.
+
+use serde::Serialize;
+
+use cosmwasm::encoding::Binary;
+use cosmwasm::serde::to_vec;
+use cosmwasm::types::{coin, Coin, CosmosMsg, HumanAddr};
+
+use mask::middleware::{DenomAlias, MiddlewareConfig};
+use mask::msg::{
+    AdminProposalResponse, AdminProposalsResponse, AdminVoteResponse, AdminsResponse,
+    AggregateQuery, AggregateResponse, AggregateResult, AllowedTargetsResponse, ArchiveMsg,
+    AttestationResponse, AuctionResponse, AuthorizationResponse, AuthorizationsResponse,
+    BatchChunk, BatchingResponse, BlackoutsResponse, BudgetChange, CalendarResponse,
+    ChainProfileResponse, CoOwnersResponse, CoinDescription, CollectedFeesResponse,
+    ConfigDiffResponse, ConfigOverride, ConfigResponse, ContractVersionResponse, CooldownChange,
+    CooldownInfo, CooldownsResponse, CosignerResponse, CostEstimateResponse, DataEntriesResponse,
+    DataEntry, DataResponse, DenomMetadataResponse, DepositsResponse, DescribeCoinsResponse,
+    EchoMsg, ExecuteManyResult, ExecutionResponse, ExecutionsResponse, ExportChunkResponse,
+    ExportEntry, ExportNamespace, FeaturesResponse, GlobalStatsResponse, GrantResponse,
+    GrantsResponse, GuardiansResponse, HandleMsg, HistoryEntryResponse, HistoryResponse, InitMsg,
+    IsDeniedResponse, KindStatuses, Lookup, MacroArg, MacroResponse, MacroRunsResponse,
+    MacrosResponse, Metric, MetricKind, MetricsResponse, MigrateNamespace, MigrateRecordsResult,
+    NamespaceUsage, OnEmpty, OperatorSpec, OperatorWindowResponse, OperatorsResponse, Order,
+    OwnerResponse, PayrollEntry, PendingConfigResponse, PendingOwnerResponse, PermissionsResponse,
+    ProposalResponse, ProposalsResponse, QueryMsg, QueuedExecutionResponse, ReceiptMsg,
+    RecoveryResponse, RecoverySessionResponse, ReflectResult, ResolveResponse, RevenueResponse,
+    RulesResponse, RulesetMsg, ScheduledBatchResponse, ScheduledBatchesResponse, SearchGroup,
+    SearchHit, SearchResponse, SelfTestResult, SessionResponse, SimulationResponse,
+    SpendLimitResponse, StagedBatchResponse, StagedBatchesResponse, StakeholderMsg,
+    StakeholdersResponse, StatsResponse, StatusCode, StatusesResponse, StorageUsageResponse,
+    SubsystemCheck, SuccessorResponse, SwapResponse, TagSpendResponse, TaggedExecutionsResponse,
+    TagsResponse, ValidationResponse, VerifySection, VerifyStateResponse, Violation, ViolationKind,
+    WeightMsg,
+};
+use mask::reference::EntityKind;
+use mask::state::{
+    Amount, Attestation, Budget, ChainProfile, ContractVersion, Cooldown, DenomMetadata, Duration,
+    ExecutionWindow, Expiration, GasHeuristic, KindCount, MacroParam, MacroQuota, Milestone,
+    MsgKind, PendingConfig, ProposalStatus, Rule, RuleAction, SpendLimit, Status, TagBudget,
+    TimeRange, TravelMemo, WasmIntent, Weekday,
+};
+
+pub type Fixtures = Vec<(&'static str, String)>;
+
+pub fn json<T: Serialize>(value: &T) -> String {
+    String::from_utf8(to_vec(value).unwrap()).unwrap()
+}
+
+/// Init, receipt and ruleset messages
+pub fn messages() -> Fixtures {
+    vec![
+        (
+            "init_msg",
+            json(&InitMsg {
+                chain: ChainProfile {
+                    bech32_prefix: "cosmos".to_string(),
+                    native_denom: "uatom".to_string(),
+                    block_time_secs: 5,
+                    default_denom: None,
+                },
+            }),
+        ),
+        (
+            "executionreceipt",
+            json(&ReceiptMsg::ExecutionReceipt {
+                batch_id: 4,
+                execution_id: 7,
+                height: 12345,
+            }),
+        ),
+        (
+            "depositreceipt",
+            json(&ReceiptMsg::DepositReceipt {
+                sender: HumanAddr::from("cosmos1payer"),
+                amount: coin("5", "uatom"),
+                tag: "fees".to_string(),
+            }),
+        ),
+        (
+            "archive",
+            json(&ArchiveMsg::Archive {
+                execution_id: 7,
+                height: 12345,
+                payload: Binary(vec![91, 93]),
+            }),
+        ),
+        (
+            "checkreflect",
+            json(&RulesetMsg::CheckReflect {
+                sender: HumanAddr::from("cosmos1hotkey"),
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                tags: vec!["ops".to_string()],
+                memo: None,
+                height: 12345,
+                time: 1571797419,
+            }),
+        ),
+        ("ping", json(&EchoMsg::Ping { height: 12345 })),
+    ]
+}
+
+/// Every HandleMsg variant
+pub fn handle_msgs() -> Fixtures {
+    vec![
+        (
+            "reflectmsg",
+            json(&HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                tags: vec!["ops".to_string()],
+                memo: Some(TravelMemo {
+                    originator: "client-42".to_string(),
+                    beneficiary: "acct-7".to_string(),
+                }),
+            }),
+        ),
+        (
+            "reflectmsg",
+            json(&HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Contract {
+                    contract_addr: HumanAddr::from("cosmos1other"),
+                    msg: Binary(vec![123, 125]),
+                    send: None,
+                }],
+                tags: vec![],
+                memo: None,
+            }),
+        ),
+        (
+            "reflectmsg",
+            json(&HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Opaque {
+                    data: Binary(vec![10, 1]),
+                }],
+                tags: vec![],
+                memo: None,
+            }),
+        ),
+        (
+            "changeowner",
+            json(&HandleMsg::ChangeOwner {
+                owner: HumanAddr::from("cosmos1new"),
+            }),
+        ),
+        (
+            "registerdenom",
+            json(&HandleMsg::RegisterDenom {
+                denom: "uatom".to_string(),
+                symbol: "ATOM".to_string(),
+                exponent: 6,
+            }),
+        ),
+        (
+            "reportgas",
+            json(&HandleMsg::ReportGas {
+                execution_id: 7,
+                gas_used: 52000,
+            }),
+        ),
+        (
+            "setcoowners",
+            json(&HandleMsg::SetCoOwners {
+                co_owners: vec![HumanAddr::from("cosmos1partner")],
+            }),
+        ),
+        (
+            "setadminweights",
+            json(&HandleMsg::SetAdminWeights {
+                weights: vec![WeightMsg {
+                    addr: HumanAddr::from("cosmos1owner"),
+                    weight: 2,
+                }],
+                threshold: Some(3),
+            }),
+        ),
+        (
+            "proposeadmin",
+            json(&HandleMsg::ProposeAdmin {
+                msg: Box::new(HandleMsg::SetTags {
+                    tags: vec!["ops".to_string()],
+                }),
+            }),
+        ),
+        ("approveadmin", json(&HandleMsg::ApproveAdmin { id: 3 })),
+        (
+            "setcooldown",
+            json(&HandleMsg::SetCooldown {
+                action: "reflect".to_string(),
+                period: Some(Duration::Height(10)),
+            }),
+        ),
+        (
+            "setoperators",
+            json(&HandleMsg::SetOperators {
+                operators: vec![HumanAddr::from("cosmos1operator")],
+            }),
+        ),
+        (
+            "applyoperatormanifest",
+            json(&HandleMsg::ApplyOperatorManifest {
+                operators: vec![OperatorSpec {
+                    addr: HumanAddr::from("cosmos1operator"),
+                }],
+                manifest_nonce: 2,
+            }),
+        ),
+        (
+            "stagebatch",
+            json(&HandleMsg::StageBatch {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                memo: "payroll".to_string(),
+                receipt_contract: None,
+                tags: vec!["ops".to_string()],
+            }),
+        ),
+        ("executestaged", json(&HandleMsg::ExecuteStaged { id: 4 })),
+        (
+            "settags",
+            json(&HandleMsg::SetTags {
+                tags: vec!["ops".to_string()],
+            }),
+        ),
+        (
+            "settagbudget",
+            json(&HandleMsg::SetTagBudget {
+                tag: "ops".to_string(),
+                budget: Some(Budget {
+                    limit: Coin {
+                        denom: "uatom".to_string(),
+                        amount: "1000".to_string(),
+                    },
+                    period: Duration::Time(86400),
+                    enforce: true,
+                }),
+            }),
+        ),
+        (
+            "setrecovery",
+            json(&HandleMsg::SetRecovery {
+                recovery: Some(HumanAddr::from("cosmos1vault")),
+            }),
+        ),
+        ("evacuate", json(&HandleMsg::Evacuate { to: None })),
+        ("resume", json(&HandleMsg::Resume {})),
+        ("pause", json(&HandleMsg::Pause {})),
+        ("unpause", json(&HandleMsg::Unpause {})),
+        (
+            "scheduleconfig",
+            json(&HandleMsg::ScheduleConfig {
+                change: ConfigOverride {
+                    tags: Some(vec!["ops".to_string(), "payroll".to_string()]),
+                    budgets: Some(vec![TagBudget {
+                        tag: "ops".to_string(),
+                        budget: Budget {
+                            limit: Coin {
+                                denom: "uatom".to_string(),
+                                amount: "1000".to_string(),
+                            },
+                            period: Duration::Time(86400),
+                            enforce: true,
+                        },
+                    }]),
+                    cooldowns: None,
+                },
+                activate_at: Expiration::AtHeight(12400),
+            }),
+        ),
+        ("cancelpending", json(&HandleMsg::CancelPending {})),
+        ("activatepending", json(&HandleMsg::ActivatePending {})),
+        (
+            "decommission",
+            json(&HandleMsg::Decommission {
+                successor: Some(HumanAddr::from("cosmos1newmask")),
+            }),
+        ),
+        (
+            "repairstate",
+            json(&HandleMsg::RepairState {
+                token: "counter_mismatch@tag_counts/ops".to_string(),
+            }),
+        ),
+        (
+            "setmsgbytelimit",
+            json(&HandleMsg::SetMsgByteLimit { limit: Some(4096) }),
+        ),
+        (
+            "executemany",
+            json(&HandleMsg::ExecuteMany {
+                targets: vec![
+                    HumanAddr::from("cosmos1pool"),
+                    HumanAddr::from("cosmos1vault"),
+                ],
+                msg: Binary(vec![123, 34, 99, 108, 97, 105, 109, 34, 58, 123, 125, 125]),
+                send: None,
+                tags: vec![],
+            }),
+        ),
+        (
+            "runpayroll",
+            json(&HandleMsg::RunPayroll {
+                entries: vec![PayrollEntry {
+                    recipient: HumanAddr::from("cosmos1alice"),
+                    amount: coin("4000", "uatom"),
+                }],
+                tag: "payroll".to_string(),
+            }),
+        ),
+        (
+            "creategrant",
+            json(&HandleMsg::CreateGrant {
+                grantee: HumanAddr::from("cosmos1grantee"),
+                approver: HumanAddr::from("cosmos1approver"),
+                milestones: vec![Milestone {
+                    description: "audit".to_string(),
+                    amount: coin("2500", "uatom"),
+                }],
+            }),
+        ),
+        (
+            "approvemilestone",
+            json(&HandleMsg::ApproveMilestone { id: 2 }),
+        ),
+        (
+            "proposenewowner",
+            json(&HandleMsg::ProposeNewOwner {
+                owner: HumanAddr::from("cosmos1newowner"),
+                expires: Expiration::AtHeight(12400),
+            }),
+        ),
+        ("acceptownership", json(&HandleMsg::AcceptOwnership {})),
+        (
+            "clawback",
+            json(&HandleMsg::Clawback {
+                id: 2,
+                reason: "missed milestone".to_string(),
+            }),
+        ),
+        (
+            "addadmin",
+            json(&HandleMsg::AddAdmin {
+                addr: HumanAddr::from("cosmos1partner"),
+            }),
+        ),
+        (
+            "removeadmin",
+            json(&HandleMsg::RemoveAdmin {
+                addr: HumanAddr::from("cosmos1partner"),
+            }),
+        ),
+        (
+            "addallowedtarget",
+            json(&HandleMsg::AddAllowedTarget {
+                contract: HumanAddr::from("cosmos1pool"),
+            }),
+        ),
+        (
+            "removeallowedtarget",
+            json(&HandleMsg::RemoveAllowedTarget {
+                contract: HumanAddr::from("cosmos1pool"),
+            }),
+        ),
+        (
+            "createswap",
+            json(&HandleMsg::CreateSwap {
+                counterparty: HumanAddr::from("cosmos1desk"),
+                give: coin("1000000", "uatom"),
+                want: coin("2500", "ujuno"),
+                expires: Expiration::AtHeight(12400),
+            }),
+        ),
+        ("fillswap", json(&HandleMsg::FillSwap { id: 1 })),
+        (
+            "grantpermission",
+            json(&HandleMsg::GrantPermission {
+                grantee: HumanAddr::from("cosmos1hotkey"),
+                msg_types: vec![MsgKind::Send],
+                expires_at_height: 12400,
+            }),
+        ),
+        (
+            "revokepermission",
+            json(&HandleMsg::RevokePermission {
+                grantee: HumanAddr::from("cosmos1hotkey"),
+            }),
+        ),
+        (
+            "createauction",
+            json(&HandleMsg::CreateAuction {
+                sell: coin("1000000", "uatom"),
+                start_price: Coin {
+                    denom: "ujuno".to_string(),
+                    amount: "5000".to_string(),
+                },
+                end_price: Coin {
+                    denom: "ujuno".to_string(),
+                    amount: "2000".to_string(),
+                },
+                duration: Duration::Height(600),
+            }),
+        ),
+        ("buyauction", json(&HandleMsg::BuyAuction { id: 1 })),
+        ("cancelauction", json(&HandleMsg::CancelAuction { id: 1 })),
+        (
+            "schedule",
+            json(&HandleMsg::Schedule {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                execute_after: Expiration::AtHeight(12400),
+                calendar: Some("quarter_end".to_string()),
+            }),
+        ),
+        (
+            "executescheduled",
+            json(&HandleMsg::ExecuteScheduled { id: 1 }),
+        ),
+        (
+            "cancelscheduled",
+            json(&HandleMsg::CancelScheduled { id: 1 }),
+        ),
+        (
+            "deposit",
+            json(&HandleMsg::Deposit {
+                tag: "fees".to_string(),
+            }),
+        ),
+        (
+            "setstakeholders",
+            json(&HandleMsg::SetStakeholders {
+                stakeholders: vec![
+                    StakeholderMsg {
+                        addr: HumanAddr::from("cosmos1alice"),
+                        shares: 2,
+                    },
+                    StakeholderMsg {
+                        addr: HumanAddr::from("cosmos1bob"),
+                        shares: 1,
+                    },
+                ],
+            }),
+        ),
+        (
+            "distributerevenue",
+            json(&HandleMsg::DistributeRevenue {
+                tag: "fees".to_string(),
+                period: 18192,
+            }),
+        ),
+        (
+            "setdeposithook",
+            json(&HandleMsg::SetDepositHook {
+                contract: Some(HumanAddr::from("cosmos1ledger")),
+            }),
+        ),
+        (
+            "setdepositors",
+            json(&HandleMsg::SetDepositors {
+                depositors: Some(vec![HumanAddr::from("cosmos1client")]),
+            }),
+        ),
+        (
+            "settravelrule",
+            json(&HandleMsg::SetTravelRule {
+                thresholds: Some(vec![Amount {
+                    denom: Some("uatom".to_string()),
+                    amount: "1000000000".to_string(),
+                }]),
+            }),
+        ),
+        (
+            "propose",
+            json(&HandleMsg::Propose {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                description: "invoice 42".to_string(),
+                intents: Some(vec![WasmIntent {
+                    contract_addr: HumanAddr::from("cosmos1pool"),
+                    method: "swap".to_string(),
+                    max_funds: coin("100", "uatom"),
+                }]),
+            }),
+        ),
+        ("approve", json(&HandleMsg::Approve { id: 1 })),
+        ("reject", json(&HandleMsg::Reject { id: 1 })),
+        (
+            "setspendlimit",
+            json(&HandleMsg::SetSpendLimit {
+                denom: "uatom".to_string(),
+                amount: "5000000".to_string(),
+                period_secs: 86400,
+            }),
+        ),
+        (
+            "removespendlimit",
+            json(&HandleMsg::RemoveSpendLimit {
+                denom: "uatom".to_string(),
+            }),
+        ),
+        (
+            "suspendoperator",
+            json(&HandleMsg::SuspendOperator {
+                operator: HumanAddr::from("cosmos1intern"),
+            }),
+        ),
+        (
+            "setoperatorwindow",
+            json(&HandleMsg::SetOperatorWindow {
+                operator: HumanAddr::from("cosmos1junior"),
+                window: Some(ExecutionWindow {
+                    days: vec![Weekday::Monday, Weekday::Friday],
+                    start_hour: 9,
+                    end_hour: 17,
+                }),
+            }),
+        ),
+        (
+            "setcalendar",
+            json(&HandleMsg::SetCalendar {
+                name: "quarter_end".to_string(),
+                allowed: vec![],
+                blackouts: vec![TimeRange {
+                    start: 1577750400,
+                    end: 1577836800,
+                }],
+            }),
+        ),
+        (
+            "setoperatorcalendar",
+            json(&HandleMsg::SetOperatorCalendar {
+                operator: HumanAddr::from("cosmos1junior"),
+                calendar: Some("quarter_end".to_string()),
+            }),
+        ),
+        (
+            "withdraw",
+            json(&HandleMsg::Withdraw {
+                to: HumanAddr::from("cosmos1treasury"),
+                amount: None,
+            }),
+        ),
+        (
+            "setguardians",
+            json(&HandleMsg::SetGuardians {
+                guardians: vec![
+                    HumanAddr::from("cosmos1alice"),
+                    HumanAddr::from("cosmos1bob"),
+                    HumanAddr::from("cosmos1carol"),
+                ],
+                threshold: 2,
+                window: Duration::Time(604800),
+            }),
+        ),
+        (
+            "initiaterecovery",
+            json(&HandleMsg::InitiateRecovery {
+                new_owner: HumanAddr::from("cosmos1newkey"),
+            }),
+        ),
+        ("approverecovery", json(&HandleMsg::ApproveRecovery {})),
+        ("cancelrecovery", json(&HandleMsg::CancelRecovery {})),
+        (
+            "denylist",
+            json(&HandleMsg::Denylist {
+                add: vec![HumanAddr::from("cosmos1mallory")],
+                remove: vec![],
+            }),
+        ),
+        (
+            "setfee",
+            json(&HandleMsg::SetFee {
+                fee: vec![Amount {
+                    denom: Some("uatom".to_string()),
+                    amount: "5".to_string(),
+                }],
+            }),
+        ),
+        (
+            "setrules",
+            json(&HandleMsg::SetRules {
+                rules: vec![
+                    Rule {
+                        kind: Some(MsgKind::Send),
+                        target: Some(HumanAddr::from("cosmos1friend")),
+                        max_amount: Some(Amount {
+                            denom: Some("uatom".to_string()),
+                            amount: "100".to_string(),
+                        }),
+                        action: RuleAction::Allow,
+                    },
+                    Rule {
+                        kind: Some(MsgKind::Send),
+                        target: None,
+                        max_amount: None,
+                        action: RuleAction::Deny,
+                    },
+                ],
+            }),
+        ),
+        (
+            "setdata",
+            json(&HandleMsg::SetData {
+                key: "fixture".to_string(),
+                value: Binary(vec![0, 159, 146, 150]),
+            }),
+        ),
+        (
+            "removedata",
+            json(&HandleMsg::RemoveData {
+                key: "fixture".to_string(),
+            }),
+        ),
+        (
+            "createsession",
+            json(&HandleMsg::CreateSession {
+                key_addr: HumanAddr::from("cosmos1session"),
+                max_msgs: 20,
+                expires: Expiration::AtTime(1571884000),
+            }),
+        ),
+        (
+            "setcosigner",
+            json(&HandleMsg::SetCosigner {
+                cosigner: Some(HumanAddr::from("cosmos1cosigner")),
+                thresholds: vec![Amount {
+                    denom: Some("uatom".to_string()),
+                    amount: "1000000".to_string(),
+                }],
+            }),
+        ),
+        (
+            "confirmexecution",
+            json(&HandleMsg::ConfirmExecution { id: 3 }),
+        ),
+        (
+            "cancelexecution",
+            json(&HandleMsg::CancelExecution { id: 3 }),
+        ),
+        (
+            "authorizeonce",
+            json(&HandleMsg::AuthorizeOnce {
+                grantee: HumanAddr::from("cosmos1relayer"),
+                msg_hash: Binary(vec![
+                    196, 145, 217, 137, 130, 19, 172, 219, 91, 124, 192, 137, 254, 17, 46, 44, 12,
+                    24, 234, 77, 128, 122, 63, 142, 121, 97, 238, 213, 185, 64, 234, 33,
+                ]),
+                expires: Expiration::AtHeight(12400),
+            }),
+        ),
+        (
+            "executeauthorized",
+            json(&HandleMsg::ExecuteAuthorized {
+                msg: CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                },
+            }),
+        ),
+        (
+            "setmiddlewares",
+            json(&HandleMsg::SetMiddlewares {
+                middlewares: vec![
+                    MiddlewareConfig::LogKinds {},
+                    MiddlewareConfig::LogMsgs {},
+                    MiddlewareConfig::MaxMsgs { max: 10 },
+                    MiddlewareConfig::Shadow {
+                        middleware: Box::new(MiddlewareConfig::MaxMsgs { max: 3 }),
+                    },
+                    MiddlewareConfig::Rollout {
+                        middleware: Box::new(MiddlewareConfig::LogKinds {}),
+                        percent: 25,
+                        ends: Expiration::AtHeight(12400),
+                    },
+                    MiddlewareConfig::Recipients {
+                        allowed: vec![HumanAddr::from("cosmos1payroll")],
+                    },
+                    MiddlewareConfig::DenomAliases {
+                        aliases: vec![DenomAlias {
+                            alias: "usdc".to_string(),
+                            denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string(),
+                        }],
+                    },
+                ],
+            }),
+        ),
+        (
+            "attest",
+            json(&HandleMsg::Attest {
+                checksum: "9f2c4e07b1d8a35c6e0f17d2a4b9c8e15f3a6d70b2c9e41f8a5d3b6c7e0f1a29".to_string(),
+                signer: "compliance".to_string(),
+                signature: Binary(vec![115, 105, 103, 110, 101, 100]),
+            }),
+        ),
+        (
+            "requireattestation",
+            json(&HandleMsg::RequireAttestation {
+                max_age: Some(Duration::Time(2592000)),
+            }),
+        ),
+        (
+            "setarchive",
+            json(&HandleMsg::SetArchive {
+                contract: Some(HumanAddr::from("cosmos1archive")),
+            }),
+        ),
+        (
+            "setgasheuristics",
+            json(&HandleMsg::SetGasHeuristics {
+                heuristics: vec![GasHeuristic {
+                    kind: MsgKind::Send,
+                    gas_per_msg: 40000,
+                }],
+            }),
+        ),
+        (
+            "executewithlookup",
+            json(&HandleMsg::ExecuteWithLookup {
+                lookup: Lookup {
+                    query: QueryMsg::CollectedFees {},
+                    pointer: "/collected/0/amount".to_string(),
+                    expr: Some("x * 90 / 100".to_string()),
+                    on_empty: Some(OnEmpty::Skip {}),
+                },
+                template: CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1treasury"),
+                    amount: coin("$lookup", "uatom"),
+                },
+                tags: vec!["fees".to_string()],
+                memo: None,
+            }),
+        ),
+        (
+            "savemacro",
+            json(&HandleMsg::SaveMacro {
+                name: "payout".to_string(),
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1payroll"),
+                    amount: coin("$amount", "uatom"),
+                }],
+                params: vec![MacroParam {
+                    name: "amount".to_string(),
+                    max: Some("5000".to_string()),
+                }],
+                runners: vec![HumanAddr::from("cosmos1operator")],
+                quota: Some(MacroQuota {
+                    runs: 2,
+                    period: Duration::Time(86400),
+                }),
+            }),
+        ),
+        (
+            "removemacro",
+            json(&HandleMsg::RemoveMacro {
+                name: "payout".to_string(),
+            }),
+        ),
+        (
+            "runmacro",
+            json(&HandleMsg::RunMacro {
+                name: "payout".to_string(),
+                args: vec![MacroArg {
+                    name: "amount".to_string(),
+                    amount: "1200".to_string(),
+                }],
+            }),
+        ),
+        (
+            "ifconfigversion",
+            json(&HandleMsg::IfConfigVersion {
+                config_version: 14,
+                msg: Box::new(HandleMsg::SetTags {
+                    tags: vec!["ops".to_string()],
+                }),
+            }),
+        ),
+        (
+            "migraterecords",
+            json(&HandleMsg::MigrateRecords {
+                namespace: MigrateNamespace::Executions,
+                limit: Some(50),
+            }),
+        ),
+        (
+            "renounceownership",
+            json(&HandleMsg::RenounceOwnership {
+                confirm: "cosmos2contract".to_string(),
+            }),
+        ),
+        (
+            "setrulesets",
+            json(&HandleMsg::SetRulesets {
+                contracts: vec![
+                    HumanAddr::from("cosmos1compliance"),
+                    HumanAddr::from("cosmos1risk"),
+                ],
+            }),
+        ),
+        (
+            "withdrawfees",
+            json(&HandleMsg::WithdrawFees {
+                to: HumanAddr::from("cosmos1treasury"),
+            }),
+        ),
+        (
+            "setfoureyes",
+            json(&HandleMsg::SetFourEyes {
+                kinds: Some(vec![MsgKind::Contract]),
+            }),
+        ),
+        (
+            "reinstateoperator",
+            json(&HandleMsg::ReinstateOperator {
+                operator: HumanAddr::from("cosmos1intern"),
+            }),
+        ),
+        (
+            "selftest",
+            json(&HandleMsg::SelfTest {
+                echo: Some(HumanAddr::from("cosmos1echo")),
+            }),
+        ),
+    ]
+}
+
+/// Every QueryMsg variant
+pub fn query_msgs() -> Fixtures {
+    vec![
+        ("getowner", json(&QueryMsg::GetOwner {})),
+        ("getconfig", json(&QueryMsg::GetConfig {})),
+        ("getchainprofile", json(&QueryMsg::GetChainProfile {})),
+        (
+            "denommetadata",
+            json(&QueryMsg::DenomMetadata {
+                denom: "uatom".to_string(),
+            }),
+        ),
+        (
+            "describecoins",
+            json(&QueryMsg::DescribeCoins {
+                coins: coin("1500000", "uatom"),
+            }),
+        ),
+        ("execution", json(&QueryMsg::Execution { id: 7 })),
+        (
+            "listexecutions",
+            json(&QueryMsg::ListExecutions {
+                start_after: Some("0000000000000007".to_string()),
+                limit: Some(5),
+                order: Some(Order::Desc),
+                min_height: Some(100),
+                max_height: None,
+                sender: Some(HumanAddr::from("cosmos1owner")),
+                tag: Some("ops".to_string()),
+                kind: Some(MsgKind::Contract),
+            }),
+        ),
+        (
+            "estimatecost",
+            json(&QueryMsg::EstimateCost {
+                kind: MsgKind::Opaque,
+            }),
+        ),
+        ("getcoowners", json(&QueryMsg::GetCoOwners {})),
+        ("adminproposal", json(&QueryMsg::AdminProposal { id: 3 })),
+        (
+            "listadminproposals",
+            json(&QueryMsg::ListAdminProposals {
+                start_after: None,
+                limit: Some(10),
+            }),
+        ),
+        (
+            "adminvote",
+            json(&QueryMsg::AdminVote {
+                id: 3,
+                owner: HumanAddr::from("cosmos1partner"),
+            }),
+        ),
+        ("cooldowns", json(&QueryMsg::Cooldowns {})),
+        ("operators", json(&QueryMsg::Operators {})),
+        ("stagedbatch", json(&QueryMsg::StagedBatch { id: 4 })),
+        (
+            "liststagedbatches",
+            json(&QueryMsg::ListStagedBatches {
+                start_after: None,
+                limit: None,
+            }),
+        ),
+        ("tags", json(&QueryMsg::Tags {})),
+        (
+            "listtaggedexecutions",
+            json(&QueryMsg::ListTaggedExecutions {
+                tag: "ops".to_string(),
+                start_after: Some("0000000000000001".to_string()),
+                limit: None,
+            }),
+        ),
+        (
+            "tagspend",
+            json(&QueryMsg::TagSpend {
+                tag: "ops".to_string(),
+            }),
+        ),
+        ("recovery", json(&QueryMsg::Recovery {})),
+        ("successor", json(&QueryMsg::Successor {})),
+        ("pendingconfig", json(&QueryMsg::PendingConfig {})),
+        ("configdiff", json(&QueryMsg::ConfigDiff {})),
+        (
+            "simulatewithconfig",
+            json(&QueryMsg::SimulateWithConfig {
+                config_override: ConfigOverride {
+                    tags: Some(vec!["ops".to_string(), "payroll".to_string()]),
+                    budgets: Some(vec![TagBudget {
+                        tag: "ops".to_string(),
+                        budget: Budget {
+                            limit: Coin {
+                                denom: "uatom".to_string(),
+                                amount: "1000".to_string(),
+                            },
+                            period: Duration::Time(86400),
+                            enforce: true,
+                        },
+                    }]),
+                    cooldowns: None,
+                },
+                sender: HumanAddr::from("cosmos1owner"),
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                tags: vec!["ops".to_string()],
+            }),
+        ),
+        (
+            "exportchunk",
+            json(&QueryMsg::ExportChunk {
+                namespace: ExportNamespace::StagedBatches,
+                start_after: None,
+                max_bytes: Some(4096),
+            }),
+        ),
+        (
+            "verifystate",
+            json(&QueryMsg::VerifyState {
+                section: VerifySection::TagIndex {
+                    tag: "ops".to_string(),
+                },
+                start_after: None,
+                limit: Some(50),
+            }),
+        ),
+        (
+            "verifystate",
+            json(&QueryMsg::VerifyState {
+                section: VerifySection::Executions,
+                start_after: None,
+                limit: None,
+            }),
+        ),
+        ("features", json(&QueryMsg::Features {})),
+        ("grant", json(&QueryMsg::Grant { id: 2 })),
+        (
+            "listgrants",
+            json(&QueryMsg::ListGrants {
+                grantee: Some(HumanAddr::from("cosmos1grantee")),
+                start_after: None,
+                limit: Some(5),
+            }),
+        ),
+        ("getpendingowner", json(&QueryMsg::GetPendingOwner {})),
+        (
+            "listadmins",
+            json(&QueryMsg::ListAdmins {
+                start_after: Some("0000000000000000".to_string()),
+                limit: None,
+            }),
+        ),
+        ("allowedtargets", json(&QueryMsg::AllowedTargets {})),
+        ("swap", json(&QueryMsg::Swap { id: 1 })),
+        (
+            "permissions",
+            json(&QueryMsg::Permissions {
+                grantee: HumanAddr::from("cosmos1hotkey"),
+            }),
+        ),
+        ("auction", json(&QueryMsg::Auction { id: 1 })),
+        (
+            "listscheduled",
+            json(&QueryMsg::ListScheduled {
+                start_after: None,
+                limit: Some(5),
+            }),
+        ),
+        (
+            "revenue",
+            json(&QueryMsg::Revenue {
+                tag: "fees".to_string(),
+                period: 18192,
+            }),
+        ),
+        ("stakeholders", json(&QueryMsg::Stakeholders {})),
+        ("proposal", json(&QueryMsg::Proposal { id: 1 })),
+        (
+            "listproposals",
+            json(&QueryMsg::ListProposals {
+                start_after: None,
+                limit: Some(5),
+            }),
+        ),
+        (
+            "spendlimit",
+            json(&QueryMsg::SpendLimit {
+                denom: "uatom".to_string(),
+            }),
+        ),
+        ("contractversion", json(&QueryMsg::ContractVersion {})),
+        (
+            "operatorwindow",
+            json(&QueryMsg::OperatorWindow {
+                operator: HumanAddr::from("cosmos1junior"),
+            }),
+        ),
+        (
+            "calendar",
+            json(&QueryMsg::Calendar {
+                name: "quarter_end".to_string(),
+            }),
+        ),
+        (
+            "upcomingblackouts",
+            json(&QueryMsg::UpcomingBlackouts {
+                calendar: "quarter_end".to_string(),
+                after: 1571797419,
+                limit: None,
+            }),
+        ),
+        ("deposits", json(&QueryMsg::Deposits {})),
+        (
+            "history",
+            json(&QueryMsg::History {
+                start_after: None,
+                limit: Some(5),
+            }),
+        ),
+        ("guardians", json(&QueryMsg::Guardians {})),
+        ("recoverysession", json(&QueryMsg::RecoverySession {})),
+        ("collectedfees", json(&QueryMsg::CollectedFees {})),
+        (
+            "isdenied",
+            json(&QueryMsg::IsDenied {
+                addr: HumanAddr::from("cosmos1mallory"),
+            }),
+        ),
+        (
+            "stats",
+            json(&QueryMsg::Stats {
+                addr: HumanAddr::from("cosmos1hotkey"),
+            }),
+        ),
+        ("globalstats", json(&QueryMsg::GlobalStats {})),
+        ("metrics", json(&QueryMsg::Metrics {})),
+        ("rules", json(&QueryMsg::Rules {})),
+        (
+            "getdata",
+            json(&QueryMsg::GetData {
+                key: "fixture".to_string(),
+            }),
+        ),
+        (
+            "listdata",
+            json(&QueryMsg::ListData {
+                start_after: Some("alpha".to_string()),
+                limit: Some(10),
+            }),
+        ),
+        (
+            "session",
+            json(&QueryMsg::Session {
+                key_addr: HumanAddr::from("cosmos1session"),
+            }),
+        ),
+        ("cosigner", json(&QueryMsg::Cosigner {})),
+        (
+            "queuedexecution",
+            json(&QueryMsg::QueuedExecution { id: 3 }),
+        ),
+        (
+            "listauthorizations",
+            json(&QueryMsg::ListAuthorizations {
+                start_after: None,
+                limit: Some(10),
+            }),
+        ),
+        ("attestation", json(&QueryMsg::Attestation {})),
+        ("storageusage", json(&QueryMsg::StorageUsage {})),
+        (
+            "aggregate",
+            json(&QueryMsg::Aggregate {
+                queries: vec![
+                    AggregateQuery {
+                        query: QueryMsg::GetOwner {},
+                        pointer: None,
+                    },
+                    AggregateQuery {
+                        query: QueryMsg::Metrics {},
+                        pointer: Some("/metrics/0/value".to_string()),
+                    },
+                ],
+            }),
+        ),
+        ("macros", json(&QueryMsg::Macros {})),
+        (
+            "resolve",
+            json(&QueryMsg::Resolve {
+                reference: "sched-3".to_string(),
+            }),
+        ),
+        ("statuses", json(&QueryMsg::Statuses {})),
+        (
+            "search",
+            json(&QueryMsg::Search {
+                term: "ops".to_string(),
+                limit: Some(5),
+            }),
+        ),
+        (
+            "suggestbatching",
+            json(&QueryMsg::SuggestBatching {
+                msgs: vec![
+                    CosmosMsg::Send {
+                        from_address: HumanAddr::from("cosmos2contract"),
+                        to_address: HumanAddr::from("cosmos1friend"),
+                        amount: coin("100", "uatom"),
+                    },
+                    CosmosMsg::Send {
+                        from_address: HumanAddr::from("cosmos2contract"),
+                        to_address: HumanAddr::from("cosmos1friend"),
+                        amount: coin("100", "uatom"),
+                    },
+                ],
+                gas_budget: 150000,
+            }),
+        ),
+        (
+            "validatemsg",
+            json(&QueryMsg::ValidateMsg {
+                sender: HumanAddr::from("cosmos1hotkey"),
+                msg: CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                },
+            }),
+        ),
+    ]
+}
+
+/// Query responses and response data
+pub fn responses() -> Fixtures {
+    vec![
+        (
+            "owner_response",
+            json(&OwnerResponse {
+                owner: HumanAddr::from("cosmos1owner"),
+                renounced: false,
+            }),
+        ),
+        (
+            "config_response",
+            json(&ConfigResponse {
+                owner: HumanAddr::from("cosmos1owner"),
+                co_owners: vec![HumanAddr::from("cosmos1partner")],
+                paused: true,
+                decommissioned: false,
+                deposit_hook: Some(HumanAddr::from("cosmos1ledger")),
+                depositors: None,
+                travel_rule: Some(coin("1000000000", "uatom")),
+                four_eyes: Some(vec![MsgKind::Contract]),
+                reflect_fee: None,
+                rulesets: Some(vec![
+                    HumanAddr::from("cosmos1compliance"),
+                    HumanAddr::from("cosmos1risk"),
+                ]),
+                middlewares: Some(vec![MiddlewareConfig::MaxMsgs { max: 10 }]),
+                archive: Some(HumanAddr::from("cosmos1archive")),
+                checksum: "9f2c4e07b1d8a35c6e0f17d2a4b9c8e15f3a6d70b2c9e41f8a5d3b6c7e0f1a29".to_string(),
+                config_version: 14,
+            }),
+        ),
+        (
+            "pending_owner_response",
+            json(&PendingOwnerResponse {
+                owner: Some(HumanAddr::from("cosmos1newowner")),
+                expires: Some(Expiration::AtHeight(12400)),
+            }),
+        ),
+        (
+            "chain_profile_response",
+            json(&ChainProfileResponse {
+                chain: ChainProfile {
+                    bech32_prefix: "cosmos".to_string(),
+                    native_denom: "uatom".to_string(),
+                    block_time_secs: 5,
+                    default_denom: None,
+                },
+            }),
+        ),
+        (
+            "denom_metadata_response",
+            json(&DenomMetadataResponse {
+                denom: "uatom".to_string(),
+                metadata: Some(DenomMetadata {
+                    symbol: "ATOM".to_string(),
+                    exponent: 6,
+                }),
+            }),
+        ),
+        (
+            "describe_coins_response",
+            json(&DescribeCoinsResponse {
+                coins: vec![CoinDescription {
+                    denom: "uatom".to_string(),
+                    amount: "1500000".to_string(),
+                    symbol: Some("ATOM".to_string()),
+                    display_amount: Some("1.5".to_string()),
+                }],
+            }),
+        ),
+        (
+            "execution_response",
+            json(&ExecutionResponse {
+                id: 7,
+                sender: HumanAddr::from("cosmos1owner"),
+                height: 12345,
+                msgs: vec![KindCount {
+                    kind: MsgKind::Send,
+                    count: 2,
+                }],
+                gas_used: Some(52000),
+                tags: vec!["ops".to_string()],
+                memo: None,
+                payload_hash: None,
+            }),
+        ),
+        (
+            "executions_response",
+            json(&ExecutionsResponse {
+                executions: vec![ExecutionResponse {
+                    id: 7,
+                    sender: HumanAddr::from("cosmos1owner"),
+                    height: 12345,
+                    msgs: vec![KindCount {
+                        kind: MsgKind::Send,
+                        count: 2,
+                    }],
+                    gas_used: Some(52000),
+                    tags: vec!["ops".to_string()],
+                    memo: None,
+                    payload_hash: None,
+                }],
+                is_truncated: true,
+                next: Some("0000000000000007".to_string()),
+            }),
+        ),
+        (
+            "cost_estimate_response",
+            json(&CostEstimateResponse {
+                kind: MsgKind::Send,
+                samples: 4,
+                gas_per_msg: Some(26000),
+            }),
+        ),
+        (
+            "co_owners_response",
+            json(&CoOwnersResponse {
+                owner: HumanAddr::from("cosmos1owner"),
+                co_owners: vec![HumanAddr::from("cosmos1partner")],
+                admin_threshold: None,
+                weights: vec![WeightMsg {
+                    addr: HumanAddr::from("cosmos1owner"),
+                    weight: 1,
+                }],
+            }),
+        ),
+        (
+            "admins_response",
+            json(&AdminsResponse {
+                admins: vec![
+                    HumanAddr::from("cosmos1owner"),
+                    HumanAddr::from("cosmos1partner"),
+                ],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "admin_proposal_response",
+            json(&AdminProposalResponse {
+                id: 3,
+                msg: HandleMsg::SetTags {
+                    tags: vec!["ops".to_string()],
+                },
+                approvals: vec![HumanAddr::from("cosmos1owner")],
+                approved_weight: 2,
+                executed: false,
+                status: Status::Pending,
+                status_code: 1,
+            }),
+        ),
+        (
+            "admin_proposals_response",
+            json(&AdminProposalsResponse {
+                proposals: vec![AdminProposalResponse {
+                    id: 3,
+                    msg: HandleMsg::SetTags {
+                        tags: vec!["ops".to_string()],
+                    },
+                    approvals: vec![HumanAddr::from("cosmos1owner")],
+                    approved_weight: 2,
+                    executed: false,
+                    status: Status::Pending,
+                    status_code: 1,
+                }],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "admin_vote_response",
+            json(&AdminVoteResponse {
+                id: 3,
+                owner: HumanAddr::from("cosmos1partner"),
+                approved: true,
+                weight: 1,
+            }),
+        ),
+        (
+            "cooldowns_response",
+            json(&CooldownsResponse {
+                cooldowns: vec![CooldownInfo {
+                    cooldown: Cooldown {
+                        action: "reflect".to_string(),
+                        period: Duration::Height(10),
+                    },
+                    next_allowed: Some(Expiration::AtHeight(12010)),
+                }],
+            }),
+        ),
+        (
+            "operators_response",
+            json(&OperatorsResponse {
+                operators: vec![
+                    HumanAddr::from("cosmos1operator"),
+                    HumanAddr::from("cosmos1intern"),
+                ],
+                manifest_nonce: 2,
+                suspended: vec![HumanAddr::from("cosmos1intern")],
+            }),
+        ),
+        (
+            "allowed_targets_response",
+            json(&AllowedTargetsResponse {
+                targets: Some(vec![HumanAddr::from("cosmos1pool")]),
+            }),
+        ),
+        (
+            "staged_batch_response",
+            json(&StagedBatchResponse {
+                id: 4,
+                operator: HumanAddr::from("cosmos1operator"),
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                memo: "payroll".to_string(),
+                receipt_contract: Some(HumanAddr::from("cosmos1books")),
+                tags: vec!["ops".to_string()],
+                staged_height: 12000,
+                executed: false,
+                status: Status::Staged,
+                status_code: 5,
+            }),
+        ),
+        (
+            "staged_batches_response",
+            json(&StagedBatchesResponse {
+                batches: vec![StagedBatchResponse {
+                    id: 4,
+                    operator: HumanAddr::from("cosmos1operator"),
+                    msgs: vec![CosmosMsg::Send {
+                        from_address: HumanAddr::from("cosmos2contract"),
+                        to_address: HumanAddr::from("cosmos1friend"),
+                        amount: coin("100", "uatom"),
+                    }],
+                    memo: "payroll".to_string(),
+                    receipt_contract: Some(HumanAddr::from("cosmos1books")),
+                    tags: vec!["ops".to_string()],
+                    staged_height: 12000,
+                    executed: false,
+                    status: Status::Staged,
+                    status_code: 5,
+                }],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "revenue_response",
+            json(&RevenueResponse {
+                tag: "fees".to_string(),
+                period: 18192,
+                starts_at: 1571788800,
+                totals: coin("12", "uatom"),
+                settled: false,
+            }),
+        ),
+        (
+            "stakeholders_response",
+            json(&StakeholdersResponse {
+                stakeholders: vec![StakeholderMsg {
+                    addr: HumanAddr::from("cosmos1alice"),
+                    shares: 2,
+                }],
+            }),
+        ),
+        (
+            "scheduled_batches_response",
+            json(&ScheduledBatchesResponse {
+                batches: vec![ScheduledBatchResponse {
+                    id: 1,
+                    owner: HumanAddr::from("cosmos1owner"),
+                    msgs: vec![CosmosMsg::Send {
+                        from_address: HumanAddr::from("cosmos2contract"),
+                        to_address: HumanAddr::from("cosmos1friend"),
+                        amount: coin("100", "uatom"),
+                    }],
+                    execute_after: Expiration::AtHeight(12400),
+                    calendar: None,
+                }],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "proposals_response",
+            json(&ProposalsResponse {
+                proposals: vec![ProposalResponse {
+                    id: 1,
+                    proposer: HumanAddr::from("cosmos1vendor"),
+                    msgs: vec![CosmosMsg::Send {
+                        from_address: HumanAddr::from("cosmos2contract"),
+                        to_address: HumanAddr::from("cosmos1friend"),
+                        amount: coin("100", "uatom"),
+                    }],
+                    description: "invoice 42".to_string(),
+                    status: ProposalStatus::Pending,
+                    status_code: 1,
+                    intents: None,
+                }],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "operator_window_response",
+            json(&OperatorWindowResponse {
+                operator: HumanAddr::from("cosmos1junior"),
+                window: None,
+                calendar: Some("quarter_end".to_string()),
+            }),
+        ),
+        (
+            "calendar_response",
+            json(&CalendarResponse {
+                name: "quarter_end".to_string(),
+                allowed: vec![],
+                blackouts: vec![TimeRange {
+                    start: 1577750400,
+                    end: 1577836800,
+                }],
+            }),
+        ),
+        (
+            "blackouts_response",
+            json(&BlackoutsResponse {
+                calendar: "quarter_end".to_string(),
+                blackouts: vec![TimeRange {
+                    start: 1577750400,
+                    end: 1577836800,
+                }],
+            }),
+        ),
+        (
+            "deposits_response",
+            json(&DepositsResponse {
+                totals: coin("42", "uatom"),
+            }),
+        ),
+        (
+            "contract_version_response",
+            json(&ContractVersionResponse {
+                version: Some(ContractVersion {
+                    contract: "mask".to_string(),
+                    version: "0.1.0".to_string(),
+                }),
+            }),
+        ),
+        (
+            "history_response",
+            json(&HistoryResponse {
+                entries: vec![
+                    HistoryEntryResponse {
+                        seq: 8,
+                        sender: HumanAddr::from("cosmos1owner"),
+                        height: 12400,
+                        action: "reflect".to_string(),
+                        msgs: vec![KindCount {
+                            kind: MsgKind::Send,
+                            count: 2,
+                        }],
+                        owner: None,
+                        intents: None,
+                    },
+                    HistoryEntryResponse {
+                        seq: 7,
+                        sender: HumanAddr::from("cosmos1owner"),
+                        height: 12390,
+                        action: "change_owner".to_string(),
+                        msgs: vec![],
+                        owner: Some(HumanAddr::from("cosmos1successor")),
+                        intents: None,
+                    },
+                ],
+                is_truncated: true,
+                next: Some("0000000000000007".to_string()),
+            }),
+        ),
+        (
+            "spend_limit_response",
+            json(&SpendLimitResponse {
+                denom: "uatom".to_string(),
+                limit: Some(SpendLimit {
+                    limit: Coin {
+                        denom: "uatom".to_string(),
+                        amount: "5000000".to_string(),
+                    },
+                    period_secs: 86400,
+                }),
+                spent: "1250000".to_string(),
+                resets_at: Some(Expiration::AtTime(1571875200)),
+            }),
+        ),
+        (
+            "swap_response",
+            json(&SwapResponse {
+                id: 1,
+                counterparty: HumanAddr::from("cosmos1desk"),
+                give: coin("1000000", "uatom"),
+                want: coin("2500", "ujuno"),
+                expires: Expiration::AtHeight(12400),
+                filled: false,
+                status: Status::Open,
+                status_code: 8,
+            }),
+        ),
+        (
+            "permissions_response",
+            json(&PermissionsResponse {
+                grantee: HumanAddr::from("cosmos1hotkey"),
+                msg_types: vec![MsgKind::Send],
+                expires_at_height: Some(12400),
+                macros: vec![MacroRunsResponse {
+                    name: "payout".to_string(),
+                    quota: Some(MacroQuota {
+                        runs: 2,
+                        period: Duration::Time(86400),
+                    }),
+                    remaining: Some(1),
+                    resets_at: Some(Expiration::AtTime(1571883819)),
+                }],
+            }),
+        ),
+        (
+            "auction_response",
+            json(&AuctionResponse {
+                id: 1,
+                sell: coin("1000000", "uatom"),
+                start_price: Coin {
+                    denom: "ujuno".to_string(),
+                    amount: "5000".to_string(),
+                },
+                end_price: Coin {
+                    denom: "ujuno".to_string(),
+                    amount: "2000".to_string(),
+                },
+                duration: Duration::Height(600),
+                start_height: 12345,
+                start_time: 1571797419,
+                buyer: Some(HumanAddr::from("cosmos1buyer")),
+                paid: Some(Coin {
+                    denom: "ujuno".to_string(),
+                    amount: "3500".to_string(),
+                }),
+                cancelled: false,
+                status: Status::Open,
+                status_code: 8,
+            }),
+        ),
+        (
+            "tags_response",
+            json(&TagsResponse {
+                tags: vec!["ops".to_string(), "payroll".to_string()],
+            }),
+        ),
+        (
+            "tagged_executions_response",
+            json(&TaggedExecutionsResponse {
+                tag: "ops".to_string(),
+                executions: vec![ExecutionResponse {
+                    id: 7,
+                    sender: HumanAddr::from("cosmos1owner"),
+                    height: 12345,
+                    msgs: vec![KindCount {
+                        kind: MsgKind::Send,
+                        count: 2,
+                    }],
+                    gas_used: Some(52000),
+                    tags: vec!["ops".to_string()],
+                    memo: None,
+                    payload_hash: None,
+                }],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "tag_spend_response",
+            json(&TagSpendResponse {
+                tag: "ops".to_string(),
+                budget: Some(Budget {
+                    limit: Coin {
+                        denom: "uatom".to_string(),
+                        amount: "1000".to_string(),
+                    },
+                    period: Duration::Time(86400),
+                    enforce: true,
+                }),
+                spent: "250".to_string(),
+                resets_at: Some(Expiration::AtTime(1571883819)),
+            }),
+        ),
+        (
+            "recovery_response",
+            json(&RecoveryResponse {
+                recovery: Some(HumanAddr::from("cosmos1vault")),
+                paused: true,
+                evacuated: true,
+                decommissioned: false,
+            }),
+        ),
+        (
+            "collected_fees_response",
+            json(&CollectedFeesResponse {
+                collected: coin("10", "uatom"),
+            }),
+        ),
+        (
+            "stats_response",
+            json(&StatsResponse {
+                addr: HumanAddr::from("cosmos1hotkey"),
+                reflections: 3,
+                last_height: Some(12400),
+            }),
+        ),
+        (
+            "metrics_response",
+            json(&MetricsResponse {
+                metrics: vec![
+                    Metric {
+                        name: "executions_total".to_string(),
+                        kind: MetricKind::Counter,
+                        value: 42,
+                    },
+                    Metric {
+                        name: "paused".to_string(),
+                        kind: MetricKind::Gauge,
+                        value: 0,
+                    },
+                ],
+            }),
+        ),
+        (
+            "rules_response",
+            json(&RulesResponse {
+                rules: vec![
+                    Rule {
+                        kind: Some(MsgKind::Send),
+                        target: Some(HumanAddr::from("cosmos1friend")),
+                        max_amount: Some(Amount {
+                            denom: Some("uatom".to_string()),
+                            amount: "100".to_string(),
+                        }),
+                        action: RuleAction::Allow,
+                    },
+                    Rule {
+                        kind: Some(MsgKind::Send),
+                        target: None,
+                        max_amount: None,
+                        action: RuleAction::Deny,
+                    },
+                ],
+            }),
+        ),
+        (
+            "data_response",
+            json(&DataResponse {
+                key: "fixture".to_string(),
+                value: Some(Binary(vec![0, 159, 146, 150])),
+            }),
+        ),
+        (
+            "data_entries_response",
+            json(&DataEntriesResponse {
+                entries: vec![DataEntry {
+                    key: "fixture".to_string(),
+                    value: Binary(vec![0, 159, 146, 150]),
+                }],
+                is_truncated: true,
+                next: Some("fixture".to_string()),
+            }),
+        ),
+        (
+            "session_response",
+            json(&SessionResponse {
+                key_addr: HumanAddr::from("cosmos1session"),
+                remaining_msgs: 17,
+                expires: Some(Expiration::AtTime(1571884000)),
+            }),
+        ),
+        (
+            "attestation_response",
+            json(&AttestationResponse {
+                attestation: Some(Attestation {
+                    checksum: "9f2c4e07b1d8a35c6e0f17d2a4b9c8e15f3a6d70b2c9e41f8a5d3b6c7e0f1a29".to_string(),
+                    signer: "compliance".to_string(),
+                    signature: Binary(vec![115, 105, 103, 110, 101, 100]),
+                    height: 12345,
+                    time: 1571797419,
+                }),
+                max_age: Some(Duration::Time(2592000)),
+                current: true,
+            }),
+        ),
+        (
+            "storage_usage_response",
+            json(&StorageUsageResponse {
+                namespaces: vec![
+                    NamespaceUsage {
+                        namespace: "history".to_string(),
+                        entries: 120,
+                        bytes: 38400,
+                    },
+                    NamespaceUsage {
+                        namespace: "data".to_string(),
+                        entries: 3,
+                        bytes: 96,
+                    },
+                ],
+            }),
+        ),
+        (
+            "statuses_response",
+            json(&StatusesResponse {
+                kinds: vec![KindStatuses {
+                    kind: EntityKind::Swap,
+                    statuses: vec![
+                        StatusCode {
+                            status: Status::Open,
+                            code: 8,
+                        },
+                        StatusCode {
+                            status: Status::Filled,
+                            code: 9,
+                        },
+                    ],
+                }],
+            }),
+        ),
+        (
+            "search_response",
+            json(&SearchResponse {
+                term: "ops".to_string(),
+                groups: vec![
+                    SearchGroup {
+                        kind: "tag".to_string(),
+                        hits: vec![SearchHit {
+                            reference: None,
+                            summary: "2 executions".to_string(),
+                        }],
+                    },
+                    SearchGroup {
+                        kind: "execution".to_string(),
+                        hits: vec![SearchHit {
+                            reference: Some("exec-2".to_string()),
+                            summary: "1 msgs at height 12345".to_string(),
+                        }],
+                    },
+                ],
+            }),
+        ),
+        (
+            "resolve_response",
+            json(&ResolveResponse {
+                reference: "sched-3".to_string(),
+                kind: EntityKind::ScheduledBatch,
+                id: 3,
+                record: Binary(vec![123, 125]),
+            }),
+        ),
+        (
+            "macros_response",
+            json(&MacrosResponse {
+                macros: vec![MacroResponse {
+                    name: "payout".to_string(),
+                    msgs: vec![CosmosMsg::Send {
+                        from_address: HumanAddr::from("cosmos2contract"),
+                        to_address: HumanAddr::from("cosmos1payroll"),
+                        amount: coin("$amount", "uatom"),
+                    }],
+                    params: vec![MacroParam {
+                        name: "amount".to_string(),
+                        max: Some("5000".to_string()),
+                    }],
+                    runners: vec![HumanAddr::from("cosmos1operator")],
+                    quota: Some(MacroQuota {
+                        runs: 2,
+                        period: Duration::Time(86400),
+                    }),
+                }],
+            }),
+        ),
+        (
+            "aggregate_response",
+            json(&AggregateResponse {
+                results: vec![
+                    AggregateResult {
+                        data: Some(Binary(vec![123, 125])),
+                        error: None,
+                    },
+                    AggregateResult {
+                        data: None,
+                        error: Some("Execution not found".to_string()),
+                    },
+                ],
+            }),
+        ),
+        (
+            "batching_response",
+            json(&BatchingResponse {
+                chunks: vec![
+                    BatchChunk {
+                        start: 0,
+                        end: 3,
+                        gas: 120000,
+                        over_budget: false,
+                    },
+                    BatchChunk {
+                        start: 3,
+                        end: 4,
+                        gas: 200000,
+                        over_budget: true,
+                    },
+                ],
+            }),
+        ),
+        (
+            "cosigner_response",
+            json(&CosignerResponse {
+                cosigner: Some(HumanAddr::from("cosmos1cosigner")),
+                thresholds: coin("1000000", "uatom"),
+            }),
+        ),
+        (
+            "queued_execution_response",
+            json(&QueuedExecutionResponse {
+                id: 3,
+                sender: HumanAddr::from("cosmos1owner"),
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("100", "uatom"),
+                }],
+                tags: vec!["ops".to_string()],
+                memo: None,
+                queued_height: 12345,
+                closed: false,
+                status: Status::Queued,
+                status_code: 6,
+            }),
+        ),
+        (
+            "authorizations_response",
+            json(&AuthorizationsResponse {
+                authorizations: vec![AuthorizationResponse {
+                    id: 1,
+                    grantee: HumanAddr::from("cosmos1relayer"),
+                    msg_hash: Binary(vec![
+                        196, 145, 217, 137, 130, 19, 172, 219, 91, 124, 192, 137, 254, 17, 46, 44,
+                        12, 24, 234, 77, 128, 122, 63, 142, 121, 97, 238, 213, 185, 64, 234, 33,
+                    ]),
+                    expires: Expiration::AtHeight(12400),
+                }],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "global_stats_response",
+            json(&GlobalStatsResponse {
+                msgs_reflected: 42,
+                owner_changes: 1,
+            }),
+        ),
+        (
+            "is_denied_response",
+            json(&IsDeniedResponse {
+                addr: HumanAddr::from("cosmos1mallory"),
+                denied: true,
+            }),
+        ),
+        (
+            "guardians_response",
+            json(&GuardiansResponse {
+                guardians: vec![
+                    HumanAddr::from("cosmos1alice"),
+                    HumanAddr::from("cosmos1bob"),
+                    HumanAddr::from("cosmos1carol"),
+                ],
+                threshold: Some(2),
+                window: Some(Duration::Time(604800)),
+            }),
+        ),
+        (
+            "recovery_session_response",
+            json(&RecoverySessionResponse {
+                new_owner: Some(HumanAddr::from("cosmos1newkey")),
+                approvals: vec![HumanAddr::from("cosmos1alice")],
+                expires: Some(Expiration::AtTime(1572402219)),
+            }),
+        ),
+        (
+            "successor_response",
+            json(&SuccessorResponse { successor: None }),
+        ),
+        (
+            "simulation_response",
+            json(&SimulationResponse {
+                error: Some("Unknown tag payroll".to_string()),
+            }),
+        ),
+        (
+            "validation_response",
+            json(&ValidationResponse {
+                valid: false,
+                reason: Some("Contract error: cosmos1mallory is on the deny-list | code=1013;recipient=cosmos1mallory".to_string()),
+            }),
+        ),
+        (
+            "pending_config_response",
+            json(&PendingConfigResponse {
+                pending: Some(PendingConfig {
+                    change: ConfigOverride {
+                        tags: Some(vec!["ops".to_string(), "payroll".to_string()]),
+                        budgets: Some(vec![TagBudget {
+                            tag: "ops".to_string(),
+                            budget: Budget {
+                                limit: Coin {
+                                    denom: "uatom".to_string(),
+                                    amount: "1000".to_string(),
+                                },
+                                period: Duration::Time(86400),
+                                enforce: true,
+                            },
+                        }]),
+                        cooldowns: None,
+                    },
+                    activate_at: Expiration::AtHeight(12400),
+                }),
+            }),
+        ),
+        (
+            "config_diff_response",
+            json(&ConfigDiffResponse {
+                activate_at: Some(Expiration::AtHeight(12400)),
+                tags_added: vec!["payroll".to_string()],
+                tags_removed: vec![],
+                budgets: vec![BudgetChange {
+                    tag: "ops".to_string(),
+                    old: None,
+                    new: Some(Budget {
+                        limit: Coin {
+                            denom: "uatom".to_string(),
+                            amount: "1000".to_string(),
+                        },
+                        period: Duration::Time(86400),
+                        enforce: true,
+                    }),
+                }],
+                cooldowns: vec![CooldownChange {
+                    action: "reflect".to_string(),
+                    old: Some(Duration::Height(10)),
+                    new: None,
+                }],
+            }),
+        ),
+        (
+            "execute_many_result",
+            json(&ExecuteManyResult {
+                execution_id: Some(8),
+                targets: vec![
+                    HumanAddr::from("cosmos1pool"),
+                    HumanAddr::from("cosmos1vault"),
+                ],
+            }),
+        ),
+        (
+            "reflect_result",
+            json(&ReflectResult {
+                execution_id: Some(7),
+                msg_count: 2,
+            }),
+        ),
+        (
+            "migrate_records_result",
+            json(&MigrateRecordsResult {
+                upgraded: 48,
+                unreadable: 0,
+                migrated_up_to: 50,
+                last: 120,
+                done: false,
+            }),
+        ),
+        (
+            "self_test_result",
+            json(&SelfTestResult {
+                checks: vec![
+                    SubsystemCheck {
+                        subsystem: "reflect".to_string(),
+                        passed: true,
+                        error: None,
+                    },
+                    SubsystemCheck {
+                        subsystem: "schedule".to_string(),
+                        passed: true,
+                        error: None,
+                    },
+                    SubsystemCheck {
+                        subsystem: "data".to_string(),
+                        passed: false,
+                        error: Some("Contract error: No data stored under self_test".to_string()),
+                    },
+                    SubsystemCheck {
+                        subsystem: "history".to_string(),
+                        passed: true,
+                        error: None,
+                    },
+                ],
+            }),
+        ),
+        (
+            "export_chunk_response",
+            json(&ExportChunkResponse {
+                entries: vec![ExportEntry {
+                    key: Binary(vec![0, 0, 0, 0, 0, 0, 0, 7]),
+                    value: Binary(vec![123, 34, 105, 100, 34, 58, 55, 125]),
+                }],
+                is_truncated: true,
+                next: Some("0000000000000007".to_string()),
+            }),
+        ),
+        (
+            "grant_response",
+            json(&GrantResponse {
+                id: 2,
+                grantee: HumanAddr::from("cosmos1grantee"),
+                approver: HumanAddr::from("cosmos1approver"),
+                milestones: vec![Milestone {
+                    description: "audit".to_string(),
+                    amount: coin("2500", "uatom"),
+                }],
+                released: 0,
+                remaining: coin("2500", "uatom"),
+                created_height: 12345,
+                clawed_back: false,
+                status: Status::Active,
+                status_code: 12,
+            }),
+        ),
+        (
+            "grants_response",
+            json(&GrantsResponse {
+                grants: vec![GrantResponse {
+                    id: 2,
+                    grantee: HumanAddr::from("cosmos1grantee"),
+                    approver: HumanAddr::from("cosmos1approver"),
+                    milestones: vec![Milestone {
+                        description: "audit".to_string(),
+                        amount: coin("2500", "uatom"),
+                    }],
+                    released: 0,
+                    remaining: coin("2500", "uatom"),
+                    created_height: 12345,
+                    clawed_back: false,
+                    status: Status::Active,
+                    status_code: 12,
+                }],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+        (
+            "features_response",
+            json(&FeaturesResponse {
+                features: vec!["multisig".to_string(), "scheduler".to_string()],
+            }),
+        ),
+        (
+            "verify_state_response",
+            json(&VerifyStateResponse {
+                violations: vec![Violation::new(
+                    ViolationKind::CounterMismatch,
+                    "tag_counts/ops".to_string(),
+                    "index has entries beyond the count of 2".to_string(),
+                )],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+    ]
+}
+
+/// Full batches, expired grants, the largest amounts and empty lists
+pub fn edge_cases() -> Fixtures {
+    vec![
+        (
+            "execute_many_max_targets",
+            json(&HandleMsg::ExecuteMany {
+                targets: vec![
+                    HumanAddr::from("cosmos1target01"),
+                    HumanAddr::from("cosmos1target02"),
+                    HumanAddr::from("cosmos1target03"),
+                    HumanAddr::from("cosmos1target04"),
+                    HumanAddr::from("cosmos1target05"),
+                    HumanAddr::from("cosmos1target06"),
+                    HumanAddr::from("cosmos1target07"),
+                    HumanAddr::from("cosmos1target08"),
+                    HumanAddr::from("cosmos1target09"),
+                    HumanAddr::from("cosmos1target10"),
+                    HumanAddr::from("cosmos1target11"),
+                    HumanAddr::from("cosmos1target12"),
+                    HumanAddr::from("cosmos1target13"),
+                    HumanAddr::from("cosmos1target14"),
+                    HumanAddr::from("cosmos1target15"),
+                    HumanAddr::from("cosmos1target16"),
+                    HumanAddr::from("cosmos1target17"),
+                    HumanAddr::from("cosmos1target18"),
+                    HumanAddr::from("cosmos1target19"),
+                    HumanAddr::from("cosmos1target20"),
+                    HumanAddr::from("cosmos1target21"),
+                    HumanAddr::from("cosmos1target22"),
+                    HumanAddr::from("cosmos1target23"),
+                    HumanAddr::from("cosmos1target24"),
+                    HumanAddr::from("cosmos1target25"),
+                ],
+                msg: Binary(vec![123, 34, 99, 108, 97, 105, 109, 34, 58, 123, 125, 125]),
+                send: Some(coin("1", "uatom")),
+                tags: vec!["ops".to_string()],
+            }),
+        ),
+        (
+            "run_payroll_max_entries",
+            json(&HandleMsg::RunPayroll {
+                entries: vec![
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee01"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee02"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee03"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee04"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee05"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee06"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee07"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee08"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee09"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee10"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee11"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee12"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee13"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee14"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee15"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee16"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee17"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee18"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee19"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee20"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee21"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee22"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee23"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee24"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee25"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee26"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee27"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee28"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee29"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee30"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee31"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee32"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee33"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee34"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee35"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee36"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee37"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee38"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee39"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee40"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee41"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee42"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee43"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee44"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee45"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee46"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee47"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee48"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee49"),
+                        amount: coin("4000", "uatom"),
+                    },
+                    PayrollEntry {
+                        recipient: HumanAddr::from("cosmos1payee50"),
+                        amount: coin("4000", "uatom"),
+                    },
+                ],
+                tag: "payroll".to_string(),
+            }),
+        ),
+        (
+            "reflect_max_amount",
+            json(&HandleMsg::ReflectMsg {
+                msgs: vec![CosmosMsg::Send {
+                    from_address: HumanAddr::from("cosmos2contract"),
+                    to_address: HumanAddr::from("cosmos1friend"),
+                    amount: coin("340282366920938463463374607431768211455", "uatom"),
+                }],
+                tags: vec![],
+                memo: None,
+            }),
+        ),
+        (
+            "set_rules_empty",
+            json(&HandleMsg::SetRules { rules: vec![] }),
+        ),
+        (
+            "set_rulesets_empty",
+            json(&HandleMsg::SetRulesets { contracts: vec![] }),
+        ),
+        (
+            "permissions_expired",
+            json(&PermissionsResponse {
+                grantee: HumanAddr::from("cosmos1hotkey"),
+                msg_types: vec![MsgKind::Send, MsgKind::Contract],
+                expires_at_height: Some(12000),
+                macros: vec![],
+            }),
+        ),
+        (
+            "permissions_none",
+            json(&PermissionsResponse {
+                grantee: HumanAddr::from("cosmos1stranger"),
+                msg_types: vec![],
+                expires_at_height: None,
+                macros: vec![],
+            }),
+        ),
+        (
+            "session_expired",
+            json(&SessionResponse {
+                key_addr: HumanAddr::from("cosmos1session"),
+                remaining_msgs: 5,
+                expires: Some(Expiration::AtHeight(12000)),
+            }),
+        ),
+        (
+            "session_exhausted",
+            json(&SessionResponse {
+                key_addr: HumanAddr::from("cosmos1session"),
+                remaining_msgs: 0,
+                expires: Some(Expiration::AtHeight(12400)),
+            }),
+        ),
+        (
+            "spend_limit_exhausted",
+            json(&SpendLimitResponse {
+                denom: "uatom".to_string(),
+                limit: Some(SpendLimit {
+                    limit: Coin {
+                        denom: "uatom".to_string(),
+                        amount: "5000000".to_string(),
+                    },
+                    period_secs: 86400,
+                }),
+                spent: "5000000".to_string(),
+                resets_at: Some(Expiration::AtTime(1571875200)),
+            }),
+        ),
+        (
+            "spend_limit_unset",
+            json(&SpendLimitResponse {
+                denom: "uatom".to_string(),
+                limit: None,
+                spent: "0".to_string(),
+                resets_at: None,
+            }),
+        ),
+        (
+            "history_empty_page",
+            json(&HistoryResponse {
+                entries: vec![],
+                is_truncated: false,
+                next: None,
+            }),
+        ),
+    ]
+}
@@ -0,0 +1,65 @@
+//! Golden files pin the exact JSON of every message and response, so a renamed field or
+//! variant breaks a test here rather than the clients.
+//!
+//! After an intended change, rewrite the files and review the diff:
+//!
+//! UPDATE_GOLDEN=1 cargo test --test golden
+
+mod fixtures;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use fixtures::Fixtures;
+
+/// Compares the fixtures line by line with tests/golden/<name>.jsonl,
+/// or rewrites the file if UPDATE_GOLDEN is set
+fn assert_golden(name: &str, fixtures: Fixtures) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.jsonl", name));
+    let actual: String = fixtures.iter().map(|(_, f)| format!("{}\n", f)).collect();
+    if env::var("UPDATE_GOLDEN").is_ok() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing {}, run with UPDATE_GOLDEN=1", path.display()));
+    for (i, (expected, actual)) in expected.lines().zip(actual.lines()).enumerate() {
+        assert_eq!(expected, actual, "{}.jsonl line {} changed", name, i + 1);
+    }
+    assert_eq!(
+        expected.lines().count(),
+        actual.lines().count(),
+        "{}.jsonl has another number of fixtures",
+        name
+    );
+}
+
+#[test]
+fn init_and_receipt_msgs() {
+    assert_golden("messages", fixtures::messages());
+}
+
+#[test]
+fn handle_msgs() {
+    assert_golden("handle_msgs", fixtures::handle_msgs());
+}
+
+#[test]
+fn query_msgs() {
+    assert_golden("query_msgs", fixtures::query_msgs());
+}
+
+#[test]
+fn responses() {
+    assert_golden("responses", fixtures::responses());
+}
+
+#[test]
+fn edge_cases() {
+    assert_golden("edge_cases", fixtures::edge_cases());
+}